@@ -2,6 +2,8 @@ import contextlib
 
 from prometheus_client import Counter, Gauge, Histogram
 
+from pre.common import ContractStats
+
 
 PROM_NAMESPACE = "pre"
 PROM_INSTANCE_LABEL = "id"
@@ -77,6 +79,43 @@ class ProxyMetrics:
             labelnames=[PROM_INSTANCE_LABEL],
         )
 
+        self._contract_total_staked = Gauge(
+            "contract_total_staked",
+            "Tracks the stake of all proxies, excluding stake locked in open tasks",
+            namespace=PROM_NAMESPACE,
+            labelnames=[PROM_INSTANCE_LABEL],
+        )
+        self._contract_active_proxies = Gauge(
+            "contract_active_proxies",
+            "Tracks the number of active proxies in the contract",
+            namespace=PROM_NAMESPACE,
+            labelnames=[PROM_INSTANCE_LABEL],
+        )
+        self._contract_open_tasks = Gauge(
+            "contract_open_tasks",
+            "Tracks the number of reencryption tasks waiting for any proxy",
+            namespace=PROM_NAMESPACE,
+            labelnames=[PROM_INSTANCE_LABEL],
+        )
+        self._contract_completed_requests = Gauge(
+            "contract_completed_requests",
+            "Tracks the number of reencryption requests completed by the contract",
+            namespace=PROM_NAMESPACE,
+            labelnames=[PROM_INSTANCE_LABEL],
+        )
+        self._contract_rewards_paid = Gauge(
+            "contract_rewards_paid",
+            "Tracks the total amount of rewards paid to proxies",
+            namespace=PROM_NAMESPACE,
+            labelnames=[PROM_INSTANCE_LABEL],
+        )
+        self._contract_slashed = Gauge(
+            "contract_slashed",
+            "Tracks the total amount of stake slashed from proxies",
+            namespace=PROM_NAMESPACE,
+            labelnames=[PROM_INSTANCE_LABEL],
+        )
+
     @property
     def time_query_tasks(self):
         if self._disable:
@@ -123,3 +162,15 @@ class ProxyMetrics:
         if self._disable:
             return
         self._balance.labels(self.label).set(balance)
+
+    def report_contract_stats(self, stats: ContractStats):
+        if self._disable:
+            return
+        self._contract_total_staked.labels(self.label).set(stats.total_staked)
+        self._contract_active_proxies.labels(self.label).set(stats.n_active_proxies)
+        self._contract_open_tasks.labels(self.label).set(stats.n_open_tasks)
+        self._contract_completed_requests.labels(self.label).set(
+            stats.n_completed_requests
+        )
+        self._contract_rewards_paid.labels(self.label).set(stats.total_rewards_paid)
+        self._contract_slashed.labels(self.label).set(stats.total_slashed)
@@ -135,6 +135,7 @@ def process_tasks(
                 if len(tasks) > 0:
                     task = tasks[0]
             metrics.report_pending_tasks_count(len(tasks))
+            metrics.report_contract_stats(proxy_api.get_contract_stats())
         except ContractQueryError as e:
             click.echo(f"Warning: failed to query contract: {str(e)}")
             metrics.report_contract_query_failure()
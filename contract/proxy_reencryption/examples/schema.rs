@@ -4,9 +4,10 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use cw_proxy_reencryption::msg::{
-    ExecuteMsg, GetAvailableProxiesResponse, GetContractStateResponse, GetDataIDResponse,
-    GetDelegationStatusResponse, GetFragmentsResponse, GetProxyTasksResponse, InstantiateMsg,
-    QueryMsg,
+    ExecuteMsg, GetAvailableProxiesResponse, GetConfigResponse, GetContractStateResponse,
+    GetContractStatsResponse, GetDataIDResponse, GetDelegationStatusResponse, GetFragmentsResponse,
+    GetProxyTasksResponse, InstantiateMsg, MigrateMsg, QueryMsg,
+    SimulateReencryptionRequestResponse, SudoMsg,
 };
 
 fn main() {
@@ -18,10 +19,15 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(SudoMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(GetAvailableProxiesResponse), &out_dir);
     export_schema(&schema_for!(GetDataIDResponse), &out_dir);
     export_schema(&schema_for!(GetFragmentsResponse), &out_dir);
     export_schema(&schema_for!(GetContractStateResponse), &out_dir);
+    export_schema(&schema_for!(GetConfigResponse), &out_dir);
+    export_schema(&schema_for!(GetContractStatsResponse), &out_dir);
     export_schema(&schema_for!(GetProxyTasksResponse), &out_dir);
     export_schema(&schema_for!(GetDelegationStatusResponse), &out_dir);
+    export_schema(&schema_for!(SimulateReencryptionRequestResponse), &out_dir);
 }
@@ -0,0 +1,169 @@
+use cosmwasm_std::{from_slice, to_vec, Addr, Order, Storage};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+
+static NEXT_ACCESS_REQUEST_ID_KEY: &[u8] = b"NextAccessRequestID";
+
+// Map access_request_id: u64 -> access_request: AccessRequest
+static ACCESS_REQUESTS_STORE_KEY: &[u8] = b"AccessRequestsStore";
+
+// Pending requests of delegator ordered by ID
+// Map delegator_pubkey: String -> access_request_id: u64 (big endian) -> is_request: bool
+static DELEGATOR_ACCESS_REQUESTS_STORE_KEY: &[u8] = b"DelegatorAccessRequestsStore";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessRequestTarget {
+    DataIds(Vec<String>),
+    Labels(Vec<String>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct AccessRequest {
+    pub delegator_pubkey: String,
+    pub delegatee_pubkey: String,
+    pub requester_addr: Addr,
+    pub target: AccessRequestTarget,
+    pub message: Option<String>,
+    pub expiry_height: u64,
+}
+
+// NEXT_ACCESS_REQUEST_ID
+pub fn store_get_next_access_request_id(storage: &dyn Storage) -> u64 {
+    storage
+        .get(NEXT_ACCESS_REQUEST_ID_KEY)
+        .map(|data| u64::from_le_bytes(data.try_into().unwrap()))
+        .unwrap_or(0)
+}
+
+pub fn store_set_next_access_request_id(storage: &mut dyn Storage, request_id: u64) {
+    storage.set(NEXT_ACCESS_REQUEST_ID_KEY, &request_id.to_le_bytes());
+}
+
+// ACCESS_REQUESTS_STORE
+pub fn store_set_access_request(
+    storage: &mut dyn Storage,
+    request_id: u64,
+    access_request: &AccessRequest,
+) {
+    let mut store = PrefixedStorage::new(storage, ACCESS_REQUESTS_STORE_KEY);
+    store.set(&request_id.to_le_bytes(), &to_vec(access_request).unwrap());
+
+    store_add_delegator_access_request(storage, &access_request.delegator_pubkey, request_id);
+}
+
+pub fn store_get_access_request(storage: &dyn Storage, request_id: u64) -> Option<AccessRequest> {
+    let store = ReadonlyPrefixedStorage::new(storage, ACCESS_REQUESTS_STORE_KEY);
+
+    store
+        .get(&request_id.to_le_bytes())
+        .map(|data| from_slice(&data).unwrap())
+}
+
+pub fn store_remove_access_request(storage: &mut dyn Storage, request_id: u64) {
+    if let Some(access_request) = store_get_access_request(storage, request_id) {
+        store_remove_delegator_access_request(
+            storage,
+            &access_request.delegator_pubkey,
+            request_id,
+        );
+    }
+
+    let mut store = PrefixedStorage::new(storage, ACCESS_REQUESTS_STORE_KEY);
+    store.remove(&request_id.to_le_bytes());
+}
+
+// DELEGATOR_ACCESS_REQUESTS_STORE
+fn store_add_delegator_access_request(
+    storage: &mut dyn Storage,
+    delegator_pubkey: &str,
+    request_id: u64,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[
+            DELEGATOR_ACCESS_REQUESTS_STORE_KEY,
+            delegator_pubkey.as_bytes(),
+        ],
+    );
+
+    // Any value in store means true - &[1]
+    store.set(&request_id.to_be_bytes(), &[1]);
+}
+
+fn store_remove_delegator_access_request(
+    storage: &mut dyn Storage,
+    delegator_pubkey: &str,
+    request_id: u64,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[
+            DELEGATOR_ACCESS_REQUESTS_STORE_KEY,
+            delegator_pubkey.as_bytes(),
+        ],
+    );
+
+    store.remove(&request_id.to_be_bytes());
+}
+
+pub fn store_get_all_delegator_access_requests(
+    storage: &dyn Storage,
+    delegator_pubkey: &str,
+) -> Vec<u64> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[
+            DELEGATOR_ACCESS_REQUESTS_STORE_KEY,
+            delegator_pubkey.as_bytes(),
+        ],
+    );
+
+    let mut deserialized_keys: Vec<u64> = Vec::new();
+
+    for pair in store.range(None, None, Order::Ascending) {
+        // Deserialize keys with inverse operation to to_be_bytes
+        deserialized_keys.push(u64::from_be_bytes(pair.0.try_into().unwrap()));
+    }
+
+    deserialized_keys
+}
+
+// High level methods
+
+pub fn prune_expired_access_requests(
+    storage: &mut dyn Storage,
+    delegator_pubkey: &str,
+    block_height: u64,
+) {
+    // Requests have the same lifetime, older IDs expire first
+    for request_id in store_get_all_delegator_access_requests(storage, delegator_pubkey) {
+        let access_request = store_get_access_request(storage, request_id).unwrap();
+        if access_request.expiry_height > block_height {
+            break;
+        }
+
+        store_remove_access_request(storage, request_id);
+    }
+}
+
+pub fn remove_data_access_requests(
+    storage: &mut dyn Storage,
+    delegator_pubkey: &str,
+    data_id: &str,
+) {
+    // Requests for data of previous owner can't be approved by new one
+    for request_id in store_get_all_delegator_access_requests(storage, delegator_pubkey) {
+        let access_request = store_get_access_request(storage, request_id).unwrap();
+        if let AccessRequestTarget::DataIds(data_ids) = &access_request.target {
+            if data_ids
+                .iter()
+                .any(|request_data_id| request_data_id == data_id)
+            {
+                store_remove_access_request(storage, request_id);
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use crate::state::Expiration;
+use cosmwasm_std::{from_slice, to_vec, Addr, BlockInfo, StdError, StdResult, Storage, Uint128};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Prepaid request rewards deposited by delegators
+// Map delegator_addr: Addr -> delegatee_pubkey: String -> allowance: DelegateeAllowance
+static DELEGATEE_ALLOWANCES_STORE_KEY: &[u8] = b"DelegateeAllowances";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegateeAllowance {
+    pub balance: Uint128,
+    // Maximum amount drawn by one re-encryption request
+    pub spend_limit: Option<Uint128>,
+    // Allowance can't be drawn after expiration, refunds are still credited
+    pub expires: Option<Expiration>,
+}
+
+// DELEGATEE_ALLOWANCES_STORE
+pub fn store_set_delegatee_allowance(
+    storage: &mut dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+    allowance: &DelegateeAllowance,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_ALLOWANCES_STORE_KEY, delegator_addr.as_bytes()],
+    );
+
+    store.set(delegatee_pubkey.as_bytes(), &to_vec(allowance).unwrap());
+}
+
+pub fn store_remove_delegatee_allowance(
+    storage: &mut dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_ALLOWANCES_STORE_KEY, delegator_addr.as_bytes()],
+    );
+
+    store.remove(delegatee_pubkey.as_bytes());
+}
+
+pub fn store_get_delegatee_allowance(
+    storage: &dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+) -> Option<DelegateeAllowance> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_ALLOWANCES_STORE_KEY, delegator_addr.as_bytes()],
+    );
+
+    store
+        .get(delegatee_pubkey.as_bytes())
+        .map(|data| from_slice(&data).unwrap())
+}
+
+// High level methods
+
+pub fn check_delegatee_allowance(
+    storage: &dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+    amount: u128,
+    block: &BlockInfo,
+) -> StdResult<DelegateeAllowance> {
+    let allowance = store_get_delegatee_allowance(storage, delegator_addr, delegatee_pubkey)
+        .ok_or_else(|| {
+            StdError::generic_err("No funds provided and no allowance for delegatee.")
+        })?;
+
+    if let Some(expires) = &allowance.expires {
+        if expires.is_expired(block) {
+            return Err(StdError::generic_err("Delegatee allowance has expired."));
+        }
+    }
+
+    if let Some(spend_limit) = allowance.spend_limit {
+        if amount > spend_limit.u128() {
+            return Err(StdError::generic_err(format!(
+                "Request requires {} which exceeds allowance spend limit {}.",
+                amount, spend_limit
+            )));
+        }
+    }
+
+    if amount > allowance.balance.u128() {
+        return Err(StdError::generic_err(format!(
+            "Insufficient delegatee allowance, requires {}.",
+            amount
+        )));
+    }
+
+    Ok(allowance)
+}
+
+pub fn draw_delegatee_allowance(
+    storage: &mut dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+    amount: u128,
+    block: &BlockInfo,
+) -> StdResult<()> {
+    let mut allowance =
+        check_delegatee_allowance(storage, delegator_addr, delegatee_pubkey, amount, block)?;
+
+    allowance.balance = Uint128::new(allowance.balance.u128() - amount);
+    store_set_delegatee_allowance(storage, delegator_addr, delegatee_pubkey, &allowance);
+
+    Ok(())
+}
+
+pub fn credit_delegatee_allowance(
+    storage: &mut dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+    amount: u128,
+) {
+    // Withdrawn allowance is created again to hold refunds
+    let mut allowance = store_get_delegatee_allowance(storage, delegator_addr, delegatee_pubkey)
+        .unwrap_or(DelegateeAllowance {
+            balance: Uint128::zero(),
+            spend_limit: None,
+            expires: None,
+        });
+
+    allowance.balance = Uint128::new(allowance.balance.u128() + amount);
+    store_set_delegatee_allowance(storage, delegator_addr, delegatee_pubkey, &allowance);
+}
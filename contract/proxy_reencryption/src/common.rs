@@ -1,4 +1,6 @@
-use cosmwasm_std::{Addr, BankMsg, Coin, Response, SubMsg};
+use cosmwasm_std::{Addr, BankMsg, Coin, Order, Response, StdError, StdResult, Storage, SubMsg};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use std::convert::TryInto;
 
 pub fn add_bank_msg(response: &mut Response, addr: &Addr, amount: u128, denom: &str) {
     // BankMsg fails if amount == 0
@@ -9,3 +11,64 @@ pub fn add_bank_msg(response: &mut Response, addr: &Addr, amount: u128, denom: &
         }));
     }
 }
+
+// Pagination of queries
+pub const DEFAULT_QUERY_LIMIT: u32 = 10;
+pub const MAX_QUERY_LIMIT: u32 = 30;
+// Filtered queries stop after scanning this many entries
+pub const MAX_QUERY_SCAN: u32 = 300;
+
+pub fn get_query_limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize
+}
+
+pub fn range_start_after(start_after: &[u8]) -> Vec<u8> {
+    // Range start is inclusive - first key after start_after is start_after + \0
+    let mut start = start_after.to_vec();
+    start.push(0);
+    start
+}
+
+// Migration from hand-rolled PrefixedStorage maps
+// Migration moves at most this many legacy entries, it is repeated until all entries are moved
+pub const DEFAULT_MIGRATION_LIMIT: u32 = 500;
+
+pub fn take_legacy_entries(
+    storage: &mut dyn Storage,
+    namespace: &[u8],
+    limit: &mut usize,
+) -> Vec<(Vec<u8>, Vec<u8>)> {
+    // Remove and return at most limit entries under namespace including nested ones
+    // Limit is shared by all legacy maps, it is decreased by number of taken entries
+    let entries: Vec<(Vec<u8>, Vec<u8>)> = ReadonlyPrefixedStorage::new(storage, namespace)
+        .range(None, None, Order::Ascending)
+        .take(*limit)
+        .collect();
+
+    let mut store = PrefixedStorage::new(storage, namespace);
+    for (key, _) in entries.iter() {
+        store.remove(key);
+    }
+    *limit -= entries.len();
+    entries
+}
+
+pub fn split_legacy_namespace(key: &[u8]) -> StdResult<(&[u8], &[u8])> {
+    // Nested namespace of multilevel storage is prefixed by its length as 2 bytes big endian
+    if key.len() < 2 {
+        return Err(StdError::generic_err("Invalid legacy storage key."));
+    }
+    let length = u16::from_be_bytes([key[0], key[1]]) as usize;
+    if key.len() < 2 + length {
+        return Err(StdError::generic_err("Invalid legacy storage key."));
+    }
+    Ok((&key[2..2 + length], &key[2 + length..]))
+}
+
+pub fn parse_legacy_id(value: &[u8]) -> StdResult<u64> {
+    // Legacy IDs are stored as 8 bytes little endian
+    match value.try_into() {
+        Ok(bytes) => Ok(u64::from_le_bytes(bytes)),
+        Err(_) => Err(StdError::generic_err("Invalid legacy ID.")),
+    }
+}
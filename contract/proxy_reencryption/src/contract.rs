@@ -1,49 +1,94 @@
 use crate::msg::{
-    ExecuteMsg, ExecuteMsgJSONResponse, GetAvailableProxiesResponse, GetContractStateResponse,
-    GetDataIDResponse, GetDataLabelsResponse, GetDelegateeLabelsResponse,
-    GetDelegationStatusResponse, GetFragmentsResponse, GetProxyStatusResponse,
-    GetProxyTasksResponse, GetStakingConfigResponse, InstantiateMsg, InstantiateMsgResponse,
-    ProxyAvailabilityResponse, ProxyDelegationString, ProxyStakeResponse, ProxyStatusResponse,
-    ProxyTaskResponse, QueryMsg,
+    AccessRequestResponse, CheckPermissionResponse, DataAccessGrant, DataBatchEntry, DataCapsule,
+    DelegateeLabel, DelegateeRequestResponse, DelegationResponse, ExecuteMsg,
+    ExecuteMsgJSONResponse, GetAvailableProxiesResponse, GetConfigResponse,
+    GetContractStateResponse, GetContractStatsResponse, GetDataAccessGrantsResponse,
+    GetDataByTagResponse, GetDataIDResponse, GetDataLabelsResponse,
+    GetDataOwnershipTransferResponse, GetDelegateeAllowanceResponse, GetDelegateeLabelsResponse,
+    GetDelegateeRegistrationResponse, GetDelegateeRequestsResponse, GetDelegationStatusResponse,
+    GetFragmentsResponse, GetPendingAccessRequestsResponse, GetProxyStatusResponse,
+    GetProxyTasksResponse, GetReencryptionRequestsResponse, GetStakingConfigResponse,
+    GetSubscriptionResponse, GetTimeoutsConfigResponse, InstantiateMsg, InstantiateMsgResponse,
+    ListDataByDelegatorResponse, ListDelegationsResponse, ListProxiesResponse,
+    ListTasksByProxyResponse, MigrateMsg, ProvideFragmentResponse, ProxyAvailabilityResponse,
+    ProxyDelegationString, ProxyEntryResponse, ProxyFragment, ProxyStakeResponse,
+    ProxyStatusResponse, ProxyTaskEntryResponse, ProxyTaskResponse, ProxyTasksCursor, QueryMsg,
+    ReencryptionRequestResponse, SimulateReencryptionRequestResponse, SudoMsg, Tag,
 };
 use crate::proxies::{
-    get_maximum_withdrawable_stake_amount, store_get_all_active_proxy_addresses,
+    get_maximum_withdrawable_stake_amount, migrate_legacy_proxies,
+    store_get_all_active_proxy_addresses, store_get_proxies, store_get_proxies_by_state,
     store_get_proxy_entry, store_remove_proxy_entry, store_set_is_proxy_active,
     store_set_proxy_entry, Proxy, ProxyState,
 };
 use crate::state::{
-    store_get_data_entry, store_get_delegator_address, store_get_staking_config, store_get_state,
-    store_get_timeouts_config, store_remove_data_entry, store_set_data_entry,
-    store_set_delegator_address, store_set_staking_config, store_set_state,
-    store_set_timeouts_config, DataEntry, StakingConfig, State, TimeoutsConfig,
+    is_data_expired, is_requester_authorised, migrate_legacy_data_entries,
+    store_add_delegatee_requester, store_get_all_delegatee_requesters,
+    store_get_all_delegator_data_ids, store_get_contract_stats, store_get_data_entry,
+    store_get_data_ownership_transfer, store_get_delegatee_address, store_get_delegator_address,
+    store_get_delegator_data_ids, store_get_expired_data_ids,
+    store_get_is_legacy_migration_pending, store_get_staking_config, store_get_state,
+    store_get_timeouts_config, store_remove_data_entry, store_remove_data_ownership_transfer,
+    store_remove_delegatee_requester, store_remove_delegator_address, store_set_data_entry,
+    store_set_data_ownership_transfer, store_set_delegatee_address, store_set_delegator_address,
+    store_set_is_legacy_migration_pending, store_set_staking_config, store_set_state,
+    store_set_timeouts_config, update_contract_stats, DataEntry, DataOwnershipTransfer, Expiration,
+    StakingConfig, State, TimeoutsConfig,
 };
 
+use crate::allowances::{
+    check_delegatee_allowance, draw_delegatee_allowance, store_get_delegatee_allowance,
+    store_remove_delegatee_allowance, store_set_delegatee_allowance, DelegateeAllowance,
+};
 use crate::delegations::{
-    get_delegation_state, get_n_available_proxies_from_delegation,
-    get_n_minimum_proxies_for_refund, remove_proxy_from_delegations,
-    store_add_per_proxy_delegation, store_get_all_proxies_from_delegation, store_get_delegation,
+    get_available_proxies_from_delegation, get_delegation_state,
+    get_n_available_proxies_from_delegation, get_n_minimum_proxies_for_refund,
+    migrate_legacy_proxy_delegations, remove_all_delegator_delegations,
+    remove_proxy_from_delegations, store_get_all_proxies_from_delegation,
+    store_get_delegatee_delegators, store_get_delegation, store_get_delegator_delegatees,
     store_get_proxy_delegation_id, store_is_proxy_delegation_empty, store_set_delegation,
-    store_set_delegation_id, ProxyDelegation,
+    ProxyDelegation,
 };
 use crate::reencryption_requests::{
-    abandon_all_proxy_tasks, abandon_proxy_task, get_all_fragments, get_reencryption_request_state,
-    store_add_data_id_task, store_add_delegatee_proxy_task, store_add_proxy_task_to_queue,
-    store_get_all_delegatee_proxy_tasks, store_get_all_proxy_tasks_in_queue,
-    store_get_data_id_tasks, store_get_delegatee_proxy_task, store_get_proxy_task,
-    store_is_list_of_delegatee_proxy_tasks_empty, store_remove_data_id_task,
-    store_remove_delegatee_proxy_task, store_remove_proxy_task, store_remove_proxy_task_from_queue,
-    store_set_proxy_task, timeout_proxy_task, ProxyTask, ReencryptionRequestState,
+    abandon_all_proxy_tasks, abandon_proxy_task, archive_delegatee_proxy_tasks, get_all_fragments,
+    get_proxy_tasks_state, get_reencryption_request_state, migrate_legacy_proxy_tasks,
+    remove_all_data_id_proxy_tasks, remove_all_delegatee_proxy_tasks, resolve_timed_out_request,
+    resolve_timed_out_tasks, store_add_delegatee_request, store_add_proxy_task_to_queue,
+    store_add_task_timeout, store_get_all_delegatee_proxy_tasks, store_get_all_delegatee_requests,
+    store_get_delegatee_data_ids, store_get_delegatee_proxy_task, store_get_next_request_seq,
+    store_get_proxy_task, store_get_proxy_tasks_history, store_get_proxy_tasks_in_queue,
+    store_is_list_of_delegatee_proxy_tasks_empty, store_remove_delegatee_request,
+    store_remove_proxy_task_from_queue, store_set_next_request_seq, store_set_proxy_task,
+    update_refunds_map, ProxyTask, ReencryptionRequestState,
+};
+use crate::subscriptions::{
+    store_get_all_delegator_subscriptions, store_get_subscription, store_remove_subscription,
+    store_set_subscription, Subscription,
 };
 use cosmwasm_std::{
-    entry_point, to_binary, Addr, Attribute, BankMsg, Binary, Coin, Deps, DepsMut, Env,
-    MessageInfo, Response, StdError, StdResult, Storage, SubMsg, Uint128,
+    entry_point, to_binary, Addr, Attribute, BankMsg, Binary, BlockInfo, Coin, Deps, DepsMut, Env,
+    Event, MessageInfo, Response, StdError, StdResult, Storage, SubMsg, Uint128,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::common::add_bank_msg;
+use crate::access_requests::{
+    prune_expired_access_requests, remove_data_access_requests, store_get_access_request,
+    store_get_all_delegator_access_requests, store_get_next_access_request_id,
+    store_remove_access_request, store_set_access_request, store_set_next_access_request_id,
+    AccessRequest, AccessRequestTarget,
+};
+use crate::common::{add_bank_msg, get_query_limit, DEFAULT_MIGRATION_LIMIT, MAX_QUERY_SCAN};
+use crate::data_tags::{
+    store_get_all_data_tags, store_get_data_ids_by_tag, store_remove_all_data_tags,
+    store_set_data_tag,
+};
 use crate::reencryption_permissions::{
-    get_permission, store_add_data_labels, store_add_delegatee_labels, store_get_all_data_labels,
-    store_get_all_delegatee_labels, store_remove_data_labels, store_remove_delegatee_labels,
+    is_reencryption_permitted, store_add_data_labels, store_add_delegatee_labels,
+    store_get_all_data_access_rules, store_get_all_data_labels,
+    store_get_all_delegatee_labels_with_expiration, store_get_data_access_rule,
+    store_get_delegatee_policy, store_remove_all_data_access_rules, store_remove_data_access_rule,
+    store_remove_data_labels, store_remove_delegatee_labels, store_remove_delegatee_policy,
+    store_set_data_access_rule, store_set_delegatee_policy, DataAccessRule, LabelExpression,
 };
 
 //use umbral_pre::{Capsule, CapsuleFrag, DeserializableFromArray, PublicKey};
@@ -60,6 +105,13 @@ pub const DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT: u128 = 100;
 pub const DEFAULT_TIMEOUT_HEIGHT: u64 = 50;
 pub const DEFAULT_WITHDRAWAL_PERIOD: u64 = 500;
 
+// Maximum nesting of delegatee policy expressions
+pub const MAX_POLICY_DEPTH: u32 = 8;
+// Number of blocks until pending access request expires
+pub const ACCESS_REQUEST_TIMEOUT_HEIGHT: u64 = 10000;
+// Percentage of slashed stake paid to caller of ResolveTimedOutRequests
+pub const KEEPER_BOUNTY_PERCENTAGE: u128 = 10;
+
 pub const FRAGMENT_VERIFICATION_ERROR: &str = "Fragment verification failed: ";
 
 #[entry_point]
@@ -79,6 +131,7 @@ pub fn instantiate(
         withdrawn: false,
         terminate_height: 0,
         withdrawal_period: msg.withdrawal_period.unwrap_or(DEFAULT_WITHDRAWAL_PERIOD),
+        paused: false,
     };
 
     if state.threshold == 0 {
@@ -114,7 +167,7 @@ pub fn instantiate(
 
     if let Some(ref proxies_addr) = msg.proxies {
         for proxy_addr in proxies_addr {
-            store_set_proxy_entry(deps.storage, proxy_addr, &new_proxy);
+            store_set_proxy_entry(deps.storage, proxy_addr, &new_proxy)?;
         }
     };
 
@@ -154,7 +207,7 @@ fn try_add_proxy(
     ensure_admin(&state, &info.sender)?;
     ensure_not_terminated(&state)?;
 
-    if store_get_proxy_entry(deps.storage, proxy_addr).is_some() {
+    if store_get_proxy_entry(deps.storage, proxy_addr)?.is_some() {
         return generic_err!(format!("{} is already proxy", proxy_addr));
     }
 
@@ -164,7 +217,7 @@ fn try_add_proxy(
         stake_amount: Uint128::new(0),
     };
 
-    store_set_proxy_entry(deps.storage, proxy_addr, &new_proxy);
+    store_set_proxy_entry(deps.storage, proxy_addr, &new_proxy)?;
 
     // Return response
     response
@@ -187,13 +240,30 @@ fn try_remove_proxy(
     proxy_addr: &Addr,
 ) -> StdResult<Response> {
     let state: State = store_get_state(deps.storage)?;
-    let staking_config: StakingConfig = store_get_staking_config(deps.storage)?;
 
     ensure_admin(&state, &info.sender)?;
+
+    remove_proxy(&mut response, deps.storage, proxy_addr)?;
+
+    response
+        .attributes
+        .push(Attribute::new("admin", info.sender.as_str()));
+    Ok(response)
+}
+
+fn remove_proxy(
+    response: &mut Response,
+    storage: &mut dyn Storage,
+    proxy_addr: &Addr,
+) -> StdResult<()> {
+    // Shared by admin and sudo
+    let state: State = store_get_state(storage)?;
+    let staking_config: StakingConfig = store_get_staking_config(storage)?;
+
     ensure_not_terminated(&state)?;
 
     // check if proxy_addr is authorised
-    let mut proxy = match store_get_proxy_entry(deps.storage, proxy_addr) {
+    let mut proxy = match store_get_proxy_entry(storage, proxy_addr)? {
         None => generic_err!("Sender is not a proxy"),
         Some(proxy) => Ok(proxy),
     }?;
@@ -202,38 +272,34 @@ fn try_remove_proxy(
     if proxy.proxy_pubkey.is_some() {
         // In leaving state this was already done
         if proxy.state != ProxyState::Leaving {
-            store_set_is_proxy_active(deps.storage, proxy_addr, false);
-            remove_proxy_from_delegations(deps.storage, proxy_addr)?;
+            store_set_is_proxy_active(storage, proxy_addr, false)?;
+            remove_proxy_from_delegations(storage, proxy_addr)?;
         }
 
-        abandon_all_proxy_tasks(deps.storage, proxy_addr, &mut response)?;
+        abandon_all_proxy_tasks(storage, proxy_addr, response)?;
     }
 
     // Update proxy entry to get correct stake amount after possible slashing
-    proxy = store_get_proxy_entry(deps.storage, proxy_addr).unwrap();
+    proxy = store_get_proxy_entry(storage, proxy_addr)?.unwrap();
 
     // Return remaining stake back to proxy
     add_bank_msg(
-        &mut response,
+        response,
         proxy_addr,
         proxy.stake_amount.u128(),
         &staking_config.stake_denom,
     );
 
     // Remove proxy entry = remove pubkey
-    store_remove_proxy_entry(deps.storage, proxy_addr);
+    store_remove_proxy_entry(storage, proxy_addr)?;
 
-    // Return response
     response
         .attributes
         .push(Attribute::new("action", "store_remove_proxy_entry"));
-    response
-        .attributes
-        .push(Attribute::new("admin", info.sender.as_str()));
     response
         .attributes
         .push(Attribute::new("proxy_addr", proxy_addr.as_str()));
-    Ok(response)
+    Ok(())
 }
 
 fn try_terminate_contract(
@@ -311,6 +377,154 @@ fn try_withdraw_contract(
     Ok(response)
 }
 
+fn try_set_paused(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    paused: bool,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+
+    ensure_admin(&state, &info.sender)?;
+
+    set_paused(&mut response, deps.storage, paused)?;
+
+    response
+        .attributes
+        .push(Attribute::new("admin", info.sender.as_str()));
+    Ok(response)
+}
+
+fn set_paused(response: &mut Response, storage: &mut dyn Storage, paused: bool) -> StdResult<()> {
+    // Shared by admin and sudo
+    let mut state: State = store_get_state(storage)?;
+
+    ensure_not_terminated(&state)?;
+
+    if state.paused == paused {
+        return match paused {
+            true => generic_err!("Contract is already paused."),
+            false => generic_err!("Contract is not paused."),
+        };
+    }
+
+    state.paused = paused;
+    store_set_state(storage, &state)?;
+
+    let action = match paused {
+        true => "pause_contract",
+        false => "unpause_contract",
+    };
+    response.attributes.push(Attribute::new("action", action));
+    Ok(())
+}
+
+fn try_update_config(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    minimum_proxy_stake_amount: &Option<Uint128>,
+    timeout_height: &Option<u64>,
+    proxy_whitelisting: &Option<bool>,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+
+    ensure_admin(&state, &info.sender)?;
+
+    update_config(
+        &mut response,
+        deps.storage,
+        minimum_proxy_stake_amount,
+        timeout_height,
+        proxy_whitelisting,
+    )?;
+
+    response
+        .attributes
+        .push(Attribute::new("admin", info.sender.as_str()));
+    Ok(response)
+}
+
+fn update_config(
+    response: &mut Response,
+    storage: &mut dyn Storage,
+    minimum_proxy_stake_amount: &Option<Uint128>,
+    timeout_height: &Option<u64>,
+    proxy_whitelisting: &Option<bool>,
+) -> StdResult<()> {
+    // Shared by admin and sudo
+    // Task reward and slash amounts aren't stored with tasks, changing them would break refunds
+    let mut state: State = store_get_state(storage)?;
+
+    ensure_not_terminated(&state)?;
+
+    if let Some(minimum_proxy_stake_amount) = minimum_proxy_stake_amount {
+        let mut staking_config: StakingConfig = store_get_staking_config(storage)?;
+        staking_config.minimum_proxy_stake_amount = *minimum_proxy_stake_amount;
+        store_set_staking_config(storage, &staking_config)?;
+    }
+
+    if let Some(timeout_height) = timeout_height {
+        if *timeout_height == 0 {
+            return generic_err!("Timeout height cannot be 0");
+        }
+
+        // Applies only to new tasks
+        let timeouts_config = TimeoutsConfig {
+            timeout_height: *timeout_height,
+        };
+        store_set_timeouts_config(storage, &timeouts_config)?;
+    }
+
+    if let Some(proxy_whitelisting) = proxy_whitelisting {
+        state.proxy_whitelisting = *proxy_whitelisting;
+        store_set_state(storage, &state)?;
+    }
+
+    response
+        .attributes
+        .push(Attribute::new("action", "update_config"));
+    Ok(())
+}
+
+fn try_update_admin(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    admin: &Addr,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+
+    ensure_admin(&state, &info.sender)?;
+
+    update_admin(&mut response, deps.storage, admin)?;
+
+    response
+        .attributes
+        .push(Attribute::new("admin", info.sender.as_str()));
+    Ok(response)
+}
+
+fn update_admin(response: &mut Response, storage: &mut dyn Storage, admin: &Addr) -> StdResult<()> {
+    // Shared by admin and sudo
+    // Allowed after termination so replaced admin can't withdraw contract
+    let mut state: State = store_get_state(storage)?;
+
+    state.admin = admin.clone();
+    store_set_state(storage, &state)?;
+
+    response
+        .attributes
+        .push(Attribute::new("action", "update_admin"));
+    response
+        .attributes
+        .push(Attribute::new("new_admin", admin.as_str()));
+    Ok(())
+}
+
 // Proxy actions
 
 fn try_register_proxy(
@@ -324,8 +538,9 @@ fn try_register_proxy(
     let state: State = store_get_state(deps.storage)?;
 
     ensure_not_terminated(&state)?;
+    ensure_not_paused(&state)?;
 
-    let mut proxy = match store_get_proxy_entry(deps.storage, &info.sender) {
+    let mut proxy = match store_get_proxy_entry(deps.storage, &info.sender)? {
         None => {
             if state.proxy_whitelisting {
                 // Whitelisting enabled - proxy is not authorised
@@ -338,7 +553,7 @@ fn try_register_proxy(
                     stake_amount: Uint128::new(0),
                 };
 
-                store_set_proxy_entry(deps.storage, &info.sender, &new_proxy);
+                store_set_proxy_entry(deps.storage, &info.sender, &new_proxy)?;
                 Ok(new_proxy)
             }
         }
@@ -376,8 +591,8 @@ fn try_register_proxy(
 
     proxy.state = ProxyState::Registered;
     proxy.stake_amount = proxy.stake_amount.checked_add(Uint128::new(funds_amount))?;
-    store_set_proxy_entry(deps.storage, &info.sender, &proxy);
-    store_set_is_proxy_active(deps.storage, &info.sender, true);
+    store_set_proxy_entry(deps.storage, &info.sender, &proxy)?;
+    store_set_is_proxy_active(deps.storage, &info.sender, true)?;
 
     // Return response
     response
@@ -401,7 +616,7 @@ fn try_unregister_proxy(
     ensure_not_withdrawn(&state)?;
 
     // Check if proxy is authorised
-    let mut proxy = match store_get_proxy_entry(deps.storage, &info.sender) {
+    let mut proxy = match store_get_proxy_entry(deps.storage, &info.sender)? {
         None => generic_err!("Sender is not a proxy"),
         Some(proxy) => Ok(proxy),
     }?;
@@ -413,7 +628,7 @@ fn try_unregister_proxy(
     }?;
 
     if proxy.state != ProxyState::Leaving {
-        store_set_is_proxy_active(deps.storage, &info.sender, false);
+        store_set_is_proxy_active(deps.storage, &info.sender, false)?;
         remove_proxy_from_delegations(deps.storage, &info.sender)?;
     }
 
@@ -421,7 +636,7 @@ fn try_unregister_proxy(
     abandon_all_proxy_tasks(deps.storage, &info.sender, &mut response)?;
 
     // Update proxy entry to get correct stake amount after possible slashing
-    proxy = store_get_proxy_entry(deps.storage, &info.sender).unwrap();
+    proxy = store_get_proxy_entry(deps.storage, &info.sender)?.unwrap();
 
     // Return remaining stake back to proxy
     add_bank_msg(
@@ -434,7 +649,7 @@ fn try_unregister_proxy(
     proxy.stake_amount = Uint128::new(0);
     proxy.state = ProxyState::Authorised;
     proxy.proxy_pubkey = None;
-    store_set_proxy_entry(deps.storage, &info.sender, &proxy);
+    store_set_proxy_entry(deps.storage, &info.sender, &proxy)?;
 
     // Return response
     response
@@ -459,7 +674,7 @@ fn try_deactivate_proxy(
 
     ensure_not_withdrawn(&state)?;
 
-    match store_get_proxy_entry(deps.storage, &info.sender) {
+    match store_get_proxy_entry(deps.storage, &info.sender)? {
         None => {
             // Unregistered state
             return generic_err!("Sender is not a proxy");
@@ -469,11 +684,11 @@ fn try_deactivate_proxy(
                 return generic_err!("Proxy already deactivated");
             }
 
-            store_set_is_proxy_active(deps.storage, &info.sender, false);
+            store_set_is_proxy_active(deps.storage, &info.sender, false)?;
             remove_proxy_from_delegations(deps.storage, &info.sender)?;
 
             proxy.state = ProxyState::Leaving;
-            store_set_proxy_entry(deps.storage, &info.sender, &proxy);
+            store_set_proxy_entry(deps.storage, &info.sender, &proxy)?;
         }
     }
 
@@ -500,65 +715,24 @@ fn try_provide_reencrypted_fragment(
 
     ensure_not_withdrawn(&state)?;
 
-    // Get proxy_pubkey or return error
-    let mut proxy = match store_get_proxy_entry(deps.storage, &info.sender) {
-        None => generic_err!("Proxy not registered"),
-        Some(proxy) => Ok(proxy),
-    }?;
-
-    if proxy.proxy_pubkey.is_none() {
-        return generic_err!("Proxy not active");
-    }
-
-    // Get task_id or return error
-    let task_id: u64 =
-        match store_get_delegatee_proxy_task(deps.storage, data_id, delegatee_pubkey, &info.sender)
-        {
-            None => generic_err!("This fragment was not requested."),
-            Some(task_id) => Ok(task_id),
-        }?;
-
-    // Task must exist - panic otherwise
-    let mut proxy_task = store_get_proxy_task(deps.storage, &task_id).unwrap();
-    if env.block.height >= proxy_task.timeout_height {
-        return generic_err!("Request timed out.");
-    }
-
-    if proxy_task.fragment.is_some() {
-        return generic_err!("Fragment already provided.");
-    }
+    let mut proxy = ensure_active_proxy(deps.storage, &info.sender)?;
 
-    /*
-    let data_entry = store_get_data_entry(deps.storage, data_id).unwrap();
-    verify_fragment(
+    provide_fragment(
+        deps.storage,
+        &env,
+        &info.sender,
+        data_id,
+        delegatee_pubkey,
         fragment,
-        &data_entry.capsule,
-        &data_entry.delegator_pubkey,
-        &proxy_task.delegatee_pubkey,
     )?;
-     */
-
-    if get_all_fragments(deps.storage, data_id, delegatee_pubkey).contains(&fragment.to_string()) {
-        return generic_err!("Fragment already provided by other proxy.");
-    }
 
     let staking_config: StakingConfig = store_get_staking_config(deps.storage)?;
 
-    // Add fragment to task
-
-    proxy_task.fragment = Some(fragment.to_string());
-
     // Add reward to proxy stake + return withdrawn stake
     let return_stake_amount = staking_config.per_proxy_task_reward_amount.u128()
         + staking_config.per_task_slash_stake_amount.u128();
     proxy.stake_amount = Uint128::new(proxy.stake_amount.u128() + return_stake_amount);
-
-    // Update maps
-    store_set_proxy_entry(deps.storage, &info.sender, &proxy);
-    store_set_proxy_task(deps.storage, &task_id, &proxy_task);
-
-    // Remove task from proxy queue as it's completed
-    store_remove_proxy_task_from_queue(deps.storage, &info.sender, &task_id);
+    store_set_proxy_entry(deps.storage, &info.sender, &proxy)?;
 
     // Return response
     response
@@ -577,6 +751,99 @@ fn try_provide_reencrypted_fragment(
     Ok(response)
 }
 
+fn try_provide_reencrypted_fragments(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    fragments: &[ProxyFragment],
+    all_or_nothing: bool,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+
+    ensure_not_withdrawn(&state)?;
+
+    let mut proxy = ensure_active_proxy(deps.storage, &info.sender)?;
+
+    if fragments.is_empty() {
+        return generic_err!("No fragments provided.");
+    }
+
+    let mut n_provided_fragments: u128 = 0;
+    let mut fragments_response: Vec<ProvideFragmentResponse> = Vec::new();
+
+    for proxy_fragment in fragments {
+        // Failed fragment doesn't modify storage so other fragments can still be provided
+        let error = match provide_fragment(
+            deps.storage,
+            &env,
+            &info.sender,
+            &proxy_fragment.data_id,
+            &proxy_fragment.delegatee_pubkey,
+            &proxy_fragment.fragment,
+        ) {
+            Ok(()) => {
+                n_provided_fragments += 1;
+                None
+            }
+            Err(err) => {
+                if all_or_nothing {
+                    return Err(err);
+                }
+                match err {
+                    StdError::GenericErr { msg, .. } => Some(msg),
+                    err => Some(err.to_string()),
+                }
+            }
+        };
+
+        fragments_response.push(ProvideFragmentResponse {
+            data_id: proxy_fragment.data_id.clone(),
+            delegatee_pubkey: proxy_fragment.delegatee_pubkey.clone(),
+            success: error.is_none(),
+            error,
+        });
+    }
+
+    let staking_config: StakingConfig = store_get_staking_config(deps.storage)?;
+
+    // Add total reward to proxy stake + return withdrawn stake
+    if n_provided_fragments > 0 {
+        let return_stake_amount = (staking_config.per_proxy_task_reward_amount.u128()
+            + staking_config.per_task_slash_stake_amount.u128())
+            * n_provided_fragments;
+        proxy.stake_amount = proxy
+            .stake_amount
+            .checked_add(Uint128::new(return_stake_amount))?;
+        store_set_proxy_entry(deps.storage, &info.sender, &proxy)?;
+    }
+
+    let json_response = ExecuteMsgJSONResponse::ProvideReencryptedFragments {
+        fragments: fragments_response,
+        new_stake: proxy.stake_amount,
+    };
+    let serialized_json_response = match serde_json::to_string(&json_response) {
+        Ok(s) => Ok(s),
+        Err(_err) => generic_err!("failed to serialize json response"),
+    }?;
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "provide_reencrypted_fragments"));
+    response.attributes.push(Attribute::new(
+        "provided_fragments",
+        n_provided_fragments.to_string(),
+    ));
+    response
+        .attributes
+        .push(Attribute::new("new_stake", proxy.stake_amount));
+    response
+        .attributes
+        .push(Attribute::new("json", serialized_json_response));
+    Ok(response)
+}
+
 fn try_skip_reencryption_task(
     mut response: Response,
     deps: DepsMut,
@@ -594,7 +861,7 @@ fn try_skip_reencryption_task(
     let mut delegator_retrieve_funds_amount: HashMap<Addr, u128> = HashMap::new();
 
     // Get proxy pubkey
-    let proxy = match store_get_proxy_entry(deps.storage, &info.sender) {
+    let proxy = match store_get_proxy_entry(deps.storage, &info.sender)? {
         None => generic_err!("Sender is not a proxy"),
         Some(proxy) => Ok(proxy),
     }?;
@@ -604,14 +871,17 @@ fn try_skip_reencryption_task(
     }
 
     // Get task_id or return error
-    let task_id: u64 =
-        match store_get_delegatee_proxy_task(deps.storage, data_id, delegatee_pubkey, &info.sender)
-        {
-            None => generic_err!("Task doesn't exist."),
-            Some(task_id) => Ok(task_id),
-        }?;
+    let task_id: u64 = match store_get_delegatee_proxy_task(
+        deps.storage,
+        data_id,
+        delegatee_pubkey,
+        &info.sender,
+    )? {
+        None => generic_err!("Task doesn't exist."),
+        Some(task_id) => Ok(task_id),
+    }?;
 
-    let proxy_task = store_get_proxy_task(deps.storage, &task_id).unwrap();
+    let proxy_task = store_get_proxy_task(deps.storage, &task_id)?.unwrap();
 
     if proxy_task.fragment.is_some() {
         return generic_err!("Task was already completed.");
@@ -663,42 +933,22 @@ pub fn try_resolve_timed_out_request(
 
     ensure_not_withdrawn(&state)?;
 
-    if get_reencryption_request_state(
-        deps.storage,
-        &state,
-        data_id,
-        delegatee_pubkey,
-        &env.block.height,
-    ) != ReencryptionRequestState::TimedOut
+    if get_reencryption_request_state(deps.storage, &state, data_id, delegatee_pubkey, &env.block)?
+        != ReencryptionRequestState::TimedOut
     {
         return generic_err!("Task is not timed-out.");
     }
 
     let staking_config: StakingConfig = store_get_staking_config(deps.storage).unwrap();
-    let task_ids = store_get_all_delegatee_proxy_tasks(deps.storage, data_id, delegatee_pubkey);
 
     let mut delegator_retrieve_funds_amount: HashMap<Addr, u128> = HashMap::new();
-    for i in task_ids {
-        match store_get_proxy_task(deps.storage, &i) {
-            // Skip if task was deleted
-            None => {}
-            Some(proxy_task) => {
-                // We can move pointer when task is already resolved in case of abandoned request
-                if proxy_task.resolved {
-                    continue;
-                }
-
-                // Resolve timed-out task
-                timeout_proxy_task(
-                    deps.storage,
-                    &i,
-                    &staking_config,
-                    &mut delegator_retrieve_funds_amount,
-                )
-                .unwrap();
-            }
-        }
-    }
+    resolve_timed_out_request(
+        deps.storage,
+        data_id,
+        delegatee_pubkey,
+        &staking_config,
+        &mut delegator_retrieve_funds_amount,
+    )?;
 
     if delegator_retrieve_funds_amount.len() > 1 {
         return generic_err!("One request can't have multiple delegators.");
@@ -726,35 +976,158 @@ pub fn try_resolve_timed_out_request(
     Ok(response)
 }
 
-fn try_withdraw_stake(
+pub fn try_cancel_reencryption_request(
     mut response: Response,
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    stake_amount: &Option<Uint128>,
+    data_id: &str,
+    delegatee_pubkey: &str,
 ) -> StdResult<Response> {
     let state: State = store_get_state(deps.storage)?;
 
     ensure_not_withdrawn(&state)?;
 
-    let staking_config = store_get_staking_config(deps.storage)?;
-
-    // Check if proxy is authorised
-    let mut proxy = match store_get_proxy_entry(deps.storage, &info.sender) {
-        None => generic_err!("Sender is not a proxy"),
-        Some(proxy) => Ok(proxy),
-    }?;
+    let task_ids = store_get_all_delegatee_proxy_tasks(deps.storage, data_id, delegatee_pubkey)?;
+    if task_ids.is_empty() {
+        return generic_err!("Reencryption request doesn't exist.");
+    }
 
-    // Check maximum amount that can be withdrawn
-    let maximum_withdrawable_amount =
-        get_maximum_withdrawable_stake_amount(&staking_config, &proxy);
+    // All tasks of request share refund address
+    let proxy_task = store_get_proxy_task(deps.storage, &task_ids[0])?.unwrap();
+    if proxy_task.refund_addr != info.sender {
+        return generic_err!("Only requester can cancel reencryption request.");
+    }
 
-    if maximum_withdrawable_amount == 0 {
-        return generic_err!("Not enough stake to withdraw");
+    if get_reencryption_request_state(deps.storage, &state, data_id, delegatee_pubkey, &env.block)?
+        != ReencryptionRequestState::Ready
+    {
+        return generic_err!("Only pending reencryption request can be cancelled.");
     }
 
-    // Withdraw maximum possible in case of stake_amount is None
-    let mut withdraw_stake_amount = stake_amount
+    let staking_config: StakingConfig = store_get_staking_config(deps.storage)?;
+
+    // Delivered fragments stay rewarded, other proxies get stake back without slashing
+    let mut refund_amounts: HashMap<Addr, u128> = HashMap::new();
+    remove_all_delegatee_proxy_tasks(
+        deps.storage,
+        data_id,
+        delegatee_pubkey,
+        &staking_config,
+        &mut refund_amounts,
+    )?;
+
+    for (refund_addr, refund_amount) in refund_amounts {
+        add_bank_msg(
+            &mut response,
+            &refund_addr,
+            refund_amount,
+            &staking_config.stake_denom,
+        );
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "cancel_reencryption_request"));
+    response.attributes.push(Attribute::new("data_id", data_id));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_resolve_timed_out_requests(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+
+    ensure_not_withdrawn(&state)?;
+
+    let staking_config: StakingConfig = store_get_staking_config(deps.storage)?;
+
+    let mut refund_amounts: HashMap<Addr, u128> = HashMap::new();
+    let resolved_tasks = resolve_timed_out_tasks(
+        deps.storage,
+        &state,
+        &staking_config,
+        &env.block,
+        get_query_limit(limit),
+        &mut refund_amounts,
+    )?;
+
+    for (refund_addr, refund_amount) in refund_amounts {
+        add_bank_msg(
+            &mut response,
+            &refund_addr,
+            refund_amount,
+            &staking_config.stake_denom,
+        );
+    }
+
+    // Keeper gets share of stake slashed by this sweep, abandoned tasks were slashed before
+    let n_slashed_tasks = resolved_tasks
+        .iter()
+        .filter(|proxy_task| !proxy_task.abandoned && proxy_task.fragment.is_none())
+        .count() as u128;
+    let keeper_bounty = staking_config.per_task_slash_stake_amount.u128()
+        * n_slashed_tasks
+        * KEEPER_BOUNTY_PERCENTAGE
+        / 100;
+    add_bank_msg(
+        &mut response,
+        &info.sender,
+        keeper_bounty,
+        &staking_config.stake_denom,
+    );
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "resolve_timed_out_requests"));
+    response.attributes.push(Attribute::new(
+        "resolved_tasks",
+        resolved_tasks.len().to_string(),
+    ));
+    response
+        .attributes
+        .push(Attribute::new("keeper_bounty", keeper_bounty.to_string()));
+    Ok(response)
+}
+
+fn try_withdraw_stake(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    stake_amount: &Option<Uint128>,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+
+    ensure_not_withdrawn(&state)?;
+
+    let staking_config = store_get_staking_config(deps.storage)?;
+
+    // Check if proxy is authorised
+    let mut proxy = match store_get_proxy_entry(deps.storage, &info.sender)? {
+        None => generic_err!("Sender is not a proxy"),
+        Some(proxy) => Ok(proxy),
+    }?;
+
+    // Check maximum amount that can be withdrawn
+    let maximum_withdrawable_amount =
+        get_maximum_withdrawable_stake_amount(&staking_config, &proxy);
+
+    if maximum_withdrawable_amount == 0 {
+        return generic_err!("Not enough stake to withdraw");
+    }
+
+    // Withdraw maximum possible in case of stake_amount is None
+    let mut withdraw_stake_amount = stake_amount
         .map(|data| data.u128())
         .unwrap_or(maximum_withdrawable_amount);
 
@@ -765,7 +1138,7 @@ fn try_withdraw_stake(
 
     // Update proxy stake amount
     proxy.stake_amount = Uint128::new(proxy.stake_amount.u128() - withdraw_stake_amount);
-    store_set_proxy_entry(deps.storage, &info.sender, &proxy);
+    store_set_proxy_entry(deps.storage, &info.sender, &proxy)?;
     add_bank_msg(
         &mut response,
         &info.sender,
@@ -798,7 +1171,7 @@ fn try_add_stake(
     ensure_not_terminated(&state)?;
 
     // Check if proxy is authorised
-    let mut proxy = match store_get_proxy_entry(deps.storage, &info.sender) {
+    let mut proxy = match store_get_proxy_entry(deps.storage, &info.sender)? {
         None => generic_err!("Sender is not a proxy"),
         Some(proxy) => Ok(proxy),
     }?;
@@ -809,7 +1182,7 @@ fn try_add_stake(
 
     // Update proxy stake amount
     proxy.stake_amount = Uint128::new(proxy.stake_amount.u128() + info.funds[0].amount.u128());
-    store_set_proxy_entry(deps.storage, &info.sender, &proxy);
+    store_set_proxy_entry(deps.storage, &info.sender, &proxy)?;
 
     // Return response
     response
@@ -829,32 +1202,36 @@ fn try_add_stake(
 fn try_add_data(
     mut response: Response,
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     data_id: &str,
     delegator_pubkey: &str,
     capsule: &str,
+    tags: &Option<Vec<Tag>>,
     data_labels: &Option<Vec<String>>,
+    expires_at: &Option<Expiration>,
 ) -> StdResult<Response> {
     let state: State = store_get_state(deps.storage)?;
 
     ensure_not_terminated(&state)?;
+    ensure_not_paused(&state)?;
 
-    if store_get_data_entry(deps.storage, data_id).is_some() {
-        return generic_err!(format!("Entry with ID {} already exist.", data_id));
-    }
-
-    ensure_delegator(deps.storage, delegator_pubkey, &info.sender)?;
+    add_data_entry(
+        deps.storage,
+        &env.block,
+        &info.sender,
+        data_id,
+        delegator_pubkey,
+        capsule,
+        tags,
+        data_labels,
+        expires_at,
+    )?;
 
-    let entry = DataEntry {
-        delegator_pubkey: delegator_pubkey.to_string(),
-        capsule: capsule.to_string(),
-    };
-    store_set_data_entry(deps.storage, data_id, &entry);
+    request_subscribed_reencryptions(&mut response, deps.storage, &env.block, data_id)?;
 
     // Add data labels
     if let Some(data_labels) = data_labels {
-        store_add_data_labels(deps.storage, data_id, data_labels);
         response
             .attributes
             .push(Attribute::new("data_labels", data_labels.join(", ")));
@@ -875,6 +1252,68 @@ fn try_add_data(
     Ok(response)
 }
 
+fn try_add_data_batch(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    entries: &[DataBatchEntry],
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+
+    ensure_not_terminated(&state)?;
+    ensure_not_paused(&state)?;
+
+    if entries.is_empty() {
+        return generic_err!("No data entries provided.");
+    }
+
+    // Reject duplicates within batch before anything is stored
+    let mut data_ids: HashSet<&str> = HashSet::new();
+    for entry in entries {
+        if !data_ids.insert(&entry.data_id) {
+            return generic_err!(format!("Duplicate data ID {} in batch.", entry.data_id));
+        }
+    }
+
+    // Entire batch fails if any entry already exists or belongs to different delegator
+    for entry in entries {
+        add_data_entry(
+            deps.storage,
+            &env.block,
+            &info.sender,
+            &entry.data_id,
+            &entry.delegator_pubkey,
+            &entry.capsule,
+            &entry.tags,
+            &entry.data_labels,
+            &entry.expires_at,
+        )?;
+    }
+
+    for entry in entries {
+        request_subscribed_reencryptions(&mut response, deps.storage, &env.block, &entry.data_id)?;
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "add_data_batch"));
+    response
+        .attributes
+        .push(Attribute::new("owner", info.sender.as_str()));
+    response.attributes.push(Attribute::new(
+        "data_ids",
+        entries
+            .iter()
+            .map(|entry| entry.data_id.as_str())
+            .collect::<Vec<&str>>()
+            .join(", "),
+    ));
+
+    Ok(response)
+}
+
 fn try_remove_data(
     mut response: Response,
     deps: DepsMut,
@@ -887,7 +1326,7 @@ fn try_remove_data(
     ensure_not_terminated(&state)?;
 
     // Only data owner can remove data
-    let data_entry: DataEntry = match store_get_data_entry(deps.storage, data_id) {
+    let data_entry: DataEntry = match store_get_data_entry(deps.storage, data_id)? {
         None => generic_err!(format!("Entry with ID {} does not exist.", data_id)),
         Some(data_entry) => Ok(data_entry),
     }?;
@@ -896,58 +1335,38 @@ fn try_remove_data(
 
     let staking_config = store_get_staking_config(deps.storage)?;
 
-    let tasks = store_get_data_id_tasks(deps.storage, data_id);
-
-    let mut refund: u128 = 0;
-    let mut proxy_stake = Vec::new();
-
-    for task_id in tasks.iter() {
-        let proxy_task = store_get_proxy_task(deps.storage, task_id).unwrap();
-
-        // Remove task from proxy queue
-        store_remove_proxy_task_from_queue(deps.storage, &proxy_task.proxy_addr, task_id);
+    // Resolved tasks were already refunded and slashed, refunds go back to funding source
+    let mut refund_amounts: HashMap<Addr, u128> = HashMap::new();
+    let stake_returned_proxies = remove_all_data_id_proxy_tasks(
+        deps.storage,
+        data_id,
+        &staking_config,
+        &mut refund_amounts,
+    )?;
 
-        // Remove delegatee proxy task
-        store_remove_delegatee_proxy_task(
-            deps.storage,
-            data_id,
-            &proxy_task.delegatee_pubkey,
-            &proxy_task.proxy_addr,
+    for (refund_addr, refund_amount) in refund_amounts {
+        add_bank_msg(
+            &mut response,
+            &refund_addr,
+            refund_amount,
+            &staking_config.stake_denom,
         );
+    }
 
-        // Remove proxy task
-        store_remove_proxy_task(deps.storage, task_id);
-        store_remove_data_id_task(deps.storage, data_id, task_id);
-
-        if proxy_task.fragment.is_none() {
-            refund += staking_config.per_proxy_task_reward_amount.u128();
-
-            let mut proxy = store_get_proxy_entry(deps.storage, &proxy_task.proxy_addr).unwrap();
-
-            // Give back stake to proxy
-            proxy.stake_amount = proxy
-                .stake_amount
-                .checked_add(staking_config.per_task_slash_stake_amount)?;
-            store_set_proxy_entry(deps.storage, &proxy_task.proxy_addr, &proxy);
-
+    let mut proxy_stake = Vec::new();
+    for proxy_addr in stake_returned_proxies {
+        if let Some(proxy) = store_get_proxy_entry(deps.storage, &proxy_addr)? {
             proxy_stake.push(ProxyStakeResponse {
-                proxy_addr: proxy_task.proxy_addr.clone(),
+                proxy_addr,
                 stake: proxy.stake_amount,
             });
         }
     }
 
-    // Return stake from unfinished tasks to delegator
-    if refund > 0 {
-        add_bank_msg(
-            &mut response,
-            &info.sender,
-            refund,
-            &staking_config.stake_denom,
-        );
-    }
-
-    store_remove_data_entry(deps.storage, data_id);
+    store_remove_data_entry(deps.storage, data_id)?;
+    store_remove_all_data_tags(deps.storage, data_id);
+    store_remove_all_data_access_rules(deps.storage, data_id);
+    store_remove_data_ownership_transfer(deps.storage, data_id);
 
     let json_response = ExecuteMsgJSONResponse::RemoveData {
         proxies: proxy_stake,
@@ -965,782 +1384,3105 @@ fn try_remove_data(
     Ok(response)
 }
 
-fn try_add_delegation(
+fn try_update_data_capsule(
     mut response: Response,
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    delegator_pubkey: &str,
-    delegatee_pubkey: &str,
-    proxy_delegations: &[ProxyDelegationString],
-    delegatee_labels: &Option<Vec<String>>,
+    data_id: &str,
+    capsule: &str,
 ) -> StdResult<Response> {
-    ensure_delegator(deps.storage, delegator_pubkey, &info.sender)?;
+    let state: State = store_get_state(deps.storage)?;
 
-    let mut state: State = store_get_state(deps.storage)?;
     ensure_not_terminated(&state)?;
+    ensure_data_owner(deps.storage, data_id, &info.sender)?;
 
-    let staking_config: StakingConfig = store_get_staking_config(deps.storage)?;
-
-    if !store_is_proxy_delegation_empty(deps.storage, delegator_pubkey, delegatee_pubkey) {
-        return generic_err!("Delegation already exists.");
-    }
-
-    let n_minimum_proxies = get_n_minimum_proxies_for_refund(&state, &staking_config);
-
-    if proxy_delegations.len() < n_minimum_proxies as usize {
-        return generic_err!(format!("Required at least {} proxies.", n_minimum_proxies));
+    let mut data_entry: DataEntry = store_get_data_entry(deps.storage, data_id)?.unwrap();
+    if data_entry.capsule == capsule {
+        return generic_err!("Capsule is unchanged.");
     }
 
-    for proxy_delegation in proxy_delegations {
-        // Proxy must be registered
-        match store_get_proxy_entry(deps.storage, &proxy_delegation.proxy_addr) {
-            None => {
-                return generic_err!(format!(
-                    "Unknown proxy with address {}",
-                    &proxy_delegation.proxy_addr
-                ));
-            }
-            Some(proxy_entry) => {
-                if proxy_entry.proxy_pubkey.is_none() {
-                    return generic_err!(format!(
-                        "Unregistered proxy with address {}",
-                        &proxy_delegation.proxy_addr
-                    ));
-                }
-            }
-        }
-
-        if store_get_proxy_delegation_id(
-            deps.storage,
-            delegator_pubkey,
-            delegatee_pubkey,
-            &proxy_delegation.proxy_addr,
-        )
-        .is_some()
-        {
-            return generic_err!(format!(
-                "Delegation string was already provided for proxy {}.",
-                &proxy_delegation.proxy_addr
-            ));
-        }
-
-        let delegation = ProxyDelegation {
-            delegator_pubkey: delegator_pubkey.to_string(),
-            delegatee_pubkey: delegatee_pubkey.to_string(),
-            delegation_string: proxy_delegation.delegation_string.clone(),
-        };
+    // Fragments were created for previous capsule
+    let staking_config = store_get_staking_config(deps.storage)?;
+    let mut refund_amounts: HashMap<Addr, u128> = HashMap::new();
+    remove_all_data_id_proxy_tasks(deps.storage, data_id, &staking_config, &mut refund_amounts)?;
 
-        store_set_delegation(deps.storage, &state.next_delegation_id, &delegation);
-        store_set_delegation_id(
-            deps.storage,
-            delegator_pubkey,
-            delegatee_pubkey,
-            &proxy_delegation.proxy_addr,
-            &state.next_delegation_id,
-        );
-        store_add_per_proxy_delegation(
-            deps.storage,
-            &proxy_delegation.proxy_addr,
-            &state.next_delegation_id,
+    for (refund_addr, refund_amount) in refund_amounts {
+        add_bank_msg(
+            &mut response,
+            &refund_addr,
+            refund_amount,
+            &staking_config.stake_denom,
         );
-
-        state.next_delegation_id += 1;
     }
 
-    store_set_state(deps.storage, &state)?;
+    // Data labels and tags are preserved
+    data_entry.capsule = capsule.to_string();
+    store_set_data_entry(deps.storage, data_id, &data_entry)?;
 
-    // Add delegatee labels
-    if let Some(delegatee_labels) = delegatee_labels {
-        store_add_delegatee_labels(
-            deps.storage,
-            &info.sender,
-            delegatee_pubkey,
-            delegatee_labels,
-        );
-        response.attributes.push(Attribute::new(
-            "delegatee_labels",
-            delegatee_labels.join(", "),
-        ));
-    }
+    // Delegatees have to request re-encryption again
+    response.events.push(
+        Event::new("data_capsule_updated")
+            .add_attribute("data_id", data_id)
+            .add_attribute("delegator_pubkey", &data_entry.delegator_pubkey),
+    );
 
     // Return response
     response
         .attributes
-        .push(Attribute::new("action", "add_delegation"));
-    response
-        .attributes
-        .push(Attribute::new("delegator_address", info.sender.as_str()));
-    response
-        .attributes
-        .push(Attribute::new("delegator_pubkey", delegator_pubkey));
+        .push(Attribute::new("action", "update_data_capsule"));
     response
         .attributes
-        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
-
+        .push(Attribute::new("owner", info.sender.as_str()));
+    response.attributes.push(Attribute::new("data_id", data_id));
     Ok(response)
 }
 
-fn try_request_reencryption(
+fn try_prune_expired_data(
     mut response: Response,
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
-    data_id: &str,
-    delegatee_pubkey: &str,
+    _info: MessageInfo,
+    limit: Option<u32>,
 ) -> StdResult<Response> {
-    // Load config
-    let mut state: State = store_get_state(deps.storage)?;
-    ensure_not_terminated(&state)?;
-
-    let staking_config: StakingConfig = store_get_staking_config(deps.storage)?;
-    let timeouts_config: TimeoutsConfig = store_get_timeouts_config(deps.storage)?;
-
-    let data_entry: DataEntry = match store_get_data_entry(deps.storage, data_id) {
-        None => generic_err!("Data entry doesn't exist."),
-        Some(data_entry) => Ok(data_entry),
-    }?;
-
-    let delegator_addr =
-        match store_get_delegator_address(deps.storage, &data_entry.delegator_pubkey) {
-            Some(delegator_addr) => Ok(delegator_addr),
-            None => generic_err!("Invalid delegator pubkey."),
-        }?;
-
-    // Get selected proxies for current delegation
-    let proxy_addresses = store_get_all_proxies_from_delegation(
-        deps.storage,
-        &data_entry.delegator_pubkey,
-        delegatee_pubkey,
-    );
-
-    if proxy_addresses.is_empty() {
-        return generic_err!("ProxyDelegation doesn't exist.");
-    }
-
-    // Check if encryption was permitted
-    if info.sender != delegator_addr
-        && !get_permission(deps.storage, &delegator_addr, delegatee_pubkey, data_id)
-    {
-        return generic_err!("Reencryption is not permitted.");
-    }
-
-    // Get number of proxies with enough stake
-    let n_available_proxies = get_n_available_proxies_from_delegation(
-        deps.storage,
-        &data_entry.delegator_pubkey,
-        delegatee_pubkey,
-        &staking_config.per_task_slash_stake_amount.u128(),
-    );
-
-    let n_minimum_proxies = get_n_minimum_proxies_for_refund(&state, &staking_config);
-
-    // Not enough request can be created
-    if n_available_proxies < n_minimum_proxies {
-        return generic_err!(format!(
-            "Proxies are too busy, try again later. Available {} proxies out of {}, minimum is {}",
-            n_available_proxies,
-            proxy_addresses.len(),
-            n_minimum_proxies
-        ));
-    }
-
-    if !store_is_list_of_delegatee_proxy_tasks_empty(deps.storage, data_id, delegatee_pubkey) {
-        return generic_err!("Reencryption already requested");
-    }
-
-    // Ensure more than per_proxy_task_reward_amount * number_of_proxies of stake provided
-    let total_required_reward_amount =
-        staking_config.per_proxy_task_reward_amount.u128() * n_available_proxies as u128;
-    ensure_stake(&staking_config, &info.funds, &total_required_reward_amount)?;
-
-    // Prepare template for each proxy task
-    let mut new_proxy_task = ProxyTask {
-        delegatee_pubkey: delegatee_pubkey.to_string(),
-        data_id: data_id.to_string(),
-        fragment: None,
-        proxy_addr: Addr::unchecked(""),
-        delegation_string: "".to_string(),
-        resolved: false,
-        abandoned: false,
-        timeout_height: env.block.height + timeouts_config.timeout_height,
-        refund_addr: info.sender.clone(),
-    };
-
-    let mut proxy_stake = Vec::new();
-
-    // Assign re-encrpytion tasks to all available proxies
-    for proxy_addr in &proxy_addresses {
-        // Check if proxy has enough stake
-        let mut proxy = store_get_proxy_entry(deps.storage, proxy_addr).unwrap();
-
-        if proxy.stake_amount.u128() < staking_config.per_task_slash_stake_amount.u128() {
-            // Proxy cannot be selected for insufficient amount
-            continue;
-        }
+    let state: State = store_get_state(deps.storage)?;
 
-        // Subtract stake from proxy
-        proxy.stake_amount = proxy
-            .stake_amount
-            .checked_sub(staking_config.per_task_slash_stake_amount)?;
-        store_set_proxy_entry(deps.storage, proxy_addr, &proxy);
+    ensure_not_withdrawn(&state)?;
 
-        // Get delegation
-        let delegation_id = store_get_proxy_delegation_id(
-            deps.storage,
-            &data_entry.delegator_pubkey,
-            delegatee_pubkey,
-            proxy_addr,
-        )
-        .unwrap();
-        let delegation = store_get_delegation(deps.storage, &delegation_id).unwrap();
+    let data_ids = store_get_expired_data_ids(deps.storage, &env.block, get_query_limit(limit))?;
 
-        // Add reencryption task for each proxy
-        match store_get_proxy_entry(deps.storage, proxy_addr) {
-            None => generic_err!("Proxy not registered"),
-            Some(proxy_entry) => match proxy_entry.proxy_pubkey {
-                None => generic_err!("Proxy not registered"),
-                Some(proxy_pubkey) => Ok(proxy_pubkey),
-            },
-        }?;
+    let staking_config = store_get_staking_config(deps.storage)?;
+    let mut refund_amounts: HashMap<Addr, u128> = HashMap::new();
 
-        new_proxy_task.proxy_addr = proxy_addr.clone();
-        new_proxy_task.delegation_string = delegation.delegation_string;
-        let task_id = state.next_proxy_task_id;
-        store_set_proxy_task(deps.storage, &task_id, &new_proxy_task);
-        store_add_delegatee_proxy_task(
+    for data_id in &data_ids {
+        remove_all_data_id_proxy_tasks(
             deps.storage,
             data_id,
-            delegatee_pubkey,
-            proxy_addr,
-            &task_id,
-        );
-        store_add_proxy_task_to_queue(deps.storage, proxy_addr, &task_id);
-        store_add_data_id_task(deps.storage, data_id, &task_id);
-        state.next_proxy_task_id += 1;
-
-        proxy_stake.push(ProxyStakeResponse {
-            proxy_addr: proxy_addr.clone(),
-            stake: proxy.stake_amount,
-        });
+            &staking_config,
+            &mut refund_amounts,
+        )?;
+
+        let data_labels = store_get_all_data_labels(deps.storage, data_id);
+        store_remove_data_labels(deps.storage, data_id, &data_labels)?;
+        store_remove_all_data_tags(deps.storage, data_id);
+        store_remove_all_data_access_rules(deps.storage, data_id);
+        store_remove_data_ownership_transfer(deps.storage, data_id);
+        store_remove_data_entry(deps.storage, data_id)?;
     }
 
-    store_set_state(deps.storage, &state)?;
-
-    // Return back part of funds if more funds than necessary was provided
-    if info.funds[0].amount.u128() > total_required_reward_amount {
+    for (refund_addr, refund_amount) in refund_amounts {
         add_bank_msg(
             &mut response,
-            &info.sender,
-            info.funds[0].amount.u128() - total_required_reward_amount,
+            &refund_addr,
+            refund_amount,
             &staking_config.stake_denom,
         );
     }
 
-    let json_response = ExecuteMsgJSONResponse::RequestReencryption {
-        proxies: proxy_stake,
-    };
-    let serialized_json_response = match serde_json::to_string(&json_response) {
-        Ok(s) => Ok(s),
-        Err(_err) => generic_err!("failed to serialize json response"),
-    }?;
-
     // Return response
     response
         .attributes
-        .push(Attribute::new("action", "request_reencryption"));
-    response.attributes.push(Attribute::new("data_id", data_id));
-    response
-        .attributes
-        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+        .push(Attribute::new("action", "prune_expired_data"));
     response
         .attributes
-        .push(Attribute::new("json", serialized_json_response));
-
+        .push(Attribute::new("data_ids", data_ids.join(", ")));
     Ok(response)
 }
 
-pub fn try_add_data_labels(
+fn try_transfer_data_ownership(
     mut response: Response,
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     data_id: &str,
-    data_labels: &[String],
+    new_delegator_pubkey: &str,
+    new_capsule: &str,
 ) -> StdResult<Response> {
-    let state: State = store_get_state(deps.storage).unwrap();
+    let state: State = store_get_state(deps.storage)?;
+
     ensure_not_terminated(&state)?;
     ensure_data_owner(deps.storage, data_id, &info.sender)?;
-    store_add_data_labels(deps.storage, data_id, data_labels);
+
+    let data_entry: DataEntry = store_get_data_entry(deps.storage, data_id)?.unwrap();
+    if data_entry.delegator_pubkey == new_delegator_pubkey {
+        return generic_err!("Data is already owned by this pubkey.");
+    }
+
+    // Replaces previous pending transfer
+    store_set_data_ownership_transfer(
+        deps.storage,
+        data_id,
+        &DataOwnershipTransfer {
+            new_delegator_pubkey: new_delegator_pubkey.to_string(),
+            new_capsule: new_capsule.to_string(),
+        },
+    );
 
     // Return response
     response
         .attributes
-        .push(Attribute::new("action", "add_data_labels"));
+        .push(Attribute::new("action", "transfer_data_ownership"));
+    response
+        .attributes
+        .push(Attribute::new("owner", info.sender.as_str()));
     response.attributes.push(Attribute::new("data_id", data_id));
     response
         .attributes
-        .push(Attribute::new("data_labels", data_labels.join(", ")));
+        .push(Attribute::new("new_delegator_pubkey", new_delegator_pubkey));
     Ok(response)
 }
 
-pub fn try_remove_data_labels(
+fn try_accept_data_ownership(
     mut response: Response,
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
     data_id: &str,
-    data_labels: &[String],
 ) -> StdResult<Response> {
-    let state: State = store_get_state(deps.storage).unwrap();
+    let state: State = store_get_state(deps.storage)?;
+
     ensure_not_terminated(&state)?;
-    ensure_data_owner(deps.storage, data_id, &info.sender)?;
-    store_remove_data_labels(deps.storage, data_id, data_labels)?;
+
+    let transfer = match store_get_data_ownership_transfer(deps.storage, data_id)? {
+        None => generic_err!(format!(
+            "No pending ownership transfer for data ID {}.",
+            data_id
+        )),
+        Some(transfer) => Ok(transfer),
+    }?;
+
+    // New owner must own the pubkey or reserve it
+    ensure_delegator(deps.storage, &transfer.new_delegator_pubkey, &info.sender)?;
+
+    let mut data_entry: DataEntry = store_get_data_entry(deps.storage, data_id)?.unwrap();
+    let previous_delegator_pubkey = data_entry.delegator_pubkey;
+
+    // Fragments of previous owner are useless for new capsule - cancel all requests
+    let staking_config = store_get_staking_config(deps.storage)?;
+    let mut refund_amounts: HashMap<Addr, u128> = HashMap::new();
+    remove_all_data_id_proxy_tasks(deps.storage, data_id, &staking_config, &mut refund_amounts)?;
+
+    for (refund_addr, refund_amount) in refund_amounts {
+        add_bank_msg(
+            &mut response,
+            &refund_addr,
+            refund_amount,
+            &staking_config.stake_denom,
+        );
+    }
+
+    // Access decisions of previous owner don't carry over, data labels and tags are preserved
+    store_remove_all_data_access_rules(deps.storage, data_id);
+    remove_data_access_requests(deps.storage, &previous_delegator_pubkey, data_id);
+
+    data_entry.delegator_pubkey = transfer.new_delegator_pubkey;
+    data_entry.capsule = transfer.new_capsule;
+    store_set_data_entry(deps.storage, data_id, &data_entry)?;
+    store_remove_data_ownership_transfer(deps.storage, data_id);
 
     // Return response
     response
         .attributes
-        .push(Attribute::new("action", "remove_data_labels"));
-    response.attributes.push(Attribute::new("data_id", data_id));
+        .push(Attribute::new("action", "accept_data_ownership"));
     response
         .attributes
-        .push(Attribute::new("data_labels", data_labels.join(", ")));
+        .push(Attribute::new("owner", info.sender.as_str()));
+    response.attributes.push(Attribute::new("data_id", data_id));
+    response.attributes.push(Attribute::new(
+        "previous_delegator_pubkey",
+        previous_delegator_pubkey,
+    ));
+    response.attributes.push(Attribute::new(
+        "delegator_pubkey",
+        data_entry.delegator_pubkey,
+    ));
     Ok(response)
 }
 
-pub fn try_add_delegatee_labels(
+fn try_cancel_data_ownership_transfer(
     mut response: Response,
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    delegatee_pubkey: &str,
-    delegatee_labels: &[String],
+    data_id: &str,
 ) -> StdResult<Response> {
-    let state: State = store_get_state(deps.storage).unwrap();
-    ensure_not_terminated(&state)?;
+    ensure_data_owner(deps.storage, data_id, &info.sender)?;
 
-    store_add_delegatee_labels(
-        deps.storage,
-        &info.sender,
-        delegatee_pubkey,
-        delegatee_labels,
-    );
+    if store_get_data_ownership_transfer(deps.storage, data_id)?.is_none() {
+        return generic_err!(format!(
+            "No pending ownership transfer for data ID {}.",
+            data_id
+        ));
+    }
+
+    store_remove_data_ownership_transfer(deps.storage, data_id);
 
     // Return response
     response
         .attributes
-        .push(Attribute::new("action", "add_delegatee_labels"));
+        .push(Attribute::new("action", "cancel_data_ownership_transfer"));
     response
         .attributes
-        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
-    response.attributes.push(Attribute::new(
-        "delegatee_labels",
-        delegatee_labels.join(", "),
-    ));
+        .push(Attribute::new("owner", info.sender.as_str()));
+    response.attributes.push(Attribute::new("data_id", data_id));
     Ok(response)
 }
 
-pub fn try_remove_delegatee_labels(
+fn try_rotate_delegator_key(
     mut response: Response,
     deps: DepsMut,
     _env: Env,
     info: MessageInfo,
-    delegatee_pubkey: &str,
-    delegatee_labels: &[String],
+    old_pubkey: &str,
+    new_pubkey: &str,
+    updated_capsules: &[DataCapsule],
 ) -> StdResult<Response> {
-    let state: State = store_get_state(deps.storage).unwrap();
+    let state: State = store_get_state(deps.storage)?;
+
     ensure_not_terminated(&state)?;
 
-    store_remove_delegatee_labels(
-        deps.storage,
-        &info.sender,
-        delegatee_pubkey,
-        delegatee_labels,
-    )?;
+    if store_get_delegator_address(deps.storage, old_pubkey)? != Some(info.sender.clone()) {
+        return generic_err!("Sender is not owner of delegator pubkey.");
+    }
 
-    // Return response
-    response
-        .attributes
-        .push(Attribute::new("action", "remove_delegatee_labels"));
-    response
-        .attributes
-        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
-    response.attributes.push(Attribute::new(
-        "delegatee_labels",
-        delegatee_labels.join(", "),
-    ));
-    Ok(response)
-}
+    if old_pubkey == new_pubkey {
+        return generic_err!("New pubkey must be different from old pubkey.");
+    }
 
-#[entry_point]
-pub fn execute(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    msg: ExecuteMsg,
-) -> Result<Response, StdError> {
-    let response: Response = Response::new();
+    // New pubkey must be free or already owned by sender
+    ensure_delegator(deps.storage, new_pubkey, &info.sender)?;
 
-    match msg {
-        // Admin actions
-        ExecuteMsg::AddProxy { proxy_addr } => {
-            try_add_proxy(response, deps, env, info, &proxy_addr)
+    // Every data entry of old pubkey needs new capsule
+    let data_ids = store_get_all_delegator_data_ids(deps.storage, old_pubkey)?;
+    let mut capsules: HashMap<&str, &str> = HashMap::new();
+    for updated_capsule in updated_capsules {
+        if !data_ids.contains(&updated_capsule.data_id) {
+            return generic_err!(format!(
+                "Data ID {} is not owned by old pubkey.",
+                updated_capsule.data_id
+            ));
         }
-        ExecuteMsg::RemoveProxy { proxy_addr } => {
-            try_remove_proxy(response, deps, env, info, &proxy_addr)
+        if capsules
+            .insert(&updated_capsule.data_id, &updated_capsule.capsule)
+            .is_some()
+        {
+            return generic_err!(format!(
+                "Duplicate capsule for data ID {}.",
+                updated_capsule.data_id
+            ));
         }
-        ExecuteMsg::TerminateContract {} => try_terminate_contract(response, deps, env, info),
-        ExecuteMsg::WithdrawContract { recipient_addr } => {
-            try_withdraw_contract(response, deps, env, info, &recipient_addr)
+    }
+    for data_id in &data_ids {
+        if !capsules.contains_key(data_id.as_str()) {
+            return generic_err!(format!("Missing capsule for data ID {}.", data_id));
         }
+    }
 
-        // Proxy actions
-        ExecuteMsg::RegisterProxy { proxy_pubkey } => {
-            try_register_proxy(response, deps, env, info, proxy_pubkey)
-        }
-        ExecuteMsg::ProvideReencryptedFragment {
-            data_id,
-            delegatee_pubkey,
-            fragment,
-        } => try_provide_reencrypted_fragment(
-            response,
-            deps,
-            env,
-            info,
-            &data_id,
-            &delegatee_pubkey,
-            &fragment,
-        ),
-        ExecuteMsg::SkipReencryptionTask {
+    let staking_config = store_get_staking_config(deps.storage)?;
+    let mut refund_amounts: HashMap<Addr, u128> = HashMap::new();
+
+    for data_id in &data_ids {
+        // Fragments made with old key are useless
+        remove_all_data_id_proxy_tasks(
+            deps.storage,
             data_id,
-            delegatee_pubkey,
-        } => try_skip_reencryption_task(response, deps, env, info, &data_id, &delegatee_pubkey),
-        ExecuteMsg::UnregisterProxy {} => try_unregister_proxy(response, deps, env, info),
-        ExecuteMsg::DeactivateProxy {} => try_deactivate_proxy(response, deps, env, info),
-        ExecuteMsg::WithdrawStake { stake_amount } => {
-            try_withdraw_stake(response, deps, env, info, &stake_amount)
+            &staking_config,
+            &mut refund_amounts,
+        )?;
+
+        let mut data_entry: DataEntry = store_get_data_entry(deps.storage, data_id)?.unwrap();
+        data_entry.delegator_pubkey = new_pubkey.to_string();
+        data_entry.capsule = capsules[data_id.as_str()].to_string();
+        store_set_data_entry(deps.storage, data_id, &data_entry)?;
+    }
+
+    // Delegation strings were created with old key
+    remove_all_delegator_delegations(deps.storage, old_pubkey)?;
+
+    // Subscriptions to old pubkey can't be served anymore, remaining budgets are returned
+    for (delegatee_pubkey, subscription) in
+        store_get_all_delegator_subscriptions(deps.storage, old_pubkey)
+    {
+        store_remove_subscription(deps.storage, old_pubkey, &delegatee_pubkey);
+        update_refunds_map(
+            &mut refund_amounts,
+            &subscription.subscriber_addr,
+            subscription.budget.u128(),
+        );
+    }
+
+    // Old pubkey is released
+    store_remove_delegator_address(deps.storage, old_pubkey);
+
+    for (refund_addr, refund_amount) in refund_amounts {
+        add_bank_msg(
+            &mut response,
+            &refund_addr,
+            refund_amount,
+            &staking_config.stake_denom,
+        );
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "rotate_delegator_key"));
+    response
+        .attributes
+        .push(Attribute::new("delegator_address", info.sender.as_str()));
+    response
+        .attributes
+        .push(Attribute::new("old_pubkey", old_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("new_pubkey", new_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("data_ids", data_ids.join(", ")));
+    Ok(response)
+}
+
+fn try_add_delegation(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+    proxy_delegations: &[ProxyDelegationString],
+    delegatee_labels: &Option<Vec<String>>,
+) -> StdResult<Response> {
+    ensure_delegator(deps.storage, delegator_pubkey, &info.sender)?;
+
+    let mut state: State = store_get_state(deps.storage)?;
+    ensure_not_terminated(&state)?;
+    ensure_not_paused(&state)?;
+
+    let staking_config: StakingConfig = store_get_staking_config(deps.storage)?;
+
+    if !store_is_proxy_delegation_empty(deps.storage, delegator_pubkey, delegatee_pubkey) {
+        return generic_err!("Delegation already exists.");
+    }
+
+    let n_minimum_proxies = get_n_minimum_proxies_for_refund(&state, &staking_config);
+
+    if proxy_delegations.len() < n_minimum_proxies as usize {
+        return generic_err!(format!("Required at least {} proxies.", n_minimum_proxies));
+    }
+
+    for proxy_delegation in proxy_delegations {
+        // Proxy must be registered
+        match store_get_proxy_entry(deps.storage, &proxy_delegation.proxy_addr)? {
+            None => {
+                return generic_err!(format!(
+                    "Unknown proxy with address {}",
+                    &proxy_delegation.proxy_addr
+                ));
+            }
+            Some(proxy_entry) => {
+                if proxy_entry.proxy_pubkey.is_none() {
+                    return generic_err!(format!(
+                        "Unregistered proxy with address {}",
+                        &proxy_delegation.proxy_addr
+                    ));
+                }
+            }
         }
-        ExecuteMsg::AddStake {} => try_add_stake(response, deps, env, info),
 
-        // Delegator actions
-        ExecuteMsg::AddData {
-            data_id,
-            delegator_pubkey,
-            capsule,
-            data_labels,
-            ..
-        } => try_add_data(
-            response,
-            deps,
-            env,
-            info,
-            &data_id,
-            &delegator_pubkey,
-            &capsule,
-            &data_labels,
-        ),
-        ExecuteMsg::RemoveData { data_id } => try_remove_data(response, deps, env, info, &data_id),
-        ExecuteMsg::AddDelegation {
+        if store_get_proxy_delegation_id(
+            deps.storage,
             delegator_pubkey,
             delegatee_pubkey,
-            proxy_delegations,
-            delegatee_labels,
-        } => try_add_delegation(
-            response,
-            deps,
-            env,
-            info,
-            &delegator_pubkey,
-            &delegatee_pubkey,
-            &proxy_delegations,
-            &delegatee_labels,
-        ),
-        ExecuteMsg::RequestReencryption {
-            data_id,
-            delegatee_pubkey,
-        } => try_request_reencryption(response, deps, env, info, &data_id, &delegatee_pubkey),
-        ExecuteMsg::ResolveTimedOutRequest {
-            data_id,
-            delegatee_pubkey,
-        } => try_resolve_timed_out_request(response, deps, env, info, &data_id, &delegatee_pubkey),
+            &proxy_delegation.proxy_addr,
+        )?
+        .is_some()
+        {
+            return generic_err!(format!(
+                "Delegation string was already provided for proxy {}.",
+                &proxy_delegation.proxy_addr
+            ));
+        }
 
-        ExecuteMsg::AddDataLabels {
-            data_id,
-            data_labels,
-        } => try_add_data_labels(response, deps, env, info, &data_id, &data_labels),
+        let delegation = ProxyDelegation {
+            delegator_pubkey: delegator_pubkey.to_string(),
+            delegatee_pubkey: delegatee_pubkey.to_string(),
+            delegation_string: proxy_delegation.delegation_string.clone(),
+            proxy_addr: proxy_delegation.proxy_addr.clone(),
+        };
 
-        ExecuteMsg::RemoveDataLabels {
-            data_id,
-            data_labels,
-        } => try_remove_data_labels(response, deps, env, info, &data_id, &data_labels),
+        store_set_delegation(deps.storage, &state.next_delegation_id, &delegation)?;
 
-        ExecuteMsg::AddDelegateeLabels {
-            delegatee_pubkey,
-            delegatee_labels,
-        } => try_add_delegatee_labels(
-            response,
-            deps,
-            env,
-            info,
-            &delegatee_pubkey,
-            &delegatee_labels,
-        ),
+        state.next_delegation_id += 1;
+    }
 
-        ExecuteMsg::RemoveDelegateeLabels {
+    store_set_state(deps.storage, &state)?;
+
+    // Add delegatee labels
+    if let Some(delegatee_labels) = delegatee_labels {
+        store_add_delegatee_labels(
+            deps.storage,
+            &info.sender,
             delegatee_pubkey,
             delegatee_labels,
-        } => try_remove_delegatee_labels(
-            response,
-            deps,
-            env,
-            info,
-            &delegatee_pubkey,
-            &delegatee_labels,
-        ),
+            &None,
+        );
+        response.attributes.push(Attribute::new(
+            "delegatee_labels",
+            delegatee_labels.join(", "),
+        ));
     }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "add_delegation"));
+    response
+        .attributes
+        .push(Attribute::new("delegator_address", info.sender.as_str()));
+    response
+        .attributes
+        .push(Attribute::new("delegator_pubkey", delegator_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+
+    Ok(response)
 }
 
-//// Query functions
+fn try_request_reencryption(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<Response> {
+    // Load config
+    let mut state: State = store_get_state(deps.storage)?;
+    let staking_config: StakingConfig = store_get_staking_config(deps.storage)?;
+    let timeouts_config: TimeoutsConfig = store_get_timeouts_config(deps.storage)?;
 
-pub fn get_proxy_tasks(
-    store: &dyn Storage,
-    proxy_addr: &Addr,
-    block_height: &u64,
-) -> StdResult<Vec<ProxyTaskResponse>> {
-    // Returns first available proxy task from queue
+    let request_plan = get_reencryption_request_plan(
+        deps.storage,
+        &env.block,
+        &state,
+        &staking_config,
+        &info.sender,
+        data_id,
+        delegatee_pubkey,
+    )?;
+    check_reencryption_request(&state, &staking_config, &request_plan)?;
+    let delegator_addr = request_plan.delegator_addr;
+
+    // Failed request can be retried, its tasks stay in request history
+    let mut refund_amounts: HashMap<Addr, u128> = HashMap::new();
+    if request_plan.previous_request_state.is_some() {
+        archive_delegatee_proxy_tasks(
+            deps.storage,
+            data_id,
+            delegatee_pubkey,
+            &staking_config,
+            &mut refund_amounts,
+        )?;
+    }
+
+    // Ensure more than per_proxy_task_reward_amount * number_of_proxies of stake provided
+    let total_required_reward_amount = request_plan.total_required_reward_amount;
+
+    // Reward is drawn from delegatee allowance when requester provides no funds
+    let allowance_owner_addr = if info.funds.is_empty() {
+        draw_delegatee_allowance(
+            deps.storage,
+            &delegator_addr,
+            delegatee_pubkey,
+            total_required_reward_amount,
+            &env.block,
+        )?;
+        Some(delegator_addr.clone())
+    } else {
+        ensure_stake(&staking_config, &info.funds, &total_required_reward_amount)?;
+        None
+    };
+
+    // Prepare template for each proxy task
+    let new_proxy_task = ProxyTask {
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+        data_id: data_id.to_string(),
+        fragment: None,
+        proxy_addr: Addr::unchecked(""),
+        delegation_string: "".to_string(),
+        resolved: false,
+        abandoned: false,
+        timeout_height: env.block.height + timeouts_config.timeout_height,
+        refund_addr: info.sender.clone(),
+        allowance_owner_addr,
+        request_seq: 0,
+    };
+
+    let (proxy_stake, request_seq) = create_proxy_tasks(
+        deps.storage,
+        &mut state,
+        &staking_config,
+        &request_plan.delegator_pubkey,
+        &request_plan.proxy_addresses,
+        new_proxy_task,
+    )?;
+
+    store_set_state(deps.storage, &state)?;
+
+    // Return back part of funds if more funds than necessary was provided
+    if !info.funds.is_empty() && info.funds[0].amount.u128() > total_required_reward_amount {
+        add_bank_msg(
+            &mut response,
+            &info.sender,
+            info.funds[0].amount.u128() - total_required_reward_amount,
+            &staking_config.stake_denom,
+        );
+    }
+
+    // Refund timed-out tasks of previous request
+    for (refund_addr, refund_amount) in refund_amounts {
+        add_bank_msg(
+            &mut response,
+            &refund_addr,
+            refund_amount,
+            &staking_config.stake_denom,
+        );
+    }
+
+    let json_response = ExecuteMsgJSONResponse::RequestReencryption {
+        proxies: proxy_stake,
+    };
+    let serialized_json_response = match serde_json::to_string(&json_response) {
+        Ok(s) => Ok(s),
+        Err(_err) => generic_err!("failed to serialize json response"),
+    }?;
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "request_reencryption"));
+    response.attributes.push(Attribute::new("data_id", data_id));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("request_seq", request_seq.to_string()));
+    response
+        .attributes
+        .push(Attribute::new("json", serialized_json_response));
+
+    Ok(response)
+}
+
+pub fn try_update_data_tags(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    data_id: &str,
+    tags: &[Tag],
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+    ensure_not_terminated(&state)?;
+    ensure_data_owner(deps.storage, data_id, &info.sender)?;
+    ensure_valid_tags(tags)?;
+
+    store_remove_all_data_tags(deps.storage, data_id);
+    for tag in tags {
+        store_set_data_tag(deps.storage, data_id, tag);
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "update_data_tags"));
+    response.attributes.push(Attribute::new("data_id", data_id));
+    response.attributes.push(Attribute::new(
+        "tags",
+        tags.iter()
+            .map(|tag| format!("{}={}", tag.key, tag.value))
+            .collect::<Vec<String>>()
+            .join(", "),
+    ));
+    Ok(response)
+}
+
+pub fn try_add_data_labels(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data_id: &str,
+    data_labels: &[String],
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+    ensure_data_owner(deps.storage, data_id, &info.sender)?;
+    store_add_data_labels(deps.storage, data_id, data_labels);
+
+    request_subscribed_reencryptions(&mut response, deps.storage, &env.block, data_id)?;
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "add_data_labels"));
+    response.attributes.push(Attribute::new("data_id", data_id));
+    response
+        .attributes
+        .push(Attribute::new("data_labels", data_labels.join(", ")));
+    Ok(response)
+}
+
+pub fn try_remove_data_labels(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    data_id: &str,
+    data_labels: &[String],
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+    ensure_data_owner(deps.storage, data_id, &info.sender)?;
+    store_remove_data_labels(deps.storage, data_id, data_labels)?;
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "remove_data_labels"));
+    response.attributes.push(Attribute::new("data_id", data_id));
+    response
+        .attributes
+        .push(Attribute::new("data_labels", data_labels.join(", ")));
+    Ok(response)
+}
+
+pub fn try_add_delegatee_labels(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delegatee_pubkey: &str,
+    delegatee_labels: &[String],
+    expires_at: &Option<Expiration>,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+
+    if let Some(expires_at) = expires_at {
+        if expires_at.is_expired(&env.block) {
+            return generic_err!("Delegatee labels are already expired.");
+        }
+    }
+
+    store_add_delegatee_labels(
+        deps.storage,
+        &info.sender,
+        delegatee_pubkey,
+        delegatee_labels,
+        expires_at,
+    );
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "add_delegatee_labels"));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    response.attributes.push(Attribute::new(
+        "delegatee_labels",
+        delegatee_labels.join(", "),
+    ));
+    Ok(response)
+}
+
+pub fn try_grant_data_access(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    data_id: &str,
+    delegatee_pubkey: &str,
+    expires_at: &Option<Expiration>,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+    ensure_data_owner(deps.storage, data_id, &info.sender)?;
+
+    if let Some(expires_at) = expires_at {
+        if expires_at.is_expired(&env.block) {
+            return generic_err!("Grant is already expired.");
+        }
+    }
+
+    store_set_data_access_rule(
+        deps.storage,
+        data_id,
+        delegatee_pubkey,
+        &DataAccessRule::Grant {
+            expires_at: expires_at.clone(),
+        },
+    );
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "grant_data_access"));
+    response.attributes.push(Attribute::new("data_id", data_id));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_deny_data_access(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+    ensure_data_owner(deps.storage, data_id, &info.sender)?;
+
+    store_set_data_access_rule(
+        deps.storage,
+        data_id,
+        delegatee_pubkey,
+        &DataAccessRule::Deny,
+    );
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "deny_data_access"));
+    response.attributes.push(Attribute::new("data_id", data_id));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_revoke_data_access(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+    ensure_data_owner(deps.storage, data_id, &info.sender)?;
+
+    if store_get_data_access_rule(deps.storage, data_id, delegatee_pubkey).is_none() {
+        return generic_err!(format!(
+            "No access rule for delegatee {}.",
+            delegatee_pubkey
+        ));
+    }
+
+    store_remove_data_access_rule(deps.storage, data_id, delegatee_pubkey);
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "revoke_data_access"));
+    response.attributes.push(Attribute::new("data_id", data_id));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_request_access(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+    target: &AccessRequestTarget,
+    message: &Option<String>,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+
+    if store_get_delegator_address(deps.storage, delegator_pubkey)?.is_none() {
+        return generic_err!("Unknown delegator pubkey.");
+    }
+
+    match target {
+        AccessRequestTarget::DataIds(items) | AccessRequestTarget::Labels(items) => {
+            if items.is_empty() {
+                return generic_err!("Nothing to request.");
+            }
+        }
+    }
+
+    prune_expired_access_requests(deps.storage, delegator_pubkey, env.block.height);
+
+    let request_id = store_get_next_access_request_id(deps.storage);
+    store_set_access_request(
+        deps.storage,
+        request_id,
+        &AccessRequest {
+            delegator_pubkey: delegator_pubkey.to_string(),
+            delegatee_pubkey: delegatee_pubkey.to_string(),
+            requester_addr: info.sender.clone(),
+            target: target.clone(),
+            message: message.clone(),
+            expiry_height: env.block.height + ACCESS_REQUEST_TIMEOUT_HEIGHT,
+        },
+    );
+    store_set_next_access_request_id(deps.storage, request_id + 1);
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "request_access"));
+    response
+        .attributes
+        .push(Attribute::new("request_id", request_id.to_string()));
+    response
+        .attributes
+        .push(Attribute::new("delegator_pubkey", delegator_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_approve_access_request(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    request_id: u64,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+
+    let access_request = ensure_pending_access_request(deps.storage, &env, &info, request_id)?;
+
+    // Delegation with proxies has to be added separately by delegator
+    match &access_request.target {
+        AccessRequestTarget::Labels(labels) => store_add_delegatee_labels(
+            deps.storage,
+            &info.sender,
+            &access_request.delegatee_pubkey,
+            labels,
+            &None,
+        ),
+        AccessRequestTarget::DataIds(data_ids) => {
+            for data_id in data_ids {
+                ensure_data_owner(deps.storage, data_id, &info.sender)?;
+                store_set_data_access_rule(
+                    deps.storage,
+                    data_id,
+                    &access_request.delegatee_pubkey,
+                    &DataAccessRule::Grant { expires_at: None },
+                );
+            }
+        }
+    }
+
+    store_remove_access_request(deps.storage, request_id);
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "approve_access_request"));
+    response
+        .attributes
+        .push(Attribute::new("request_id", request_id.to_string()));
+    response.attributes.push(Attribute::new(
+        "delegatee_pubkey",
+        access_request.delegatee_pubkey,
+    ));
+    Ok(response)
+}
+
+pub fn try_reject_access_request(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    request_id: u64,
+) -> StdResult<Response> {
+    let access_request = ensure_pending_access_request(deps.storage, &env, &info, request_id)?;
+
+    store_remove_access_request(deps.storage, request_id);
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "reject_access_request"));
+    response
+        .attributes
+        .push(Attribute::new("request_id", request_id.to_string()));
+    response.attributes.push(Attribute::new(
+        "delegatee_pubkey",
+        access_request.delegatee_pubkey,
+    ));
+    Ok(response)
+}
+
+pub fn try_set_delegatee_policy(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegatee_pubkey: &str,
+    policy: &Option<LabelExpression>,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+
+    match policy {
+        Some(policy) => {
+            ensure_valid_policy(policy)?;
+            store_set_delegatee_policy(deps.storage, &info.sender, delegatee_pubkey, policy);
+        }
+        None => store_remove_delegatee_policy(deps.storage, &info.sender, delegatee_pubkey),
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "set_delegatee_policy"));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_register_delegatee(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegatee_pubkey: &str,
+    authorised_requesters: &[Addr],
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+
+    if let Some(delegatee_addr) = store_get_delegatee_address(deps.storage, delegatee_pubkey) {
+        return generic_err!(format!(
+            "Delegatee {} already registered with this pubkey.",
+            delegatee_addr
+        ));
+    }
+
+    store_set_delegatee_address(deps.storage, delegatee_pubkey, &info.sender);
+
+    for requester_addr in authorised_requesters {
+        store_add_delegatee_requester(deps.storage, delegatee_pubkey, requester_addr);
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "register_delegatee"));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_override_delegatee_registration(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+    delegatee_addr: &Addr,
+    authorised_requesters: &[Addr],
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+    ensure_not_terminated(&state)?;
+
+    if store_get_delegator_address(deps.storage, delegator_pubkey)? != Some(info.sender.clone()) {
+        return generic_err!("Sender is not owner of delegator pubkey.");
+    }
+
+    // Delegator knows delegatee it delegated to, registration can't be squatted by anyone else
+    if store_is_proxy_delegation_empty(deps.storage, delegator_pubkey, delegatee_pubkey) {
+        return generic_err!("Delegation doesn't exist.");
+    }
+
+    // Requesters authorised by previous address are dropped
+    for requester_addr in store_get_all_delegatee_requesters(deps.storage, delegatee_pubkey) {
+        store_remove_delegatee_requester(deps.storage, delegatee_pubkey, &requester_addr);
+    }
+
+    store_set_delegatee_address(deps.storage, delegatee_pubkey, delegatee_addr);
+
+    for requester_addr in authorised_requesters {
+        store_add_delegatee_requester(deps.storage, delegatee_pubkey, requester_addr);
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "override_delegatee_registration"));
+    response
+        .attributes
+        .push(Attribute::new("delegator_pubkey", delegator_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_addr", delegatee_addr.as_str()));
+    Ok(response)
+}
+
+pub fn try_add_authorised_requesters(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegatee_pubkey: &str,
+    requesters: &[Addr],
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+
+    ensure_delegatee_owner(deps.storage, delegatee_pubkey, &info.sender)?;
+
+    for requester_addr in requesters {
+        store_add_delegatee_requester(deps.storage, delegatee_pubkey, requester_addr);
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "add_authorised_requesters"));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_fund_delegatee_allowance(
+    mut response: Response,
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delegatee_pubkey: &str,
+    spend_limit: &Option<Uint128>,
+    expires: &Option<Expiration>,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+    ensure_not_terminated(&state)?;
+    ensure_not_paused(&state)?;
+
+    let staking_config = store_get_staking_config(deps.storage)?;
+
+    if let Some(expires) = expires {
+        if expires.is_expired(&env.block) {
+            return generic_err!("Delegatee allowance is already expired.");
+        }
+    }
+
+    let mut allowance =
+        match store_get_delegatee_allowance(deps.storage, &info.sender, delegatee_pubkey) {
+            Some(allowance) => allowance,
+            None if info.funds.is_empty() => {
+                return generic_err!("New delegatee allowance has to be funded.");
+            }
+            None => DelegateeAllowance {
+                balance: Uint128::zero(),
+                spend_limit: None,
+                expires: None,
+            },
+        };
+
+    // Terms of existing allowance can be updated without funds
+    if !info.funds.is_empty() {
+        let amount = ensure_stake(&staking_config, &info.funds, &1)?;
+        allowance.balance = allowance.balance.checked_add(Uint128::new(amount))?;
+    }
+    allowance.spend_limit = *spend_limit;
+    allowance.expires = expires.clone();
+
+    store_set_delegatee_allowance(deps.storage, &info.sender, delegatee_pubkey, &allowance);
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "fund_delegatee_allowance"));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("balance", allowance.balance.to_string()));
+    Ok(response)
+}
+
+pub fn try_withdraw_delegatee_allowance(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegatee_pubkey: &str,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+    ensure_not_withdrawn(&state)?;
+
+    let staking_config = store_get_staking_config(deps.storage)?;
+
+    let allowance =
+        match store_get_delegatee_allowance(deps.storage, &info.sender, delegatee_pubkey) {
+            None => generic_err!(format!("No allowance for delegatee {}.", delegatee_pubkey)),
+            Some(allowance) => Ok(allowance),
+        }?;
+
+    store_remove_delegatee_allowance(deps.storage, &info.sender, delegatee_pubkey);
+
+    if !allowance.balance.is_zero() {
+        add_bank_msg(
+            &mut response,
+            &info.sender,
+            allowance.balance.u128(),
+            &staking_config.stake_denom,
+        );
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "withdraw_delegatee_allowance"));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("amount", allowance.balance.to_string()));
+    Ok(response)
+}
+
+pub fn try_subscribe(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+    labels: &[String],
+    budget: &Uint128,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+    ensure_not_terminated(&state)?;
+    ensure_not_paused(&state)?;
+
+    let staking_config = store_get_staking_config(deps.storage)?;
+
+    if labels.is_empty() {
+        return generic_err!("No subscription labels provided.");
+    }
+
+    if store_get_delegator_address(deps.storage, delegator_pubkey)?.is_none() {
+        return generic_err!("Unknown delegator pubkey.");
+    }
+
+    if !is_requester_authorised(deps.storage, delegatee_pubkey, &info.sender) {
+        return generic_err!("Sender is not authorised to request for delegatee.");
+    }
+
+    let provided_amount = ensure_stake(&staking_config, &info.funds, &budget.u128())?;
+
+    // Subscriber can top up budget and replace labels
+    let mut subscription =
+        match store_get_subscription(deps.storage, delegator_pubkey, delegatee_pubkey) {
+            Some(subscription) if subscription.subscriber_addr != info.sender => {
+                return generic_err!("Delegatee is already subscribed by other address.");
+            }
+            Some(subscription) => subscription,
+            None => Subscription {
+                subscriber_addr: info.sender.clone(),
+                labels: Vec::new(),
+                budget: Uint128::zero(),
+            },
+        };
+    subscription.labels = labels.to_vec();
+    subscription.budget = subscription.budget.checked_add(*budget)?;
+
+    store_set_subscription(
+        deps.storage,
+        delegator_pubkey,
+        delegatee_pubkey,
+        &subscription,
+    );
+
+    // Return back part of funds if more funds than budget was provided
+    if provided_amount > budget.u128() {
+        add_bank_msg(
+            &mut response,
+            &info.sender,
+            provided_amount - budget.u128(),
+            &staking_config.stake_denom,
+        );
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "subscribe"));
+    response
+        .attributes
+        .push(Attribute::new("delegator_pubkey", delegator_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("budget", subscription.budget.to_string()));
+    Ok(response)
+}
+
+pub fn try_unsubscribe(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage)?;
+    ensure_not_withdrawn(&state)?;
+
+    let staking_config = store_get_staking_config(deps.storage)?;
+
+    let subscription =
+        match store_get_subscription(deps.storage, delegator_pubkey, delegatee_pubkey) {
+            None => generic_err!("Subscription doesn't exist."),
+            Some(subscription) => Ok(subscription),
+        }?;
+
+    if subscription.subscriber_addr != info.sender {
+        return generic_err!("Sender is not the subscriber.");
+    }
+
+    store_remove_subscription(deps.storage, delegator_pubkey, delegatee_pubkey);
+
+    // Return remaining budget
+    if !subscription.budget.is_zero() {
+        add_bank_msg(
+            &mut response,
+            &info.sender,
+            subscription.budget.u128(),
+            &staking_config.stake_denom,
+        );
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "unsubscribe"));
+    response
+        .attributes
+        .push(Attribute::new("delegator_pubkey", delegator_pubkey));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_remove_authorised_requesters(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegatee_pubkey: &str,
+    requesters: &[Addr],
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+
+    ensure_delegatee_owner(deps.storage, delegatee_pubkey, &info.sender)?;
+
+    for requester_addr in requesters {
+        store_remove_delegatee_requester(deps.storage, delegatee_pubkey, requester_addr);
+    }
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "remove_authorised_requesters"));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    Ok(response)
+}
+
+pub fn try_remove_delegatee_labels(
+    mut response: Response,
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    delegatee_pubkey: &str,
+    delegatee_labels: &[String],
+) -> StdResult<Response> {
+    let state: State = store_get_state(deps.storage).unwrap();
+    ensure_not_terminated(&state)?;
+
+    store_remove_delegatee_labels(
+        deps.storage,
+        &info.sender,
+        delegatee_pubkey,
+        delegatee_labels,
+    )?;
+
+    // Return response
+    response
+        .attributes
+        .push(Attribute::new("action", "remove_delegatee_labels"));
+    response
+        .attributes
+        .push(Attribute::new("delegatee_pubkey", delegatee_pubkey));
+    response.attributes.push(Attribute::new(
+        "delegatee_labels",
+        delegatee_labels.join(", "),
+    ));
+    Ok(response)
+}
+
+#[entry_point]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, StdError> {
+    let response: Response = Response::new();
+
+    // Legacy entries aren't visible to contract logic until migration moves all of them
+    if store_get_is_legacy_migration_pending(deps.storage)? {
+        return generic_err!("Legacy storage migration is not finished.");
+    }
+
+    match msg {
+        // Admin actions
+        ExecuteMsg::AddProxy { proxy_addr } => {
+            try_add_proxy(response, deps, env, info, &proxy_addr)
+        }
+        ExecuteMsg::RemoveProxy { proxy_addr } => {
+            try_remove_proxy(response, deps, env, info, &proxy_addr)
+        }
+        ExecuteMsg::TerminateContract {} => try_terminate_contract(response, deps, env, info),
+        ExecuteMsg::PauseContract {} => try_set_paused(response, deps, env, info, true),
+        ExecuteMsg::UnpauseContract {} => try_set_paused(response, deps, env, info, false),
+        ExecuteMsg::UpdateConfig {
+            minimum_proxy_stake_amount,
+            timeout_height,
+            proxy_whitelisting,
+        } => try_update_config(
+            response,
+            deps,
+            env,
+            info,
+            &minimum_proxy_stake_amount,
+            &timeout_height,
+            &proxy_whitelisting,
+        ),
+        ExecuteMsg::UpdateAdmin { admin } => try_update_admin(response, deps, env, info, &admin),
+        ExecuteMsg::WithdrawContract { recipient_addr } => {
+            try_withdraw_contract(response, deps, env, info, &recipient_addr)
+        }
+
+        // Proxy actions
+        ExecuteMsg::RegisterProxy { proxy_pubkey } => {
+            try_register_proxy(response, deps, env, info, proxy_pubkey)
+        }
+        ExecuteMsg::ProvideReencryptedFragment {
+            data_id,
+            delegatee_pubkey,
+            fragment,
+        } => try_provide_reencrypted_fragment(
+            response,
+            deps,
+            env,
+            info,
+            &data_id,
+            &delegatee_pubkey,
+            &fragment,
+        ),
+        ExecuteMsg::ProvideReencryptedFragments {
+            fragments,
+            all_or_nothing,
+        } => try_provide_reencrypted_fragments(
+            response,
+            deps,
+            env,
+            info,
+            &fragments,
+            all_or_nothing.unwrap_or(false),
+        ),
+        ExecuteMsg::SkipReencryptionTask {
+            data_id,
+            delegatee_pubkey,
+        } => try_skip_reencryption_task(response, deps, env, info, &data_id, &delegatee_pubkey),
+        ExecuteMsg::UnregisterProxy {} => try_unregister_proxy(response, deps, env, info),
+        ExecuteMsg::DeactivateProxy {} => try_deactivate_proxy(response, deps, env, info),
+        ExecuteMsg::WithdrawStake { stake_amount } => {
+            try_withdraw_stake(response, deps, env, info, &stake_amount)
+        }
+        ExecuteMsg::AddStake {} => try_add_stake(response, deps, env, info),
+
+        // Delegator actions
+        ExecuteMsg::AddData {
+            data_id,
+            delegator_pubkey,
+            capsule,
+            tags,
+            data_labels,
+            expires_at,
+        } => try_add_data(
+            response,
+            deps,
+            env,
+            info,
+            &data_id,
+            &delegator_pubkey,
+            &capsule,
+            &tags,
+            &data_labels,
+            &expires_at,
+        ),
+        ExecuteMsg::AddDataBatch { entries } => {
+            try_add_data_batch(response, deps, env, info, &entries)
+        }
+        ExecuteMsg::UpdateDataCapsule { data_id, capsule } => {
+            try_update_data_capsule(response, deps, env, info, &data_id, &capsule)
+        }
+        ExecuteMsg::TransferDataOwnership {
+            data_id,
+            new_delegator_pubkey,
+            new_capsule,
+        } => try_transfer_data_ownership(
+            response,
+            deps,
+            env,
+            info,
+            &data_id,
+            &new_delegator_pubkey,
+            &new_capsule,
+        ),
+        ExecuteMsg::AcceptDataOwnership { data_id } => {
+            try_accept_data_ownership(response, deps, env, info, &data_id)
+        }
+        ExecuteMsg::CancelDataOwnershipTransfer { data_id } => {
+            try_cancel_data_ownership_transfer(response, deps, env, info, &data_id)
+        }
+        ExecuteMsg::RotateDelegatorKey {
+            old_pubkey,
+            new_pubkey,
+            updated_capsules,
+        } => try_rotate_delegator_key(
+            response,
+            deps,
+            env,
+            info,
+            &old_pubkey,
+            &new_pubkey,
+            &updated_capsules,
+        ),
+        ExecuteMsg::PruneExpiredData { limit } => {
+            try_prune_expired_data(response, deps, env, info, limit)
+        }
+        ExecuteMsg::RemoveData { data_id } => try_remove_data(response, deps, env, info, &data_id),
+        ExecuteMsg::AddDelegation {
+            delegator_pubkey,
+            delegatee_pubkey,
+            proxy_delegations,
+            delegatee_labels,
+        } => try_add_delegation(
+            response,
+            deps,
+            env,
+            info,
+            &delegator_pubkey,
+            &delegatee_pubkey,
+            &proxy_delegations,
+            &delegatee_labels,
+        ),
+        ExecuteMsg::RequestReencryption {
+            data_id,
+            delegatee_pubkey,
+        } => try_request_reencryption(response, deps, env, info, &data_id, &delegatee_pubkey),
+        ExecuteMsg::ResolveTimedOutRequest {
+            data_id,
+            delegatee_pubkey,
+        } => try_resolve_timed_out_request(response, deps, env, info, &data_id, &delegatee_pubkey),
+
+        ExecuteMsg::UpdateDataTags { data_id, tags } => {
+            try_update_data_tags(response, deps, env, info, &data_id, &tags)
+        }
+
+        ExecuteMsg::AddDataLabels {
+            data_id,
+            data_labels,
+        } => try_add_data_labels(response, deps, env, info, &data_id, &data_labels),
+
+        ExecuteMsg::RemoveDataLabels {
+            data_id,
+            data_labels,
+        } => try_remove_data_labels(response, deps, env, info, &data_id, &data_labels),
+
+        ExecuteMsg::AddDelegateeLabels {
+            delegatee_pubkey,
+            delegatee_labels,
+            expires_at,
+        } => try_add_delegatee_labels(
+            response,
+            deps,
+            env,
+            info,
+            &delegatee_pubkey,
+            &delegatee_labels,
+            &expires_at,
+        ),
+
+        ExecuteMsg::RemoveDelegateeLabels {
+            delegatee_pubkey,
+            delegatee_labels,
+        } => try_remove_delegatee_labels(
+            response,
+            deps,
+            env,
+            info,
+            &delegatee_pubkey,
+            &delegatee_labels,
+        ),
+        ExecuteMsg::GrantDataAccess {
+            data_id,
+            delegatee_pubkey,
+            expires_at,
+        } => try_grant_data_access(
+            response,
+            deps,
+            env,
+            info,
+            &data_id,
+            &delegatee_pubkey,
+            &expires_at,
+        ),
+        ExecuteMsg::DenyDataAccess {
+            data_id,
+            delegatee_pubkey,
+        } => try_deny_data_access(response, deps, env, info, &data_id, &delegatee_pubkey),
+        ExecuteMsg::RevokeDataAccess {
+            data_id,
+            delegatee_pubkey,
+        } => try_revoke_data_access(response, deps, env, info, &data_id, &delegatee_pubkey),
+        ExecuteMsg::RequestAccess {
+            delegator_pubkey,
+            delegatee_pubkey,
+            target,
+            message,
+        } => try_request_access(
+            response,
+            deps,
+            env,
+            info,
+            &delegator_pubkey,
+            &delegatee_pubkey,
+            &target,
+            &message,
+        ),
+        ExecuteMsg::ApproveAccessRequest { request_id } => {
+            try_approve_access_request(response, deps, env, info, request_id)
+        }
+        ExecuteMsg::RejectAccessRequest { request_id } => {
+            try_reject_access_request(response, deps, env, info, request_id)
+        }
+        ExecuteMsg::SetDelegateePolicy {
+            delegatee_pubkey,
+            policy,
+        } => try_set_delegatee_policy(response, deps, env, info, &delegatee_pubkey, &policy),
+        ExecuteMsg::RegisterDelegatee {
+            delegatee_pubkey,
+            authorised_requesters,
+        } => try_register_delegatee(
+            response,
+            deps,
+            env,
+            info,
+            &delegatee_pubkey,
+            &authorised_requesters,
+        ),
+        ExecuteMsg::OverrideDelegateeRegistration {
+            delegator_pubkey,
+            delegatee_pubkey,
+            delegatee_addr,
+            authorised_requesters,
+        } => try_override_delegatee_registration(
+            response,
+            deps,
+            env,
+            info,
+            &delegator_pubkey,
+            &delegatee_pubkey,
+            &delegatee_addr,
+            &authorised_requesters,
+        ),
+        ExecuteMsg::AddAuthorisedRequesters {
+            delegatee_pubkey,
+            requesters,
+        } => {
+            try_add_authorised_requesters(response, deps, env, info, &delegatee_pubkey, &requesters)
+        }
+        ExecuteMsg::RemoveAuthorisedRequesters {
+            delegatee_pubkey,
+            requesters,
+        } => try_remove_authorised_requesters(
+            response,
+            deps,
+            env,
+            info,
+            &delegatee_pubkey,
+            &requesters,
+        ),
+        ExecuteMsg::FundDelegateeAllowance {
+            delegatee_pubkey,
+            spend_limit,
+            expires,
+        } => try_fund_delegatee_allowance(
+            response,
+            deps,
+            env,
+            info,
+            &delegatee_pubkey,
+            &spend_limit,
+            &expires,
+        ),
+        ExecuteMsg::WithdrawDelegateeAllowance { delegatee_pubkey } => {
+            try_withdraw_delegatee_allowance(response, deps, env, info, &delegatee_pubkey)
+        }
+        ExecuteMsg::ResolveTimedOutRequests { limit } => {
+            try_resolve_timed_out_requests(response, deps, env, info, limit)
+        }
+        ExecuteMsg::CancelReencryptionRequest {
+            data_id,
+            delegatee_pubkey,
+        } => {
+            try_cancel_reencryption_request(response, deps, env, info, &data_id, &delegatee_pubkey)
+        }
+        ExecuteMsg::Subscribe {
+            delegator_pubkey,
+            delegatee_pubkey,
+            labels,
+            budget,
+        } => try_subscribe(
+            response,
+            deps,
+            env,
+            info,
+            &delegator_pubkey,
+            &delegatee_pubkey,
+            &labels,
+            &budget,
+        ),
+        ExecuteMsg::Unsubscribe {
+            delegator_pubkey,
+            delegatee_pubkey,
+        } => try_unsubscribe(
+            response,
+            deps,
+            env,
+            info,
+            &delegator_pubkey,
+            &delegatee_pubkey,
+        ),
+    }
+}
+
+//// Query functions
+
+pub fn get_proxy_tasks(
+    store: &dyn Storage,
+    proxy_addr: &Addr,
+    block_height: &u64,
+    start_after: &Option<ProxyTasksCursor>,
+    limit: Option<u32>,
+) -> StdResult<GetProxyTasksResponse> {
+    // Returns page of proxy tasks from queue ordered by timeout, TimedOut tasks are skipped
+
+    let limit = get_query_limit(limit);
+    let tasks = store_get_proxy_tasks_in_queue(
+        store,
+        proxy_addr,
+        *block_height,
+        start_after
+            .as_ref()
+            .map(|cursor| (cursor.timeout_height, cursor.task_id)),
+        limit,
+    );
+
+    // Next page can exist only if this one is full
+    let cursor = match tasks.len() == limit {
+        true => tasks
+            .last()
+            .map(|(timeout_height, task_id)| ProxyTasksCursor {
+                timeout_height: *timeout_height,
+                task_id: *task_id,
+            }),
+        false => None,
+    };
+
+    let mut tasks_response: Vec<ProxyTaskResponse> = Vec::new();
+    for (_, task_id) in tasks {
+        // Stale queue entry is skipped
+        let proxy_task: ProxyTask = match store_get_proxy_task(store, &task_id)? {
+            Some(proxy_task) => proxy_task,
+            None => continue,
+        };
+        let data_entry = match store_get_data_entry(store, &proxy_task.data_id)? {
+            Some(data_entry) => data_entry,
+            None => continue,
+        };
+
+        tasks_response.push(ProxyTaskResponse {
+            data_id: proxy_task.data_id.clone(),
+            capsule: data_entry.capsule.clone(),
+            delegatee_pubkey: proxy_task.delegatee_pubkey,
+            delegator_pubkey: data_entry.delegator_pubkey,
+            delegation_string: proxy_task.delegation_string,
+        });
+    }
+
+    Ok(GetProxyTasksResponse {
+        proxy_tasks: tasks_response,
+        cursor,
+    })
+}
+
+pub fn simulate_reencryption_request(
+    store: &dyn Storage,
+    block: &BlockInfo,
+    requester: &Addr,
+    funds: &Option<Coin>,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<SimulateReencryptionRequestResponse> {
+    // Dry-run of RequestReencryption, runs the same checks without modifying storage
+
+    let state: State = store_get_state(store)?;
+    let staking_config: StakingConfig = store_get_staking_config(store)?;
+    let timeouts_config: TimeoutsConfig = store_get_timeouts_config(store)?;
+
+    let (request_plan, result) = match get_reencryption_request_plan(
+        store,
+        block,
+        &state,
+        &staking_config,
+        requester,
+        data_id,
+        delegatee_pubkey,
+    ) {
+        Ok(request_plan) => {
+            let result = check_reencryption_request(&state, &staking_config, &request_plan)
+                .and_then(|_| match funds {
+                    None => check_delegatee_allowance(
+                        store,
+                        &request_plan.delegator_addr,
+                        delegatee_pubkey,
+                        request_plan.total_required_reward_amount,
+                        block,
+                    )
+                    .map(|_| ()),
+                    Some(funds) => ensure_stake(
+                        &staking_config,
+                        std::slice::from_ref(funds),
+                        &request_plan.total_required_reward_amount,
+                    )
+                    .map(|_| ()),
+                });
+            (Some(request_plan), result)
+        }
+        Err(err) => (None, Err(err)),
+    };
+
+    let failure_reason = match result {
+        Ok(()) => None,
+        Err(StdError::GenericErr { msg, .. }) => Some(msg),
+        Err(err) => Some(err.to_string()),
+    };
+
+    let (is_permitted, available_proxies, required_amount) = match request_plan {
+        Some(request_plan) => (
+            request_plan.permission_error.is_none(),
+            request_plan.available_proxies,
+            request_plan.total_required_reward_amount,
+        ),
+        None => (false, vec![], 0),
+    };
+
+    // Same refund as RequestReencryption sends back for funds above required amount
+    let excess_refund_amount = match funds {
+        Some(funds) => funds.amount.u128().saturating_sub(required_amount),
+        None => 0,
+    };
+
+    Ok(SimulateReencryptionRequestResponse {
+        is_permitted,
+        available_proxies,
+        required_funds: Coin::new(required_amount, &staking_config.stake_denom),
+        excess_refund: Coin::new(excess_refund_amount, &staking_config.stake_denom),
+        timeout_height: block.height + timeouts_config.timeout_height,
+        failure_reason,
+    })
+}
+
+pub fn get_delegatee_requests(
+    store: &dyn Storage,
+    block: &BlockInfo,
+    delegatee_pubkey: &str,
+    state_filter: &Option<ReencryptionRequestState>,
+    start_after: &Option<String>,
+    limit: Option<u32>,
+) -> StdResult<GetDelegateeRequestsResponse> {
+    // Latest requests of delegatee, scan continues until limit of requests matches state_filter
+
+    let state = store_get_state(store)?;
+
+    let limit = get_query_limit(limit);
+    let mut requests: Vec<DelegateeRequestResponse> = Vec::new();
+    let mut cursor = start_after.clone();
+    let mut n_scanned: u32 = 0;
+
+    loop {
+        let data_ids =
+            store_get_delegatee_data_ids(store, delegatee_pubkey, cursor.as_deref(), limit)?;
+
+        // Next page can exist only if this one is full
+        let is_last_page = data_ids.is_empty() || data_ids.len() < limit;
+
+        for data_id in data_ids {
+            n_scanned += 1;
+            cursor = Some(data_id.clone());
+
+            let reencryption_request_state =
+                get_reencryption_request_state(store, &state, &data_id, delegatee_pubkey, block)?;
+
+            let is_matching = match state_filter {
+                Some(state_filter) => state_filter == &reencryption_request_state,
+                None => true,
+            };
+
+            // Index entry exists only with tasks, all tasks of request share timeout
+            let proxy_task = match is_matching {
+                true => {
+                    match store_get_all_delegatee_proxy_tasks(store, &data_id, delegatee_pubkey)?
+                        .first()
+                    {
+                        Some(task_id) => store_get_proxy_task(store, task_id)?,
+                        None => None,
+                    }
+                }
+                false => None,
+            };
+
+            // Inconsistent index entry is skipped
+            if let Some(proxy_task) = proxy_task {
+                // Fragments of expired data are not shared any more
+                let n_fragments = match is_data_expired(store, &data_id, block)? {
+                    true => 0,
+                    false => get_all_fragments(store, &data_id, delegatee_pubkey)?.len() as u32,
+                };
+
+                requests.push(DelegateeRequestResponse {
+                    data_id,
+                    reencryption_request_state,
+                    n_fragments,
+                    threshold: state.threshold,
+                    timeout_height: proxy_task.timeout_height,
+                });
+            }
+
+            if requests.len() == limit || n_scanned == MAX_QUERY_SCAN {
+                return Ok(GetDelegateeRequestsResponse { requests, cursor });
+            }
+        }
+
+        if is_last_page {
+            return Ok(GetDelegateeRequestsResponse {
+                requests,
+                cursor: None,
+            });
+        }
+    }
+}
+
+pub fn get_proxies_availability(store: &dyn Storage) -> StdResult<Vec<ProxyAvailabilityResponse>> {
+    let proxy_addresses = store_get_all_active_proxy_addresses(store)?;
+
+    let mut res: Vec<ProxyAvailabilityResponse> = Vec::new();
+
+    for proxy_addr in proxy_addresses {
+        let proxy_entry: Proxy = store_get_proxy_entry(store, &proxy_addr)?.unwrap();
+
+        res.push(ProxyAvailabilityResponse {
+            proxy_addr,
+            // If a proxy is in an active proxies map it has a pubkey
+            proxy_pubkey: proxy_entry.proxy_pubkey.unwrap(),
+            stake_amount: proxy_entry.stake_amount,
+        });
+    }
+
+    Ok(res)
+}
+
+#[entry_point]
+pub fn sudo(deps: DepsMut, _env: Env, msg: SudoMsg) -> StdResult<Response> {
+    let mut response: Response = Response::new();
+
+    match msg {
+        SudoMsg::RemoveProxy { proxy_addr } => {
+            remove_proxy(&mut response, deps.storage, &proxy_addr)?
+        }
+        SudoMsg::PauseContract {} => set_paused(&mut response, deps.storage, true)?,
+        SudoMsg::UnpauseContract {} => set_paused(&mut response, deps.storage, false)?,
+        SudoMsg::UpdateConfig {
+            minimum_proxy_stake_amount,
+            timeout_height,
+            proxy_whitelisting,
+        } => update_config(
+            &mut response,
+            deps.storage,
+            &minimum_proxy_stake_amount,
+            &timeout_height,
+            &proxy_whitelisting,
+        )?,
+        SudoMsg::UpdateAdmin { admin } => update_admin(&mut response, deps.storage, &admin)?,
+    }
+
+    response.attributes.push(Attribute::new("sudo", "true"));
+    Ok(response)
+}
+
+#[entry_point]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> StdResult<Response> {
+    let mut response: Response = Response::new();
+
+    // At most limit legacy entries are moved, migration is repeated until it's finished
+    let mut limit = msg.limit.unwrap_or(DEFAULT_MIGRATION_LIMIT) as usize;
+    if limit == 0 {
+        return generic_err!("Migration limit must be greater than 0.");
+    }
+
+    // Move entries from hand-rolled PrefixedStorage maps to cw-storage-plus IndexedMaps
+    let n_proxies = migrate_legacy_proxies(deps.storage, &mut limit)?;
+    let n_delegations = migrate_legacy_proxy_delegations(deps.storage, &mut limit)?;
+    let n_proxy_tasks = migrate_legacy_proxy_tasks(deps.storage, &mut limit)?;
+    let n_data_entries = migrate_legacy_data_entries(deps.storage, &mut limit)?;
+
+    // Exhausted limit means legacy entries can remain
+    let is_finished = limit > 0;
+    store_set_is_legacy_migration_pending(deps.storage, !is_finished)?;
+
+    response
+        .attributes
+        .push(Attribute::new("action", "migrate"));
+    response
+        .attributes
+        .push(Attribute::new("migrated_proxies", n_proxies.to_string()));
+    response.attributes.push(Attribute::new(
+        "migrated_delegations",
+        n_delegations.to_string(),
+    ));
+    response.attributes.push(Attribute::new(
+        "migrated_proxy_tasks",
+        n_proxy_tasks.to_string(),
+    ));
+    response.attributes.push(Attribute::new(
+        "migrated_data_entries",
+        n_data_entries.to_string(),
+    ));
+    response.attributes.push(Attribute::new(
+        "migration_finished",
+        is_finished.to_string(),
+    ));
+    Ok(response)
+}
+
+#[entry_point]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetAvailableProxies {} => {
+            let state = store_get_state(deps.storage)?;
+
+            if state.terminated {
+                return to_binary(&GetAvailableProxiesResponse {
+                    proxies: Vec::new(),
+                });
+            }
+
+            Ok(to_binary(&GetAvailableProxiesResponse {
+                proxies: get_proxies_availability(deps.storage)?,
+            })?)
+        }
+        QueryMsg::GetDataID { data_id } => Ok(to_binary(&GetDataIDResponse {
+            data_entry: store_get_data_entry(deps.storage, &data_id)?,
+            tags: store_get_all_data_tags(deps.storage, &data_id),
+        })?),
+        QueryMsg::GetDataOwnershipTransfer { data_id } => {
+            Ok(to_binary(&GetDataOwnershipTransferResponse {
+                transfer: store_get_data_ownership_transfer(deps.storage, &data_id)?,
+            })?)
+        }
+        QueryMsg::GetDataByTag {
+            key,
+            value,
+            start_after,
+            limit,
+        } => Ok(to_binary(&GetDataByTagResponse {
+            data_ids: store_get_data_ids_by_tag(
+                deps.storage,
+                &key,
+                &value,
+                start_after.as_deref(),
+                get_query_limit(limit),
+            ),
+        })?),
+        QueryMsg::ListProxies {
+            state_filter,
+            start_after,
+            limit,
+        } => {
+            let staking_config = store_get_staking_config(deps.storage)?;
+
+            let proxy_addresses = match state_filter {
+                None => {
+                    store_get_proxies(deps.storage, start_after.as_ref(), get_query_limit(limit))?
+                }
+                Some(proxy_state) => store_get_proxies_by_state(
+                    deps.storage,
+                    &proxy_state,
+                    start_after.as_ref(),
+                    get_query_limit(limit),
+                )?,
+            };
+
+            let proxies = proxy_addresses
+                .into_iter()
+                .map(|proxy_addr| {
+                    let proxy_entry = store_get_proxy_entry(deps.storage, &proxy_addr)?.unwrap();
+
+                    Ok(ProxyEntryResponse {
+                        proxy_addr,
+                        proxy_status: get_proxy_status(&staking_config, proxy_entry),
+                    })
+                })
+                .collect::<StdResult<Vec<ProxyEntryResponse>>>()?;
+
+            Ok(to_binary(&ListProxiesResponse { proxies })?)
+        }
+        QueryMsg::ListDataByDelegator {
+            delegator_pubkey,
+            start_after,
+            limit,
+        } => Ok(to_binary(&ListDataByDelegatorResponse {
+            data_ids: store_get_delegator_data_ids(
+                deps.storage,
+                &delegator_pubkey,
+                start_after.as_deref(),
+                get_query_limit(limit),
+            )?,
+        })?),
+        QueryMsg::ListDelegationsByDelegator {
+            delegator_pubkey,
+            start_after,
+            limit,
+        } => {
+            let delegations = store_get_delegator_delegatees(
+                deps.storage,
+                &delegator_pubkey,
+                start_after.as_deref(),
+                get_query_limit(limit),
+            )?
+            .into_iter()
+            .map(|delegatee_pubkey| {
+                Ok(DelegationResponse {
+                    delegation_state: get_delegation_state(
+                        deps.storage,
+                        &delegator_pubkey,
+                        &delegatee_pubkey,
+                    )?,
+                    delegator_pubkey: delegator_pubkey.clone(),
+                    delegatee_pubkey,
+                })
+            })
+            .collect::<StdResult<Vec<DelegationResponse>>>()?;
+
+            Ok(to_binary(&ListDelegationsResponse { delegations })?)
+        }
+        QueryMsg::ListDelegationsByDelegatee {
+            delegatee_pubkey,
+            start_after,
+            limit,
+        } => {
+            let delegations = store_get_delegatee_delegators(
+                deps.storage,
+                &delegatee_pubkey,
+                start_after.as_deref(),
+                get_query_limit(limit),
+            )?
+            .into_iter()
+            .map(|delegator_pubkey| {
+                Ok(DelegationResponse {
+                    delegation_state: get_delegation_state(
+                        deps.storage,
+                        &delegator_pubkey,
+                        &delegatee_pubkey,
+                    )?,
+                    delegator_pubkey,
+                    delegatee_pubkey: delegatee_pubkey.clone(),
+                })
+            })
+            .collect::<StdResult<Vec<DelegationResponse>>>()?;
+
+            Ok(to_binary(&ListDelegationsResponse { delegations })?)
+        }
+        QueryMsg::ListTasksByProxy {
+            proxy_addr,
+            start_after,
+            limit,
+        } => {
+            let tasks = store_get_proxy_tasks_history(
+                deps.storage,
+                &proxy_addr,
+                start_after,
+                get_query_limit(limit),
+            )?
+            .into_iter()
+            .map(|task_id| {
+                Ok(ProxyTaskEntryResponse {
+                    task_id,
+                    proxy_task: store_get_proxy_task(deps.storage, &task_id)?.unwrap(),
+                })
+            })
+            .collect::<StdResult<Vec<ProxyTaskEntryResponse>>>()?;
+
+            Ok(to_binary(&ListTasksByProxyResponse { tasks })?)
+        }
+        QueryMsg::GetFragments {
+            data_id,
+            delegatee_pubkey,
+        } => {
+            let state = store_get_state(deps.storage)?;
+            let data_entry = match store_get_data_entry(deps.storage, &data_id)? {
+                None => generic_err!("Data entry doesn't exist"),
+                Some(data) => Ok(data),
+            }?;
+
+            // Fragments of expired data are not shared any more
+            let fragments = match is_data_expired(deps.storage, &data_id, &env.block)? {
+                true => Vec::new(),
+                false => get_all_fragments(deps.storage, &data_id, &delegatee_pubkey)?,
+            };
+
+            Ok(to_binary(&GetFragmentsResponse {
+                reencryption_request_state: get_reencryption_request_state(
+                    deps.storage,
+                    &state,
+                    &data_id,
+                    &delegatee_pubkey,
+                    &env.block,
+                )?,
+                capsule: data_entry.capsule,
+                fragments,
+                threshold: state.threshold,
+            })?)
+        }
+        QueryMsg::GetReencryptionRequests {
+            data_id,
+            delegatee_pubkey,
+        } => {
+            let state = store_get_state(deps.storage)?;
+            let is_expired = is_data_expired(deps.storage, &data_id, &env.block)?;
+
+            let mut requests: Vec<ReencryptionRequestResponse> = Vec::new();
+            for (request_seq, task_ids) in
+                store_get_all_delegatee_requests(deps.storage, &data_id, &delegatee_pubkey)?
+            {
+                let mut fragments: Vec<String> = Vec::new();
+                for task_id in &task_ids {
+                    let proxy_task = store_get_proxy_task(deps.storage, task_id)?.unwrap();
+                    if let Some(fragment) = proxy_task.fragment {
+                        fragments.push(fragment);
+                    }
+                }
+
+                // Fragments of expired data are not shared any more
+                requests.push(match is_expired {
+                    true => ReencryptionRequestResponse {
+                        request_seq,
+                        reencryption_request_state: ReencryptionRequestState::Inaccessible,
+                        fragments: Vec::new(),
+                    },
+                    false => ReencryptionRequestResponse {
+                        request_seq,
+                        reencryption_request_state: get_proxy_tasks_state(
+                            deps.storage,
+                            &state,
+                            &task_ids,
+                            &env.block,
+                        )?,
+                        fragments,
+                    },
+                });
+            }
+
+            Ok(to_binary(&GetReencryptionRequestsResponse { requests })?)
+        }
+        QueryMsg::GetDelegateeRequests {
+            delegatee_pubkey,
+            state_filter,
+            start_after,
+            limit,
+        } => Ok(to_binary(&get_delegatee_requests(
+            deps.storage,
+            &env.block,
+            &delegatee_pubkey,
+            &state_filter,
+            &start_after,
+            limit,
+        )?)?),
+        QueryMsg::GetContractState {} => {
+            let state = store_get_state(deps.storage)?;
+
+            Ok(to_binary(&GetContractStateResponse {
+                admin: state.admin,
+                threshold: state.threshold,
+                terminated: state.terminated,
+                withdrawn: state.withdrawn,
+                paused: state.paused,
+            })?)
+        }
+        QueryMsg::GetContractStats {} => {
+            let contract_stats = store_get_contract_stats(deps.storage)?;
+
+            Ok(to_binary(&GetContractStatsResponse {
+                total_staked: contract_stats.total_staked,
+                n_active_proxies: contract_stats.n_active_proxies,
+                n_open_tasks: contract_stats.n_open_tasks,
+                n_completed_requests: contract_stats.n_completed_requests,
+                total_rewards_paid: contract_stats.total_rewards_paid,
+                total_slashed: contract_stats.total_slashed,
+            })?)
+        }
+
+        QueryMsg::GetStakingConfig {} => {
+            let staking_config = store_get_staking_config(deps.storage)?;
+
+            Ok(to_binary(&GetStakingConfigResponse {
+                stake_denom: staking_config.stake_denom,
+                minimum_proxy_stake_amount: staking_config.minimum_proxy_stake_amount,
+                per_proxy_task_reward_amount: staking_config.per_proxy_task_reward_amount,
+                per_task_slash_stake_amount: staking_config.per_task_slash_stake_amount,
+            })?)
+        }
+
+        QueryMsg::GetTimeoutsConfig {} => {
+            let timeouts_config = store_get_timeouts_config(deps.storage)?;
+
+            Ok(to_binary(&GetTimeoutsConfigResponse {
+                timeout_height: timeouts_config.timeout_height,
+            })?)
+        }
+
+        QueryMsg::GetConfig {} => Ok(to_binary(&GetConfigResponse {
+            state: store_get_state(deps.storage)?,
+            staking_config: store_get_staking_config(deps.storage)?,
+            timeouts_config: store_get_timeouts_config(deps.storage)?,
+        })?),
+
+        QueryMsg::GetProxyTasks {
+            proxy_addr,
+            start_after,
+            limit,
+        } => {
+            let state = store_get_state(deps.storage)?;
+
+            if state.withdrawn {
+                return to_binary(&GetProxyTasksResponse {
+                    proxy_tasks: Vec::new(),
+                    cursor: None,
+                });
+            }
+
+            Ok(to_binary(&get_proxy_tasks(
+                deps.storage,
+                &proxy_addr,
+                &env.block.height,
+                &start_after,
+                limit,
+            )?)?)
+        }
+
+        QueryMsg::GetDelegationStatus {
+            delegator_pubkey,
+            delegatee_pubkey,
+        } => {
+            let staking_config = store_get_staking_config(deps.storage)?;
+            let n_availbale_proxies = get_n_available_proxies_from_delegation(
+                deps.storage,
+                &delegator_pubkey,
+                &delegatee_pubkey,
+                &staking_config.per_task_slash_stake_amount.u128(),
+            )?;
+
+            let minimum_stake_amount =
+                n_availbale_proxies as u128 * staking_config.per_proxy_task_reward_amount.u128();
+
+            Ok(to_binary(&GetDelegationStatusResponse {
+                delegation_state: get_delegation_state(
+                    deps.storage,
+                    &delegator_pubkey,
+                    &delegatee_pubkey,
+                )?,
+                total_request_reward_amount: Coin {
+                    denom: staking_config.stake_denom,
+                    amount: Uint128::new(minimum_stake_amount),
+                },
+            })?)
+        }
+
+        QueryMsg::SimulateReencryptionRequest {
+            data_id,
+            delegatee_pubkey,
+            requester,
+            funds,
+        } => Ok(to_binary(&simulate_reencryption_request(
+            deps.storage,
+            &env.block,
+            &requester,
+            &funds,
+            &data_id,
+            &delegatee_pubkey,
+        )?)?),
+
+        QueryMsg::GetProxyStatus { proxy_addr } => {
+            let mut proxy_status: Option<ProxyStatusResponse> = None;
+
+            if let Some(proxy_entry) = store_get_proxy_entry(deps.storage, &proxy_addr)? {
+                let staking_config = store_get_staking_config(deps.storage)?;
+
+                proxy_status = Some(get_proxy_status(&staking_config, proxy_entry));
+            }
+
+            Ok(to_binary(&GetProxyStatusResponse { proxy_status })?)
+        }
+        QueryMsg::GetDataLabels { data_id } => Ok(to_binary(&GetDataLabelsResponse {
+            data_labels: store_get_all_data_labels(deps.storage, &data_id),
+        })?),
+        QueryMsg::GetDelegateeLabels {
+            delegator_addr,
+            delegatee_pubkey,
+        } => {
+            let delegatee_label_expirations: Vec<DelegateeLabel> =
+                store_get_all_delegatee_labels_with_expiration(
+                    deps.storage,
+                    &delegator_addr,
+                    &delegatee_pubkey,
+                )
+                .into_iter()
+                .map(|(label, expires_at)| DelegateeLabel { label, expires_at })
+                .collect();
+
+            // Plain label list keeps response compatible with existing clients
+            Ok(to_binary(&GetDelegateeLabelsResponse {
+                delegatee_labels: delegatee_label_expirations
+                    .iter()
+                    .map(|delegatee_label| delegatee_label.label.clone())
+                    .collect(),
+                delegatee_label_expirations,
+            })?)
+        }
+        QueryMsg::GetDataAccessGrants { data_id } => {
+            let mut grants: Vec<DataAccessGrant> = Vec::new();
+            let mut denied_delegatees: Vec<String> = Vec::new();
+
+            for (delegatee_pubkey, rule) in store_get_all_data_access_rules(deps.storage, &data_id)
+            {
+                match rule {
+                    DataAccessRule::Grant { expires_at } => grants.push(DataAccessGrant {
+                        delegatee_pubkey,
+                        expires_at,
+                    }),
+                    DataAccessRule::Deny => denied_delegatees.push(delegatee_pubkey),
+                }
+            }
+
+            Ok(to_binary(&GetDataAccessGrantsResponse {
+                grants,
+                denied_delegatees,
+            })?)
+        }
+        QueryMsg::GetPendingAccessRequests { delegator_pubkey } => {
+            let mut requests: Vec<AccessRequestResponse> = Vec::new();
+
+            for request_id in
+                store_get_all_delegator_access_requests(deps.storage, &delegator_pubkey)
+            {
+                let access_request = store_get_access_request(deps.storage, request_id).unwrap();
+
+                // Expired requests are pruned later
+                if access_request.expiry_height > env.block.height {
+                    requests.push(AccessRequestResponse {
+                        request_id,
+                        access_request,
+                    });
+                }
+            }
+
+            Ok(to_binary(&GetPendingAccessRequestsResponse { requests })?)
+        }
+        QueryMsg::CheckPermission {
+            delegator_addr,
+            delegatee_pubkey,
+            data_id,
+        } => Ok(to_binary(&CheckPermissionResponse {
+            policy: store_get_delegatee_policy(deps.storage, &delegator_addr, &delegatee_pubkey),
+            permitted: is_reencryption_permitted(
+                deps.storage,
+                &delegator_addr,
+                &delegatee_pubkey,
+                &data_id,
+                &env.block,
+            ),
+        })?),
+        QueryMsg::GetDelegateeAllowance {
+            delegator_addr,
+            delegatee_pubkey,
+        } => Ok(to_binary(&GetDelegateeAllowanceResponse {
+            allowance: store_get_delegatee_allowance(
+                deps.storage,
+                &delegator_addr,
+                &delegatee_pubkey,
+            ),
+        })?),
+        QueryMsg::GetSubscription {
+            delegator_pubkey,
+            delegatee_pubkey,
+        } => Ok(to_binary(&GetSubscriptionResponse {
+            subscription: store_get_subscription(
+                deps.storage,
+                &delegator_pubkey,
+                &delegatee_pubkey,
+            ),
+        })?),
+        QueryMsg::GetDelegateeRegistration { delegatee_pubkey } => {
+            Ok(to_binary(&GetDelegateeRegistrationResponse {
+                delegatee_addr: store_get_delegatee_address(deps.storage, &delegatee_pubkey),
+                authorised_requesters: store_get_all_delegatee_requesters(
+                    deps.storage,
+                    &delegatee_pubkey,
+                ),
+            })?)
+        }
+    }
+}
+
+// Private functions
+
+fn get_proxy_status(staking_config: &StakingConfig, proxy_entry: Proxy) -> ProxyStatusResponse {
+    ProxyStatusResponse {
+        withdrawable_stake_amount: Uint128::new(get_maximum_withdrawable_stake_amount(
+            staking_config,
+            &proxy_entry,
+        )),
+        proxy_pubkey: proxy_entry.proxy_pubkey,
+        stake_amount: proxy_entry.stake_amount,
+        proxy_state: proxy_entry.state,
+    }
+}
+
+fn ensure_admin(state: &State, addr: &Addr) -> StdResult<()> {
+    if addr != &state.admin {
+        return generic_err!("Only admin can execute this method.");
+    }
+    Ok(())
+}
+
+fn ensure_delegator(
+    storage: &mut dyn Storage,
+    delegator_pubkey: &str,
+    delegator_address: &Addr,
+) -> StdResult<()> {
+    if let Some(correct_delegator_addr) = store_get_delegator_address(storage, delegator_pubkey)? {
+        // Check if delegator_pubkey is registered with delegator_address
+
+        if &correct_delegator_addr != delegator_address {
+            return generic_err!(format!(
+                "Delegator {} already registered with this pubkey.",
+                correct_delegator_addr
+            ));
+        }
+    } else {
+        // Reserve delegator_pubkey for current delegator_address
+        store_set_delegator_address(storage, delegator_pubkey, delegator_address);
+    }
+    Ok(())
+}
+
+fn ensure_delegatee_owner(
+    storage: &dyn Storage,
+    delegatee_pubkey: &str,
+    delegatee_address: &Addr,
+) -> StdResult<()> {
+    match store_get_delegatee_address(storage, delegatee_pubkey) {
+        None => generic_err!(format!("Delegatee {} is not registered.", delegatee_pubkey)),
+        Some(correct_delegatee_addr) if &correct_delegatee_addr != delegatee_address => {
+            generic_err!("Sender is not owner of delegatee pubkey.")
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+fn ensure_data_owner(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    delegator_address: &Addr,
+) -> StdResult<()> {
+    let data_entry: DataEntry = match store_get_data_entry(storage, data_id)? {
+        None => generic_err!("Data entry doesn't exist."),
+        Some(data_entry) => Ok(data_entry),
+    }?;
+
+    let correct_delegator_addr: Addr =
+        store_get_delegator_address(storage, &data_entry.delegator_pubkey)?.unwrap();
+
+    if &correct_delegator_addr != delegator_address {
+        return generic_err!("Sender is not a data owner.");
+    }
+
+    Ok(())
+}
+
+fn ensure_active_proxy(storage: &dyn Storage, proxy_addr: &Addr) -> StdResult<Proxy> {
+    // Get proxy or return error
+    let proxy = match store_get_proxy_entry(storage, proxy_addr)? {
+        None => generic_err!("Proxy not registered"),
+        Some(proxy) => Ok(proxy),
+    }?;
+
+    if proxy.proxy_pubkey.is_none() {
+        return generic_err!("Proxy not active");
+    }
+
+    Ok(proxy)
+}
+
+fn provide_fragment(
+    storage: &mut dyn Storage,
+    env: &Env,
+    proxy_addr: &Addr,
+    data_id: &str,
+    delegatee_pubkey: &str,
+    fragment: &str,
+) -> StdResult<()> {
+    // Add fragment to proxy task, storage is modified only when all checks pass
+
+    // Get task_id or return error
+    let task_id: u64 =
+        match store_get_delegatee_proxy_task(storage, data_id, delegatee_pubkey, proxy_addr)? {
+            None => generic_err!("This fragment was not requested."),
+            Some(task_id) => Ok(task_id),
+        }?;
+
+    // Task must exist - panic otherwise
+    let mut proxy_task = store_get_proxy_task(storage, &task_id)?.unwrap();
+    if env.block.height >= proxy_task.timeout_height {
+        return generic_err!("Request timed out.");
+    }
+
+    if is_data_expired(storage, data_id, &env.block)? {
+        return generic_err!("Data entry has expired.");
+    }
+
+    if proxy_task.fragment.is_some() {
+        return generic_err!("Fragment already provided.");
+    }
+
+    /*
+    let data_entry = store_get_data_entry(storage, data_id)?.unwrap();
+    verify_fragment(
+        fragment,
+        &data_entry.capsule,
+        &data_entry.delegator_pubkey,
+        &proxy_task.delegatee_pubkey,
+    )?;
+     */
+
+    if get_all_fragments(storage, data_id, delegatee_pubkey)?.contains(&fragment.to_string()) {
+        return generic_err!("Fragment already provided by other proxy.");
+    }
+
+    // Add fragment to task
+    proxy_task.fragment = Some(fragment.to_string());
+    store_set_proxy_task(storage, &task_id, &proxy_task)?;
+
+    let state: State = store_get_state(storage)?;
+    let staking_config: StakingConfig = store_get_staking_config(storage)?;
+    let is_request_completed =
+        get_all_fragments(storage, data_id, delegatee_pubkey)?.len() as u32 == state.threshold;
+    update_contract_stats(storage, |stats| {
+        stats.total_rewards_paid += staking_config.per_proxy_task_reward_amount;
+        if is_request_completed {
+            stats.n_completed_requests += 1;
+        }
+    })?;
+
+    // Remove task from proxy queue as it's completed
+    store_remove_proxy_task_from_queue(storage, proxy_addr, proxy_task.timeout_height, &task_id)?;
+
+    Ok(())
+}
+
+fn request_subscribed_reencryptions(
+    response: &mut Response,
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    data_id: &str,
+) -> StdResult<()> {
+    // Subscriptions are served on best effort basis, requests which can't be created are skipped
+
+    let data_labels = store_get_all_data_labels(storage, data_id);
+    if data_labels.is_empty() || is_data_expired(storage, data_id, block)? {
+        return Ok(());
+    }
+
+    let data_entry = store_get_data_entry(storage, data_id)?.unwrap();
+    let delegator_addr =
+        store_get_delegator_address(storage, &data_entry.delegator_pubkey)?.unwrap();
+
+    let mut state: State = store_get_state(storage)?;
+    if state.paused {
+        return Ok(());
+    }
+
+    let staking_config: StakingConfig = store_get_staking_config(storage)?;
+    let timeouts_config: TimeoutsConfig = store_get_timeouts_config(storage)?;
+    let n_minimum_proxies = get_n_minimum_proxies_for_refund(&state, &staking_config);
+
+    for (delegatee_pubkey, mut subscription) in
+        store_get_all_delegator_subscriptions(storage, &data_entry.delegator_pubkey)
+    {
+        if !subscription
+            .labels
+            .iter()
+            .any(|label| data_labels.contains(label))
+        {
+            continue;
+        }
+
+        if !is_reencryption_permitted(storage, &delegator_addr, &delegatee_pubkey, data_id, block)
+            || !store_is_list_of_delegatee_proxy_tasks_empty(storage, data_id, &delegatee_pubkey)?
+        {
+            continue;
+        }
+
+        let proxy_addresses = store_get_all_proxies_from_delegation(
+            storage,
+            &data_entry.delegator_pubkey,
+            &delegatee_pubkey,
+        )?;
+        let n_available_proxies = get_n_available_proxies_from_delegation(
+            storage,
+            &data_entry.delegator_pubkey,
+            &delegatee_pubkey,
+            &staking_config.per_task_slash_stake_amount.u128(),
+        )?;
+        if n_available_proxies == 0 || n_available_proxies < n_minimum_proxies {
+            continue;
+        }
+
+        // Rewards are paid from subscription budget
+        let total_required_reward_amount =
+            staking_config.per_proxy_task_reward_amount.u128() * n_available_proxies as u128;
+        if subscription.budget.u128() < total_required_reward_amount {
+            continue;
+        }
+        subscription.budget =
+            Uint128::new(subscription.budget.u128() - total_required_reward_amount);
+        store_set_subscription(
+            storage,
+            &data_entry.delegator_pubkey,
+            &delegatee_pubkey,
+            &subscription,
+        );
+
+        let new_proxy_task = ProxyTask {
+            delegatee_pubkey: delegatee_pubkey.clone(),
+            data_id: data_id.to_string(),
+            fragment: None,
+            proxy_addr: Addr::unchecked(""),
+            delegation_string: "".to_string(),
+            resolved: false,
+            abandoned: false,
+            timeout_height: block.height + timeouts_config.timeout_height,
+            refund_addr: subscription.subscriber_addr.clone(),
+            allowance_owner_addr: None,
+            request_seq: 0,
+        };
+        create_proxy_tasks(
+            storage,
+            &mut state,
+            &staking_config,
+            &data_entry.delegator_pubkey,
+            &proxy_addresses,
+            new_proxy_task,
+        )?;
+
+        response.events.push(
+            Event::new("subscription_reencryption_requested")
+                .add_attribute("data_id", data_id)
+                .add_attribute("delegatee_pubkey", &delegatee_pubkey),
+        );
+    }
+
+    store_set_state(storage, &state)
+}
+
+fn create_proxy_tasks(
+    storage: &mut dyn Storage,
+    state: &mut State,
+    staking_config: &StakingConfig,
+    delegator_pubkey: &str,
+    proxy_addresses: &[Addr],
+    mut new_proxy_task: ProxyTask,
+) -> StdResult<(Vec<ProxyStakeResponse>, u64)> {
+    // Returns stake of assigned proxies and sequence number of created request
+    let data_id = new_proxy_task.data_id.clone();
+    let delegatee_pubkey = new_proxy_task.delegatee_pubkey.clone();
+
+    // Each request of delegatee for data_id gets new sequence number
+    let request_seq = store_get_next_request_seq(storage, &data_id, &delegatee_pubkey);
+    store_set_next_request_seq(storage, &data_id, &delegatee_pubkey, request_seq + 1);
+    new_proxy_task.request_seq = request_seq;
+
+    let mut proxy_stake = Vec::new();
+
+    // Assign re-encrpytion tasks to all available proxies
+    for proxy_addr in proxy_addresses {
+        // Check if proxy has enough stake
+        let mut proxy = store_get_proxy_entry(storage, proxy_addr)?.unwrap();
+
+        if proxy.stake_amount.u128() < staking_config.per_task_slash_stake_amount.u128() {
+            // Proxy cannot be selected for insufficient amount
+            continue;
+        }
+
+        // Subtract stake from proxy
+        proxy.stake_amount = proxy
+            .stake_amount
+            .checked_sub(staking_config.per_task_slash_stake_amount)?;
+        store_set_proxy_entry(storage, proxy_addr, &proxy)?;
+
+        // Get delegation
+        let delegation_id = store_get_proxy_delegation_id(
+            storage,
+            delegator_pubkey,
+            &delegatee_pubkey,
+            proxy_addr,
+        )?
+        .unwrap();
+        let delegation = store_get_delegation(storage, &delegation_id)?.unwrap();
 
-    let mut tasks_response: Vec<ProxyTaskResponse> = Vec::new();
+        // Add reencryption task for each proxy
+        match store_get_proxy_entry(storage, proxy_addr)? {
+            None => generic_err!("Proxy not registered"),
+            Some(proxy_entry) => match proxy_entry.proxy_pubkey {
+                None => generic_err!("Proxy not registered"),
+                Some(proxy_pubkey) => Ok(proxy_pubkey),
+            },
+        }?;
 
-    let tasks = store_get_all_proxy_tasks_in_queue(store, proxy_addr);
+        new_proxy_task.proxy_addr = proxy_addr.clone();
+        new_proxy_task.delegation_string = delegation.delegation_string;
+        let task_id = state.next_proxy_task_id;
+        store_set_proxy_task(storage, &task_id, &new_proxy_task)?;
+        store_add_proxy_task_to_queue(
+            storage,
+            proxy_addr,
+            new_proxy_task.timeout_height,
+            &task_id,
+        )?;
+        store_add_task_timeout(storage, new_proxy_task.timeout_height, &task_id);
+        state.next_proxy_task_id += 1;
 
-    // No tasks
-    if tasks.is_empty() {
-        return Ok(tasks_response);
+        proxy_stake.push(ProxyStakeResponse {
+            proxy_addr: proxy_addr.clone(),
+            stake: proxy.stake_amount,
+        });
     }
 
-    // Skip all TimedOut tasks
-    for task_id in tasks {
-        let proxy_task: ProxyTask = store_get_proxy_task(store, &task_id).unwrap();
-        if block_height < &proxy_task.timeout_height {
-            let data_entry = store_get_data_entry(store, &proxy_task.data_id).unwrap();
-
-            let proxy_task = ProxyTaskResponse {
-                data_id: proxy_task.data_id.clone(),
-                capsule: data_entry.capsule.clone(),
-                delegatee_pubkey: proxy_task.delegatee_pubkey,
-                delegator_pubkey: data_entry.delegator_pubkey,
-                delegation_string: proxy_task.delegation_string,
-            };
-            tasks_response.push(proxy_task);
-        }
+    // Delegatee lists data_id while latest request has tasks
+    match proxy_stake.is_empty() {
+        true => store_remove_delegatee_request(storage, &delegatee_pubkey, &data_id),
+        false => store_add_delegatee_request(storage, &delegatee_pubkey, &data_id),
     }
-    Ok(tasks_response)
-}
 
-pub fn get_proxies_availability(store: &dyn Storage) -> Vec<ProxyAvailabilityResponse> {
-    let proxy_addresses = store_get_all_active_proxy_addresses(store);
+    Ok((proxy_stake, request_seq))
+}
 
-    let mut res: Vec<ProxyAvailabilityResponse> = Vec::new();
+// Values of re-encryption request read from storage, shared by RequestReencryption and its simulation
+struct ReencryptionRequestPlan {
+    delegator_addr: Addr,
+    delegator_pubkey: String,
+    // All proxies from delegation
+    proxy_addresses: Vec<Addr>,
+    // Proxies from delegation with enough stake, each of them gets a task
+    available_proxies: Vec<Addr>,
+    total_required_reward_amount: u128,
+    // Reason why requester can't request re-encryption, None when permitted
+    permission_error: Option<String>,
+    // State of previous request, it gets archived when abandoned or timed-out
+    previous_request_state: Option<ReencryptionRequestState>,
+}
 
-    for proxy_addr in proxy_addresses {
-        let proxy_entry: Proxy = store_get_proxy_entry(store, &proxy_addr).unwrap();
+fn get_reencryption_request_plan(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    state: &State,
+    staking_config: &StakingConfig,
+    requester: &Addr,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<ReencryptionRequestPlan> {
+    // Storage is not modified, fails only when request can't be evaluated at all
 
-        res.push(ProxyAvailabilityResponse {
-            proxy_addr,
-            // If a proxy is in an active proxies map it has a pubkey
-            proxy_pubkey: proxy_entry.proxy_pubkey.unwrap(),
-            stake_amount: proxy_entry.stake_amount,
-        });
-    }
+    ensure_not_terminated(state)?;
+    ensure_not_paused(state)?;
 
-    res
-}
+    let data_entry: DataEntry = match store_get_data_entry(storage, data_id)? {
+        None => generic_err!("Data entry doesn't exist."),
+        Some(data_entry) => Ok(data_entry),
+    }?;
 
-#[entry_point]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetAvailableProxies {} => {
-            let state = store_get_state(deps.storage)?;
+    if is_data_expired(storage, data_id, block)? {
+        return generic_err!("Data entry has expired.");
+    }
 
-            if state.terminated {
-                return to_binary(&GetAvailableProxiesResponse {
-                    proxies: Vec::new(),
-                });
-            }
+    let delegator_addr = match store_get_delegator_address(storage, &data_entry.delegator_pubkey)? {
+        Some(delegator_addr) => Ok(delegator_addr),
+        None => generic_err!("Invalid delegator pubkey."),
+    }?;
 
-            Ok(to_binary(&GetAvailableProxiesResponse {
-                proxies: get_proxies_availability(deps.storage),
-            })?)
-        }
-        QueryMsg::GetDataID { data_id } => Ok(to_binary(&GetDataIDResponse {
-            data_entry: store_get_data_entry(deps.storage, &data_id),
-        })?),
-        QueryMsg::GetFragments {
-            data_id,
-            delegatee_pubkey,
-        } => {
-            let state = store_get_state(deps.storage)?;
-            let data_entry = match store_get_data_entry(deps.storage, &data_id) {
-                None => generic_err!("Data entry doesn't exist"),
-                Some(data) => Ok(data),
-            }?;
+    // Get selected proxies for current delegation
+    let proxy_addresses = store_get_all_proxies_from_delegation(
+        storage,
+        &data_entry.delegator_pubkey,
+        delegatee_pubkey,
+    )?;
 
-            Ok(to_binary(&GetFragmentsResponse {
-                reencryption_request_state: get_reencryption_request_state(
-                    deps.storage,
-                    &state,
-                    &data_id,
-                    &delegatee_pubkey,
-                    &env.block.height,
-                ),
-                capsule: data_entry.capsule,
-                fragments: get_all_fragments(deps.storage, &data_id, &delegatee_pubkey),
-                threshold: state.threshold,
-            })?)
-        }
-        QueryMsg::GetContractState {} => {
-            let state = store_get_state(deps.storage)?;
+    // Get proxies with enough stake
+    let available_proxies = get_available_proxies_from_delegation(
+        storage,
+        &data_entry.delegator_pubkey,
+        delegatee_pubkey,
+        &staking_config.per_task_slash_stake_amount.u128(),
+    )?;
 
-            Ok(to_binary(&GetContractStateResponse {
-                admin: state.admin,
-                threshold: state.threshold,
-                terminated: state.terminated,
-                withdrawn: state.withdrawn,
-            })?)
-        }
+    let permission_error = match ensure_requester_permitted(
+        storage,
+        block,
+        requester,
+        &delegator_addr,
+        data_id,
+        delegatee_pubkey,
+    ) {
+        Ok(()) => None,
+        Err(StdError::GenericErr { msg, .. }) => Some(msg),
+        Err(err) => return Err(err),
+    };
 
-        QueryMsg::GetStakingConfig {} => {
-            let staking_config = store_get_staking_config(deps.storage)?;
+    let previous_request_state =
+        match store_is_list_of_delegatee_proxy_tasks_empty(storage, data_id, delegatee_pubkey)? {
+            true => None,
+            false => Some(get_reencryption_request_state(
+                storage,
+                state,
+                data_id,
+                delegatee_pubkey,
+                block,
+            )?),
+        };
 
-            Ok(to_binary(&GetStakingConfigResponse {
-                stake_denom: staking_config.stake_denom,
-                minimum_proxy_stake_amount: staking_config.minimum_proxy_stake_amount,
-                per_proxy_task_reward_amount: staking_config.per_proxy_task_reward_amount,
-            })?)
-        }
+    Ok(ReencryptionRequestPlan {
+        delegator_addr,
+        delegator_pubkey: data_entry.delegator_pubkey,
+        proxy_addresses,
+        total_required_reward_amount: staking_config.per_proxy_task_reward_amount.u128()
+            * available_proxies.len() as u128,
+        available_proxies,
+        permission_error,
+        previous_request_state,
+    })
+}
 
-        QueryMsg::GetProxyTasks { proxy_addr } => {
-            let state = store_get_state(deps.storage)?;
+fn check_reencryption_request(
+    state: &State,
+    staking_config: &StakingConfig,
+    request_plan: &ReencryptionRequestPlan,
+) -> StdResult<()> {
+    // Checks shared by RequestReencryption and its simulation
 
-            if state.withdrawn {
-                return to_binary(&GetProxyTasksResponse {
-                    proxy_tasks: Vec::new(),
-                });
-            }
+    if request_plan.proxy_addresses.is_empty() {
+        return generic_err!("ProxyDelegation doesn't exist.");
+    }
 
-            Ok(to_binary(&GetProxyTasksResponse {
-                proxy_tasks: get_proxy_tasks(deps.storage, &proxy_addr, &env.block.height)?,
-            })?)
-        }
+    if let Some(permission_error) = &request_plan.permission_error {
+        return generic_err!(permission_error);
+    }
 
-        QueryMsg::GetDelegationStatus {
-            delegator_pubkey,
-            delegatee_pubkey,
-        } => {
-            let staking_config = store_get_staking_config(deps.storage)?;
-            let n_availbale_proxies = get_n_available_proxies_from_delegation(
-                deps.storage,
-                &delegator_pubkey,
-                &delegatee_pubkey,
-                &staking_config.per_task_slash_stake_amount.u128(),
-            );
+    let n_available_proxies = request_plan.available_proxies.len() as u32;
+    let n_minimum_proxies = get_n_minimum_proxies_for_refund(state, staking_config);
 
-            let minimum_stake_amount =
-                n_availbale_proxies as u128 * staking_config.per_proxy_task_reward_amount.u128();
+    // Not enough request can be created
+    if n_available_proxies < n_minimum_proxies {
+        return generic_err!(format!(
+            "Proxies are too busy, try again later. Available {} proxies out of {}, minimum is {}",
+            n_available_proxies,
+            request_plan.proxy_addresses.len(),
+            n_minimum_proxies
+        ));
+    }
 
-            Ok(to_binary(&GetDelegationStatusResponse {
-                delegation_state: get_delegation_state(
-                    deps.storage,
-                    &delegator_pubkey,
-                    &delegatee_pubkey,
-                ),
-                total_request_reward_amount: Coin {
-                    denom: staking_config.stake_denom,
-                    amount: Uint128::new(minimum_stake_amount),
-                },
-            })?)
+    match request_plan.previous_request_state {
+        None | Some(ReencryptionRequestState::Abandoned | ReencryptionRequestState::TimedOut) => {
+            Ok(())
         }
+        _ => generic_err!("Reencryption already requested"),
+    }
+}
 
-        QueryMsg::GetProxyStatus { proxy_addr } => {
-            let mut proxy_status: Option<ProxyStatusResponse> = None;
+fn add_data_entry(
+    storage: &mut dyn Storage,
+    block: &BlockInfo,
+    delegator_addr: &Addr,
+    data_id: &str,
+    delegator_pubkey: &str,
+    capsule: &str,
+    tags: &Option<Vec<Tag>>,
+    data_labels: &Option<Vec<String>>,
+    expires_at: &Option<Expiration>,
+) -> StdResult<()> {
+    if store_get_data_entry(storage, data_id)?.is_some() {
+        return generic_err!(format!("Entry with ID {} already exist.", data_id));
+    }
 
-            if let Some(proxy_entry) = store_get_proxy_entry(deps.storage, &proxy_addr) {
-                let staking_config = store_get_staking_config(deps.storage)?;
+    if let Some(expires_at) = expires_at {
+        if expires_at.is_expired(block) {
+            return generic_err!(format!("Entry with ID {} is already expired.", data_id));
+        }
+    }
 
-                proxy_status = Some(ProxyStatusResponse {
-                    proxy_pubkey: proxy_entry.proxy_pubkey.clone(),
-                    stake_amount: proxy_entry.stake_amount,
-                    withdrawable_stake_amount: Uint128::new(get_maximum_withdrawable_stake_amount(
-                        &staking_config,
-                        &proxy_entry,
-                    )),
-                    proxy_state: proxy_entry.state,
-                })
-            }
+    ensure_delegator(storage, delegator_pubkey, delegator_addr)?;
 
-            Ok(to_binary(&GetProxyStatusResponse { proxy_status })?)
+    // Add data tags
+    if let Some(tags) = tags {
+        ensure_valid_tags(tags)?;
+        for tag in tags {
+            store_set_data_tag(storage, data_id, tag);
         }
-        QueryMsg::GetDataLabels { data_id } => Ok(to_binary(&GetDataLabelsResponse {
-            data_labels: store_get_all_data_labels(deps.storage, &data_id),
-        })?),
-        QueryMsg::GetDelegateeLabels {
-            delegator_addr,
-            delegatee_pubkey,
-        } => Ok(to_binary(&GetDelegateeLabelsResponse {
-            delegatee_labels: store_get_all_delegatee_labels(
-                deps.storage,
-                &delegator_addr,
-                &delegatee_pubkey,
-            ),
-        })?),
     }
-}
 
-// Private functions
+    let entry = DataEntry {
+        delegator_pubkey: delegator_pubkey.to_string(),
+        capsule: capsule.to_string(),
+        expires_at: expires_at.clone(),
+    };
+    store_set_data_entry(storage, data_id, &entry)?;
 
-fn ensure_admin(state: &State, addr: &Addr) -> StdResult<()> {
-    if addr != &state.admin {
-        return generic_err!("Only admin can execute this method.");
+    // Add data labels
+    if let Some(data_labels) = data_labels {
+        store_add_data_labels(storage, data_id, data_labels);
     }
+
     Ok(())
 }
 
-fn ensure_delegator(
-    storage: &mut dyn Storage,
-    delegator_pubkey: &str,
-    delegator_address: &Addr,
-) -> StdResult<()> {
-    if let Some(correct_delegator_addr) = store_get_delegator_address(storage, delegator_pubkey) {
-        // Check if delegator_pubkey is registered with delegator_address
+fn ensure_valid_tags(tags: &[Tag]) -> StdResult<()> {
+    let mut tag_keys: HashSet<&str> = HashSet::new();
+    for tag in tags {
+        if tag.key.is_empty() {
+            return generic_err!("Tag key cannot be empty.");
+        }
 
-        if &correct_delegator_addr != delegator_address {
-            return generic_err!(format!(
-                "Delegator {} already registered with this pubkey.",
-                correct_delegator_addr
-            ));
+        if !tag_keys.insert(&tag.key) {
+            return generic_err!(format!("Duplicate tag key {}.", tag.key));
         }
-    } else {
-        // Reserve delegator_pubkey for current delegator_address
-        store_set_delegator_address(storage, delegator_pubkey, delegator_address);
     }
+
     Ok(())
 }
 
-fn ensure_data_owner(
+fn ensure_pending_access_request(
     storage: &mut dyn Storage,
-    data_id: &str,
-    delegator_address: &Addr,
-) -> StdResult<()> {
-    let data_entry: DataEntry = match store_get_data_entry(storage, data_id) {
-        None => generic_err!("Data entry doesn't exist."),
-        Some(data_entry) => Ok(data_entry),
+    env: &Env,
+    info: &MessageInfo,
+    request_id: u64,
+) -> StdResult<AccessRequest> {
+    let access_request = match store_get_access_request(storage, request_id) {
+        None => generic_err!(format!("Access request {} doesn't exist.", request_id)),
+        Some(access_request) => Ok(access_request),
     }?;
 
-    let correct_delegator_addr: Addr =
-        store_get_delegator_address(storage, &data_entry.delegator_pubkey).unwrap();
+    if store_get_delegator_address(storage, &access_request.delegator_pubkey)?
+        != Some(info.sender.clone())
+    {
+        return generic_err!("Sender is not owner of delegator pubkey.");
+    }
 
-    if &correct_delegator_addr != delegator_address {
-        return generic_err!("Sender is not a data owner.");
+    prune_expired_access_requests(storage, &access_request.delegator_pubkey, env.block.height);
+
+    if access_request.expiry_height <= env.block.height {
+        return generic_err!(format!("Access request {} expired.", request_id));
+    }
+
+    Ok(access_request)
+}
+
+fn ensure_valid_policy(policy: &LabelExpression) -> StdResult<()> {
+    if policy.depth() > MAX_POLICY_DEPTH {
+        return generic_err!(format!(
+            "Policy is too deep, maximum depth is {}.",
+            MAX_POLICY_DEPTH
+        ));
+    }
+
+    ensure_valid_policy_expression(policy)
+}
+
+fn ensure_valid_policy_expression(expression: &LabelExpression) -> StdResult<()> {
+    match expression {
+        LabelExpression::Label(label) => {
+            if label.is_empty() {
+                return generic_err!("Policy label cannot be empty.");
+            }
+        }
+        LabelExpression::And(expressions) | LabelExpression::Or(expressions) => {
+            if expressions.is_empty() {
+                return generic_err!("Policy operator needs at least one operand.");
+            }
+            for expression in expressions {
+                ensure_valid_policy_expression(expression)?;
+            }
+        }
+        LabelExpression::Not(expression) => ensure_valid_policy_expression(expression)?,
     }
 
     Ok(())
@@ -1754,6 +4496,14 @@ fn ensure_not_terminated(state: &State) -> StdResult<()> {
     Ok(())
 }
 
+fn ensure_not_paused(state: &State) -> StdResult<()> {
+    if state.paused {
+        return generic_err!("Contract is paused.");
+    }
+
+    Ok(())
+}
+
 fn ensure_not_withdrawn(state: &State) -> StdResult<()> {
     if state.withdrawn {
         return generic_err!("Remaining balances from contract were already withdrawn.");
@@ -1762,6 +4512,31 @@ fn ensure_not_withdrawn(state: &State) -> StdResult<()> {
     Ok(())
 }
 
+fn ensure_requester_permitted(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    requester: &Addr,
+    delegator_addr: &Addr,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<()> {
+    // Delegator can always request re-encryption of own data
+    if requester == delegator_addr {
+        return Ok(());
+    }
+
+    // Check if encryption was permitted
+    if !is_reencryption_permitted(storage, delegator_addr, delegatee_pubkey, data_id, block) {
+        return generic_err!("Reencryption is not permitted.");
+    }
+
+    // Registered delegatees choose who can request on behalf of their pubkey
+    if !is_requester_authorised(storage, delegatee_pubkey, requester) {
+        return generic_err!("Sender is not authorised to request for delegatee.");
+    }
+    Ok(())
+}
+
 fn ensure_stake(
     staking_config: &StakingConfig,
     funds: &[Coin],
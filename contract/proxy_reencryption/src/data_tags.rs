@@ -0,0 +1,103 @@
+use crate::common::range_start_after;
+use crate::msg::Tag;
+use cosmwasm_std::{Order, Storage};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+
+// Map data_id: String -> tag_key: String -> tag_value: String
+static DATA_TAGS_STORE_KEY: &[u8] = b"DataTagsStore";
+
+// To lookup data by tag
+// Map tag_key: String -> tag_value: String -> data_id: String -> is_data: bool
+static TAG_DATA_IDS_STORE_KEY: &[u8] = b"TagDataIDsStore";
+
+// DATA_TAGS_STORE
+pub fn store_set_data_tag(storage: &mut dyn Storage, data_id: &str, tag: &Tag) {
+    // Remove previous value of the tag from index
+    if let Some(value) = store_get_data_tag(storage, data_id, &tag.key) {
+        store_remove_tag_data_id(storage, &tag.key, &value, data_id);
+    }
+
+    let mut store =
+        PrefixedStorage::multilevel(storage, &[DATA_TAGS_STORE_KEY, data_id.as_bytes()]);
+    store.set(tag.key.as_bytes(), tag.value.as_bytes());
+
+    store_add_tag_data_id(storage, &tag.key, &tag.value, data_id);
+}
+
+pub fn store_get_data_tag(storage: &dyn Storage, data_id: &str, key: &str) -> Option<String> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(storage, &[DATA_TAGS_STORE_KEY, data_id.as_bytes()]);
+
+    store
+        .get(key.as_bytes())
+        .map(|data| String::from_utf8(data).unwrap())
+}
+
+pub fn store_get_all_data_tags(storage: &dyn Storage, data_id: &str) -> Vec<Tag> {
+    let store =
+        ReadonlyPrefixedStorage::multilevel(storage, &[DATA_TAGS_STORE_KEY, data_id.as_bytes()]);
+
+    let mut tags: Vec<Tag> = Vec::new();
+
+    for pair in store.range(None, None, Order::Ascending) {
+        // Deserialize keys and values with inverse operation to &string.as_bytes()
+        tags.push(Tag {
+            key: String::from_utf8(pair.0).unwrap(),
+            value: String::from_utf8(pair.1).unwrap(),
+        });
+    }
+
+    tags
+}
+
+pub fn store_remove_all_data_tags(storage: &mut dyn Storage, data_id: &str) {
+    for tag in store_get_all_data_tags(storage, data_id) {
+        store_remove_tag_data_id(storage, &tag.key, &tag.value, data_id);
+
+        let mut store =
+            PrefixedStorage::multilevel(storage, &[DATA_TAGS_STORE_KEY, data_id.as_bytes()]);
+        store.remove(tag.key.as_bytes());
+    }
+}
+
+// TAG_DATA_IDS_STORE
+fn store_add_tag_data_id(storage: &mut dyn Storage, key: &str, value: &str, data_id: &str) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[TAG_DATA_IDS_STORE_KEY, key.as_bytes(), value.as_bytes()],
+    );
+
+    // Any value in store means true - &[1]
+    store.set(data_id.as_bytes(), &[1]);
+}
+
+fn store_remove_tag_data_id(storage: &mut dyn Storage, key: &str, value: &str, data_id: &str) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[TAG_DATA_IDS_STORE_KEY, key.as_bytes(), value.as_bytes()],
+    );
+
+    store.remove(data_id.as_bytes());
+}
+
+pub fn store_get_data_ids_by_tag(
+    storage: &dyn Storage,
+    key: &str,
+    value: &str,
+    start_after: Option<&str>,
+    limit: usize,
+) -> Vec<String> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[TAG_DATA_IDS_STORE_KEY, key.as_bytes(), value.as_bytes()],
+    );
+
+    let start = start_after.map(|data_id| range_start_after(data_id.as_bytes()));
+
+    store
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        // Deserialize keys with inverse operation to &string.as_bytes()
+        .map(|pair| String::from_utf8(pair.0).unwrap())
+        .collect()
+}
@@ -1,27 +1,79 @@
+use crate::common::{parse_legacy_id, split_legacy_namespace, take_legacy_entries};
 use crate::proxies::store_get_proxy_entry;
 use crate::state::{store_get_staking_config, store_get_state, StakingConfig, State};
-use cosmwasm_std::{from_slice, to_vec, Addr, Order, StdResult, Storage};
+use cosmwasm_std::{from_slice, Addr, Order, StdError, StdResult, Storage};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::convert::TryInto;
 
-// To get all proxies from 1 delegation
+// Legacy layout, entries are moved to proxy_delegations() by migration
 // Map delegator_pubkey: String -> delegatee_pubkey: String -> proxy_addr: String -> delegation_id: u64
-static PROXY_DELEGATIONS_ID_STORE_KEY: &[u8] = b"ProxyDelegationIDStore";
-
-// To get all delegations for proxy
-// Map proxy_addr: Addr -> delegation_id: u64 -> is_delegation: bool
-static PER_PROXY_DELEGATIONS_STORE_KEY: &[u8] = b"PerProxyDelegationsStore";
-
-// Map delegation_id: u64 -> delegation: ProxyDelegation
-static PROXY_DELEGATIONS_STORE_KEY: &[u8] = b"ProxyDelegationsStore";
+static LEGACY_PROXY_DELEGATIONS_ID_STORE_KEY: &[u8] = b"ProxyDelegationIDStore";
+static LEGACY_PER_PROXY_DELEGATIONS_STORE_KEY: &[u8] = b"PerProxyDelegationsStore";
+static LEGACY_PROXY_DELEGATIONS_STORE_KEY: &[u8] = b"ProxyDelegationsStore";
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct ProxyDelegation {
     pub delegator_pubkey: String,
     pub delegatee_pubkey: String,
     pub delegation_string: String,
+    pub proxy_addr: Addr,
+}
+
+// Value of legacy layout, proxy_addr was only part of LEGACY_PROXY_DELEGATIONS_ID_STORE_KEY
+#[derive(Serialize, Deserialize)]
+struct LegacyProxyDelegation {
+    delegator_pubkey: String,
+    delegatee_pubkey: String,
+    delegation_string: String,
+}
+
+struct ProxyDelegationIndexes<'a> {
+    proxy: MultiIndex<'a, Addr, ProxyDelegation, u64>,
+    delegator: MultiIndex<'a, (String, String), ProxyDelegation, u64>,
+    delegatee: MultiIndex<'a, (String, String), ProxyDelegation, u64>,
+}
+
+impl<'a> IndexList<ProxyDelegation> for ProxyDelegationIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ProxyDelegation>> + '_> {
+        let v: Vec<&dyn Index<ProxyDelegation>> =
+            vec![&self.proxy, &self.delegator, &self.delegatee];
+        Box::new(v.into_iter())
+    }
+}
+
+// To get all delegations for proxy, all proxies of delegation and delegations of delegator or delegatee
+// Map delegation_id: u64 -> delegation: ProxyDelegation, indexed by proxy_addr, (delegator_pubkey, delegatee_pubkey) and (delegatee_pubkey, delegator_pubkey)
+fn proxy_delegations<'a>() -> IndexedMap<'a, u64, ProxyDelegation, ProxyDelegationIndexes<'a>> {
+    let indexes = ProxyDelegationIndexes {
+        proxy: MultiIndex::new(
+            |delegation: &ProxyDelegation| delegation.proxy_addr.clone(),
+            "proxy_reencryption:proxy-delegations",
+            "proxy_reencryption:proxy-delegations__proxy",
+        ),
+        delegator: MultiIndex::new(
+            |delegation: &ProxyDelegation| {
+                (
+                    delegation.delegator_pubkey.clone(),
+                    delegation.delegatee_pubkey.clone(),
+                )
+            },
+            "proxy_reencryption:proxy-delegations",
+            "proxy_reencryption:proxy-delegations__delegator",
+        ),
+        delegatee: MultiIndex::new(
+            |delegation: &ProxyDelegation| {
+                (
+                    delegation.delegatee_pubkey.clone(),
+                    delegation.delegator_pubkey.clone(),
+                )
+            },
+            "proxy_reencryption:proxy-delegations",
+            "proxy_reencryption:proxy-delegations__delegatee",
+        ),
+    };
+    IndexedMap::new("proxy_reencryption:proxy-delegations", indexes)
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -32,42 +84,18 @@ pub enum DelegationState {
     ProxiesAreBusy,
 }
 
-// PROXY_DELEGATIONS_ID_STORE_KEY
-pub fn store_set_delegation_id(
-    storage: &mut dyn Storage,
-    delegator_pubkey: &str,
-    delegatee_pubkey: &str,
-    proxy_addr: &Addr,
-    delegation_id: &u64,
-) {
-    let mut store = PrefixedStorage::multilevel(
-        storage,
-        &[
-            PROXY_DELEGATIONS_ID_STORE_KEY,
-            delegator_pubkey.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
-    );
-
-    store.set(proxy_addr.as_bytes(), &delegation_id.to_le_bytes());
-}
-
-pub fn store_remove_proxy_delegation_id(
-    storage: &mut dyn Storage,
+// PROXY_DELEGATIONS delegator and delegatee indexes
+pub fn store_get_delegation_ids(
+    storage: &dyn Storage,
     delegator_pubkey: &str,
     delegatee_pubkey: &str,
-    proxy_addr: &Addr,
-) {
-    let mut store = PrefixedStorage::multilevel(
-        storage,
-        &[
-            PROXY_DELEGATIONS_ID_STORE_KEY,
-            delegator_pubkey.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
-    );
-
-    store.remove(proxy_addr.as_bytes());
+) -> StdResult<Vec<u64>> {
+    proxy_delegations()
+        .idx
+        .delegator
+        .prefix((delegator_pubkey.to_string(), delegatee_pubkey.to_string()))
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
 }
 
 pub fn store_get_proxy_delegation_id(
@@ -75,43 +103,38 @@ pub fn store_get_proxy_delegation_id(
     delegator_pubkey: &str,
     delegatee_pubkey: &str,
     proxy_addr: &Addr,
-) -> Option<u64> {
-    let store = ReadonlyPrefixedStorage::multilevel(
-        storage,
-        &[
-            PROXY_DELEGATIONS_ID_STORE_KEY,
-            delegator_pubkey.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
-    );
-
-    store
-        .get(proxy_addr.as_bytes())
-        .map(|data| u64::from_le_bytes(data.try_into().unwrap()))
+) -> StdResult<Option<u64>> {
+    // Each proxy has at most one delegation string in delegation
+    for item in proxy_delegations()
+        .idx
+        .delegator
+        .prefix((delegator_pubkey.to_string(), delegatee_pubkey.to_string()))
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (delegation_id, delegation) = item?;
+        if &delegation.proxy_addr == proxy_addr {
+            return Ok(Some(delegation_id));
+        }
+    }
+    Ok(None)
 }
 
 pub fn store_get_all_proxies_from_delegation(
     storage: &dyn Storage,
     delegator_pubkey: &str,
     delegatee_pubkey: &str,
-) -> Vec<Addr> {
-    let store = ReadonlyPrefixedStorage::multilevel(
-        storage,
-        &[
-            PROXY_DELEGATIONS_ID_STORE_KEY,
-            delegator_pubkey.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
-    );
-
-    let mut deserialized_keys: Vec<Addr> = Vec::new();
-
-    for pair in store.range(None, None, Order::Ascending) {
-        // Deserialize keys with inverse operation to string.as_bytes()
-        deserialized_keys.push(Addr::unchecked(String::from_utf8(pair.0).unwrap()));
-    }
-
-    deserialized_keys
+) -> StdResult<Vec<Addr>> {
+    let mut proxy_addresses = proxy_delegations()
+        .idx
+        .delegator
+        .prefix((delegator_pubkey.to_string(), delegatee_pubkey.to_string()))
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, delegation)| delegation.proxy_addr))
+        .collect::<StdResult<Vec<Addr>>>()?;
+
+    // Proxies are assigned in order of address
+    proxy_addresses.sort();
+    Ok(proxy_addresses)
 }
 
 pub fn store_is_proxy_delegation_empty(
@@ -119,114 +142,186 @@ pub fn store_is_proxy_delegation_empty(
     delegator_pubkey: &str,
     delegatee_pubkey: &str,
 ) -> bool {
-    let store = ReadonlyPrefixedStorage::multilevel(
-        storage,
-        &[
-            PROXY_DELEGATIONS_ID_STORE_KEY,
-            delegator_pubkey.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
-    );
-
-    let is_empty: bool = store
-        .range(None, None, Order::Ascending)
-        .peekable()
-        .peek()
-        .is_none();
-    is_empty
+    proxy_delegations()
+        .idx
+        .delegator
+        .prefix((delegator_pubkey.to_string(), delegatee_pubkey.to_string()))
+        .keys_raw(storage, None, None, Order::Ascending)
+        .next()
+        .is_none()
 }
 
-// PROXY_DELEGATIONS_STORE_KEY
-pub fn store_set_delegation(
-    storage: &mut dyn Storage,
-    delegation_id: &u64,
-    delegation: &ProxyDelegation,
-) {
-    let mut store = PrefixedStorage::new(storage, PROXY_DELEGATIONS_STORE_KEY);
-
-    store.set(&delegation_id.to_le_bytes(), &to_vec(delegation).unwrap());
+fn get_distinct_pubkeys(
+    delegations: impl Iterator<Item = StdResult<(u64, ProxyDelegation)>>,
+    get_pubkey: fn(ProxyDelegation) -> String,
+    limit: usize,
+) -> StdResult<Vec<String>> {
+    // Index has entry for each proxy, consecutive entries of same delegation are merged
+    let mut pubkeys: Vec<String> = Vec::new();
+    for item in delegations {
+        let pubkey = get_pubkey(item?.1);
+        if pubkeys.last() != Some(&pubkey) {
+            if pubkeys.len() == limit {
+                break;
+            }
+            pubkeys.push(pubkey);
+        }
+    }
+    Ok(pubkeys)
 }
 
-pub fn store_get_delegation(storage: &dyn Storage, delegation_id: &u64) -> Option<ProxyDelegation> {
-    let store = ReadonlyPrefixedStorage::new(storage, PROXY_DELEGATIONS_STORE_KEY);
-
-    store
-        .get(&delegation_id.to_le_bytes())
-        .map(|data| from_slice(&data).unwrap())
+pub fn store_get_delegator_delegatees(
+    storage: &dyn Storage,
+    delegator_pubkey: &str,
+    start_after: Option<&str>,
+    limit: usize,
+) -> StdResult<Vec<String>> {
+    // Skip all entries of start_after delegation
+    let start = start_after
+        .map(|delegatee_pubkey| Bound::exclusive((delegatee_pubkey.to_string(), u64::MAX)));
+
+    get_distinct_pubkeys(
+        proxy_delegations()
+            .idx
+            .delegator
+            .sub_prefix(delegator_pubkey.to_string())
+            .range(storage, start, None, Order::Ascending),
+        |delegation| delegation.delegatee_pubkey,
+        limit,
+    )
 }
 
-pub fn store_remove_delegation(storage: &mut dyn Storage, delegation_id: &u64) {
-    let mut store = PrefixedStorage::new(storage, PROXY_DELEGATIONS_STORE_KEY);
-
-    store.remove(&delegation_id.to_le_bytes());
+pub fn store_get_delegatee_delegators(
+    storage: &dyn Storage,
+    delegatee_pubkey: &str,
+    start_after: Option<&str>,
+    limit: usize,
+) -> StdResult<Vec<String>> {
+    // Skip all entries of start_after delegation
+    let start = start_after
+        .map(|delegator_pubkey| Bound::exclusive((delegator_pubkey.to_string(), u64::MAX)));
+
+    get_distinct_pubkeys(
+        proxy_delegations()
+            .idx
+            .delegatee
+            .sub_prefix(delegatee_pubkey.to_string())
+            .range(storage, start, None, Order::Ascending),
+        |delegation| delegation.delegator_pubkey,
+        limit,
+    )
 }
 
-// PER_PROXY_DELEGATIONS_STORE
-pub fn store_add_per_proxy_delegation(
+// PROXY_DELEGATIONS
+pub fn store_set_delegation(
     storage: &mut dyn Storage,
-    proxy_addr: &Addr,
     delegation_id: &u64,
-) {
-    let mut store = PrefixedStorage::multilevel(
-        storage,
-        &[PER_PROXY_DELEGATIONS_STORE_KEY, proxy_addr.as_bytes()],
-    );
-
-    // Any value in store means true - &[1]
-    store.set(&delegation_id.to_le_bytes(), &[1]);
+    delegation: &ProxyDelegation,
+) -> StdResult<()> {
+    proxy_delegations().save(storage, *delegation_id, delegation)
 }
 
-pub fn store_remove_per_proxy_delegation(
-    storage: &mut dyn Storage,
-    proxy_addr: &Addr,
+pub fn store_get_delegation(
+    storage: &dyn Storage,
     delegation_id: &u64,
-) {
-    let mut store = PrefixedStorage::multilevel(
-        storage,
-        &[PER_PROXY_DELEGATIONS_STORE_KEY, proxy_addr.as_bytes()],
-    );
+) -> StdResult<Option<ProxyDelegation>> {
+    proxy_delegations().may_load(storage, *delegation_id)
+}
 
-    store.remove(&delegation_id.to_le_bytes());
+pub fn store_remove_delegation(storage: &mut dyn Storage, delegation_id: &u64) -> StdResult<()> {
+    proxy_delegations().remove(storage, *delegation_id)
 }
 
 pub fn store_is_proxy_delegation(
     storage: &dyn Storage,
     proxy_addr: &Addr,
     delegation_id: &u64,
-) -> bool {
-    let store = ReadonlyPrefixedStorage::multilevel(
-        storage,
-        &[PER_PROXY_DELEGATIONS_STORE_KEY, proxy_addr.as_bytes()],
-    );
+) -> StdResult<bool> {
+    Ok(match store_get_delegation(storage, delegation_id)? {
+        Some(delegation) => &delegation.proxy_addr == proxy_addr,
+        None => false,
+    })
+}
 
-    store.get(&delegation_id.to_le_bytes()).is_some()
+pub fn store_get_all_proxy_delegations(
+    storage: &dyn Storage,
+    proxy_addr: &Addr,
+) -> StdResult<Vec<u64>> {
+    proxy_delegations()
+        .idx
+        .proxy
+        .prefix(proxy_addr.clone())
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
 }
 
-pub fn store_get_all_proxy_delegations(storage: &dyn Storage, proxy_addr: &Addr) -> Vec<u64> {
-    let store = ReadonlyPrefixedStorage::multilevel(
-        storage,
-        &[PER_PROXY_DELEGATIONS_STORE_KEY, proxy_addr.as_bytes()],
-    );
+// High level methods
 
-    let mut deserialized_keys: Vec<u64> = Vec::new();
+pub fn migrate_legacy_proxy_delegations(
+    storage: &mut dyn Storage,
+    limit: &mut usize,
+) -> StdResult<u32> {
+    // Move delegations to indexed map, proxy of each delegation is taken from its id entry
+    // Per proxy, delegator and delegatee lookups are rebuilt as indexes
+    take_legacy_entries(storage, LEGACY_PER_PROXY_DELEGATIONS_STORE_KEY, limit);
+
+    let mut n_delegations: u32 = 0;
+    for (key, delegation_id) in
+        take_legacy_entries(storage, LEGACY_PROXY_DELEGATIONS_ID_STORE_KEY, limit)
+    {
+        let (_, key) = split_legacy_namespace(&key)?;
+        let (_, proxy_addr) = split_legacy_namespace(key)?;
+        let proxy_addr = Addr::unchecked(String::from_utf8(proxy_addr.to_vec())?);
+        let delegation_id = parse_legacy_id(&delegation_id)?;
+
+        let mut store = PrefixedStorage::new(storage, LEGACY_PROXY_DELEGATIONS_STORE_KEY);
+        let legacy_delegation: LegacyProxyDelegation = match store.get(&delegation_id.to_le_bytes())
+        {
+            None => {
+                return Err(StdError::generic_err(format!(
+                    "Delegation {} doesn't exist.",
+                    delegation_id
+                )));
+            }
+            Some(delegation) => from_slice(&delegation)?,
+        };
+        store.remove(&delegation_id.to_le_bytes());
+
+        let delegation = ProxyDelegation {
+            delegator_pubkey: legacy_delegation.delegator_pubkey,
+            delegatee_pubkey: legacy_delegation.delegatee_pubkey,
+            delegation_string: legacy_delegation.delegation_string,
+            proxy_addr,
+        };
+        proxy_delegations().save(storage, delegation_id, &delegation)?;
+        n_delegations += 1;
+    }
 
-    for pair in store.range(None, None, Order::Ascending) {
-        // Deserialize keys with inverse operation to to_vec
-        deserialized_keys.push(u64::from_le_bytes(pair.0.try_into().unwrap()));
+    // Every legacy delegation has id entry, checked once all id entries are moved
+    if *limit == 0 {
+        return Ok(n_delegations);
+    }
+    if let Some((delegation_id, _)) =
+        ReadonlyPrefixedStorage::new(storage, LEGACY_PROXY_DELEGATIONS_STORE_KEY)
+            .range(None, None, Order::Ascending)
+            .next()
+    {
+        return Err(StdError::generic_err(format!(
+            "Proxy of delegation {} doesn't exist.",
+            parse_legacy_id(&delegation_id)?
+        )));
     }
 
-    deserialized_keys
+    Ok(n_delegations)
 }
 
-// High level methods
-
 pub fn get_delegation_state(
     storage: &dyn Storage,
     delegator_pubkey: &str,
     delegatee_pubkey: &str,
-) -> DelegationState {
-    let state = store_get_state(storage).unwrap();
-    let staking_config = store_get_staking_config(storage).unwrap();
+) -> StdResult<DelegationState> {
+    let state = store_get_state(storage)?;
+    let staking_config = store_get_staking_config(storage)?;
 
     if !store_is_proxy_delegation_empty(storage, delegator_pubkey, delegatee_pubkey) {
         let n_available_proxies = get_n_available_proxies_from_delegation(
@@ -234,14 +329,14 @@ pub fn get_delegation_state(
             delegator_pubkey,
             delegatee_pubkey,
             &staking_config.per_task_slash_stake_amount.u128(),
-        );
+        )?;
         if n_available_proxies < get_n_minimum_proxies_for_refund(&state, &staking_config) {
-            DelegationState::ProxiesAreBusy
+            Ok(DelegationState::ProxiesAreBusy)
         } else {
-            DelegationState::Active
+            Ok(DelegationState::Active)
         }
     } else {
-        DelegationState::NonExisting
+        Ok(DelegationState::NonExisting)
     }
 }
 
@@ -253,74 +348,86 @@ pub fn remove_proxy_from_delegations(
     let state = store_get_state(storage)?;
 
     // Delete all proxy delegations -- Make proxy inactive / stop requests factory
-    for delegation_id in store_get_all_proxy_delegations(storage, proxy_addr) {
-        let delegation = store_get_delegation(storage, &delegation_id).unwrap();
+    for delegation_id in store_get_all_proxy_delegations(storage, proxy_addr)? {
+        let delegation = store_get_delegation(storage, &delegation_id)?.unwrap();
 
         // Remove itself from delegation
-        store_remove_delegation(storage, &delegation_id);
-        store_remove_proxy_delegation_id(
-            storage,
-            &delegation.delegator_pubkey,
-            &delegation.delegatee_pubkey,
-            proxy_addr,
-        );
-        store_remove_per_proxy_delegation(storage, proxy_addr, &delegation_id);
+        store_remove_delegation(storage, &delegation_id)?;
 
         // Check if delegation has enough proxies
-        let all_delegation_proxies = store_get_all_proxies_from_delegation(
+        let delegation_ids = store_get_delegation_ids(
             storage,
             &delegation.delegator_pubkey,
             &delegation.delegatee_pubkey,
-        );
+        )?;
 
         let n_minimum_proxies = get_n_minimum_proxies_for_refund(&state, &staking_config);
 
         // Delete entire delegation = delete each proxy delegation in delegation if there is less than minimum proxies
-        if all_delegation_proxies.len() < n_minimum_proxies as usize {
-            for i_proxy_address in all_delegation_proxies {
-                let i_delegation_id = store_get_proxy_delegation_id(
-                    storage,
-                    &delegation.delegator_pubkey,
-                    &delegation.delegatee_pubkey,
-                    &i_proxy_address,
-                )
-                .unwrap();
-
-                store_remove_delegation(storage, &i_delegation_id);
-                store_remove_proxy_delegation_id(
-                    storage,
-                    &delegation.delegator_pubkey,
-                    &delegation.delegatee_pubkey,
-                    &i_proxy_address,
-                );
-                store_remove_per_proxy_delegation(storage, &i_proxy_address, &i_delegation_id);
+        if delegation_ids.len() < n_minimum_proxies as usize {
+            for i_delegation_id in delegation_ids {
+                store_remove_delegation(storage, &i_delegation_id)?;
             }
         }
     }
     Ok(())
 }
 
-pub fn get_n_available_proxies_from_delegation(
+pub fn remove_all_delegator_delegations(
+    storage: &mut dyn Storage,
+    delegator_pubkey: &str,
+) -> StdResult<()> {
+    // Remove every delegation created with delegator_pubkey
+
+    let delegation_ids = proxy_delegations()
+        .idx
+        .delegator
+        .sub_prefix(delegator_pubkey.to_string())
+        .keys(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<u64>>>()?;
+
+    for delegation_id in delegation_ids {
+        store_remove_delegation(storage, &delegation_id)?;
+    }
+    Ok(())
+}
+
+pub fn get_available_proxies_from_delegation(
     storage: &dyn Storage,
     delegator_pubkey: &str,
     delegatee_pubkey: &str,
     proxy_slashed_amount: &u128,
-) -> u32 {
-    // Return number of proxies from delegation with enough stake to get re-encryption request
+) -> StdResult<Vec<Addr>> {
+    // Return proxies from delegation with enough stake to get re-encryption request
 
     // Get all proxy delegations
     let delegation_proxies =
-        store_get_all_proxies_from_delegation(storage, delegator_pubkey, delegatee_pubkey);
+        store_get_all_proxies_from_delegation(storage, delegator_pubkey, delegatee_pubkey)?;
 
-    let mut n_available_proxies: u32 = 0;
+    let mut available_proxies: Vec<Addr> = Vec::new();
     for proxy_addr in delegation_proxies {
         // Check if each proxy in delegation has enough stake
-        let proxy = store_get_proxy_entry(storage, &proxy_addr).unwrap();
+        let proxy = store_get_proxy_entry(storage, &proxy_addr)?.unwrap();
         if &proxy.stake_amount.u128() >= proxy_slashed_amount {
-            n_available_proxies += 1;
+            available_proxies.push(proxy_addr);
         }
     }
-    n_available_proxies
+    Ok(available_proxies)
+}
+
+pub fn get_n_available_proxies_from_delegation(
+    storage: &dyn Storage,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+    proxy_slashed_amount: &u128,
+) -> StdResult<u32> {
+    Ok(get_available_proxies_from_delegation(
+        storage,
+        delegator_pubkey,
+        delegatee_pubkey,
+        proxy_slashed_amount,
+    )?
+    .len() as u32)
 }
 
 pub fn get_n_minimum_proxies_for_refund(state: &State, staking_config: &StakingConfig) -> u32 {
@@ -1,12 +1,16 @@
 #![allow(clippy::too_many_arguments)]
+pub mod access_requests;
+pub mod allowances;
 pub mod common;
 pub mod contract;
+pub mod data_tags;
 pub mod delegations;
 pub mod msg;
 pub mod proxies;
 pub mod reencryption_permissions;
 pub mod reencryption_requests;
 pub mod state;
+pub mod subscriptions;
 
 #[cfg(test)]
 mod tests;
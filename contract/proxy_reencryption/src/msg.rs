@@ -1,7 +1,13 @@
+use crate::access_requests::{AccessRequest, AccessRequestTarget};
+use crate::allowances::DelegateeAllowance;
 use crate::delegations::DelegationState;
 use crate::proxies::ProxyState;
-use crate::reencryption_requests::ReencryptionRequestState;
-use crate::state::DataEntry;
+use crate::reencryption_permissions::LabelExpression;
+use crate::reencryption_requests::{ProxyTask, ReencryptionRequestState};
+use crate::state::{
+    DataEntry, DataOwnershipTransfer, Expiration, StakingConfig, State, TimeoutsConfig,
+};
+use crate::subscriptions::Subscription;
 use cosmwasm_std::{Addr, Coin, Uint128};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -12,6 +18,13 @@ pub struct ProxyDelegationString {
     pub delegation_string: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ProxyFragment {
+    pub data_id: String,
+    pub delegatee_pubkey: String,
+    pub fragment: String,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct ProxyTaskResponse {
     pub data_id: String,
@@ -21,6 +34,13 @@ pub struct ProxyTaskResponse {
     pub delegation_string: String,
 }
 
+// Proxy tasks are ordered by timeout
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ProxyTasksCursor {
+    pub timeout_height: u64,
+    pub task_id: u64,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct ProxyAvailabilityResponse {
     pub proxy_addr: Addr,
@@ -55,12 +75,28 @@ pub struct InstantiateMsg {
     pub withdrawal_period: Option<u64>,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct Tag {
     pub key: String,
     pub value: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DataBatchEntry {
+    pub data_id: String,
+    pub delegator_pubkey: String,
+    pub capsule: String,
+    pub tags: Option<Vec<Tag>>,
+    pub data_labels: Option<Vec<String>>,
+    pub expires_at: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DataCapsule {
+    pub data_id: String,
+    pub capsule: String,
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -75,6 +111,16 @@ pub enum ExecuteMsg {
     WithdrawContract {
         recipient_addr: Addr,
     },
+    PauseContract {},
+    UnpauseContract {},
+    UpdateConfig {
+        minimum_proxy_stake_amount: Option<Uint128>,
+        timeout_height: Option<u64>,
+        proxy_whitelisting: Option<bool>,
+    },
+    UpdateAdmin {
+        admin: Addr,
+    },
 
     // Proxy actions
     RegisterProxy {
@@ -86,6 +132,12 @@ pub enum ExecuteMsg {
         delegatee_pubkey: String,
         fragment: String,
     },
+    // Provide multiple fragments at once, reward is credited once for all provided fragments
+    ProvideReencryptedFragments {
+        fragments: Vec<ProxyFragment>,
+        // Fail entire batch if any of the fragments cannot be provided
+        all_or_nothing: Option<bool>,
+    },
     SkipReencryptionTask {
         data_id: String,
         delegatee_pubkey: String,
@@ -105,6 +157,40 @@ pub enum ExecuteMsg {
         capsule: String, // symmetric key encoded with data owner public key (only data owner can decode this)
         tags: Option<Vec<Tag>>,
         data_labels: Option<Vec<String>>,
+        expires_at: Option<Expiration>,
+    },
+    // Add multiple data entries at once, fails if any of the entries cannot be added
+    AddDataBatch {
+        entries: Vec<DataBatchEntry>,
+    },
+    // Replace capsule of data entry, all fragments for previous capsule are removed
+    UpdateDataCapsule {
+        data_id: String,
+        capsule: String,
+    },
+    // Offer data ownership to new delegator, new_capsule has to be re-encrypted for new owner
+    TransferDataOwnership {
+        data_id: String,
+        new_delegator_pubkey: String,
+        new_capsule: String,
+    },
+    // New owner accepts data, all requests for data are cancelled and refunded
+    AcceptDataOwnership {
+        data_id: String,
+    },
+    CancelDataOwnershipTransfer {
+        data_id: String,
+    },
+    // Move all data entries to new delegator pubkey, capsules have to be re-encrypted for new_pubkey
+    // All delegations and requests made with old_pubkey are removed
+    RotateDelegatorKey {
+        old_pubkey: String,
+        new_pubkey: String,
+        updated_capsules: Vec<DataCapsule>,
+    },
+    // Remove expired data entries, unresolved requests are refunded
+    PruneExpiredData {
+        limit: Option<u32>,
     },
     // Remove data, reencryption request and fragments
     RemoveData {
@@ -125,6 +211,12 @@ pub enum ExecuteMsg {
         delegatee_pubkey: String,
     },
 
+    // Replace all tags of data entry
+    UpdateDataTags {
+        data_id: String,
+        tags: Vec<Tag>,
+    },
+
     AddDataLabels {
         data_id: String,
         data_labels: Vec<String>,
@@ -136,11 +228,120 @@ pub enum ExecuteMsg {
     AddDelegateeLabels {
         delegatee_pubkey: String,
         delegatee_labels: Vec<String>,
+        // Labels are ignored from this block on
+        expires_at: Option<Expiration>,
     },
     RemoveDelegateeLabels {
         delegatee_pubkey: String,
         delegatee_labels: Vec<String>,
     },
+    // Explicit access rules of single data entry, deny overrides labels and policies
+    GrantDataAccess {
+        data_id: String,
+        delegatee_pubkey: String,
+        expires_at: Option<Expiration>,
+    },
+    DenyDataAccess {
+        data_id: String,
+        delegatee_pubkey: String,
+    },
+    RevokeDataAccess {
+        data_id: String,
+        delegatee_pubkey: String,
+    },
+    // Delegatee asks data owner for access to data entries or labels
+    RequestAccess {
+        delegator_pubkey: String,
+        delegatee_pubkey: String,
+        target: AccessRequestTarget,
+        message: Option<String>,
+    },
+    // Adds delegatee labels or data access grants of request
+    ApproveAccessRequest {
+        request_id: u64,
+    },
+    RejectAccessRequest {
+        request_id: u64,
+    },
+    // Policy is used instead of delegatee labels, None removes policy
+    SetDelegateePolicy {
+        delegatee_pubkey: String,
+        policy: Option<LabelExpression>,
+    },
+    // Only sender and authorised requesters can request re-encryption for delegatee_pubkey
+    RegisterDelegatee {
+        delegatee_pubkey: String,
+        authorised_requesters: Vec<Addr>,
+    },
+    // Delegator with delegation to delegatee_pubkey can replace registration made by other address
+    OverrideDelegateeRegistration {
+        delegator_pubkey: String,
+        delegatee_pubkey: String,
+        delegatee_addr: Addr,
+        authorised_requesters: Vec<Addr>,
+    },
+    AddAuthorisedRequesters {
+        delegatee_pubkey: String,
+        requesters: Vec<Addr>,
+    },
+    RemoveAuthorisedRequesters {
+        delegatee_pubkey: String,
+        requesters: Vec<Addr>,
+    },
+    // Prepaid rewards for requests of delegatee_pubkey to data of sender, funds are added to balance
+    FundDelegateeAllowance {
+        delegatee_pubkey: String,
+        spend_limit: Option<Uint128>,
+        expires: Option<Expiration>,
+    },
+    WithdrawDelegateeAllowance {
+        delegatee_pubkey: String,
+    },
+    // Resolves tasks of timed-out requests in order of timeout, caller gets keeper bounty
+    ResolveTimedOutRequests {
+        limit: Option<u32>,
+    },
+    // Requester withdraws pending request, unfinished tasks are refunded
+    CancelReencryptionRequest {
+        data_id: String,
+        delegatee_pubkey: String,
+    },
+    // Re-encryption is requested automatically for new data with any of labels, paid from budget
+    Subscribe {
+        delegator_pubkey: String,
+        delegatee_pubkey: String,
+        labels: Vec<String>,
+        budget: Uint128,
+    },
+    Unsubscribe {
+        delegator_pubkey: String,
+        delegatee_pubkey: String,
+    },
+}
+
+// Chain governance actions, executed without admin check
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    RemoveProxy {
+        proxy_addr: Addr,
+    },
+    PauseContract {},
+    UnpauseContract {},
+    UpdateConfig {
+        minimum_proxy_stake_amount: Option<Uint128>,
+        timeout_height: Option<u64>,
+        proxy_whitelisting: Option<bool>,
+    },
+    UpdateAdmin {
+        admin: Addr,
+    },
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct MigrateMsg {
+    // Maximum number of legacy entries moved by one migration, default is used if None
+    pub limit: Option<u32>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -150,20 +351,83 @@ pub enum QueryMsg {
     GetDataID {
         data_id: String,
     },
+    GetDataOwnershipTransfer {
+        data_id: String,
+    },
+    GetDataByTag {
+        key: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    ListProxies {
+        state_filter: Option<ProxyState>,
+        start_after: Option<Addr>,
+        limit: Option<u32>,
+    },
+    ListDataByDelegator {
+        delegator_pubkey: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    ListDelegationsByDelegator {
+        delegator_pubkey: String,
+        // Delegatee pubkey of last returned delegation
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    ListDelegationsByDelegatee {
+        delegatee_pubkey: String,
+        // Delegator pubkey of last returned delegation
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    ListTasksByProxy {
+        proxy_addr: Addr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
     GetFragments {
         data_id: String,
         delegatee_pubkey: String,
     },
+    GetReencryptionRequests {
+        data_id: String,
+        delegatee_pubkey: String,
+    },
+    // Requests are scanned until limit of them matches state_filter or MAX_QUERY_SCAN is reached,
+    // page can be shorter than limit while cursor is set, continue until cursor is None
+    GetDelegateeRequests {
+        delegatee_pubkey: String,
+        state_filter: Option<ReencryptionRequestState>,
+        // Data ID of last scanned request, returned as cursor
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     GetContractState {},
+    GetContractStats {},
     GetStakingConfig {},
+    GetTimeoutsConfig {},
+    // All configuration singletons
+    GetConfig {},
 
     GetProxyTasks {
         proxy_addr: Addr,
+        // Cursor returned with previous page
+        start_after: Option<ProxyTasksCursor>,
+        limit: Option<u32>,
     },
     GetDelegationStatus {
         delegator_pubkey: String,
         delegatee_pubkey: String,
     },
+    SimulateReencryptionRequest {
+        data_id: String,
+        delegatee_pubkey: String,
+        requester: Addr,
+        // Reward is drawn from delegatee allowance when no funds are provided
+        funds: Option<Coin>,
+    },
     GetProxyStatus {
         proxy_addr: Addr,
     },
@@ -175,6 +439,28 @@ pub enum QueryMsg {
         delegator_addr: Addr,
         delegatee_pubkey: String,
     },
+    GetDataAccessGrants {
+        data_id: String,
+    },
+    GetPendingAccessRequests {
+        delegator_pubkey: String,
+    },
+    CheckPermission {
+        delegator_addr: Addr,
+        delegatee_pubkey: String,
+        data_id: String,
+    },
+    GetDelegateeRegistration {
+        delegatee_pubkey: String,
+    },
+    GetDelegateeAllowance {
+        delegator_addr: Addr,
+        delegatee_pubkey: String,
+    },
+    GetSubscription {
+        delegator_pubkey: String,
+        delegatee_pubkey: String,
+    },
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -185,6 +471,56 @@ pub struct GetAvailableProxiesResponse {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct GetDataIDResponse {
     pub data_entry: Option<DataEntry>,
+    pub tags: Vec<Tag>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetDataOwnershipTransferResponse {
+    pub transfer: Option<DataOwnershipTransfer>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetDataByTagResponse {
+    pub data_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ProxyEntryResponse {
+    pub proxy_addr: Addr,
+    pub proxy_status: ProxyStatusResponse,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ListProxiesResponse {
+    pub proxies: Vec<ProxyEntryResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ListDataByDelegatorResponse {
+    pub data_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegationResponse {
+    pub delegator_pubkey: String,
+    pub delegatee_pubkey: String,
+    pub delegation_state: DelegationState,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ListDelegationsResponse {
+    pub delegations: Vec<DelegationResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ProxyTaskEntryResponse {
+    pub task_id: u64,
+    pub proxy_task: ProxyTask,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ListTasksByProxyResponse {
+    pub tasks: Vec<ProxyTaskEntryResponse>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -195,12 +531,53 @@ pub struct GetFragmentsResponse {
     pub threshold: u32,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct ReencryptionRequestResponse {
+    pub request_seq: u64,
+    pub reencryption_request_state: ReencryptionRequestState,
+    pub fragments: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetReencryptionRequestsResponse {
+    pub requests: Vec<ReencryptionRequestResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegateeRequestResponse {
+    pub data_id: String,
+    pub reencryption_request_state: ReencryptionRequestState,
+    pub n_fragments: u32,
+    // Fragments needed to decrypt
+    pub threshold: u32,
+    pub timeout_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetDelegateeRequestsResponse {
+    pub requests: Vec<DelegateeRequestResponse>,
+    // Start of next page, None when there are no more requests
+    pub cursor: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct GetContractStateResponse {
     pub admin: Addr,
     pub threshold: u32,
     pub terminated: bool,
     pub withdrawn: bool,
+    pub paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetContractStatsResponse {
+    // Stake of proxies without per_task_slash_stake_amount locked by each open task
+    pub total_staked: Uint128,
+    pub n_active_proxies: u32,
+    pub n_open_tasks: u64,
+    pub n_completed_requests: u64,
+    pub total_rewards_paid: Uint128,
+    pub total_slashed: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -208,11 +585,26 @@ pub struct GetStakingConfigResponse {
     pub stake_denom: String,
     pub minimum_proxy_stake_amount: Uint128,
     pub per_proxy_task_reward_amount: Uint128,
+    pub per_task_slash_stake_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetTimeoutsConfigResponse {
+    pub timeout_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetConfigResponse {
+    pub state: State,
+    pub staking_config: StakingConfig,
+    pub timeouts_config: TimeoutsConfig,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct GetProxyTasksResponse {
     pub proxy_tasks: Vec<ProxyTaskResponse>,
+    // Start of next page, None when there are no more tasks
+    pub cursor: Option<ProxyTasksCursor>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -221,6 +613,22 @@ pub struct GetDelegationStatusResponse {
     pub total_request_reward_amount: Coin,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct SimulateReencryptionRequestResponse {
+    // Requester is delegator or is permitted and authorised to request for delegatee
+    pub is_permitted: bool,
+    // Proxies which would get re-encryption task
+    pub available_proxies: Vec<Addr>,
+    pub required_funds: Coin,
+    // Part of simulated request funds returned back to requester, needs funds of the query
+    // as RequestReencryption refunds what was sent above required_funds.
+    // Zero without funds, reward is then drawn from delegatee allowance
+    pub excess_refund: Coin,
+    pub timeout_height: u64,
+    // Error of RequestReencryption, None when request would succeed
+    pub failure_reason: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct GetProxyStatusResponse {
     pub proxy_status: Option<ProxyStatusResponse>,
@@ -234,6 +642,58 @@ pub struct GetDataLabelsResponse {
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct GetDelegateeLabelsResponse {
     pub delegatee_labels: Vec<String>,
+    pub delegatee_label_expirations: Vec<DelegateeLabel>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegateeLabel {
+    pub label: String,
+    pub expires_at: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DataAccessGrant {
+    pub delegatee_pubkey: String,
+    pub expires_at: Option<Expiration>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetDataAccessGrantsResponse {
+    pub grants: Vec<DataAccessGrant>,
+    pub denied_delegatees: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct AccessRequestResponse {
+    pub request_id: u64,
+    pub access_request: AccessRequest,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetPendingAccessRequestsResponse {
+    pub requests: Vec<AccessRequestResponse>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct CheckPermissionResponse {
+    pub policy: Option<LabelExpression>,
+    pub permitted: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetDelegateeRegistrationResponse {
+    pub delegatee_addr: Option<Addr>,
+    pub authorised_requesters: Vec<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetDelegateeAllowanceResponse {
+    pub allowance: Option<DelegateeAllowance>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct GetSubscriptionResponse {
+    pub subscription: Option<Subscription>,
 }
 
 #[derive(Serialize, Deserialize, JsonSchema)]
@@ -261,9 +721,26 @@ pub struct ProxyStakeResponse {
     pub stake: Uint128,
 }
 
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProvideFragmentResponse {
+    pub data_id: String,
+    pub delegatee_pubkey: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsgJSONResponse {
-    RequestReencryption { proxies: Vec<ProxyStakeResponse> },
-    RemoveData { proxies: Vec<ProxyStakeResponse> },
+    RequestReencryption {
+        proxies: Vec<ProxyStakeResponse>,
+    },
+    RemoveData {
+        proxies: Vec<ProxyStakeResponse>,
+    },
+    ProvideReencryptedFragments {
+        fragments: Vec<ProvideFragmentResponse>,
+        new_stake: Uint128,
+    },
 }
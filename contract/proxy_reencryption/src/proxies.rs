@@ -1,6 +1,8 @@
-use crate::state::StakingConfig;
-use cosmwasm_std::{from_slice, to_vec, Addr, Order, Storage, Uint128};
+use crate::common::take_legacy_entries;
+use crate::state::{update_contract_stats, StakingConfig};
+use cosmwasm_std::{from_slice, Addr, Order, StdResult, Storage, Uint128};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -19,48 +21,114 @@ pub struct Proxy {
     pub stake_amount: Uint128,
 }
 
+struct ProxyIndexes<'a> {
+    state: MultiIndex<'a, String, Proxy, Addr>,
+}
+
+impl<'a> IndexList<Proxy> for ProxyIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Proxy>> + '_> {
+        let v: Vec<&dyn Index<Proxy>> = vec![&self.state];
+        Box::new(v.into_iter())
+    }
+}
+
 // Proxy register whitelist
-// Map proxy_addr: Addr -> proxy: Proxy
-static PROXIES_KEY: &[u8] = b"Proxies";
+// Map proxy_addr: Addr -> proxy: Proxy, indexed by proxy state
+fn proxies<'a>() -> IndexedMap<'a, &'a Addr, Proxy, ProxyIndexes<'a>> {
+    let indexes = ProxyIndexes {
+        state: MultiIndex::new(
+            |proxy: &Proxy| proxy_state_key(&proxy.state).to_string(),
+            "proxy_reencryption:proxies",
+            "proxy_reencryption:proxies__state",
+        ),
+    };
+    IndexedMap::new("proxy_reencryption:proxies", indexes)
+}
 
 // Active proxies
 // Map proxy_addr: String -> is_active: bool
 static IS_PROXY_ACTIVE: &[u8] = b"IsProxyActive";
 
-// Getters and setters
+// Legacy layout, entries are moved to proxies() by migration
+static LEGACY_PROXIES_KEY: &[u8] = b"Proxies";
 
-// PROXIES_KEY
-pub fn store_set_proxy_entry(storage: &mut dyn Storage, proxy_addr: &Addr, proxy: &Proxy) {
-    let mut store = PrefixedStorage::new(storage, PROXIES_KEY);
+// Getters and setters
 
-    store.set(proxy_addr.as_bytes(), &to_vec(proxy).unwrap());
+// PROXIES
+pub fn store_set_proxy_entry(
+    storage: &mut dyn Storage,
+    proxy_addr: &Addr,
+    proxy: &Proxy,
+) -> StdResult<()> {
+    let old_stake_amount = store_get_proxy_entry(storage, proxy_addr)?
+        .map(|old_proxy| old_proxy.stake_amount)
+        .unwrap_or_default();
+
+    // Every stake change goes through here, counters can be behind in contracts that predate them
+    update_contract_stats(storage, |stats| {
+        stats.total_staked =
+            stats.total_staked.saturating_sub(old_stake_amount) + proxy.stake_amount;
+    })?;
+
+    proxies().save(storage, proxy_addr, proxy)
 }
 
-pub fn store_remove_proxy_entry(storage: &mut dyn Storage, proxy_addr: &Addr) {
-    let mut store = PrefixedStorage::new(storage, PROXIES_KEY);
+pub fn store_remove_proxy_entry(storage: &mut dyn Storage, proxy_addr: &Addr) -> StdResult<()> {
+    if let Some(old_proxy) = store_get_proxy_entry(storage, proxy_addr)? {
+        update_contract_stats(storage, |stats| {
+            stats.total_staked = stats.total_staked.saturating_sub(old_proxy.stake_amount);
+        })?;
+    }
 
-    store.remove(proxy_addr.as_bytes());
+    proxies().remove(storage, proxy_addr)
 }
 
-pub fn store_get_proxy_entry(storage: &dyn Storage, proxy_addr: &Addr) -> Option<Proxy> {
-    let store = ReadonlyPrefixedStorage::new(storage, PROXIES_KEY);
-
-    store
-        .get(proxy_addr.as_bytes())
-        .map(|data| from_slice(&data).unwrap())
+pub fn store_get_proxy_entry(storage: &dyn Storage, proxy_addr: &Addr) -> StdResult<Option<Proxy>> {
+    proxies().may_load(storage, proxy_addr)
 }
 
-pub fn store_get_all_proxies(storage: &dyn Storage) -> Vec<Addr> {
-    let store = ReadonlyPrefixedStorage::new(storage, PROXIES_KEY);
+pub fn store_get_all_proxies(storage: &dyn Storage) -> StdResult<Vec<Addr>> {
+    proxies()
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
+}
 
-    let mut deserialized_keys: Vec<Addr> = Vec::new();
+pub fn store_get_proxies(
+    storage: &dyn Storage,
+    start_after: Option<&Addr>,
+    limit: usize,
+) -> StdResult<Vec<Addr>> {
+    let start = start_after.map(Bound::exclusive);
+
+    proxies()
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
 
-    for pair in store.range(None, None, Order::Ascending) {
-        // Deserialize keys
-        deserialized_keys.push(Addr::unchecked(String::from_utf8(pair.0).unwrap()));
+fn proxy_state_key(proxy_state: &ProxyState) -> &'static str {
+    match proxy_state {
+        ProxyState::Authorised => "authorised",
+        ProxyState::Registered => "registered",
+        ProxyState::Leaving => "leaving",
     }
+}
 
-    deserialized_keys
+pub fn store_get_proxies_by_state(
+    storage: &dyn Storage,
+    proxy_state: &ProxyState,
+    start_after: Option<&Addr>,
+    limit: usize,
+) -> StdResult<Vec<Addr>> {
+    let start = start_after.map(|proxy_addr| Bound::exclusive(proxy_addr.clone()));
+
+    proxies()
+        .idx
+        .state
+        .prefix(proxy_state_key(proxy_state).to_string())
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
 }
 
 // IS_PROXY_ACTIVE
@@ -68,7 +136,14 @@ pub fn store_set_is_proxy_active(
     storage: &mut dyn Storage,
     proxy_addr: &Addr,
     is_proxy_active: bool,
-) {
+) -> StdResult<()> {
+    if store_get_is_proxy_active(storage, proxy_addr) != is_proxy_active {
+        update_contract_stats(storage, |stats| match is_proxy_active {
+            true => stats.n_active_proxies += 1,
+            false => stats.n_active_proxies = stats.n_active_proxies.saturating_sub(1),
+        })?;
+    }
+
     let mut store = PrefixedStorage::new(storage, IS_PROXY_ACTIVE);
 
     // Any value in store means true - &[1]
@@ -76,6 +151,7 @@ pub fn store_set_is_proxy_active(
         true => store.set(proxy_addr.as_bytes(), &[1]),
         false => store.remove(proxy_addr.as_bytes()),
     }
+    Ok(())
 }
 
 pub fn store_get_is_proxy_active(storage: &dyn Storage, proxy_addr: &Addr) -> bool {
@@ -84,20 +160,49 @@ pub fn store_get_is_proxy_active(storage: &dyn Storage, proxy_addr: &Addr) -> bo
     store.get(proxy_addr.as_bytes()).is_some()
 }
 
-pub fn store_get_all_active_proxy_addresses(storage: &dyn Storage) -> Vec<Addr> {
+pub fn store_get_all_active_proxy_addresses(storage: &dyn Storage) -> StdResult<Vec<Addr>> {
     let store = ReadonlyPrefixedStorage::new(storage, IS_PROXY_ACTIVE);
 
     let mut deserialized_keys: Vec<Addr> = Vec::new();
 
     for pair in store.range(None, None, Order::Ascending) {
         // Deserialize keys with inverse operation to &string.as_bytes()
-        deserialized_keys.push(Addr::unchecked(String::from_utf8(pair.0).unwrap()));
+        deserialized_keys.push(Addr::unchecked(String::from_utf8(pair.0)?));
     }
 
-    deserialized_keys
+    Ok(deserialized_keys)
 }
 
 // High level methods
+pub fn migrate_legacy_proxies(storage: &mut dyn Storage, limit: &mut usize) -> StdResult<u32> {
+    // Move proxy entries to indexed map, state index is rebuilt on save
+    let entries = take_legacy_entries(storage, LEGACY_PROXIES_KEY, limit);
+    if entries.is_empty() {
+        return Ok(0);
+    }
+
+    let mut total_staked = Uint128::zero();
+    let mut n_active_proxies: u32 = 0;
+    for (proxy_addr, proxy) in entries.iter() {
+        let proxy_addr = Addr::unchecked(String::from_utf8(proxy_addr.clone())?);
+        let proxy: Proxy = from_slice(proxy)?;
+        total_staked += proxy.stake_amount;
+        // IS_PROXY_ACTIVE keeps its layout
+        if store_get_is_proxy_active(storage, &proxy_addr) {
+            n_active_proxies += 1;
+        }
+        proxies().save(storage, &proxy_addr, &proxy)?;
+    }
+
+    // Legacy contract has no stats, they are accumulated over migration batches
+    update_contract_stats(storage, |stats| {
+        stats.total_staked += total_staked;
+        stats.n_active_proxies += n_active_proxies;
+    })?;
+
+    Ok(entries.len() as u32)
+}
+
 pub fn get_maximum_withdrawable_stake_amount(
     staking_config: &StakingConfig,
     proxy: &Proxy,
@@ -1,12 +1,68 @@
-use cosmwasm_std::{Addr, Order, StdError, StdResult, Storage};
+use crate::state::Expiration;
+use cosmwasm_std::{from_slice, to_vec, Addr, BlockInfo, Order, StdError, StdResult, Storage};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
 // Map data_id: String -> label: String -> is_label: bool
 static DATA_LABELS_STORE_KEY: &[u8] = b"DataLabelsStore";
 
-// Map delegator_address: Addr -> delegatee_pubkey: String -> label: String -> is_label: bool
+// Map delegator_address: Addr -> delegatee_pubkey: String -> label: String -> expires_at: Option<Expiration>
 static DELEGATEE_LABELS_STORE_KEY: &[u8] = b"DelegateeLabelsStore";
 
+// Map delegator_address: Addr -> delegatee_pubkey: String -> policy: LabelExpression
+static DELEGATEE_POLICIES_STORE_KEY: &[u8] = b"DelegateePoliciesStore";
+
+// Explicit access rules of data entry
+// Map data_id: String -> delegatee_pubkey: String -> rule: DataAccessRule
+static DATA_ACCESS_RULES_STORE_KEY: &[u8] = b"DataAccessRulesStore";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DataAccessRule {
+    Grant { expires_at: Option<Expiration> },
+    Deny,
+}
+
+// Policy evaluated against labels of data
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LabelExpression {
+    Label(String),
+    And(Vec<LabelExpression>),
+    Or(Vec<LabelExpression>),
+    Not(Box<LabelExpression>),
+}
+
+impl LabelExpression {
+    pub fn evaluate(&self, data_labels: &[String]) -> bool {
+        match self {
+            LabelExpression::Label(label) => data_labels.contains(label),
+            LabelExpression::And(expressions) => expressions
+                .iter()
+                .all(|expression| expression.evaluate(data_labels)),
+            LabelExpression::Or(expressions) => expressions
+                .iter()
+                .any(|expression| expression.evaluate(data_labels)),
+            LabelExpression::Not(expression) => !expression.evaluate(data_labels),
+        }
+    }
+
+    pub fn depth(&self) -> u32 {
+        match self {
+            LabelExpression::Label(_) => 1,
+            LabelExpression::And(expressions) | LabelExpression::Or(expressions) => {
+                1 + expressions
+                    .iter()
+                    .map(|expression| expression.depth())
+                    .max()
+                    .unwrap_or(0)
+            }
+            LabelExpression::Not(expression) => 1 + expression.depth(),
+        }
+    }
+}
+
 // DATA_LABELS_STORE
 pub fn store_add_data_labels(storage: &mut dyn Storage, data_id: &str, labels: &[String]) {
     let mut store =
@@ -66,6 +122,7 @@ pub fn store_add_delegatee_labels(
     delegator_addr: &Addr,
     delegatee_pubkey: &str,
     labels: &[String],
+    expires_at: &Option<Expiration>,
 ) {
     let mut store = PrefixedStorage::multilevel(
         storage,
@@ -77,7 +134,7 @@ pub fn store_add_delegatee_labels(
     );
 
     for label in labels {
-        store.set(label.as_bytes(), &[1])
+        store.set(label.as_bytes(), &to_vec(expires_at).unwrap())
     }
 }
 
@@ -152,18 +209,188 @@ pub fn store_get_all_delegatee_labels(
     deserialized_keys
 }
 
+pub fn store_get_all_delegatee_labels_with_expiration(
+    storage: &dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+) -> Vec<(String, Option<Expiration>)> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[
+            DELEGATEE_LABELS_STORE_KEY,
+            delegator_addr.as_bytes(),
+            delegatee_pubkey.as_bytes(),
+        ],
+    );
+
+    let mut labels: Vec<(String, Option<Expiration>)> = Vec::new();
+
+    for pair in store.range(None, None, Order::Ascending) {
+        // Labels stored without expiration have value &[1]
+        labels.push((
+            String::from_utf8(pair.0).unwrap(),
+            from_slice(&pair.1).unwrap_or(None),
+        ));
+    }
+
+    labels
+}
+
+// DELEGATEE_POLICIES_STORE
+pub fn store_set_delegatee_policy(
+    storage: &mut dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+    policy: &LabelExpression,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_POLICIES_STORE_KEY, delegator_addr.as_bytes()],
+    );
+
+    store.set(delegatee_pubkey.as_bytes(), &to_vec(policy).unwrap());
+}
+
+pub fn store_remove_delegatee_policy(
+    storage: &mut dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_POLICIES_STORE_KEY, delegator_addr.as_bytes()],
+    );
+
+    store.remove(delegatee_pubkey.as_bytes());
+}
+
+pub fn store_get_delegatee_policy(
+    storage: &dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+) -> Option<LabelExpression> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_POLICIES_STORE_KEY, delegator_addr.as_bytes()],
+    );
+
+    store
+        .get(delegatee_pubkey.as_bytes())
+        .map(|data| from_slice(&data).unwrap())
+}
+
+// DATA_ACCESS_RULES_STORE
+pub fn store_set_data_access_rule(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+    rule: &DataAccessRule,
+) {
+    let mut store =
+        PrefixedStorage::multilevel(storage, &[DATA_ACCESS_RULES_STORE_KEY, data_id.as_bytes()]);
+
+    store.set(delegatee_pubkey.as_bytes(), &to_vec(rule).unwrap());
+}
+
+pub fn store_remove_data_access_rule(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) {
+    let mut store =
+        PrefixedStorage::multilevel(storage, &[DATA_ACCESS_RULES_STORE_KEY, data_id.as_bytes()]);
+
+    store.remove(delegatee_pubkey.as_bytes());
+}
+
+pub fn store_get_data_access_rule(
+    storage: &dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> Option<DataAccessRule> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[DATA_ACCESS_RULES_STORE_KEY, data_id.as_bytes()],
+    );
+
+    store
+        .get(delegatee_pubkey.as_bytes())
+        .map(|data| from_slice(&data).unwrap())
+}
+
+pub fn store_get_all_data_access_rules(
+    storage: &dyn Storage,
+    data_id: &str,
+) -> Vec<(String, DataAccessRule)> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[DATA_ACCESS_RULES_STORE_KEY, data_id.as_bytes()],
+    );
+
+    let mut rules: Vec<(String, DataAccessRule)> = Vec::new();
+
+    for pair in store.range(None, None, Order::Ascending) {
+        // Deserialize keys with inverse operation to &string.as_bytes()
+        rules.push((
+            String::from_utf8(pair.0).unwrap(),
+            from_slice(&pair.1).unwrap(),
+        ));
+    }
+
+    rules
+}
+
+pub fn store_remove_all_data_access_rules(storage: &mut dyn Storage, data_id: &str) {
+    for (delegatee_pubkey, _) in store_get_all_data_access_rules(storage, data_id) {
+        store_remove_data_access_rule(storage, data_id, &delegatee_pubkey);
+    }
+}
+
 // Public functions
 
+pub fn is_reencryption_permitted(
+    storage: &dyn Storage,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+    data_id: &str,
+    block: &BlockInfo,
+) -> bool {
+    // Explicit rules of data entry take precedence over labels and policies
+    match store_get_data_access_rule(storage, data_id, delegatee_pubkey) {
+        Some(DataAccessRule::Deny) => false,
+        Some(DataAccessRule::Grant { expires_at }) if !is_expired(&expires_at, block) => true,
+        _ => get_permission(storage, delegator_addr, delegatee_pubkey, data_id, block),
+    }
+}
+
+fn is_expired(expires_at: &Option<Expiration>, block: &BlockInfo) -> bool {
+    match expires_at {
+        Some(expires_at) => expires_at.is_expired(block),
+        None => false,
+    }
+}
+
 pub fn get_permission(
     storage: &dyn Storage,
     delegator_addr: &Addr,
     delegatee_pubkey: &str,
     data_id: &str,
+    block: &BlockInfo,
 ) -> bool {
+    // Policy replaces matching of delegatee labels
+    if let Some(policy) = store_get_delegatee_policy(storage, delegator_addr, delegatee_pubkey) {
+        return policy.evaluate(&store_get_all_data_labels(storage, data_id));
+    }
+
     let delegatee_labels =
-        store_get_all_delegatee_labels(storage, delegator_addr, delegatee_pubkey);
+        store_get_all_delegatee_labels_with_expiration(storage, delegator_addr, delegatee_pubkey);
+
+    for (delegatee_label, expires_at) in delegatee_labels {
+        // Lapsed labels are ignored
+        if is_expired(&expires_at, block) {
+            continue;
+        }
 
-    for delegatee_label in delegatee_labels {
         if store_is_data_label(storage, data_id, &delegatee_label) {
             return true;
         }
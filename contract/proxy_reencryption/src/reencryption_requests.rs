@@ -1,25 +1,43 @@
-use crate::common::add_bank_msg;
-use crate::state::{store_get_staking_config, store_get_state, StakingConfig, State};
-use cosmwasm_std::{from_slice, to_vec, Addr, Order, Response, StdResult, Storage};
+use crate::allowances::credit_delegatee_allowance;
+use crate::common::{add_bank_msg, parse_legacy_id, range_start_after, take_legacy_entries};
+use crate::proxies::{store_get_proxy_entry, store_set_proxy_entry};
+use crate::state::{
+    is_data_expired, store_get_staking_config, store_get_state, update_contract_stats,
+    StakingConfig, State,
+};
+use cosmwasm_std::{from_slice, Addr, BlockInfo, Order, Response, StdResult, Storage, Timestamp};
 use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::TryInto;
 
-// Map proxy_task_id: u64 -> task: ProxyTask
-static PROXY_TASKS_STORE_KEY: &[u8] = b"ProxyTasks";
-
-// Delegatee side to lookup fragments
-// Map data_id: String -> delegatee_pubkey: String -> proxy_addr: Addr -> proxy_task_id: u64
-static DELEGATEE_PROXY_TASKS_STORE_KEY: &[u8] = b"DelegateeProxyTasks";
+// Delegatee side to list requests, entry is removed with last task of latest request of data_id
+// Map delegatee_pubkey: String -> data_id: String -> is_request: bool
+static DELEGATEE_REQUESTS_STORE_KEY: &[u8] = b"DelegateeRequests";
 
 // Proxy side to lookup active tasks
 // Map proxy_addr: Address -> proxy_task_id: u64 -> is_task: bool
 static PROXY_TASKS_QUEUE_STORE_KEY: &[u8] = b"ProxyTasksQueue";
 
-// Map data_id: String -> proxy_task_id: u64 -> is_task: bool
-static DATA_ID_TASKS_STORE_KEY: &[u8] = b"DataIDProxyTasks";
+// Proxy side to page through active tasks skipping timed-out ones, updated with PROXY_TASKS_QUEUE_STORE_KEY
+// Map proxy_addr: Address -> timeout_height: u64 (big endian) + proxy_task_id: u64 (big endian) -> is_task: bool
+static PROXY_TASKS_TIMEOUT_QUEUE_STORE_KEY: &[u8] = b"ProxyTasksTimeoutQueue";
+
+// Timed-out tasks are resolved in order of timeout, entries of finished tasks are removed lazily
+// Map timeout_height: u64 (big endian) + proxy_task_id: u64 (big endian) -> is_task: bool
+static TASK_TIMEOUTS_STORE_KEY: &[u8] = b"TaskTimeouts";
+
+// Sequence number of next re-encryption request
+// Map data_id: String -> delegatee_pubkey: String -> next_request_seq: u64
+static NEXT_REQUEST_SEQS_STORE_KEY: &[u8] = b"NextRequestSeqs";
+
+// Legacy layout, entries are moved to proxy_tasks() by migration
+static LEGACY_PROXY_TASKS_STORE_KEY: &[u8] = b"ProxyTasks";
+static LEGACY_DATA_ID_TASKS_STORE_KEY: &[u8] = b"DataIDProxyTasks";
+// Map data_id: String -> delegatee_pubkey: String -> proxy_addr: Addr -> proxy_task_id: u64
+static LEGACY_DELEGATEE_PROXY_TASKS_STORE_KEY: &[u8] = b"DelegateeProxyTasks";
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct ProxyTask {
@@ -36,6 +54,11 @@ pub struct ProxyTask {
     pub timeout_height: u64,
     // Reward will be returned to this address when request cannot be completed
     pub refund_addr: Addr,
+    // Reward was drawn from delegatee allowance of this delegator and is refunded there
+    pub allowance_owner_addr: Option<Addr>,
+    // Sequence number of re-encryption request of delegatee for data_id
+    #[serde(default)]
+    pub request_seq: u64,
 
     // When task was finished and proxy got rewarded or it was abandoned/timed-out and delegator got refunded
     pub resolved: bool,
@@ -43,6 +66,48 @@ pub struct ProxyTask {
     pub abandoned: bool,
 }
 
+struct ProxyTaskIndexes<'a> {
+    proxy: MultiIndex<'a, Addr, ProxyTask, u64>,
+    data_id: MultiIndex<'a, String, ProxyTask, u64>,
+    request: MultiIndex<'a, (String, String, u64), ProxyTask, u64>,
+}
+
+impl<'a> IndexList<ProxyTask> for ProxyTaskIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<ProxyTask>> + '_> {
+        let v: Vec<&dyn Index<ProxyTask>> = vec![&self.proxy, &self.data_id, &self.request];
+        Box::new(v.into_iter())
+    }
+}
+
+// All tasks including resolved ones
+// Map proxy_task_id: u64 -> task: ProxyTask, indexed by proxy_addr, data_id and (data_id, delegatee_pubkey, request_seq)
+fn proxy_tasks<'a>() -> IndexedMap<'a, u64, ProxyTask, ProxyTaskIndexes<'a>> {
+    let indexes = ProxyTaskIndexes {
+        proxy: MultiIndex::new(
+            |proxy_task: &ProxyTask| proxy_task.proxy_addr.clone(),
+            "proxy_reencryption:proxy-tasks",
+            "proxy_reencryption:proxy-tasks__proxy",
+        ),
+        data_id: MultiIndex::new(
+            |proxy_task: &ProxyTask| proxy_task.data_id.clone(),
+            "proxy_reencryption:proxy-tasks",
+            "proxy_reencryption:proxy-tasks__data-id",
+        ),
+        request: MultiIndex::new(
+            |proxy_task: &ProxyTask| {
+                (
+                    proxy_task.data_id.clone(),
+                    proxy_task.delegatee_pubkey.clone(),
+                    proxy_task.request_seq,
+                )
+            },
+            "proxy_reencryption:proxy-tasks",
+            "proxy_reencryption:proxy-tasks__request",
+        ),
+    };
+    IndexedMap::new("proxy_reencryption:proxy-tasks", indexes)
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReencryptionRequestState {
@@ -53,175 +118,287 @@ pub enum ReencryptionRequestState {
     TimedOut,
 }
 
-// PROXY_TASKS_STORE_KEY
+// PROXY_TASKS
 pub fn store_set_proxy_task(
     storage: &mut dyn Storage,
     proxy_task_id: &u64,
     reencryption_task: &ProxyTask,
-) {
-    let mut store = PrefixedStorage::new(storage, PROXY_TASKS_STORE_KEY);
-
-    store.set(
-        &proxy_task_id.to_le_bytes(),
-        &to_vec(reencryption_task).unwrap(),
-    );
+) -> StdResult<()> {
+    proxy_tasks().save(storage, *proxy_task_id, reencryption_task)
 }
 
-pub fn store_get_proxy_task(storage: &dyn Storage, proxy_task_id: &u64) -> Option<ProxyTask> {
-    let store = ReadonlyPrefixedStorage::new(storage, PROXY_TASKS_STORE_KEY);
+pub fn store_get_proxy_task(
+    storage: &dyn Storage,
+    proxy_task_id: &u64,
+) -> StdResult<Option<ProxyTask>> {
+    proxy_tasks().may_load(storage, *proxy_task_id)
+}
 
-    store
-        .get(&proxy_task_id.to_le_bytes())
-        .map(|data| from_slice(&data).unwrap())
+pub fn store_remove_proxy_task(storage: &mut dyn Storage, proxy_task_id: &u64) -> StdResult<()> {
+    proxy_tasks().remove(storage, *proxy_task_id)
 }
 
-pub fn store_remove_proxy_task(storage: &mut dyn Storage, proxy_task_id: &u64) {
-    let mut store = PrefixedStorage::new(storage, PROXY_TASKS_STORE_KEY);
+pub fn store_get_proxy_tasks_history(
+    storage: &dyn Storage,
+    proxy_addr: &Addr,
+    start_after: Option<u64>,
+    limit: usize,
+) -> StdResult<Vec<u64>> {
+    let start = start_after.map(Bound::exclusive);
+
+    proxy_tasks()
+        .idx
+        .proxy
+        .prefix(proxy_addr.clone())
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
 
-    store.remove(&proxy_task_id.to_le_bytes());
+pub fn store_get_data_id_tasks(storage: &dyn Storage, data_id: &str) -> StdResult<Vec<u64>> {
+    proxy_tasks()
+        .idx
+        .data_id
+        .prefix(data_id.to_string())
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
 }
 
-// DELEGATEE_PROXY_TASKS_STORE_KEY
-pub fn store_add_delegatee_proxy_task(
-    storage: &mut dyn Storage,
+// PROXY_TASKS request index, latest request of delegatee for data_id has sequence number next_request_seq - 1
+pub fn store_get_delegatee_proxy_task(
+    storage: &dyn Storage,
     data_id: &str,
     delegatee_pubkey: &str,
     proxy_addr: &Addr,
-    proxy_task_id: &u64,
+) -> StdResult<Option<u64>> {
+    let request_seq = match store_get_next_request_seq(storage, data_id, delegatee_pubkey) {
+        0 => return Ok(None),
+        next_request_seq => next_request_seq - 1,
+    };
+
+    // Each proxy has at most one task in request
+    for item in proxy_tasks()
+        .idx
+        .request
+        .prefix((
+            data_id.to_string(),
+            delegatee_pubkey.to_string(),
+            request_seq,
+        ))
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (proxy_task_id, proxy_task) = item?;
+        if &proxy_task.proxy_addr == proxy_addr {
+            return Ok(Some(proxy_task_id));
+        }
+    }
+    Ok(None)
+}
+
+pub fn store_get_all_delegatee_proxy_tasks(
+    storage: &dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<Vec<u64>> {
+    let request_seq = match store_get_next_request_seq(storage, data_id, delegatee_pubkey) {
+        0 => return Ok(Vec::new()),
+        next_request_seq => next_request_seq - 1,
+    };
+
+    proxy_tasks()
+        .idx
+        .request
+        .prefix((
+            data_id.to_string(),
+            delegatee_pubkey.to_string(),
+            request_seq,
+        ))
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+pub fn store_is_list_of_delegatee_proxy_tasks_empty(
+    storage: &dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<bool> {
+    Ok(store_get_all_delegatee_proxy_tasks(storage, data_id, delegatee_pubkey)?.is_empty())
+}
+
+// DELEGATEE_REQUESTS_STORE_KEY
+pub fn store_add_delegatee_request(
+    storage: &mut dyn Storage,
+    delegatee_pubkey: &str,
+    data_id: &str,
 ) {
     let mut store = PrefixedStorage::multilevel(
         storage,
-        &[
-            DELEGATEE_PROXY_TASKS_STORE_KEY,
-            data_id.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
+        &[DELEGATEE_REQUESTS_STORE_KEY, delegatee_pubkey.as_bytes()],
     );
 
-    store.set(proxy_addr.as_bytes(), &proxy_task_id.to_le_bytes());
+    // Any value in store means true - &[1]
+    store.set(data_id.as_bytes(), &[1]);
 }
 
-pub fn store_get_delegatee_proxy_task(
+pub fn store_remove_delegatee_request(
     storage: &mut dyn Storage,
-    data_id: &str,
     delegatee_pubkey: &str,
-    proxy_addr: &Addr,
-) -> Option<u64> {
-    let store = ReadonlyPrefixedStorage::multilevel(
+    data_id: &str,
+) {
+    let mut store = PrefixedStorage::multilevel(
         storage,
-        &[
-            DELEGATEE_PROXY_TASKS_STORE_KEY,
-            data_id.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
+        &[DELEGATEE_REQUESTS_STORE_KEY, delegatee_pubkey.as_bytes()],
     );
 
-    store
-        .get(proxy_addr.as_bytes())
-        .map(|data| u64::from_le_bytes(data.try_into().unwrap()))
+    store.remove(data_id.as_bytes());
 }
 
-pub fn store_get_all_delegatee_proxy_tasks(
+pub fn store_get_delegatee_data_ids(
     storage: &dyn Storage,
-    data_id: &str,
     delegatee_pubkey: &str,
-) -> Vec<u64> {
+    start_after: Option<&str>,
+    limit: usize,
+) -> StdResult<Vec<String>> {
     let store = ReadonlyPrefixedStorage::multilevel(
         storage,
-        &[
-            DELEGATEE_PROXY_TASKS_STORE_KEY,
-            data_id.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
+        &[DELEGATEE_REQUESTS_STORE_KEY, delegatee_pubkey.as_bytes()],
     );
 
-    let mut deserialized_keys: Vec<u64> = Vec::new();
+    let start = start_after.map(|data_id| range_start_after(data_id.as_bytes()));
 
-    for pair in store.range(None, None, Order::Ascending) {
-        // Deserialize keys with inverse operation to to_vec
-        deserialized_keys.push(u64::from_le_bytes(pair.1.try_into().unwrap()));
-    }
+    store
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        // Deserialize keys with inverse operation to string.as_bytes()
+        .map(|pair| Ok(String::from_utf8(pair.0)?))
+        .collect()
+}
 
-    deserialized_keys
+// TASK_TIMEOUTS_STORE_KEY
+fn task_timeout_key(timeout_height: u64, proxy_task_id: &u64) -> Vec<u8> {
+    let mut key = timeout_height.to_be_bytes().to_vec();
+    key.extend_from_slice(&proxy_task_id.to_be_bytes());
+    key
 }
 
-pub fn store_is_list_of_delegatee_proxy_tasks_empty(
+pub fn store_add_task_timeout(storage: &mut dyn Storage, timeout_height: u64, proxy_task_id: &u64) {
+    let mut store = PrefixedStorage::new(storage, TASK_TIMEOUTS_STORE_KEY);
+
+    // Any value in store means true - &[1]
+    store.set(&task_timeout_key(timeout_height, proxy_task_id), &[1]);
+}
+
+pub fn store_remove_task_timeout(
+    storage: &mut dyn Storage,
+    timeout_height: u64,
+    proxy_task_id: &u64,
+) {
+    let mut store = PrefixedStorage::new(storage, TASK_TIMEOUTS_STORE_KEY);
+
+    store.remove(&task_timeout_key(timeout_height, proxy_task_id));
+}
+
+pub fn store_get_timed_out_tasks(
+    storage: &dyn Storage,
+    block_height: u64,
+    limit: usize,
+) -> Vec<(u64, u64)> {
+    let store = ReadonlyPrefixedStorage::new(storage, TASK_TIMEOUTS_STORE_KEY);
+
+    // All entries with timeout_height <= block_height
+    let end = (block_height + 1).to_be_bytes();
+
+    store
+        .range(None, Some(&end), Order::Ascending)
+        .take(limit)
+        .map(|pair| {
+            (
+                u64::from_be_bytes(pair.0[..8].try_into().unwrap()),
+                u64::from_be_bytes(pair.0[8..].try_into().unwrap()),
+            )
+        })
+        .collect()
+}
+
+// NEXT_REQUEST_SEQS_STORE_KEY
+pub fn store_get_next_request_seq(
     storage: &dyn Storage,
     data_id: &str,
     delegatee_pubkey: &str,
-) -> bool {
+) -> u64 {
     let store = ReadonlyPrefixedStorage::multilevel(
         storage,
-        &[
-            DELEGATEE_PROXY_TASKS_STORE_KEY,
-            data_id.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
+        &[NEXT_REQUEST_SEQS_STORE_KEY, data_id.as_bytes()],
     );
 
-    let is_empty: bool = store
-        .range(None, None, Order::Ascending)
-        .peekable()
-        .peek()
-        .is_none();
-    is_empty
+    store
+        .get(delegatee_pubkey.as_bytes())
+        .map(|data| u64::from_le_bytes(data.try_into().unwrap()))
+        .unwrap_or(0)
 }
 
-pub fn store_remove_delegatee_proxy_task(
+pub fn store_set_next_request_seq(
     storage: &mut dyn Storage,
     data_id: &str,
     delegatee_pubkey: &str,
-    proxy_addr: &Addr,
+    request_seq: u64,
 ) {
-    let mut store = PrefixedStorage::multilevel(
-        storage,
-        &[
-            DELEGATEE_PROXY_TASKS_STORE_KEY,
-            data_id.as_bytes(),
-            delegatee_pubkey.as_bytes(),
-        ],
-    );
-
-    store.remove(proxy_addr.as_bytes());
-}
-
-// DATA_ID_TASKS_STORE_KEY
-pub fn store_add_data_id_task(storage: &mut dyn Storage, data_id: &str, task_id: &u64) {
     let mut store =
-        PrefixedStorage::multilevel(storage, &[DATA_ID_TASKS_STORE_KEY, data_id.as_bytes()]);
+        PrefixedStorage::multilevel(storage, &[NEXT_REQUEST_SEQS_STORE_KEY, data_id.as_bytes()]);
 
-    store.set(&task_id.to_le_bytes(), &[1]);
+    store.set(delegatee_pubkey.as_bytes(), &request_seq.to_le_bytes());
 }
 
-pub fn store_remove_data_id_task(storage: &mut dyn Storage, data_id: &str, task_id: &u64) {
+pub fn store_remove_next_request_seq(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) {
     let mut store =
-        PrefixedStorage::multilevel(storage, &[DATA_ID_TASKS_STORE_KEY, data_id.as_bytes()]);
+        PrefixedStorage::multilevel(storage, &[NEXT_REQUEST_SEQS_STORE_KEY, data_id.as_bytes()]);
 
-    store.remove(&task_id.to_le_bytes());
+    store.remove(delegatee_pubkey.as_bytes());
 }
 
-pub fn store_get_data_id_tasks(storage: &dyn Storage, data_id: &str) -> Vec<u64> {
-    let store = ReadonlyPrefixedStorage::multilevel(
-        storage,
-        &[DATA_ID_TASKS_STORE_KEY, data_id.as_bytes()],
-    );
-
-    let mut deserialized_keys: Vec<u64> = Vec::new();
+// PROXY_TASKS request index
+pub fn store_get_all_delegatee_requests(
+    storage: &dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<Vec<(u64, Vec<u64>)>> {
+    // Group task IDs by request, index is ordered by request_seq
+    let mut requests: Vec<(u64, Vec<u64>)> = Vec::new();
+
+    for item in proxy_tasks()
+        .idx
+        .request
+        .sub_prefix((data_id.to_string(), delegatee_pubkey.to_string()))
+        .range(storage, None, None, Order::Ascending)
+    {
+        let (proxy_task_id, proxy_task) = item?;
 
-    for pair in store.range(None, None, Order::Ascending) {
-        // Deserialize keys with inverse operation to to_vec
-        deserialized_keys.push(u64::from_le_bytes(pair.0.try_into().unwrap()));
+        match requests.last_mut() {
+            Some((last_request_seq, task_ids)) if *last_request_seq == proxy_task.request_seq => {
+                task_ids.push(proxy_task_id)
+            }
+            _ => requests.push((proxy_task.request_seq, vec![proxy_task_id])),
+        }
     }
 
-    deserialized_keys
+    Ok(requests)
 }
 
 // PROXY_TASKS_QUEUE_STORE_KEY
 pub fn store_add_proxy_task_to_queue(
     storage: &mut dyn Storage,
     proxy_addr: &Addr,
+    timeout_height: u64,
     proxy_task_id: &u64,
-) {
+) -> StdResult<()> {
+    if !store_is_proxy_task_in_queue(storage, proxy_addr, proxy_task_id) {
+        update_contract_stats(storage, |stats| stats.n_open_tasks += 1)?;
+    }
+
     let mut store = PrefixedStorage::multilevel(
         storage,
         &[PROXY_TASKS_QUEUE_STORE_KEY, proxy_addr.as_bytes()],
@@ -229,19 +406,43 @@ pub fn store_add_proxy_task_to_queue(
 
     // Any value in store means true - &[1]
     store.set(&proxy_task_id.to_le_bytes(), &[1]);
+
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[PROXY_TASKS_TIMEOUT_QUEUE_STORE_KEY, proxy_addr.as_bytes()],
+    );
+
+    store.set(&task_timeout_key(timeout_height, proxy_task_id), &[1]);
+    Ok(())
 }
 
 pub fn store_remove_proxy_task_from_queue(
     storage: &mut dyn Storage,
     proxy_addr: &Addr,
+    timeout_height: u64,
     proxy_task_id: &u64,
-) {
+) -> StdResult<()> {
+    // Tasks are removed from queue repeatedly when requests are resolved
+    if store_is_proxy_task_in_queue(storage, proxy_addr, proxy_task_id) {
+        update_contract_stats(storage, |stats| {
+            stats.n_open_tasks = stats.n_open_tasks.saturating_sub(1)
+        })?;
+    }
+
     let mut store = PrefixedStorage::multilevel(
         storage,
         &[PROXY_TASKS_QUEUE_STORE_KEY, proxy_addr.as_bytes()],
     );
 
     store.remove(&proxy_task_id.to_le_bytes());
+
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[PROXY_TASKS_TIMEOUT_QUEUE_STORE_KEY, proxy_addr.as_bytes()],
+    );
+
+    store.remove(&task_timeout_key(timeout_height, proxy_task_id));
+    Ok(())
 }
 
 pub fn store_is_proxy_task_in_queue(
@@ -273,28 +474,155 @@ pub fn store_get_all_proxy_tasks_in_queue(storage: &dyn Storage, proxy_addr: &Ad
     deserialized_keys
 }
 
+// PROXY_TASKS_TIMEOUT_QUEUE_STORE_KEY
+pub fn store_get_proxy_tasks_in_queue(
+    storage: &dyn Storage,
+    proxy_addr: &Addr,
+    block_height: u64,
+    start_after: Option<(u64, u64)>,
+    limit: usize,
+) -> Vec<(u64, u64)> {
+    // Returns (timeout_height, proxy_task_id) of tasks which are not timed-out yet
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[PROXY_TASKS_TIMEOUT_QUEUE_STORE_KEY, proxy_addr.as_bytes()],
+    );
+
+    // Tasks are timed-out when block_height >= timeout_height
+    let mut start = (block_height + 1).to_be_bytes().to_vec();
+    if let Some((timeout_height, proxy_task_id)) = start_after {
+        start = start.max(range_start_after(&task_timeout_key(
+            timeout_height,
+            &proxy_task_id,
+        )));
+    }
+
+    store
+        .range(Some(&start), None, Order::Ascending)
+        .take(limit)
+        .map(|pair| {
+            (
+                u64::from_be_bytes(pair.0[..8].try_into().unwrap()),
+                u64::from_be_bytes(pair.0[8..].try_into().unwrap()),
+            )
+        })
+        .collect()
+}
+
 // High level methods
 
+pub fn migrate_legacy_proxy_tasks(storage: &mut dyn Storage, limit: &mut usize) -> StdResult<u32> {
+    // Move proxy tasks to indexed map, data_id and delegatee lookups are rebuilt as indexes
+    take_legacy_entries(storage, LEGACY_DATA_ID_TASKS_STORE_KEY, limit);
+
+    let mut n_proxy_tasks: u32 = 0;
+    let mut n_open_tasks: u64 = 0;
+
+    // Legacy layout kept only latest request, it becomes request 1 and older tasks stay in request 0
+    for (_, proxy_task_id) in
+        take_legacy_entries(storage, LEGACY_DELEGATEE_PROXY_TASKS_STORE_KEY, limit)
+    {
+        let proxy_task_id = parse_legacy_id(&proxy_task_id)?;
+
+        let mut store = PrefixedStorage::new(storage, LEGACY_PROXY_TASKS_STORE_KEY);
+        let proxy_task = match store.get(&proxy_task_id.to_le_bytes()) {
+            None => continue,
+            Some(proxy_task) => proxy_task,
+        };
+        store.remove(&proxy_task_id.to_le_bytes());
+
+        let mut proxy_task: ProxyTask = from_slice(&proxy_task)?;
+        proxy_task.request_seq = 1;
+        store_add_delegatee_request(storage, &proxy_task.delegatee_pubkey, &proxy_task.data_id);
+        store_set_next_request_seq(
+            storage,
+            &proxy_task.data_id,
+            &proxy_task.delegatee_pubkey,
+            proxy_task.request_seq + 1,
+        );
+
+        if migrate_legacy_proxy_task(storage, proxy_task_id, &proxy_task)? {
+            n_open_tasks += 1;
+        }
+        n_proxy_tasks += 1;
+    }
+
+    for (proxy_task_id, proxy_task) in
+        take_legacy_entries(storage, LEGACY_PROXY_TASKS_STORE_KEY, limit)
+    {
+        let proxy_task_id = parse_legacy_id(&proxy_task_id)?;
+        let proxy_task: ProxyTask = from_slice(&proxy_task)?;
+
+        if migrate_legacy_proxy_task(storage, proxy_task_id, &proxy_task)? {
+            n_open_tasks += 1;
+        }
+        n_proxy_tasks += 1;
+    }
+
+    if n_proxy_tasks > 0 {
+        update_contract_stats(storage, |stats| stats.n_open_tasks += n_open_tasks)?;
+    }
+
+    Ok(n_proxy_tasks)
+}
+
+fn migrate_legacy_proxy_task(
+    storage: &mut dyn Storage,
+    proxy_task_id: u64,
+    proxy_task: &ProxyTask,
+) -> StdResult<bool> {
+    // Returns true if task is open
+    proxy_tasks().save(storage, proxy_task_id, proxy_task)?;
+
+    // Lookups which legacy layout didn't have
+    if !proxy_task.resolved {
+        store_add_task_timeout(storage, proxy_task.timeout_height, &proxy_task_id);
+    }
+
+    // Task stays in proxy queue, this adds it to timeout queue
+    if store_is_proxy_task_in_queue(storage, &proxy_task.proxy_addr, &proxy_task_id) {
+        store_add_proxy_task_to_queue(
+            storage,
+            &proxy_task.proxy_addr,
+            proxy_task.timeout_height,
+            &proxy_task_id,
+        )?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
 pub fn get_reencryption_request_state(
     storage: &dyn Storage,
     state: &State,
     data_id: &str,
     delegatee_pubkey: &str,
-    block_height: &u64,
-) -> ReencryptionRequestState {
+    block: &BlockInfo,
+) -> StdResult<ReencryptionRequestState> {
     // Return state of re-encryption request by aggregating states of all individual tasks
 
-    let proxy_tasks = store_get_all_delegatee_proxy_tasks(storage, data_id, delegatee_pubkey);
+    let proxy_tasks = store_get_all_delegatee_proxy_tasks(storage, data_id, delegatee_pubkey)?;
 
-    if proxy_tasks.is_empty() {
-        return ReencryptionRequestState::Inaccessible;
+    if proxy_tasks.is_empty() || is_data_expired(storage, data_id, block)? {
+        return Ok(ReencryptionRequestState::Inaccessible);
     }
 
+    get_proxy_tasks_state(storage, state, &proxy_tasks, block)
+}
+
+pub fn get_proxy_tasks_state(
+    storage: &dyn Storage,
+    state: &State,
+    proxy_tasks: &[u64],
+    block: &BlockInfo,
+) -> StdResult<ReencryptionRequestState> {
+    // Aggregate states of tasks of one request, used for archived requests as well
+
     let mut n_provided_fragments: u32 = 0;
     let mut n_incompletable_tasks: u32 = 0;
     let mut timeout_height: u64 = 0;
-    for &task_id in &proxy_tasks {
-        let task: ProxyTask = store_get_proxy_task(storage, &task_id).unwrap();
+    for task_id in proxy_tasks {
+        let task: ProxyTask = store_get_proxy_task(storage, task_id)?.unwrap();
         timeout_height = task.timeout_height;
         if task.fragment.is_some() {
             n_provided_fragments += 1;
@@ -304,19 +632,19 @@ pub fn get_reencryption_request_state(
     }
 
     if n_provided_fragments >= state.threshold {
-        return ReencryptionRequestState::Granted;
+        return Ok(ReencryptionRequestState::Granted);
     }
 
-    if block_height >= &timeout_height {
-        return ReencryptionRequestState::TimedOut;
+    if block.height >= timeout_height {
+        return Ok(ReencryptionRequestState::TimedOut);
     }
 
     // Task cannot be completed any more
     if (proxy_tasks.len() - n_incompletable_tasks as usize) < state.threshold as usize {
-        return ReencryptionRequestState::Abandoned;
+        return Ok(ReencryptionRequestState::Abandoned);
     }
 
-    ReencryptionRequestState::Ready
+    Ok(ReencryptionRequestState::Ready)
 }
 
 pub fn abandon_proxy_task(
@@ -328,27 +656,44 @@ pub fn abandon_proxy_task(
 ) -> StdResult<()> {
     // Abandon individual proxy task and refunds delegator if request cannot be complete any more
 
-    let mut re_task: ProxyTask = store_get_proxy_task(storage, re_task_id).unwrap();
+    let mut re_task: ProxyTask = store_get_proxy_task(storage, re_task_id)?.unwrap();
+
+    // Withdrawn stake of proxy is not returned for abandoned task
+    if !re_task.abandoned && re_task.fragment.is_none() {
+        update_contract_stats(storage, |stats| {
+            stats.total_slashed += staking_config.per_task_slash_stake_amount
+        })?;
+    }
 
     // Abandon task
     re_task.abandoned = true;
-    store_set_proxy_task(storage, re_task_id, &re_task);
+    store_set_proxy_task(storage, re_task_id, &re_task)?;
 
     // Remove task from proxy queue
-    store_remove_proxy_task_from_queue(storage, &re_task.proxy_addr, re_task_id);
+    store_remove_proxy_task_from_queue(
+        storage,
+        &re_task.proxy_addr,
+        re_task.timeout_height,
+        re_task_id,
+    )?;
 
     if re_task.resolved {
         return Ok(());
     }
 
-    // Block height is irrelevant here as we don't expect TimedOut state
+    // Block is irrelevant here as we don't expect TimedOut state, expired data is refunded when pruned
+    let block = BlockInfo {
+        height: 0,
+        time: Timestamp::from_nanos(0),
+        chain_id: String::new(),
+    };
     if get_reencryption_request_state(
         storage,
         state,
         &re_task.data_id,
         &re_task.delegatee_pubkey,
-        &0,
-    ) == ReencryptionRequestState::Abandoned
+        &block,
+    )? == ReencryptionRequestState::Abandoned
     {
         // Resolve all neighbour proxy tasks if request cannot be completed any more
 
@@ -357,9 +702,9 @@ pub fn abandon_proxy_task(
             storage,
             &re_task.data_id,
             &re_task.delegatee_pubkey,
-        );
+        )?;
         for task_id in proxy_tasks {
-            let mut task: ProxyTask = store_get_proxy_task(storage, &task_id).unwrap();
+            let mut task: ProxyTask = store_get_proxy_task(storage, &task_id)?.unwrap();
 
             // Skip already resolved tasks
             if task.resolved {
@@ -367,13 +712,14 @@ pub fn abandon_proxy_task(
             }
 
             task.resolved = true;
-            store_set_proxy_task(storage, &task_id, &task);
+            store_set_proxy_task(storage, &task_id, &task)?;
 
             // Refund the delegator - even when is completed
-            update_refunds_map(
-                delegator_retrieve_funds_amount,
-                &task.refund_addr,
+            refund_proxy_task(
+                storage,
+                &task,
                 staking_config.per_proxy_task_reward_amount.u128(),
+                delegator_retrieve_funds_amount,
             );
         }
     }
@@ -423,7 +769,7 @@ pub fn timeout_proxy_task(
 ) -> StdResult<()> {
     // Resolve proxy task, remove it from proxy que and refund the delegator
 
-    let mut re_task: ProxyTask = store_get_proxy_task(storage, re_task_id).unwrap();
+    let mut re_task: ProxyTask = store_get_proxy_task(storage, re_task_id)?.unwrap();
 
     // Completed or abandoned task
     if re_task.resolved {
@@ -431,38 +777,271 @@ pub fn timeout_proxy_task(
     }
 
     // Refund the delegator - even when is completed
-    update_refunds_map(
-        delegator_retrieve_funds_amount,
-        &re_task.refund_addr,
+    refund_proxy_task(
+        storage,
+        &re_task,
         staking_config.per_proxy_task_reward_amount.u128(),
+        delegator_retrieve_funds_amount,
     );
 
+    if !re_task.abandoned && re_task.fragment.is_none() {
+        update_contract_stats(storage, |stats| {
+            stats.total_slashed += staking_config.per_task_slash_stake_amount
+        })?;
+    }
+
     // Abandon task
     re_task.abandoned = true;
     re_task.resolved = true;
-    store_set_proxy_task(storage, re_task_id, &re_task);
+    store_set_proxy_task(storage, re_task_id, &re_task)?;
 
     // Remove task from proxy queue
-    store_remove_proxy_task_from_queue(storage, &re_task.proxy_addr, re_task_id);
+    store_remove_proxy_task_from_queue(
+        storage,
+        &re_task.proxy_addr,
+        re_task.timeout_height,
+        re_task_id,
+    )?;
 
     Ok(())
 }
 
+pub fn resolve_timed_out_tasks(
+    storage: &mut dyn Storage,
+    state: &State,
+    staking_config: &StakingConfig,
+    block: &BlockInfo,
+    limit: usize,
+    refund_amounts: &mut HashMap<Addr, u128>,
+) -> StdResult<Vec<ProxyTask>> {
+    // Walk timeout index and resolve tasks of timed-out requests, returns resolved tasks
+
+    let mut resolved_tasks: Vec<ProxyTask> = Vec::new();
+
+    for (timeout_height, task_id) in store_get_timed_out_tasks(storage, block.height, limit) {
+        store_remove_task_timeout(storage, timeout_height, &task_id);
+
+        // Task was removed or already resolved
+        let proxy_task = match store_get_proxy_task(storage, &task_id)? {
+            Some(proxy_task) if !proxy_task.resolved => proxy_task,
+            _ => continue,
+        };
+
+        // Only latest request can still be pending
+        if store_get_delegatee_proxy_task(
+            storage,
+            &proxy_task.data_id,
+            &proxy_task.delegatee_pubkey,
+            &proxy_task.proxy_addr,
+        )? != Some(task_id)
+        {
+            continue;
+        }
+
+        // Granted requests are not refunded
+        if get_reencryption_request_state(
+            storage,
+            state,
+            &proxy_task.data_id,
+            &proxy_task.delegatee_pubkey,
+            block,
+        )? != ReencryptionRequestState::TimedOut
+        {
+            continue;
+        }
+
+        timeout_proxy_task(storage, &task_id, staking_config, refund_amounts)?;
+        resolved_tasks.push(proxy_task);
+    }
+
+    Ok(resolved_tasks)
+}
+
+pub fn resolve_timed_out_request(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+    staking_config: &StakingConfig,
+    refund_amounts: &mut HashMap<Addr, u128>,
+) -> StdResult<()> {
+    // Resolve unresolved tasks of latest timed-out request, slash is counted once per task here
+
+    for task_id in store_get_all_delegatee_proxy_tasks(storage, data_id, delegatee_pubkey)? {
+        match store_get_proxy_task(storage, &task_id)? {
+            // Resolved tasks of abandoned request or already swept by keeper
+            Some(proxy_task) if !proxy_task.resolved => {
+                timeout_proxy_task(storage, &task_id, staking_config, refund_amounts)?;
+            }
+            _ => continue,
+        }
+    }
+
+    Ok(())
+}
+
+pub fn remove_all_data_id_proxy_tasks(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    staking_config: &StakingConfig,
+    refund_amounts: &mut HashMap<Addr, u128>,
+) -> StdResult<Vec<Addr>> {
+    // Remove all proxy tasks of data entry, refund unresolved tasks and return stake to proxies
+    // Returns proxies which got stake back
+
+    let mut stake_returned_proxies: Vec<Addr> = Vec::new();
+    let mut delegatee_pubkeys: Vec<String> = Vec::new();
+    for task_id in store_get_data_id_tasks(storage, data_id)? {
+        let proxy_task: ProxyTask = store_get_proxy_task(storage, &task_id)?.unwrap();
+        if !delegatee_pubkeys.contains(&proxy_task.delegatee_pubkey) {
+            delegatee_pubkeys.push(proxy_task.delegatee_pubkey);
+        }
+
+        if let Some(proxy_addr) =
+            remove_proxy_task_with_refund(storage, &task_id, staking_config, refund_amounts)?
+        {
+            if !stake_returned_proxies.contains(&proxy_addr) {
+                stake_returned_proxies.push(proxy_addr);
+            }
+        }
+    }
+
+    // Latest requests are identified by sequence numbers, they are reset after all tasks are gone
+    for delegatee_pubkey in delegatee_pubkeys {
+        store_remove_next_request_seq(storage, data_id, &delegatee_pubkey);
+    }
+
+    Ok(stake_returned_proxies)
+}
+
+pub fn archive_delegatee_proxy_tasks(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+    staking_config: &StakingConfig,
+    refund_amounts: &mut HashMap<Addr, u128>,
+) -> StdResult<()> {
+    // Latest request stays in history, retry gets next sequence number
+    // Unswept tasks go through the same path as ResolveTimedOutRequest so they are slashed only once
+    resolve_timed_out_request(
+        storage,
+        data_id,
+        delegatee_pubkey,
+        staking_config,
+        refund_amounts,
+    )
+}
+
+pub fn remove_all_delegatee_proxy_tasks(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    delegatee_pubkey: &str,
+    staking_config: &StakingConfig,
+    refund_amounts: &mut HashMap<Addr, u128>,
+) -> StdResult<()> {
+    // Remove proxy tasks of single re-encryption request so it can be requested again
+
+    for task_id in store_get_all_delegatee_proxy_tasks(storage, data_id, delegatee_pubkey)? {
+        remove_proxy_task_with_refund(storage, &task_id, staking_config, refund_amounts)?;
+    }
+
+    Ok(())
+}
+
+fn remove_proxy_task_with_refund(
+    storage: &mut dyn Storage,
+    task_id: &u64,
+    staking_config: &StakingConfig,
+    refund_amounts: &mut HashMap<Addr, u128>,
+) -> StdResult<Option<Addr>> {
+    // Returns proxy address if its stake was returned
+    let proxy_task: ProxyTask = store_get_proxy_task(storage, task_id)?.unwrap();
+
+    store_remove_proxy_task_from_queue(
+        storage,
+        &proxy_task.proxy_addr,
+        proxy_task.timeout_height,
+        task_id,
+    )?;
+    store_remove_proxy_task(storage, task_id)?;
+
+    // Archived tasks of previous requests were already resolved
+    if proxy_task.request_seq + 1
+        != store_get_next_request_seq(storage, &proxy_task.data_id, &proxy_task.delegatee_pubkey)
+    {
+        return Ok(None);
+    }
+
+    // Last task of request removed
+    if store_is_list_of_delegatee_proxy_tasks_empty(
+        storage,
+        &proxy_task.data_id,
+        &proxy_task.delegatee_pubkey,
+    )? {
+        store_remove_delegatee_request(storage, &proxy_task.delegatee_pubkey, &proxy_task.data_id);
+    }
+
+    // Completed tasks were already rewarded
+    if proxy_task.fragment.is_some() {
+        return Ok(None);
+    }
+
+    // Requester wasn't refunded yet
+    if !proxy_task.resolved {
+        refund_proxy_task(
+            storage,
+            &proxy_task,
+            staking_config.per_proxy_task_reward_amount.u128(),
+            refund_amounts,
+        );
+    }
+
+    // Proxy can't finish the task any more - return stake unless it was slashed
+    if !proxy_task.abandoned {
+        if let Some(mut proxy) = store_get_proxy_entry(storage, &proxy_task.proxy_addr)? {
+            proxy.stake_amount = proxy
+                .stake_amount
+                .checked_add(staking_config.per_task_slash_stake_amount)?;
+            store_set_proxy_entry(storage, &proxy_task.proxy_addr, &proxy)?;
+            return Ok(Some(proxy_task.proxy_addr));
+        }
+    }
+
+    Ok(None)
+}
+
 pub fn get_all_fragments(
     storage: &dyn Storage,
     data_id: &str,
     delegatee_pubkey: &str,
-) -> Vec<String> {
+) -> StdResult<Vec<String>> {
     let mut fragments: Vec<String> = Vec::new();
-    for task_id in store_get_all_delegatee_proxy_tasks(storage, data_id, delegatee_pubkey) {
-        let task = store_get_proxy_task(storage, &task_id).unwrap();
+    for task_id in store_get_all_delegatee_proxy_tasks(storage, data_id, delegatee_pubkey)? {
+        let task = store_get_proxy_task(storage, &task_id)?.unwrap();
 
         match task.fragment {
             None => continue,
             Some(fragment) => fragments.push(fragment.clone()),
         }
     }
-    fragments
+    Ok(fragments)
+}
+
+pub fn refund_proxy_task(
+    storage: &mut dyn Storage,
+    proxy_task: &ProxyTask,
+    refund_amount: u128,
+    refund_amounts: &mut HashMap<Addr, u128>,
+) {
+    match &proxy_task.allowance_owner_addr {
+        // Prepaid rewards go back to allowance instead of requester
+        Some(allowance_owner_addr) => credit_delegatee_allowance(
+            storage,
+            allowance_owner_addr,
+            &proxy_task.delegatee_pubkey,
+            refund_amount,
+        ),
+        None => update_refunds_map(refund_amounts, &proxy_task.refund_addr, refund_amount),
+    }
 }
 
 pub fn update_refunds_map(
@@ -1,7 +1,11 @@
-use cosmwasm_std::{from_slice, to_vec, Addr, StdResult, Storage, Uint128};
+use crate::common::take_legacy_entries;
+use cosmwasm_std::{
+    from_slice, to_vec, Addr, BlockInfo, Order, StdResult, Storage, Timestamp, Uint128,
+};
 use cosmwasm_storage::{
     singleton, singleton_read, PrefixedStorage, ReadonlyPrefixedStorage, Singleton,
 };
+use cw_storage_plus::{Bound, Index, IndexList, IndexedMap, MultiIndex};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -9,15 +13,35 @@ use serde::{Deserialize, Serialize};
 static STATE_KEY: &[u8] = b"State";
 static STAKING_CONFIG_KEY: &[u8] = b"StakingConfig";
 static TIMEOUTS_CONFIG_KEY: &[u8] = b"TimeoutsConfig";
+static CONTRACT_STATS_KEY: &[u8] = b"ContractStats";
+// Set while legacy entries remain after batched migration
+static LEGACY_MIGRATION_PENDING_KEY: &[u8] = b"LegacyMigrationPending";
 
 // Maps
 
-// Map data_id: String -> data_entry: DataEntry
-static DATA_ENTRIES_KEY: &[u8] = b"DataEntries";
-
 // Map delegator_pubkey: String -> delegator_addr: Addr
 static DELEGATOR_ADDRESS_KEY: &[u8] = b"DelegatorAddr";
 
+// To get expired data entries
+// Map expiration_height: u64 (big endian) + data_id: String -> is_data_id: bool
+static DATA_EXPIRATION_HEIGHTS_KEY: &[u8] = b"DataExpirationHeights";
+// Map expiration_time: u64 nanoseconds (big endian) + data_id: String -> is_data_id: bool
+static DATA_EXPIRATION_TIMES_KEY: &[u8] = b"DataExpirationTimes";
+
+// Registered delegatees restrict who can request re-encryption for their pubkey
+// Map delegatee_pubkey: String -> delegatee_addr: Addr
+static DELEGATEE_ADDRESS_KEY: &[u8] = b"DelegateeAddr";
+
+// Map delegatee_pubkey: String -> requester_addr: Addr -> is_requester: bool
+static DELEGATEE_REQUESTERS_KEY: &[u8] = b"DelegateeRequesters";
+
+// Pending data ownership transfers
+// Map data_id: String -> transfer: DataOwnershipTransfer
+static DATA_OWNERSHIP_TRANSFERS_KEY: &[u8] = b"DataOwnershipTransfers";
+
+// Legacy layout, entries are moved to data_entries() by migration
+static LEGACY_DATA_ENTRIES_KEY: &[u8] = b"DataEntries";
+
 // Singleton structures
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct State {
@@ -35,6 +59,9 @@ pub struct State {
     pub terminate_height: u64,
     // How many blocks after termination we allow withdrawal
     pub withdrawal_period: u64,
+    // Paused contract doesn't accept new data, delegations and requests
+    #[serde(default)]
+    pub paused: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -51,11 +78,74 @@ pub struct TimeoutsConfig {
     pub timeout_height: u64,
 }
 
+// Running counters, missing in contracts instantiated before they were introduced
+// Decrements saturate at zero so such contracts keep working until migration recounts them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema, Default)]
+pub struct ContractStats {
+    // Sum of stake amounts of all proxies, stake locked in open tasks is not included
+    pub total_staked: Uint128,
+    pub n_active_proxies: u32,
+    // Tasks waiting in proxy queues
+    pub n_open_tasks: u64,
+    // Requests which received threshold number of fragments
+    pub n_completed_requests: u64,
+    pub total_rewards_paid: Uint128,
+    pub total_slashed: Uint128,
+}
+
 // Store structures
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct DataEntry {
     pub delegator_pubkey: String,
     pub capsule: String,
+    // Data is not accessible from this block on
+    pub expires_at: Option<Expiration>,
+}
+
+struct DataEntryIndexes<'a> {
+    delegator: MultiIndex<'a, String, DataEntry, String>,
+}
+
+impl<'a> IndexList<DataEntry> for DataEntryIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<DataEntry>> + '_> {
+        let v: Vec<&dyn Index<DataEntry>> = vec![&self.delegator];
+        Box::new(v.into_iter())
+    }
+}
+
+// Map data_id: String -> data_entry: DataEntry, indexed by delegator_pubkey
+fn data_entries<'a>() -> IndexedMap<'a, &'a str, DataEntry, DataEntryIndexes<'a>> {
+    let indexes = DataEntryIndexes {
+        delegator: MultiIndex::new(
+            |data_entry: &DataEntry| data_entry.delegator_pubkey.clone(),
+            "proxy_reencryption:data-entries",
+            "proxy_reencryption:data-entries__delegator",
+        ),
+    };
+    IndexedMap::new("proxy_reencryption:data-entries", indexes)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Expiration {
+    AtHeight(u64),
+    AtTime(Timestamp),
+}
+
+impl Expiration {
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        match self {
+            Expiration::AtHeight(height) => block.height >= *height,
+            Expiration::AtTime(time) => block.time >= *time,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DataOwnershipTransfer {
+    pub new_delegator_pubkey: String,
+    // Capsule re-encrypted by current owner for new owner
+    pub new_capsule: String,
 }
 
 // Getters and setters
@@ -96,23 +186,102 @@ pub fn store_set_timeouts_config(
     singl.save(timeouts_config)
 }
 
+// CONTRACT_STATS
+pub fn store_get_contract_stats(storage: &dyn Storage) -> StdResult<ContractStats> {
+    Ok(singleton_read(storage, CONTRACT_STATS_KEY)
+        .may_load()?
+        .unwrap_or_default())
+}
+
+pub fn store_set_contract_stats(
+    storage: &mut dyn Storage,
+    contract_stats: &ContractStats,
+) -> StdResult<()> {
+    let mut singl: Singleton<ContractStats> = singleton(storage, CONTRACT_STATS_KEY);
+    singl.save(contract_stats)
+}
+
+// LEGACY_MIGRATION_PENDING
+pub fn store_get_is_legacy_migration_pending(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(singleton_read(storage, LEGACY_MIGRATION_PENDING_KEY)
+        .may_load()?
+        .unwrap_or(false))
+}
+
+pub fn store_set_is_legacy_migration_pending(
+    storage: &mut dyn Storage,
+    is_pending: bool,
+) -> StdResult<()> {
+    let mut singl: Singleton<bool> = singleton(storage, LEGACY_MIGRATION_PENDING_KEY);
+    match is_pending {
+        true => singl.save(&is_pending),
+        false => {
+            singl.remove();
+            Ok(())
+        }
+    }
+}
+
 // DATA_ENTRIES
-pub fn store_set_data_entry(storage: &mut dyn Storage, data_id: &str, data_entry: &DataEntry) {
-    let mut store = PrefixedStorage::new(storage, DATA_ENTRIES_KEY);
-    store.set(data_id.as_bytes(), &to_vec(data_entry).unwrap());
+pub fn store_set_data_entry(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    data_entry: &DataEntry,
+) -> StdResult<()> {
+    // Keep expiration indexes in sync when entry changes
+    if let Some(previous_entry) = store_get_data_entry(storage, data_id)? {
+        if let Some(expires_at) = &previous_entry.expires_at {
+            store_remove_data_expiration(storage, expires_at, data_id);
+        }
+    }
+    if let Some(expires_at) = &data_entry.expires_at {
+        store_add_data_expiration(storage, expires_at, data_id);
+    }
+
+    data_entries().save(storage, data_id, data_entry)
 }
 
-pub fn store_remove_data_entry(storage: &mut dyn Storage, data_id: &str) {
-    let mut store = PrefixedStorage::new(storage, DATA_ENTRIES_KEY);
-    store.remove(data_id.as_bytes());
+pub fn store_remove_data_entry(storage: &mut dyn Storage, data_id: &str) -> StdResult<()> {
+    if let Some(previous_entry) = store_get_data_entry(storage, data_id)? {
+        if let Some(expires_at) = &previous_entry.expires_at {
+            store_remove_data_expiration(storage, expires_at, data_id);
+        }
+    }
+
+    data_entries().remove(storage, data_id)
 }
 
-pub fn store_get_data_entry(storage: &dyn Storage, data_id: &str) -> Option<DataEntry> {
-    let store = ReadonlyPrefixedStorage::new(storage, DATA_ENTRIES_KEY);
+pub fn store_get_data_entry(storage: &dyn Storage, data_id: &str) -> StdResult<Option<DataEntry>> {
+    data_entries().may_load(storage, data_id)
+}
 
-    store
-        .get(data_id.as_bytes())
-        .map(|data| from_slice(&data).unwrap())
+pub fn store_get_all_delegator_data_ids(
+    storage: &dyn Storage,
+    delegator_pubkey: &str,
+) -> StdResult<Vec<String>> {
+    data_entries()
+        .idx
+        .delegator
+        .prefix(delegator_pubkey.to_string())
+        .keys(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+pub fn store_get_delegator_data_ids(
+    storage: &dyn Storage,
+    delegator_pubkey: &str,
+    start_after: Option<&str>,
+    limit: usize,
+) -> StdResult<Vec<String>> {
+    let start = start_after.map(|data_id| Bound::exclusive(data_id.to_string()));
+
+    data_entries()
+        .idx
+        .delegator
+        .prefix(delegator_pubkey.to_string())
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
 }
 
 // DELEGATOR_ADDRESS
@@ -126,9 +295,228 @@ pub fn store_set_delegator_address(
     storage.set(delegator_pubkey.as_bytes(), delegator_addr.as_bytes());
 }
 
-pub fn store_get_delegator_address(storage: &dyn Storage, delegator_pubkey: &str) -> Option<Addr> {
+pub fn store_remove_delegator_address(storage: &mut dyn Storage, delegator_pubkey: &str) {
+    let mut storage = PrefixedStorage::new(storage, DELEGATOR_ADDRESS_KEY);
+
+    storage.remove(delegator_pubkey.as_bytes());
+}
+
+pub fn store_get_delegator_address(
+    storage: &dyn Storage,
+    delegator_pubkey: &str,
+) -> StdResult<Option<Addr>> {
     let store = ReadonlyPrefixedStorage::new(storage, DELEGATOR_ADDRESS_KEY);
 
-    let res = store.get(delegator_pubkey.as_bytes());
+    match store.get(delegator_pubkey.as_bytes()) {
+        None => Ok(None),
+        Some(res) => Ok(Some(Addr::unchecked(String::from_utf8(res)?))),
+    }
+}
+
+// DATA_EXPIRATION_HEIGHTS and DATA_EXPIRATION_TIMES
+fn data_expiration_key(expires_at: &Expiration, data_id: &str) -> (&'static [u8], Vec<u8>) {
+    // Big endian prefix keeps keys ordered by expiration
+    let (store_key, value) = match expires_at {
+        Expiration::AtHeight(height) => (DATA_EXPIRATION_HEIGHTS_KEY, *height),
+        Expiration::AtTime(time) => (DATA_EXPIRATION_TIMES_KEY, time.nanos()),
+    };
+
+    let mut key = value.to_be_bytes().to_vec();
+    key.extend_from_slice(data_id.as_bytes());
+    (store_key, key)
+}
+
+fn store_add_data_expiration(storage: &mut dyn Storage, expires_at: &Expiration, data_id: &str) {
+    let (store_key, key) = data_expiration_key(expires_at, data_id);
+    let mut store = PrefixedStorage::new(storage, store_key);
+
+    // Any value in store means true - &[1]
+    store.set(&key, &[1]);
+}
+
+fn store_remove_data_expiration(storage: &mut dyn Storage, expires_at: &Expiration, data_id: &str) {
+    let (store_key, key) = data_expiration_key(expires_at, data_id);
+    let mut store = PrefixedStorage::new(storage, store_key);
+
+    store.remove(&key);
+}
+
+pub fn store_get_expired_data_ids(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    limit: usize,
+) -> StdResult<Vec<String>> {
+    let mut data_ids: Vec<String> = Vec::new();
+
+    for (store_key, current) in [
+        (DATA_EXPIRATION_HEIGHTS_KEY, block.height),
+        (DATA_EXPIRATION_TIMES_KEY, block.time.nanos()),
+    ] {
+        let store = ReadonlyPrefixedStorage::new(storage, store_key);
+
+        // All entries with expiration <= current
+        let end = (current + 1).to_be_bytes();
+        for pair in store.range(None, Some(&end), Order::Ascending) {
+            if data_ids.len() >= limit {
+                return Ok(data_ids);
+            }
+            // Strip expiration prefix
+            data_ids.push(String::from_utf8(pair.0[8..].to_vec())?);
+        }
+    }
+
+    Ok(data_ids)
+}
+
+// DATA_OWNERSHIP_TRANSFERS
+pub fn store_set_data_ownership_transfer(
+    storage: &mut dyn Storage,
+    data_id: &str,
+    transfer: &DataOwnershipTransfer,
+) {
+    let mut store = PrefixedStorage::new(storage, DATA_OWNERSHIP_TRANSFERS_KEY);
+    store.set(data_id.as_bytes(), &to_vec(transfer).unwrap());
+}
+
+pub fn store_remove_data_ownership_transfer(storage: &mut dyn Storage, data_id: &str) {
+    let mut store = PrefixedStorage::new(storage, DATA_OWNERSHIP_TRANSFERS_KEY);
+    store.remove(data_id.as_bytes());
+}
+
+pub fn store_get_data_ownership_transfer(
+    storage: &dyn Storage,
+    data_id: &str,
+) -> StdResult<Option<DataOwnershipTransfer>> {
+    let store = ReadonlyPrefixedStorage::new(storage, DATA_OWNERSHIP_TRANSFERS_KEY);
+
+    store
+        .get(data_id.as_bytes())
+        .map(|data| from_slice(&data))
+        .transpose()
+}
+
+// DELEGATEE_ADDRESS
+pub fn store_set_delegatee_address(
+    storage: &mut dyn Storage,
+    delegatee_pubkey: &str,
+    delegatee_addr: &Addr,
+) {
+    let mut storage = PrefixedStorage::new(storage, DELEGATEE_ADDRESS_KEY);
+
+    storage.set(delegatee_pubkey.as_bytes(), delegatee_addr.as_bytes());
+}
+
+pub fn store_get_delegatee_address(storage: &dyn Storage, delegatee_pubkey: &str) -> Option<Addr> {
+    let store = ReadonlyPrefixedStorage::new(storage, DELEGATEE_ADDRESS_KEY);
+
+    let res = store.get(delegatee_pubkey.as_bytes());
     res.map(|res| Addr::unchecked(String::from_utf8(res).unwrap()))
 }
+
+// DELEGATEE_REQUESTERS
+pub fn store_add_delegatee_requester(
+    storage: &mut dyn Storage,
+    delegatee_pubkey: &str,
+    requester_addr: &Addr,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_REQUESTERS_KEY, delegatee_pubkey.as_bytes()],
+    );
+
+    // Any value in store means true - &[1]
+    store.set(requester_addr.as_bytes(), &[1]);
+}
+
+pub fn store_remove_delegatee_requester(
+    storage: &mut dyn Storage,
+    delegatee_pubkey: &str,
+    requester_addr: &Addr,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_REQUESTERS_KEY, delegatee_pubkey.as_bytes()],
+    );
+
+    store.remove(requester_addr.as_bytes());
+}
+
+pub fn store_is_delegatee_requester(
+    storage: &dyn Storage,
+    delegatee_pubkey: &str,
+    requester_addr: &Addr,
+) -> bool {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_REQUESTERS_KEY, delegatee_pubkey.as_bytes()],
+    );
+
+    store.get(requester_addr.as_bytes()).is_some()
+}
+
+pub fn store_get_all_delegatee_requesters(
+    storage: &dyn Storage,
+    delegatee_pubkey: &str,
+) -> Vec<Addr> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[DELEGATEE_REQUESTERS_KEY, delegatee_pubkey.as_bytes()],
+    );
+
+    let mut deserialized_keys: Vec<Addr> = Vec::new();
+
+    for pair in store.range(None, None, Order::Ascending) {
+        // Deserialize keys with inverse operation to addr.as_bytes()
+        deserialized_keys.push(Addr::unchecked(String::from_utf8(pair.0).unwrap()));
+    }
+
+    deserialized_keys
+}
+
+// High level methods
+
+pub fn migrate_legacy_data_entries(storage: &mut dyn Storage, limit: &mut usize) -> StdResult<u32> {
+    // Move data entries to indexed map, delegator index is built on save
+    let entries = take_legacy_entries(storage, LEGACY_DATA_ENTRIES_KEY, limit);
+    for (data_id, data_entry) in entries.iter() {
+        let data_id = String::from_utf8(data_id.clone())?;
+        let data_entry: DataEntry = from_slice(data_entry)?;
+        data_entries().save(storage, &data_id, &data_entry)?;
+    }
+
+    Ok(entries.len() as u32)
+}
+
+pub fn is_data_expired(storage: &dyn Storage, data_id: &str, block: &BlockInfo) -> StdResult<bool> {
+    Ok(match store_get_data_entry(storage, data_id)? {
+        Some(DataEntry {
+            expires_at: Some(expires_at),
+            ..
+        }) => expires_at.is_expired(block),
+        _ => false,
+    })
+}
+
+pub fn is_requester_authorised(
+    storage: &dyn Storage,
+    delegatee_pubkey: &str,
+    requester_addr: &Addr,
+) -> bool {
+    match store_get_delegatee_address(storage, delegatee_pubkey) {
+        // Unregistered delegatees accept requests from anyone
+        None => true,
+        Some(delegatee_addr) => {
+            &delegatee_addr == requester_addr
+                || store_is_delegatee_requester(storage, delegatee_pubkey, requester_addr)
+        }
+    }
+}
+
+pub fn update_contract_stats<F: FnOnce(&mut ContractStats)>(
+    storage: &mut dyn Storage,
+    update: F,
+) -> StdResult<()> {
+    let mut contract_stats = store_get_contract_stats(storage)?;
+    update(&mut contract_stats);
+    store_set_contract_stats(storage, &contract_stats)
+}
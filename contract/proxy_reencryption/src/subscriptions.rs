@@ -0,0 +1,81 @@
+use cosmwasm_std::{from_slice, to_vec, Addr, Order, Storage, Uint128};
+use cosmwasm_storage::{PrefixedStorage, ReadonlyPrefixedStorage};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+// Requests are created automatically for new data of delegator matching subscription labels
+// Map delegator_pubkey: String -> delegatee_pubkey: String -> subscription: Subscription
+static SUBSCRIPTIONS_STORE_KEY: &[u8] = b"Subscriptions";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct Subscription {
+    pub subscriber_addr: Addr,
+    pub labels: Vec<String>,
+    // Remaining funds for rewards of automatic requests
+    pub budget: Uint128,
+}
+
+// SUBSCRIPTIONS_STORE
+pub fn store_set_subscription(
+    storage: &mut dyn Storage,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+    subscription: &Subscription,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[SUBSCRIPTIONS_STORE_KEY, delegator_pubkey.as_bytes()],
+    );
+
+    store.set(delegatee_pubkey.as_bytes(), &to_vec(subscription).unwrap());
+}
+
+pub fn store_remove_subscription(
+    storage: &mut dyn Storage,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+) {
+    let mut store = PrefixedStorage::multilevel(
+        storage,
+        &[SUBSCRIPTIONS_STORE_KEY, delegator_pubkey.as_bytes()],
+    );
+
+    store.remove(delegatee_pubkey.as_bytes());
+}
+
+pub fn store_get_subscription(
+    storage: &dyn Storage,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+) -> Option<Subscription> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[SUBSCRIPTIONS_STORE_KEY, delegator_pubkey.as_bytes()],
+    );
+
+    store
+        .get(delegatee_pubkey.as_bytes())
+        .map(|data| from_slice(&data).unwrap())
+}
+
+pub fn store_get_all_delegator_subscriptions(
+    storage: &dyn Storage,
+    delegator_pubkey: &str,
+) -> Vec<(String, Subscription)> {
+    let store = ReadonlyPrefixedStorage::multilevel(
+        storage,
+        &[SUBSCRIPTIONS_STORE_KEY, delegator_pubkey.as_bytes()],
+    );
+
+    let mut subscriptions: Vec<(String, Subscription)> = Vec::new();
+
+    for pair in store.range(None, None, Order::Ascending) {
+        // Deserialize keys with inverse operation to string.as_bytes()
+        subscriptions.push((
+            String::from_utf8(pair.0).unwrap(),
+            from_slice(&pair.1).unwrap(),
+        ));
+    }
+
+    subscriptions
+}
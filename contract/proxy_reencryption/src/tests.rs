@@ -1,35 +1,62 @@
-use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
 use cosmwasm_std::{
-    Addr, BankMsg, Coin, DepsMut, Env, MessageInfo, Response, StdError, StdResult, SubMsg, Uint128,
+    from_binary, Addr, Attribute, BankMsg, BlockInfo, Coin, DepsMut, Env, Event, MessageInfo,
+    OwnedDeps, Response, StdError, StdResult, Storage, SubMsg, Uint128,
 };
+use cosmwasm_storage::{to_length_prefixed, PrefixedStorage};
 
+use crate::access_requests::{
+    store_get_access_request, store_get_all_delegator_access_requests, AccessRequest,
+    AccessRequestTarget,
+};
+use crate::common::split_legacy_namespace;
 use crate::contract::{
-    execute, get_proxies_availability, get_proxy_tasks, instantiate,
-    DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT, DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT,
-    DEFAULT_TASK_REWARD_AMOUNT,
+    execute, get_proxies_availability, get_proxy_tasks, instantiate, migrate, query, sudo,
+    ACCESS_REQUEST_TIMEOUT_HEIGHT, DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT,
+    DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT, DEFAULT_TASK_REWARD_AMOUNT, DEFAULT_TIMEOUT_HEIGHT,
+    KEEPER_BOUNTY_PERCENTAGE, MAX_POLICY_DEPTH,
 };
 
 //use crate::contract::verify_fragment;
+use crate::data_tags::{store_get_all_data_tags, store_get_data_ids_by_tag};
 use crate::delegations::{
     get_delegation_state, get_n_available_proxies_from_delegation,
-    get_n_minimum_proxies_for_refund, store_add_per_proxy_delegation, store_get_delegation,
+    get_n_minimum_proxies_for_refund, store_get_all_proxy_delegations, store_get_delegation,
     store_get_proxy_delegation_id, store_is_proxy_delegation, store_set_delegation,
-    store_set_delegation_id, DelegationState, ProxyDelegation,
+    DelegationState, ProxyDelegation,
+};
+use crate::msg::{
+    CheckPermissionResponse, DataAccessGrant, DataBatchEntry, DataCapsule, DelegateeLabel,
+    DelegateeRequestResponse, ExecuteMsg, GetConfigResponse, GetContractStateResponse,
+    GetContractStatsResponse, GetDataAccessGrantsResponse, GetDataByTagResponse, GetDataIDResponse,
+    GetDataOwnershipTransferResponse, GetDelegateeAllowanceResponse, GetDelegateeLabelsResponse,
+    GetDelegateeRegistrationResponse, GetDelegateeRequestsResponse, GetFragmentsResponse,
+    GetPendingAccessRequestsResponse, GetProxyTasksResponse, GetReencryptionRequestsResponse,
+    GetStakingConfigResponse, GetSubscriptionResponse, GetTimeoutsConfigResponse, InstantiateMsg,
+    ListDataByDelegatorResponse, ListDelegationsResponse, ListProxiesResponse,
+    ListTasksByProxyResponse, MigrateMsg, ProxyDelegationString, ProxyFragment, ProxyTaskResponse,
+    ProxyTasksCursor, QueryMsg, SimulateReencryptionRequestResponse, SudoMsg, Tag,
 };
-use crate::msg::{ExecuteMsg, InstantiateMsg, ProxyDelegationString, ProxyTaskResponse};
 use crate::proxies::{
     store_get_all_active_proxy_addresses, store_get_is_proxy_active, store_get_proxy_entry,
     ProxyState,
 };
-use crate::reencryption_permissions::{get_permission, store_get_all_data_labels};
+use crate::reencryption_permissions::{
+    get_permission, store_get_all_data_access_rules, store_get_all_data_labels,
+    store_get_data_access_rule, DataAccessRule, LabelExpression,
+};
 use crate::reencryption_requests::{
     get_all_fragments, get_reencryption_request_state, store_get_all_proxy_tasks_in_queue,
-    store_get_delegatee_proxy_task, store_get_proxy_task, store_is_proxy_task_in_queue,
-    ReencryptionRequestState,
+    store_get_delegatee_proxy_task, store_get_next_request_seq, store_get_proxy_task,
+    store_is_proxy_task_in_queue, store_remove_proxy_task, ReencryptionRequestState,
 };
 use crate::state::{
-    store_get_data_entry, store_get_delegator_address, store_get_state, DataEntry, StakingConfig,
-    State,
+    is_data_expired, store_get_all_delegator_data_ids, store_get_data_entry,
+    store_get_data_ownership_transfer, store_get_delegator_address, store_get_expired_data_ids,
+    store_get_staking_config, store_get_state, DataEntry, DataOwnershipTransfer, Expiration,
+    StakingConfig, State, TimeoutsConfig,
 };
 
 // Test constants
@@ -66,6 +93,14 @@ fn mock_env_height(signer: &Addr, height: u64, coins: &Vec<Coin>) -> (Env, Messa
     (env, info)
 }
 
+fn mock_block_height(height: u64) -> BlockInfo {
+    let mut env = mock_env();
+
+    env.block.height = height;
+
+    env.block
+}
+
 fn is_err(result: StdResult<Response>, must_contain: &str) -> bool {
     // Returns true if error message contains specific string
     match result {
@@ -146,6 +181,35 @@ fn withdraw_contract(
     execute(deps, env.0, env.1, msg)
 }
 
+fn pause_contract(deps: DepsMut, creator: &Addr, block_height: u64) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::PauseContract {};
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn update_admin(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    admin: &Addr,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::UpdateAdmin {
+        admin: admin.clone(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn sudo_height(deps: DepsMut, block_height: u64, msg: SudoMsg) -> StdResult<Response> {
+    let env = mock_env_height(&Addr::unchecked("governance"), block_height, &vec![]);
+
+    sudo(deps, env.0, msg)
+}
+
 fn remove_proxy(
     deps: DepsMut,
     creator: &Addr,
@@ -240,6 +304,23 @@ fn provide_reencrypted_fragment(
     execute(deps, env.0, env.1, msg)
 }
 
+fn provide_reencrypted_fragments(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    fragments: &[ProxyFragment],
+    all_or_nothing: &Option<bool>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::ProvideReencryptedFragments {
+        fragments: fragments.to_vec(),
+        all_or_nothing: *all_or_nothing,
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
 fn skip_reencryption_task(
     deps: DepsMut,
     creator: &Addr,
@@ -274,6 +355,98 @@ fn add_data(
         capsule: capsule.clone(),
         tags: None,
         data_labels: data_labels.clone(),
+        expires_at: None,
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn add_data_with_tags(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+    delegator_pubkey: &String,
+    capsule: &String,
+    tags: &[Tag],
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::AddData {
+        data_id: data_id.clone(),
+        delegator_pubkey: delegator_pubkey.clone(),
+        capsule: capsule.clone(),
+        tags: Some(tags.to_vec()),
+        data_labels: None,
+        expires_at: None,
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn add_data_with_expiration(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+    delegator_pubkey: &String,
+    capsule: &String,
+    expires_at: &Expiration,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::AddData {
+        data_id: data_id.clone(),
+        delegator_pubkey: delegator_pubkey.clone(),
+        capsule: capsule.clone(),
+        tags: None,
+        data_labels: None,
+        expires_at: Some(expires_at.clone()),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn prune_expired_data(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    limit: Option<u32>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::PruneExpiredData { limit };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn update_data_tags(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+    tags: &[Tag],
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::UpdateDataTags {
+        data_id: data_id.clone(),
+        tags: tags.to_vec(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn add_data_batch(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    entries: &[DataBatchEntry],
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::AddDataBatch {
+        entries: entries.to_vec(),
     };
 
     execute(deps, env.0, env.1, msg)
@@ -294,6 +467,91 @@ fn remove_data(
     execute(deps, env.0, env.1, msg)
 }
 
+fn update_data_capsule(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+    capsule: &String,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::UpdateDataCapsule {
+        data_id: data_id.clone(),
+        capsule: capsule.clone(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn transfer_data_ownership(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+    new_delegator_pubkey: &String,
+    new_capsule: &String,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::TransferDataOwnership {
+        data_id: data_id.clone(),
+        new_delegator_pubkey: new_delegator_pubkey.clone(),
+        new_capsule: new_capsule.clone(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn accept_data_ownership(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::AcceptDataOwnership {
+        data_id: data_id.clone(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn cancel_data_ownership_transfer(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::CancelDataOwnershipTransfer {
+        data_id: data_id.clone(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn rotate_delegator_key(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    old_pubkey: &String,
+    new_pubkey: &String,
+    updated_capsules: &[DataCapsule],
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::RotateDelegatorKey {
+        old_pubkey: old_pubkey.clone(),
+        new_pubkey: new_pubkey.clone(),
+        updated_capsules: updated_capsules.to_vec(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
 fn add_delegation(
     deps: DepsMut,
     creator: &Addr,
@@ -350,6 +608,19 @@ fn resolve_timed_out_request(
     execute(deps, env.0, env.1, msg)
 }
 
+fn resolve_timed_out_requests(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    limit: Option<u32>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::ResolveTimedOutRequests { limit };
+
+    execute(deps, env.0, env.1, msg)
+}
+
 fn add_data_labels(
     deps: DepsMut,
     creator: &Addr,
@@ -396,366 +667,593 @@ fn add_delegatee_labels(
     let msg = ExecuteMsg::AddDelegateeLabels {
         delegatee_pubkey: delegatee_pubkey.clone(),
         delegatee_labels: delegatee_labels.to_vec(),
+        expires_at: None,
     };
 
     execute(deps, env.0, env.1, msg)
 }
 
-fn remove_delegatee_labels(
+fn set_delegatee_policy(
     deps: DepsMut,
     creator: &Addr,
     block_height: u64,
     delegatee_pubkey: &String,
-    delegatee_labels: &[String],
+    policy: &Option<LabelExpression>,
 ) -> StdResult<Response> {
     let env = mock_env_height(creator, block_height, &vec![]);
 
-    let msg = ExecuteMsg::RemoveDelegateeLabels {
+    let msg = ExecuteMsg::SetDelegateePolicy {
         delegatee_pubkey: delegatee_pubkey.clone(),
-        delegatee_labels: delegatee_labels.to_vec(),
+        policy: policy.clone(),
     };
 
     execute(deps, env.0, env.1, msg)
 }
 
-#[test]
-fn test_new_contract_default_values() {
-    let mut deps = mock_dependencies();
-    let creator = Addr::unchecked("creator".to_string());
-    let proxy = Addr::unchecked("proxy".to_string());
+fn grant_data_access(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+    delegatee_pubkey: &String,
+    expires_at: &Option<Expiration>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
 
-    let proxies: Vec<Addr> = vec![creator.clone(), proxy];
+    let msg = ExecuteMsg::GrantDataAccess {
+        data_id: data_id.clone(),
+        delegatee_pubkey: delegatee_pubkey.clone(),
+        expires_at: expires_at.clone(),
+    };
 
-    assert!(init_contract(
-        deps.as_mut(),
-        &creator,
-        DEFAULT_BLOCK_HEIGHT,
-        &None,
-        &None,
-        &Some(proxies),
-        &DEFAULT_STAKE_DENOM.to_string(),
-        &None,
-        &None,
-        &None,
-        &None,
-        &None,
-        &None,
-    )
-    .is_ok());
+    execute(deps, env.0, env.1, msg)
+}
 
-    let state: State = store_get_state(&deps.storage).unwrap();
-    let available_proxies = store_get_all_active_proxy_addresses(&deps.storage);
+fn deny_data_access(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+    delegatee_pubkey: &String,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
 
-    assert_eq!(available_proxies.len(), 0);
+    let msg = ExecuteMsg::DenyDataAccess {
+        data_id: data_id.clone(),
+        delegatee_pubkey: delegatee_pubkey.clone(),
+    };
 
-    assert_eq!(&state.admin, &creator);
-    assert_eq!(&state.threshold, &1u32);
-    assert_eq!(&state.next_proxy_task_id, &0u64);
-    assert_eq!(&state.next_delegation_id, &0u64);
+    execute(deps, env.0, env.1, msg)
 }
 
-#[test]
-fn test_new_contract_custom_values() {
-    let mut deps = mock_dependencies();
-    let creator = Addr::unchecked("creator".to_string());
-    let proxy = Addr::unchecked("proxy".to_string());
+fn revoke_data_access(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+    delegatee_pubkey: &String,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
 
-    let proxies: Vec<Addr> = vec![creator.clone(), proxy.clone()];
+    let msg = ExecuteMsg::RevokeDataAccess {
+        data_id: data_id.clone(),
+        delegatee_pubkey: delegatee_pubkey.clone(),
+    };
 
-    // Threshold cannot be zero
-    assert!(is_err(
-        init_contract(
-            deps.as_mut(),
-            &creator,
-            DEFAULT_BLOCK_HEIGHT,
-            &Some(0),
-            &Some(proxy.clone()),
-            &Some(proxies.clone()),
-            &DEFAULT_STAKE_DENOM.to_string(),
-            &None,
-            &None,
-            &None,
-            &None,
-            &None,
-            &None,
-        ),
-        "cannot be 0",
-    ));
+    execute(deps, env.0, env.1, msg)
+}
 
-    assert!(init_contract(
-        deps.as_mut(),
-        &creator,
-        DEFAULT_BLOCK_HEIGHT,
-        &Some(123),
-        &Some(proxy.clone()),
-        &Some(proxies),
-        &DEFAULT_STAKE_DENOM.to_string(),
-        &None,
-        &None,
-        &None,
-        &None,
-        &None,
-        &None,
-    )
-    .is_ok());
+fn request_access(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+    target: &AccessRequestTarget,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
 
-    let state: State = store_get_state(&deps.storage).unwrap();
-    let available_proxies = store_get_all_active_proxy_addresses(&deps.storage);
+    let msg = ExecuteMsg::RequestAccess {
+        delegator_pubkey: delegator_pubkey.to_string(),
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+        target: target.clone(),
+        message: Some(String::from("Please")),
+    };
 
-    assert_eq!(available_proxies.len(), 0);
+    execute(deps, env.0, env.1, msg)
+}
 
-    assert_eq!(&state.admin, &proxy);
-    assert_eq!(&state.threshold, &123);
-    assert_eq!(&state.next_proxy_task_id, &0u64);
-    assert_eq!(&state.next_delegation_id, &0u64);
+fn approve_access_request(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    request_id: u64,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::ApproveAccessRequest { request_id };
+
+    execute(deps, env.0, env.1, msg)
 }
 
-#[test]
-fn test_add_remove_proxy() {
-    let mut deps = mock_dependencies();
-    let creator = Addr::unchecked("creator".to_string());
-    let admin = Addr::unchecked("admin".to_string());
-    let proxy = Addr::unchecked("proxy".to_string());
-    let proxy_pubkey = String::from("proxy_pubkey");
+fn reject_access_request(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    request_id: u64,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
 
-    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let proxy_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
-    }];
+    let msg = ExecuteMsg::RejectAccessRequest { request_id };
 
-    assert!(init_contract(
-        deps.as_mut(),
-        &creator,
-        DEFAULT_BLOCK_HEIGHT,
-        &None,
-        &Some(admin.clone()),
-        &None,
-        &stake_denom,
-        &None,
-        &None,
-        &None,
-        &None,
-        &None,
-        &None,
+    execute(deps, env.0, env.1, msg)
+}
+
+fn get_pending_access_requests(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    block_height: u64,
+    delegator_pubkey: &str,
+) -> Vec<u64> {
+    let res: GetPendingAccessRequestsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env_height(&Addr::unchecked("anyone"), block_height, &vec![]).0,
+            QueryMsg::GetPendingAccessRequests {
+                delegator_pubkey: delegator_pubkey.to_string(),
+            },
+        )
+        .unwrap(),
     )
-    .is_ok());
+    .unwrap();
 
-    // Only admin can add proxies
-    assert!(is_err(
-        add_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &proxy),
-        "Only admin",
-    ));
-    assert!(add_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy).is_ok());
+    res.requests
+        .iter()
+        .map(|request| request.request_id)
+        .collect()
+}
 
-    // Already added
-    assert!(is_err(
-        add_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy),
-        "already proxy",
-    ));
+fn register_delegatee(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegatee_pubkey: &str,
+    authorised_requesters: Vec<Addr>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
 
-    // Only admin can remove proxies
-    assert!(is_err(
-        remove_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &proxy),
-        "Only admin",
-    ));
+    let msg = ExecuteMsg::RegisterDelegatee {
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+        authorised_requesters,
+    };
 
-    let remove_proxy_response =
-        remove_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy).unwrap();
-    // When stake is 0 no BankMsg is created
-    assert_eq!(remove_proxy_response.messages.len(), 0);
+    execute(deps, env.0, env.1, msg)
+}
 
-    // Already removed
-    assert!(is_err(
-        remove_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy),
-        "not a proxy",
-    ));
+fn override_delegatee_registration(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+    delegatee_addr: &Addr,
+    authorised_requesters: Vec<Addr>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
 
-    assert!(add_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy).is_ok());
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
+    let msg = ExecuteMsg::OverrideDelegateeRegistration {
+        delegator_pubkey: delegator_pubkey.to_string(),
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+        delegatee_addr: delegatee_addr.clone(),
+        authorised_requesters,
+    };
 
-    // Check if stake gets returned to proxy
-    let remove_proxy_response =
-        remove_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy).unwrap();
-    assert_eq!(
-        remove_proxy_response.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT,
-                stake_denom.as_str(),
-            )],
-        })
-    );
+    execute(deps, env.0, env.1, msg)
 }
 
-#[test]
-fn test_register_unregister_proxy_whitelisting() {
-    let mut deps = mock_dependencies();
-    let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy1".to_string());
-    let proxy2 = Addr::unchecked("proxy2".to_string());
+fn add_authorised_requesters(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegatee_pubkey: &str,
+    requesters: Vec<Addr>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
 
-    let proxy_pubkey: String = String::from("proxy_pubkey");
+    let msg = ExecuteMsg::AddAuthorisedRequesters {
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+        requesters,
+    };
 
-    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone()];
+    execute(deps, env.0, env.1, msg)
+}
 
-    // Staking
-    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let proxy_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
-    }];
-    let insufficient_proxy_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 1),
-    }];
+fn remove_authorised_requesters(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegatee_pubkey: &str,
+    requesters: Vec<Addr>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
 
-    assert!(init_contract(
-        deps.as_mut(),
-        &creator,
-        DEFAULT_BLOCK_HEIGHT,
-        &None,
-        &None,
-        &Some(proxies),
-        &stake_denom,
-        &None,
-        &None,
-        &None,
-        &None,
-        &Some(true),
-        &None,
-    )
-    .is_ok());
+    let msg = ExecuteMsg::RemoveAuthorisedRequesters {
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+        requesters,
+    };
 
-    assert_eq!(store_get_all_active_proxy_addresses(&deps.storage).len(), 0);
+    execute(deps, env.0, env.1, msg)
+}
 
-    // Check proxy state
-    assert!(!store_get_is_proxy_active(deps.as_mut().storage, &proxy1));
-    assert!(get_proxy_tasks(deps.as_mut().storage, &proxy1, &0)
-        .unwrap()
-        .is_empty());
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1).unwrap();
-    assert_eq!(proxy.state, ProxyState::Authorised);
-    assert!(proxy.proxy_pubkey.is_none());
+fn fund_delegatee_allowance(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegatee_pubkey: &str,
+    spend_limit: &Option<Uint128>,
+    expires: &Option<Expiration>,
+    coins: &Vec<Coin>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, coins);
 
-    // Only proxy can add pubkeys
-    assert!(is_err(
-        register_proxy(
-            deps.as_mut(),
-            &creator,
-            DEFAULT_BLOCK_HEIGHT,
-            &proxy_pubkey,
-            &proxy_stake,
-        ),
-        "not a proxy",
-    ));
+    let msg = ExecuteMsg::FundDelegateeAllowance {
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+        spend_limit: *spend_limit,
+        expires: expires.clone(),
+    };
 
-    // Insufficient stake amount
-    assert!(is_err(
-        register_proxy(
-            deps.as_mut(),
-            &proxy1,
-            DEFAULT_BLOCK_HEIGHT,
-            &proxy_pubkey,
-            &insufficient_proxy_stake,
-        ),
-        "Requires at least 1000 atestfet",
-    ));
+    execute(deps, env.0, env.1, msg)
+}
 
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy1,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy_pubkey,
-        &proxy_stake,
+fn withdraw_delegatee_allowance(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegatee_pubkey: &str,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::WithdrawDelegateeAllowance {
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn get_allowance_balance(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+) -> Option<u128> {
+    let res: GetDelegateeAllowanceResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDelegateeAllowance {
+                delegator_addr: delegator_addr.clone(),
+                delegatee_pubkey: delegatee_pubkey.to_string(),
+            },
+        )
+        .unwrap(),
     )
-    .is_ok());
-    // Already registered
-    assert!(is_err(
-        register_proxy(
-            deps.as_mut(),
-            &proxy1,
-            DEFAULT_BLOCK_HEIGHT,
-            &proxy_pubkey,
-            &proxy_stake,
-        ),
-        "already registered",
-    ));
+    .unwrap();
 
-    // Check proxy state
-    assert!(store_get_is_proxy_active(deps.as_mut().storage, &proxy1));
-    assert!(
-        get_proxy_tasks(deps.as_mut().storage, &proxy1, &DEFAULT_BLOCK_HEIGHT)
-            .unwrap()
-            .is_empty()
-    );
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1).unwrap();
-    assert_eq!(proxy.state, ProxyState::Registered);
-    assert_eq!(proxy.proxy_pubkey.unwrap(), proxy_pubkey);
+    res.allowance.map(|allowance| allowance.balance.u128())
+}
 
-    let available_proxy_addresses = store_get_all_active_proxy_addresses(&deps.storage);
-    assert_eq!(available_proxy_addresses.len(), 1);
-    assert_eq!(&available_proxy_addresses, &[proxy1.clone()]);
+fn subscribe(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+    labels: &[&str],
+    budget: u128,
+    coins: &Vec<Coin>,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, coins);
 
-    // Number of available pubkeys remains the same
-    let available_proxy_addresses = store_get_all_active_proxy_addresses(&deps.storage);
-    assert_eq!(available_proxy_addresses.len(), 1);
-    assert_eq!(&available_proxy_addresses, &[proxy1.clone()]);
+    let msg = ExecuteMsg::Subscribe {
+        delegator_pubkey: delegator_pubkey.to_string(),
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+        labels: labels.iter().map(|label| label.to_string()).collect(),
+        budget: Uint128::new(budget),
+    };
 
-    // Only proxy can remove pubkeys
-    assert!(is_err(
-        unregister_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT),
-        "not a proxy",
-    ));
+    execute(deps, env.0, env.1, msg)
+}
 
-    // Check if stake gets returned to proxy
-    let unregister_response =
-        unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(
-        unregister_response.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy1.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT,
-                stake_denom.as_str(),
-            )],
+fn unsubscribe(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::Unsubscribe {
+        delegator_pubkey: delegator_pubkey.to_string(),
+        delegatee_pubkey: delegatee_pubkey.to_string(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn get_subscription_budget(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    delegator_pubkey: &str,
+    delegatee_pubkey: &str,
+) -> Option<u128> {
+    let res: GetSubscriptionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetSubscription {
+                delegator_pubkey: delegator_pubkey.to_string(),
+                delegatee_pubkey: delegatee_pubkey.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    res.subscription
+        .map(|subscription| subscription.budget.u128())
+}
+
+fn cancel_reencryption_request(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    data_id: &String,
+    delegatee_pubkey: &String,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::CancelReencryptionRequest {
+        data_id: data_id.clone(),
+        delegatee_pubkey: delegatee_pubkey.clone(),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn get_reencryption_requests(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    block_height: u64,
+    data_id: &str,
+    delegatee_pubkey: &str,
+) -> Vec<(u64, ReencryptionRequestState, usize)> {
+    let res: GetReencryptionRequestsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env_height(&Addr::unchecked("anyone"), block_height, &vec![]).0,
+            QueryMsg::GetReencryptionRequests {
+                data_id: data_id.to_string(),
+                delegatee_pubkey: delegatee_pubkey.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    res.requests
+        .into_iter()
+        .map(|request| {
+            (
+                request.request_seq,
+                request.reencryption_request_state,
+                request.fragments.len(),
+            )
         })
-    );
+        .collect()
+}
 
-    // Already unregistered
-    assert!(is_err(
-        unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT),
-        "already unregistered",
-    ));
+fn get_proxy_tasks_page(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    block_height: u64,
+    proxy_addr: &Addr,
+    start_after: Option<ProxyTasksCursor>,
+    limit: Option<u32>,
+) -> (Vec<String>, Option<ProxyTasksCursor>) {
+    let res: GetProxyTasksResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env_height(proxy_addr, block_height, &vec![]).0,
+            QueryMsg::GetProxyTasks {
+                proxy_addr: proxy_addr.clone(),
+                start_after,
+                limit,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
 
-    // All proxies unregistered
-    assert_eq!(store_get_all_active_proxy_addresses(&deps.storage).len(), 0);
+    (
+        res.proxy_tasks
+            .into_iter()
+            .map(|proxy_task| proxy_task.data_id)
+            .collect(),
+        res.cursor,
+    )
+}
+
+fn get_delegatee_requests(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    block_height: u64,
+    delegatee_pubkey: &str,
+    state_filter: Option<ReencryptionRequestState>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> GetDelegateeRequestsResponse {
+    from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env_height(&Addr::unchecked("anyone"), block_height, &vec![]).0,
+            QueryMsg::GetDelegateeRequests {
+                delegatee_pubkey: delegatee_pubkey.to_string(),
+                state_filter,
+                start_after,
+                limit,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn list_proxies(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    state_filter: Option<ProxyState>,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+) -> Vec<Addr> {
+    let res: ListProxiesResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListProxies {
+                state_filter,
+                start_after,
+                limit,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    res.proxies
+        .into_iter()
+        .map(|proxy| proxy.proxy_addr)
+        .collect()
+}
+
+fn list_tasks_by_proxy(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    proxy_addr: &Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Vec<(u64, String)> {
+    let res: ListTasksByProxyResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListTasksByProxy {
+                proxy_addr: proxy_addr.clone(),
+                start_after,
+                limit,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    res.tasks
+        .into_iter()
+        .map(|task| (task.task_id, task.proxy_task.data_id))
+        .collect()
+}
+
+fn get_contract_stats(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+) -> GetContractStatsResponse {
+    from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetContractStats {}).unwrap()).unwrap()
+}
+
+fn simulate_reencryption_request(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    block_height: u64,
+    data_id: &str,
+    delegatee_pubkey: &str,
+    requester: &Addr,
+    funds: &Option<Coin>,
+) -> SimulateReencryptionRequestResponse {
+    let mut env = mock_env();
+    env.block.height = block_height;
+
+    from_binary(
+        &query(
+            deps.as_ref(),
+            env,
+            QueryMsg::SimulateReencryptionRequest {
+                data_id: data_id.to_string(),
+                delegatee_pubkey: delegatee_pubkey.to_string(),
+                requester: requester.clone(),
+                funds: funds.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+fn check_permission(
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    delegator_addr: &Addr,
+    delegatee_pubkey: &str,
+    data_id: &str,
+) -> bool {
+    let res: CheckPermissionResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CheckPermission {
+                delegator_addr: delegator_addr.clone(),
+                delegatee_pubkey: delegatee_pubkey.to_string(),
+                data_id: data_id.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+
+    res.permitted
+}
+
+fn add_delegatee_labels_with_expiration(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegatee_pubkey: &String,
+    delegatee_labels: &[String],
+    expires_at: &Expiration,
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::AddDelegateeLabels {
+        delegatee_pubkey: delegatee_pubkey.clone(),
+        delegatee_labels: delegatee_labels.to_vec(),
+        expires_at: Some(expires_at.clone()),
+    };
+
+    execute(deps, env.0, env.1, msg)
+}
+
+fn remove_delegatee_labels(
+    deps: DepsMut,
+    creator: &Addr,
+    block_height: u64,
+    delegatee_pubkey: &String,
+    delegatee_labels: &[String],
+) -> StdResult<Response> {
+    let env = mock_env_height(creator, block_height, &vec![]);
+
+    let msg = ExecuteMsg::RemoveDelegateeLabels {
+        delegatee_pubkey: delegatee_pubkey.clone(),
+        delegatee_labels: delegatee_labels.to_vec(),
+    };
+
+    execute(deps, env.0, env.1, msg)
 }
 
 #[test]
-fn test_register_unregister_proxy_no_whitelisting() {
-    // Test register, unregister and deactivate behaviour
+fn test_new_contract_default_values() {
     let mut deps = mock_dependencies();
     let creator = Addr::unchecked("creator".to_string());
-    let proxy = Addr::unchecked("proxy1".to_string());
-
-    let proxy_pubkey: String = String::from("proxy_pubkey");
-    let proxy_pubkey2: String = String::from("proxy_pubkey2");
+    let proxy = Addr::unchecked("proxy".to_string());
 
-    // Staking
-    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let proxy_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
-    }];
+    let proxies: Vec<Addr> = vec![creator.clone(), proxy];
 
     assert!(init_contract(
         deps.as_mut(),
@@ -763,116 +1261,106 @@ fn test_register_unregister_proxy_no_whitelisting() {
         DEFAULT_BLOCK_HEIGHT,
         &None,
         &None,
+        &Some(proxies),
+        &DEFAULT_STAKE_DENOM.to_string(),
         &None,
-        &stake_denom,
         &None,
         &None,
         &None,
         &None,
-        &Some(false),
         &None,
     )
     .is_ok());
 
-    assert!(is_err(
-        deactivate_proxy(deps.as_mut(), &proxy, DEFAULT_BLOCK_HEIGHT),
-        "Sender is not a proxy",
-    ));
+    let state: State = store_get_state(&deps.storage).unwrap();
+    let available_proxies = store_get_all_active_proxy_addresses(&deps.storage).unwrap();
 
-    // Test if proxy can register when whitelisting is off
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
+    assert_eq!(available_proxies.len(), 0);
 
-    // Already registered
+    assert_eq!(&state.admin, &creator);
+    assert_eq!(&state.threshold, &1u32);
+    assert_eq!(&state.next_proxy_task_id, &0u64);
+    assert_eq!(&state.next_delegation_id, &0u64);
+}
+
+#[test]
+fn test_new_contract_custom_values() {
+    let mut deps = mock_dependencies();
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy = Addr::unchecked("proxy".to_string());
+
+    let proxies: Vec<Addr> = vec![creator.clone(), proxy.clone()];
+
+    // Threshold cannot be zero
     assert!(is_err(
-        register_proxy(
+        init_contract(
             deps.as_mut(),
-            &proxy,
+            &creator,
             DEFAULT_BLOCK_HEIGHT,
-            &proxy_pubkey,
-            &proxy_stake,
+            &Some(0),
+            &Some(proxy.clone()),
+            &Some(proxies.clone()),
+            &DEFAULT_STAKE_DENOM.to_string(),
+            &None,
+            &None,
+            &None,
+            &None,
+            &None,
+            &None,
         ),
-        "already registered",
-    ));
-
-    assert!(deactivate_proxy(deps.as_mut(), &proxy, DEFAULT_BLOCK_HEIGHT).is_ok());
-
-    assert!(is_err(
-        deactivate_proxy(deps.as_mut(), &proxy, DEFAULT_BLOCK_HEIGHT),
-        "Proxy already deactivated",
-    ));
-
-    let proxy_entry = store_get_proxy_entry(deps.as_mut().storage, &proxy).unwrap();
-    assert_eq!(
-        proxy_entry.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
-    );
-
-    // Can't re-activate when providing different pubkey
-    assert!(is_err(
-        register_proxy(
-            deps.as_mut(),
-            &proxy,
-            DEFAULT_BLOCK_HEIGHT,
-            &proxy_pubkey2,
-            &proxy_stake,
-        ),
-        "Proxy need to be unregistered to use a different public key",
+        "cannot be 0",
     ));
 
-    // Re-register/activate proxy
-    assert!(register_proxy(
+    assert!(init_contract(
         deps.as_mut(),
-        &proxy,
+        &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy_pubkey,
-        &proxy_stake,
+        &Some(123),
+        &Some(proxy.clone()),
+        &Some(proxies),
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
     )
     .is_ok());
 
-    let proxy_entry = store_get_proxy_entry(deps.as_mut().storage, &proxy).unwrap();
-    // Provided stake is added
-    assert_eq!(
-        proxy_entry.stake_amount.u128(),
-        2 * DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
-    );
+    let state: State = store_get_state(&deps.storage).unwrap();
+    let available_proxies = store_get_all_active_proxy_addresses(&deps.storage).unwrap();
+
+    assert_eq!(available_proxies.len(), 0);
+
+    assert_eq!(&state.admin, &proxy);
+    assert_eq!(&state.threshold, &123);
+    assert_eq!(&state.next_proxy_task_id, &0u64);
+    assert_eq!(&state.next_delegation_id, &0u64);
 }
 
 #[test]
-fn test_add_data() {
+fn test_add_remove_proxy() {
     let mut deps = mock_dependencies();
-
-    // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let delegator1 = Addr::unchecked("delegator1".to_string());
-    let delegator2 = Addr::unchecked("delegator2".to_string());
-
-    // Pubkeys
-    let data_id1 = String::from("DATA1");
-    let data_id2 = String::from("DATA2");
-
-    let capsule = String::from("capsule");
+    let admin = Addr::unchecked("admin".to_string());
+    let proxy = Addr::unchecked("proxy".to_string());
+    let proxy_pubkey = String::from("proxy_pubkey");
 
-    let data_entry = DataEntry {
-        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-        capsule: capsule.clone(),
-    };
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let proxy_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
 
-    /*************** Initialise *************/
     assert!(init_contract(
         deps.as_mut(),
         &creator,
         DEFAULT_BLOCK_HEIGHT,
         &None,
+        &Some(admin.clone()),
         &None,
-        &None,
-        &DEFAULT_STAKE_DENOM.to_string(),
+        &stake_denom,
         &None,
         &None,
         &None,
@@ -882,364 +1370,718 @@ fn test_add_data() {
     )
     .is_ok());
 
-    /*************** Add data and delegations by delegator *************/
-    // Add data by delegator
-    assert!(add_data(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &data_entry.delegator_pubkey,
-        &capsule,
-        &None,
-    )
-    .is_ok());
+    // Only admin can add proxies
+    assert!(is_err(
+        add_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &proxy),
+        "Only admin",
+    ));
+    assert!(add_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy).is_ok());
 
-    // Data already added
+    // Already added
     assert!(is_err(
-        add_data(
-            deps.as_mut(),
-            &delegator1,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
-            &data_entry.delegator_pubkey,
-            &capsule,
-            &None,
-        ),
-        "already exist",
+        add_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy),
+        "already proxy",
     ));
 
-    assert_eq!(
-        &store_get_data_entry(deps.as_mut().storage, &data_id1).unwrap(),
-        &data_entry
-    );
-    assert_eq!(
-        store_get_delegator_address(deps.as_mut().storage, &DELEGATOR1_PUBKEY.to_string()).unwrap(),
-        delegator1
-    );
+    // Only admin can remove proxies
+    assert!(is_err(
+        remove_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &proxy),
+        "Only admin",
+    ));
 
-    // Delgator2 cannot use delegator1 pubkey
+    let remove_proxy_response =
+        remove_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy).unwrap();
+    // When stake is 0 no BankMsg is created
+    assert_eq!(remove_proxy_response.messages.len(), 0);
+
+    // Already removed
     assert!(is_err(
-        add_data(
-            deps.as_mut(),
-            &delegator2,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id2,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &capsule,
-            &None,
-        ),
-        "already registered with this pubkey",
+        remove_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy),
+        "not a proxy",
     ));
+
+    assert!(add_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy).is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    // Check if stake gets returned to proxy
+    let remove_proxy_response =
+        remove_proxy(deps.as_mut(), &admin, DEFAULT_BLOCK_HEIGHT, &proxy).unwrap();
+    assert_eq!(
+        remove_proxy_response.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy.to_string(),
+            amount: vec![Coin::new(
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT,
+                stake_denom.as_str(),
+            )],
+        })
+    );
 }
 
 #[test]
-fn test_remove_data() {
+fn test_register_unregister_proxy_whitelisting() {
     let mut deps = mock_dependencies();
-
-    // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let delegator1 = Addr::unchecked("delegator1".to_string());
-    let delegator2 = Addr::unchecked("delegator2".to_string());
+    let proxy1 = Addr::unchecked("proxy1".to_string());
+    let proxy2 = Addr::unchecked("proxy2".to_string());
 
-    // Pubkeys
-    let data_id1 = String::from("DATA1");
+    let proxy_pubkey: String = String::from("proxy_pubkey");
 
-    let capsule = String::from("capsule");
+    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone()];
 
-    let data_entry = DataEntry {
-        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-        capsule: capsule.clone(),
-    };
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let proxy_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
+    let insufficient_proxy_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 1),
+    }];
 
-    /*************** Initialise *************/
     assert!(init_contract(
         deps.as_mut(),
         &creator,
         DEFAULT_BLOCK_HEIGHT,
         &None,
         &None,
-        &None,
-        &DEFAULT_STAKE_DENOM.to_string(),
-        &None,
-        &None,
+        &Some(proxies),
+        &stake_denom,
         &None,
         &None,
         &None,
         &None,
-    )
-    .is_ok());
-
-    /*************** Add data and delegations by delegator *************/
-    // Add data by delegator
-    assert!(add_data(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &data_entry.delegator_pubkey,
-        &capsule,
+        &Some(true),
         &None,
     )
     .is_ok());
 
     assert_eq!(
-        &store_get_data_entry(deps.as_mut().storage, &data_id1).unwrap(),
-        &data_entry
+        store_get_all_active_proxy_addresses(&deps.storage)
+            .unwrap()
+            .len(),
+        0
     );
-    assert_eq!(
-        store_get_delegator_address(deps.as_mut().storage, &DELEGATOR1_PUBKEY.to_string()).unwrap(),
-        delegator1
+
+    // Check proxy state
+    assert!(!store_get_is_proxy_active(deps.as_mut().storage, &proxy1));
+    assert!(
+        get_proxy_tasks(deps.as_mut().storage, &proxy1, &0, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .is_empty()
     );
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(proxy.state, ProxyState::Authorised);
+    assert!(proxy.proxy_pubkey.is_none());
 
+    // Only proxy can add pubkeys
     assert!(is_err(
-        remove_data(deps.as_mut(), &delegator2, DEFAULT_BLOCK_HEIGHT, &data_id1),
-        format!(
-            "Delegator {} already registered with this pubkey.",
-            delegator1
-        )
-        .as_str(),
+        register_proxy(
+            deps.as_mut(),
+            &creator,
+            DEFAULT_BLOCK_HEIGHT,
+            &proxy_pubkey,
+            &proxy_stake,
+        ),
+        "not a proxy",
     ));
 
-    assert!(remove_data(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &data_id1).is_ok());
-
-    assert_eq!(store_get_data_entry(deps.as_mut().storage, &data_id1), None,);
-}
-
-#[test]
-fn test_add_delegation_and_request_reencryption() {
-    let mut deps = mock_dependencies();
-
-    // Addresses
-    let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy1".to_string());
-    let proxy2 = Addr::unchecked("proxy2".to_string());
+    // Insufficient stake amount
+    assert!(is_err(
+        register_proxy(
+            deps.as_mut(),
+            &proxy1,
+            DEFAULT_BLOCK_HEIGHT,
+            &proxy_pubkey,
+            &insufficient_proxy_stake,
+        ),
+        "Requires at least 1000 atestfet",
+    ));
 
-    let delegator1 = Addr::unchecked("delegator1".to_string());
-    let delegatee1 = Addr::unchecked("delegatee1".to_string());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+    // Already registered
+    assert!(is_err(
+        register_proxy(
+            deps.as_mut(),
+            &proxy1,
+            DEFAULT_BLOCK_HEIGHT,
+            &proxy_pubkey,
+            &proxy_stake,
+        ),
+        "already registered",
+    ));
 
-    // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy1_pubkey");
-    let proxy2_pubkey: String = String::from("proxy2_pubkey");
+    // Check proxy state
+    assert!(store_get_is_proxy_active(deps.as_mut().storage, &proxy1));
+    assert!(get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy1,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None
+    )
+    .unwrap()
+    .proxy_tasks
+    .is_empty());
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(proxy.state, ProxyState::Registered);
+    assert_eq!(proxy.proxy_pubkey.unwrap(), proxy_pubkey);
 
-    let data_id = String::from("DATA");
-    let capsule = String::from("capsule");
+    let available_proxy_addresses = store_get_all_active_proxy_addresses(&deps.storage).unwrap();
+    assert_eq!(available_proxy_addresses.len(), 1);
+    assert_eq!(&available_proxy_addresses, &[proxy1.clone()]);
 
-    let data_entry = DataEntry {
-        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-        capsule: capsule.clone(),
-    };
+    // Number of available pubkeys remains the same
+    let available_proxy_addresses = store_get_all_active_proxy_addresses(&deps.storage).unwrap();
+    assert_eq!(available_proxy_addresses.len(), 1);
+    assert_eq!(&available_proxy_addresses, &[proxy1.clone()]);
+
+    // Only proxy can remove pubkeys
+    assert!(is_err(
+        unregister_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT),
+        "not a proxy",
+    ));
+
+    // Check if stake gets returned to proxy
+    let unregister_response =
+        unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
+    assert_eq!(
+        unregister_response.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy1.to_string(),
+            amount: vec![Coin::new(
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+
+    // Already unregistered
+    assert!(is_err(
+        unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT),
+        "already unregistered",
+    ));
+
+    // All proxies unregistered
+    assert_eq!(
+        store_get_all_active_proxy_addresses(&deps.storage)
+            .unwrap()
+            .len(),
+        0
+    );
+}
+
+#[test]
+fn test_register_unregister_proxy_no_whitelisting() {
+    // Test register, unregister and deactivate behaviour
+    let mut deps = mock_dependencies();
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy = Addr::unchecked("proxy1".to_string());
+
+    let proxy_pubkey: String = String::from("proxy_pubkey");
+    let proxy_pubkey2: String = String::from("proxy_pubkey2");
 
     // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
     let proxy_stake = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
+        denom: stake_denom.clone(),
         amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
     }];
-    let request_reward = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
-    }];
-    let insufficient_request_reward = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT - 1),
-    }];
-
-    let higher_request_reward = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(50 + DEFAULT_TASK_REWARD_AMOUNT * 2),
-    }];
 
-    /*************** Initialise *************/
     assert!(init_contract(
         deps.as_mut(),
         &creator,
         DEFAULT_BLOCK_HEIGHT,
         &None,
         &None,
-        &Some(vec![proxy1.clone(), proxy2.clone()]),
-        &DEFAULT_STAKE_DENOM.to_string(),
         &None,
+        &stake_denom,
         &None,
         &None,
         &None,
         &None,
+        &Some(false),
         &None,
     )
     .is_ok());
 
-    /*************** Register proxies *************/
-    // Proxies register -> submits pubkeys
+    assert!(is_err(
+        deactivate_proxy(deps.as_mut(), &proxy, DEFAULT_BLOCK_HEIGHT),
+        "Sender is not a proxy",
+    ));
+
+    // Test if proxy can register when whitelisting is off
     assert!(register_proxy(
         deps.as_mut(),
-        &proxy1,
+        &proxy,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy1_pubkey,
+        &proxy_pubkey,
         &proxy_stake,
     )
     .is_ok());
+
+    // Already registered
+    assert!(is_err(
+        register_proxy(
+            deps.as_mut(),
+            &proxy,
+            DEFAULT_BLOCK_HEIGHT,
+            &proxy_pubkey,
+            &proxy_stake,
+        ),
+        "already registered",
+    ));
+
+    assert!(deactivate_proxy(deps.as_mut(), &proxy, DEFAULT_BLOCK_HEIGHT).is_ok());
+
+    assert!(is_err(
+        deactivate_proxy(deps.as_mut(), &proxy, DEFAULT_BLOCK_HEIGHT),
+        "Proxy already deactivated",
+    ));
+
+    let proxy_entry = store_get_proxy_entry(deps.as_mut().storage, &proxy)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        proxy_entry.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+    );
+
+    // Can't re-activate when providing different pubkey
+    assert!(is_err(
+        register_proxy(
+            deps.as_mut(),
+            &proxy,
+            DEFAULT_BLOCK_HEIGHT,
+            &proxy_pubkey2,
+            &proxy_stake,
+        ),
+        "Proxy need to be unregistered to use a different public key",
+    ));
+
+    // Re-register/activate proxy
     assert!(register_proxy(
         deps.as_mut(),
-        &proxy2,
+        &proxy,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy2_pubkey,
+        &proxy_pubkey,
         &proxy_stake,
     )
     .is_ok());
 
+    let proxy_entry = store_get_proxy_entry(deps.as_mut().storage, &proxy)
+        .unwrap()
+        .unwrap();
+    // Provided stake is added
+    assert_eq!(
+        proxy_entry.stake_amount.u128(),
+        2 * DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+    );
+}
+
+#[test]
+fn test_add_data() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
+
+    // Pubkeys
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+
+    let capsule = String::from("capsule");
+
+    let data_entry = DataEntry {
+        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+        capsule: capsule.clone(),
+        expires_at: None,
+    };
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &None,
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
     /*************** Add data and delegations by delegator *************/
     // Add data by delegator
     assert!(add_data(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id,
+        &data_id1,
         &data_entry.delegator_pubkey,
         &capsule,
         &None,
     )
     .is_ok());
 
-    // Add delegation for proxy
-    let proxy1_delegation_string = String::from("DS_P1");
-
-    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
-        proxy_addr: proxy1.clone(),
-        delegation_string: proxy1_delegation_string,
-    }];
-
-    // Reencryption can't be requested yet
+    // Data already added
     assert!(is_err(
-        request_reencryption(
+        add_data(
             deps.as_mut(),
             &delegator1,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &request_reward,
+            &data_id1,
+            &data_entry.delegator_pubkey,
+            &capsule,
+            &None,
         ),
-        "ProxyDelegation doesn't exist",
+        "already exist",
     ));
 
-    // Add delegation
-    assert!(add_delegation(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy_delegations,
+    assert_eq!(
+        &store_get_data_entry(deps.as_mut().storage, &data_id1)
+            .unwrap()
+            .unwrap(),
+        &data_entry
+    );
+    assert_eq!(
+        store_get_delegator_address(deps.as_mut().storage, &DELEGATOR1_PUBKEY.to_string())
+            .unwrap()
+            .unwrap(),
+        delegator1
+    );
+
+    // Delgator2 cannot use delegator1 pubkey
+    assert!(is_err(
+        add_data(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id2,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &None,
+        ),
+        "already registered with this pubkey",
+    ));
+}
+
+#[test]
+fn test_add_data_batch() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
+
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+
+    let capsule = String::from("capsule");
+
+    let batch_entry = |data_id: &String, delegator_pubkey: &str, labels: &[&str]| DataBatchEntry {
+        data_id: data_id.clone(),
+        delegator_pubkey: delegator_pubkey.to_string(),
+        capsule: capsule.clone(),
+        tags: None,
+        data_labels: Some(labels.iter().map(|label| label.to_string()).collect()),
+        expires_at: None,
+    };
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &None,
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
         &None,
     )
     .is_ok());
 
-    // Cannot add same delegation twice
+    /*************** Add data batch *************/
+    // Empty batch
     assert!(is_err(
-        add_delegation(
+        add_data_batch(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &[]),
+        "No data entries provided.",
+    ));
+
+    // Duplicate data ID within batch
+    assert!(is_err(
+        add_data_batch(
             deps.as_mut(),
             &delegator1,
             DEFAULT_BLOCK_HEIGHT,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy_delegations,
-            &None,
+            &[
+                batch_entry(&data_id1, DELEGATOR1_PUBKEY, &["a"]),
+                batch_entry(&data_id2, DELEGATOR1_PUBKEY, &[]),
+                batch_entry(&data_id1, DELEGATOR1_PUBKEY, &["b"]),
+            ],
         ),
-        "Delegation already exists.",
+        "Duplicate data ID DATA1 in batch.",
     ));
+    assert!(store_get_data_entry(deps.as_ref().storage, &data_id1)
+        .unwrap()
+        .is_none());
 
-    // Insufficient stake amount
+    assert!(add_data_batch(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &[
+            batch_entry(&data_id1, DELEGATOR1_PUBKEY, &["a", "b"]),
+            batch_entry(&data_id2, DELEGATOR1_PUBKEY, &[]),
+        ],
+    )
+    .is_ok());
+
+    assert_eq!(
+        store_get_data_entry(deps.as_ref().storage, &data_id1)
+            .unwrap()
+            .unwrap(),
+        DataEntry {
+            delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+            capsule: capsule.clone(),
+            expires_at: None,
+        }
+    );
+    assert!(store_get_data_entry(deps.as_ref().storage, &data_id2)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_all_data_labels(deps.as_ref().storage, &data_id1),
+        vec!["a".to_string(), "b".to_string()]
+    );
+    assert!(store_get_all_data_labels(deps.as_ref().storage, &data_id2).is_empty());
+
+    // Data already in storage
     assert!(is_err(
-        request_reencryption(
+        add_data_batch(
             deps.as_mut(),
             &delegator1,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &insufficient_request_reward,
+            &[batch_entry(&data_id2, DELEGATOR1_PUBKEY, &[])],
         ),
-        "Requires at least 100 atestfet.",
+        "Entry with ID DATA2 already exist.",
     ));
 
-    // Only delegator can request re-encryption
+    // Delegator2 cannot use delegator1 pubkey
     assert!(is_err(
-        request_reencryption(
+        add_data_batch(
             deps.as_mut(),
-            &delegatee1,
+            &delegator2,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &higher_request_reward,
+            &[batch_entry(&data_id3, DELEGATOR1_PUBKEY, &[])],
         ),
-        "Reencryption is not permitted",
+        "already registered with this pubkey",
     ));
+}
 
-    // Reencryption can be requested only after add_delegation
-    // Delegatee can request reencryption
-    let res = request_reencryption(
+#[test]
+fn test_data_tags() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
+
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+
+    let capsule = String::from("capsule");
+
+    let content_type_tag = Tag {
+        key: "content_type".to_string(),
+        value: "text/csv".to_string(),
+    };
+    let size_tag = Tag {
+        key: "size".to_string(),
+        value: "1024".to_string(),
+    };
+
+    /*************** Initialise *************/
+    assert!(init_contract(
         deps.as_mut(),
-        &delegator1,
+        &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &higher_request_reward,
+        &None,
+        &None,
+        &None,
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    /*************** Add data with tags *************/
+    // Duplicate tag keys
+    assert!(is_err(
+        add_data_with_tags(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &[size_tag.clone(), size_tag.clone()],
+        ),
+        "Duplicate tag key size.",
+    ));
+
+    for data_id in [&data_id1, &data_id2, &data_id3] {
+        assert!(add_data_with_tags(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &[size_tag.clone(), content_type_tag.clone()],
+        )
+        .is_ok());
+    }
+
+    let res: GetDataIDResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDataID {
+                data_id: data_id1.clone(),
+            },
+        )
+        .unwrap(),
     )
     .unwrap();
+    // Tags are sorted by key
+    assert_eq!(res.tags, vec![content_type_tag.clone(), size_tag.clone()]);
 
-    // Check if given stake above required is returned back
+    /*************** Query data by tag *************/
     assert_eq!(
-        res.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: delegator1.to_string(),
-            amount: vec![Coin::new(150, DEFAULT_STAKE_DENOM)],
-        })
-    );
+        store_get_data_ids_by_tag(deps.as_ref().storage, "size", "1024", None, 10),
+        vec![data_id1.clone(), data_id2.clone(), data_id3.clone()]
+    );
+
+    let res: GetDataByTagResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDataByTag {
+                key: "size".to_string(),
+                value: "1024".to_string(),
+                start_after: Some(data_id1.clone()),
+                limit: Some(1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.data_ids, vec![data_id2.clone()]);
 
-    // Reencryption already requested
+    /*************** Update data tags *************/
+    // Only owner can update tags
     assert!(is_err(
-        request_reencryption(
+        update_data_tags(
             deps.as_mut(),
-            &delegator1,
+            &delegator2,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &request_reward,
+            &data_id2,
+            &[],
         ),
-        "Reencryption already requested",
+        "Sender is not a data owner.",
     ));
 
-    // Check if task was created
+    let new_size_tag = Tag {
+        key: "size".to_string(),
+        value: "2048".to_string(),
+    };
+    assert!(update_data_tags(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &[new_size_tag.clone()],
+    )
+    .is_ok());
+
     assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy1,
-        ),
-        Some(0u64)
+        store_get_all_data_tags(deps.as_ref().storage, &data_id2),
+        vec![new_size_tag]
+    );
+    assert_eq!(
+        store_get_data_ids_by_tag(deps.as_ref().storage, "size", "1024", None, 10),
+        vec![data_id1.clone(), data_id3.clone()]
+    );
+    assert_eq!(
+        store_get_data_ids_by_tag(deps.as_ref().storage, "size", "2048", None, 10),
+        vec![data_id2.clone()]
+    );
+    assert_eq!(
+        store_get_data_ids_by_tag(deps.as_ref().storage, "content_type", "text/csv", None, 10),
+        vec![data_id1.clone(), data_id3.clone()]
     );
 
+    /*************** Remove data *************/
+    assert!(remove_data(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &data_id1).is_ok());
+
+    assert!(store_get_all_data_tags(deps.as_ref().storage, &data_id1).is_empty());
     assert_eq!(
-        store_get_state(deps.as_mut().storage)
-            .unwrap()
-            .next_proxy_task_id,
-        1u64
+        store_get_data_ids_by_tag(deps.as_ref().storage, "size", "1024", None, 10),
+        vec![data_id3.clone()]
     );
 }
 
 #[test]
-fn test_remove_data_when_reencryption_requested() {
+fn test_remove_data() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy1".to_string());
-    let proxy2 = Addr::unchecked("proxy2".to_string());
-
     let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
 
     // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy1_pubkey");
-    let proxy2_pubkey: String = String::from("proxy2_pubkey");
-
     let data_id1 = String::from("DATA1");
 
     let capsule = String::from("capsule");
@@ -1247,13 +2089,9 @@ fn test_remove_data_when_reencryption_requested() {
     let data_entry = DataEntry {
         delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
         capsule: capsule.clone(),
+        expires_at: None,
     };
 
-    let higher_request_reward = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(50 + DEFAULT_TASK_REWARD_AMOUNT * 2),
-    }];
-
     /*************** Initialise *************/
     assert!(init_contract(
         deps.as_mut(),
@@ -1272,29 +2110,6 @@ fn test_remove_data_when_reencryption_requested() {
     )
     .is_ok());
 
-    /*************** Register proxies *************/
-    let proxy_stake = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
-    }];
-    // Proxies register -> submits pubkeys
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy1,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy1_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy2,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy2_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-
     /*************** Add data and delegations by delegator *************/
     // Add data by delegator
     assert!(add_data(
@@ -1309,110 +2124,37 @@ fn test_remove_data_when_reencryption_requested() {
     .is_ok());
 
     assert_eq!(
-        &store_get_data_entry(deps.as_mut().storage, &data_id1).unwrap(),
+        &store_get_data_entry(deps.as_mut().storage, &data_id1)
+            .unwrap()
+            .unwrap(),
         &data_entry
     );
     assert_eq!(
-        store_get_delegator_address(deps.as_mut().storage, &DELEGATOR1_PUBKEY.to_string()).unwrap(),
+        store_get_delegator_address(deps.as_mut().storage, &DELEGATOR1_PUBKEY.to_string())
+            .unwrap()
+            .unwrap(),
         delegator1
     );
 
-    // Add delegation for proxy
-    let proxy1_delegation_string = String::from("DS_P1");
-
-    let proxy_delegations: Vec<ProxyDelegationString> = vec![
-        ProxyDelegationString {
-            proxy_addr: proxy1.clone(),
-            delegation_string: proxy1_delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy2.clone(),
-            delegation_string: proxy1_delegation_string,
-        },
-    ];
-
-    // Add delegation
-    assert!(add_delegation(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy_delegations,
-        &None,
-    )
-    .is_ok());
-
-    // Reencryption can be requested only after add_delegation
-    assert!(request_reencryption(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &higher_request_reward,
-    )
-    .is_ok());
-
-    // Provide fragment
-    let proxy_fragment = String::from(FRAGMENT_P1_DR1_DE1);
-    assert!(provide_reencrypted_fragment(
-        deps.as_mut(),
-        &proxy2,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy_fragment,
-    )
-    .is_ok());
-
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy1.clone(),
-        ),
-        Some(0u64)
-    );
-
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy2.clone(),
-        ),
-        Some(1u64)
-    );
+    assert!(is_err(
+        remove_data(deps.as_mut(), &delegator2, DEFAULT_BLOCK_HEIGHT, &data_id1),
+        format!(
+            "Delegator {} already registered with this pubkey.",
+            delegator1
+        )
+        .as_str(),
+    ));
 
     assert!(remove_data(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &data_id1).is_ok());
 
-    assert_eq!(store_get_data_entry(deps.as_mut().storage, &data_id1), None,);
-
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy1,
-        ),
-        None
-    );
-
     assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy2,
-        ),
-        None
+        store_get_data_entry(deps.as_mut().storage, &data_id1).unwrap(),
+        None,
     );
 }
 
 #[test]
-fn test_add_delegation_and_then_data_with_diffent_proxy_same_pubkey() {
+fn test_add_delegation_and_request_reencryption() {
     let mut deps = mock_dependencies();
 
     // Addresses
@@ -1421,21 +2163,39 @@ fn test_add_delegation_and_then_data_with_diffent_proxy_same_pubkey() {
     let proxy2 = Addr::unchecked("proxy2".to_string());
 
     let delegator1 = Addr::unchecked("delegator1".to_string());
-    let delegator2 = Addr::unchecked("delegator2".to_string());
+    let delegatee1 = Addr::unchecked("delegatee1".to_string());
 
     // Pubkeys
     let proxy1_pubkey: String = String::from("proxy1_pubkey");
+    let proxy2_pubkey: String = String::from("proxy2_pubkey");
 
-    let data_id1 = String::from("DATA1");
-    let data_id2 = String::from("DATA2");
-
+    let data_id = String::from("DATA");
     let capsule = String::from("capsule");
 
+    let data_entry = DataEntry {
+        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+        capsule: capsule.clone(),
+        expires_at: None,
+    };
+
     // Staking
     let proxy_stake = vec![Coin {
         denom: DEFAULT_STAKE_DENOM.to_string(),
         amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
     }];
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
+    }];
+    let insufficient_request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT - 1),
+    }];
+
+    let higher_request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(50 + DEFAULT_TASK_REWARD_AMOUNT * 2),
+    }];
 
     /*************** Initialise *************/
     assert!(init_contract(
@@ -1444,7 +2204,7 @@ fn test_add_delegation_and_then_data_with_diffent_proxy_same_pubkey() {
         DEFAULT_BLOCK_HEIGHT,
         &None,
         &None,
-        &Some(vec![proxy1.clone(), proxy2]),
+        &Some(vec![proxy1.clone(), proxy2.clone()]),
         &DEFAULT_STAKE_DENOM.to_string(),
         &None,
         &None,
@@ -1465,15 +2225,49 @@ fn test_add_delegation_and_then_data_with_diffent_proxy_same_pubkey() {
         &proxy_stake,
     )
     .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    /*************** Add data and delegations by delegator *************/
+    // Add data by delegator
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id,
+        &data_entry.delegator_pubkey,
+        &capsule,
+        &None,
+    )
+    .is_ok());
 
     // Add delegation for proxy
     let proxy1_delegation_string = String::from("DS_P1");
 
     let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
-        proxy_addr: proxy1,
+        proxy_addr: proxy1.clone(),
         delegation_string: proxy1_delegation_string,
     }];
 
+    // Reencryption can't be requested yet
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "ProxyDelegation doesn't exist",
+    ));
+
     // Add delegation
     assert!(add_delegation(
         deps.as_mut(),
@@ -1486,34 +2280,102 @@ fn test_add_delegation_and_then_data_with_diffent_proxy_same_pubkey() {
     )
     .is_ok());
 
-    // Add data by delegator2 with already used delegator1_pubkey is prevented
+    // Cannot add same delegation twice
     assert!(is_err(
-        add_data(
+        add_delegation(
             deps.as_mut(),
-            &delegator2,
+            &delegator1,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
             &DELEGATOR1_PUBKEY.to_string(),
-            &capsule,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy_delegations,
             &None,
         ),
-        "Delegator delegator1 already registered with this pubkey.",
+        "Delegation already exists.",
     ));
 
-    assert!(add_data(
+    // Insufficient stake amount
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &insufficient_request_reward,
+        ),
+        "Requires at least 100 atestfet.",
+    ));
+
+    // Only delegator can request re-encryption
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &higher_request_reward,
+        ),
+        "Reencryption is not permitted",
+    ));
+
+    // Reencryption can be requested only after add_delegation
+    // Delegatee can request reencryption
+    let res = request_reencryption(
         deps.as_mut(),
-        &delegator2,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id2,
-        &DELEGATOR2_PUBKEY.to_string(),
-        &capsule,
-        &None,
+        &data_id,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &higher_request_reward,
     )
-    .is_ok());
+    .unwrap();
+
+    // Check if given stake above required is returned back
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(150, DEFAULT_STAKE_DENOM)],
+        })
+    );
+
+    // Reencryption already requested
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "Reencryption already requested",
+    ));
+
+    // Check if task was created
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy1,
+        )
+        .unwrap(),
+        Some(0u64)
+    );
+
+    assert_eq!(
+        store_get_state(deps.as_mut().storage)
+            .unwrap()
+            .next_proxy_task_id,
+        1u64
+    );
 }
 
 #[test]
-fn test_add_delegation_edge_cases() {
+fn test_remove_data_when_reencryption_requested() {
     let mut deps = mock_dependencies();
 
     // Addresses
@@ -1525,14 +2387,21 @@ fn test_add_delegation_edge_cases() {
 
     // Pubkeys
     let proxy1_pubkey: String = String::from("proxy1_pubkey");
+    let proxy2_pubkey: String = String::from("proxy2_pubkey");
 
-    let proxy1_delegation_string = String::from("DS_P1");
-    let proxy2_delegation_string = String::from("DS_P2");
+    let data_id1 = String::from("DATA1");
 
-    // Staking
-    let proxy_stake = vec![Coin {
+    let capsule = String::from("capsule");
+
+    let data_entry = DataEntry {
+        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+        capsule: capsule.clone(),
+        expires_at: None,
+    };
+
+    let higher_request_reward = vec![Coin {
         denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+        amount: Uint128::new(50 + DEFAULT_TASK_REWARD_AMOUNT * 2),
     }];
 
     /*************** Initialise *************/
@@ -1542,18 +2411,22 @@ fn test_add_delegation_edge_cases() {
         DEFAULT_BLOCK_HEIGHT,
         &None,
         &None,
-        &Some(vec![proxy1.clone(), proxy2.clone()]),
+        &None,
         &DEFAULT_STAKE_DENOM.to_string(),
         &None,
         &None,
         &None,
         &None,
-        &Some(false),
+        &None,
         &None,
     )
     .is_ok());
 
     /*************** Register proxies *************/
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
     // Proxies register -> submits pubkeys
     assert!(register_proxy(
         deps.as_mut(),
@@ -1563,91 +2436,168 @@ fn test_add_delegation_edge_cases() {
         &proxy_stake,
     )
     .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
 
-    // Try adding delegation for proxy 2 which is not registered
-    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
-        proxy_addr: proxy2,
-        delegation_string: proxy2_delegation_string,
-    }];
+    /*************** Add data and delegations by delegator *************/
+    // Add data by delegator
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &data_entry.delegator_pubkey,
+        &capsule,
+        &None,
+    )
+    .is_ok());
 
-    assert!(is_err(
-        add_delegation(
-            deps.as_mut(),
-            &delegator1,
-            DEFAULT_BLOCK_HEIGHT,
-            &DELEGATOR1_PUBKEY.to_string(),
+    assert_eq!(
+        &store_get_data_entry(deps.as_mut().storage, &data_id1)
+            .unwrap()
+            .unwrap(),
+        &data_entry
+    );
+    assert_eq!(
+        store_get_delegator_address(deps.as_mut().storage, &DELEGATOR1_PUBKEY.to_string())
+            .unwrap()
+            .unwrap(),
+        delegator1
+    );
+
+    // Add delegation for proxy
+    let proxy1_delegation_string = String::from("DS_P1");
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: proxy1_delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: proxy1_delegation_string,
+        },
+    ];
+
+    // Add delegation
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+
+    // Reencryption can be requested only after add_delegation
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &higher_request_reward,
+    )
+    .is_ok());
+
+    // Provide fragment
+    let proxy_fragment = String::from(FRAGMENT_P1_DR1_DE1);
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_fragment,
+    )
+    .is_ok());
+
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
             &DELEGATEE1_PUBKEY.to_string(),
-            &proxy_delegations,
-            &None,
-        ),
-        "Unregistered proxy with address proxy2",
-    ));
+            &proxy1.clone(),
+        )
+        .unwrap(),
+        Some(0u64)
+    );
 
-    // Cannot add same pubkey twice
-    let proxy_1_delegation = ProxyDelegationString {
-        proxy_addr: proxy1,
-        delegation_string: proxy1_delegation_string,
-    };
-    let proxy_delegations: Vec<ProxyDelegationString> =
-        vec![proxy_1_delegation.clone(), proxy_1_delegation];
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy2.clone(),
+        )
+        .unwrap(),
+        Some(1u64)
+    );
 
-    assert!(is_err(
-        add_delegation(
-            deps.as_mut(),
-            &delegator1,
-            DEFAULT_BLOCK_HEIGHT,
-            &DELEGATOR1_PUBKEY.to_string(),
+    assert!(remove_data(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &data_id1).is_ok());
+
+    assert_eq!(
+        store_get_data_entry(deps.as_mut().storage, &data_id1).unwrap(),
+        None,
+    );
+
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
             &DELEGATEE1_PUBKEY.to_string(),
-            &proxy_delegations,
-            &None,
-        ),
-        "Delegation string was already provided for proxy proxy1",
-    ));
+            &proxy1,
+        )
+        .unwrap(),
+        None
+    );
 
-    // Cannot add delegation for less than minimum number of proxies
-    assert!(is_err(
-        add_delegation(
-            deps.as_mut(),
-            &delegator1,
-            DEFAULT_BLOCK_HEIGHT,
-            &DELEGATOR2_PUBKEY.to_string(),
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
             &DELEGATEE1_PUBKEY.to_string(),
-            &[],
-            &None,
-        ),
-        "Required at least 1 proxies.",
-    ));
+            &proxy2,
+        )
+        .unwrap(),
+        None
+    );
 }
 
 #[test]
-fn test_provide_reencrypted_fragment() {
+fn test_update_data_capsule() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let proxy = Addr::unchecked("proxy".to_string());
+    let proxy1 = Addr::unchecked("proxy1".to_string());
+    let proxy2 = Addr::unchecked("proxy2".to_string());
 
-    let delegator = Addr::unchecked("delegator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
 
     // Pubkeys
-    let delegator_pubkey = String::from(DELEGATOR1_PUBKEY);
-    let delegatee_pubkey = String::from(DELEGATEE1_PUBKEY);
-    let other_delegatee_pubkey: String = String::from("DEK2");
-    let proxy_pubkey: String = String::from("proxy_pubkey");
+    let proxy1_pubkey: String = String::from("proxy1_pubkey");
+    let proxy2_pubkey: String = String::from("proxy2_pubkey");
 
-    let data_id = String::from("DATA");
-    let capsule = String::from(CAPSULE);
+    let data_id1 = String::from("DATA1");
+    let data_labels = vec![String::from("label1")];
+    let tags = vec![Tag {
+        key: String::from("type"),
+        value: String::from("image"),
+    }];
 
-    let data_entry = DataEntry {
-        capsule: capsule.clone(),
-        delegator_pubkey: delegator_pubkey.clone(),
-    };
+    let capsule = String::from("capsule");
+    let new_capsule = String::from("new_capsule");
 
-    // Staking
-    let proxy_stake = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
-    }];
     let request_reward = vec![Coin {
         denom: DEFAULT_STAKE_DENOM.to_string(),
         amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
@@ -1660,7 +2610,7 @@ fn test_provide_reencrypted_fragment() {
         DEFAULT_BLOCK_HEIGHT,
         &None,
         &None,
-        &Some(vec![proxy.clone()]),
+        &None,
         &DEFAULT_STAKE_DENOM.to_string(),
         &None,
         &None,
@@ -1672,174 +2622,217 @@ fn test_provide_reencrypted_fragment() {
     .is_ok());
 
     /*************** Register proxies *************/
-    // Proxies register -> submits pubkeys
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
     assert!(register_proxy(
         deps.as_mut(),
-        &proxy,
+        &proxy1,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy_pubkey,
+        &proxy1_pubkey,
         &proxy_stake,
     )
     .is_ok());
-
-    /*************** Add data and delegations by delegator *************/
-    // Add data by delegator
-    assert!(add_data(
+    assert!(register_proxy(
         deps.as_mut(),
-        &delegator,
+        &proxy2,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id,
-        &data_entry.delegator_pubkey,
-        &capsule,
-        &None,
+        &proxy2_pubkey,
+        &proxy_stake,
     )
     .is_ok());
 
-    // Add delegation for proxy
-    let proxy_delegation_string = String::from("DS_P1");
-    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
-        proxy_addr: proxy.clone(),
-        delegation_string: proxy_delegation_string,
-    }];
-
-    assert!(add_delegation(
+    /*************** Add data, delegation and request *************/
+    assert!(add_data_with_tags(
         deps.as_mut(),
-        &delegator,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &delegator_pubkey,
-        &delegatee_pubkey,
-        &proxy_delegations,
-        &None,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &tags,
+    )
+    .is_ok());
+    assert!(add_data_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &data_labels,
+    )
+    .is_ok());
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: String::from("DS_P1"),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: String::from("DS_P2"),
+        },
+    ];
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
     )
     .is_ok());
 
-    /*************** Request re-encryption *************/
     assert!(request_reencryption(
         deps.as_mut(),
-        &delegator,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id,
-        &delegatee_pubkey,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
         &request_reward,
     )
     .is_ok());
 
-    /*************** Provide reencrypted fragment *************/
-    assert_eq!(
-        store_get_delegatee_proxy_task(deps.as_mut().storage, &data_id, &delegatee_pubkey, &proxy)
-            .unwrap(),
-        0u64
-    );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy,
-        &0u64,
-    ));
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &String::from(FRAGMENT_P1_DR1_DE1),
+    )
+    .is_ok());
 
-    let proxy_fragment = String::from(FRAGMENT_P1_DR1_DE1);
-    // Provide unwanted fragment
+    /*************** Update capsule *************/
+    // Only owner can update capsule
     assert!(is_err(
-        provide_reencrypted_fragment(
+        update_data_capsule(
             deps.as_mut(),
-            &proxy,
+            &delegator2,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id,
-            &other_delegatee_pubkey,
-            &proxy_fragment,
+            &data_id1,
+            &new_capsule,
         ),
-        "This fragment was not requested.",
+        "not a data owner",
     ));
 
-    // Not a proxy
     assert!(is_err(
-        provide_reencrypted_fragment(
+        update_data_capsule(
             deps.as_mut(),
-            &creator,
+            &delegator1,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id,
-            &delegatee_pubkey,
-            &proxy_fragment,
+            &data_id1,
+            &capsule,
         ),
-        "Proxy not registered",
+        "Capsule is unchanged",
     ));
 
-    // Provide fragment correctly
-    assert!(provide_reencrypted_fragment(
+    let res = update_data_capsule(
         deps.as_mut(),
-        &proxy,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id,
-        &delegatee_pubkey,
-        &proxy_fragment,
+        &data_id1,
+        &new_capsule,
     )
-    .is_ok());
-    // Fragment already provided
-    assert!(is_err(
-        provide_reencrypted_fragment(
-            deps.as_mut(),
-            &proxy,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id,
-            &delegatee_pubkey,
-            &proxy_fragment,
-        ),
-        "Fragment already provided.",
-    ));
+    .unwrap();
 
-    // This entry is removed when proxy task is done
-    assert!(!store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy,
-        &0u64,
-    ));
+    // Unfinished task is refunded
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(DEFAULT_TASK_REWARD_AMOUNT, DEFAULT_STAKE_DENOM)],
+        })]
+    );
+    assert_eq!(
+        res.events,
+        vec![Event::new("data_capsule_updated")
+            .add_attribute("data_id", &data_id1)
+            .add_attribute("delegator_pubkey", DELEGATOR1_PUBKEY)]
+    );
 
-    let task = store_get_proxy_task(deps.as_mut().storage, &0u64).unwrap();
-    assert_eq!(task.fragment, Some(proxy_fragment));
+    // Old fragments are gone
+    assert!(
+        get_all_fragments(deps.as_mut().storage, &data_id1, DELEGATEE1_PUBKEY)
+            .unwrap()
+            .is_empty()
+    );
+    assert_eq!(
+        store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+            .unwrap()
+            .unwrap()
+            .stake_amount
+            .u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+    );
+
+    // Labels and tags are kept
+    assert_eq!(
+        store_get_data_entry(deps.as_mut().storage, &data_id1)
+            .unwrap()
+            .unwrap()
+            .capsule,
+        new_capsule
+    );
+    assert_eq!(
+        store_get_all_data_labels(deps.as_mut().storage, &data_id1),
+        data_labels
+    );
+    assert_eq!(
+        store_get_all_data_tags(deps.as_mut().storage, &data_id1),
+        tags
+    );
+
+    // Delegatee can request again
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
 }
 
 #[test]
-fn test_contract_lifecycle() {
+fn test_transfer_data_ownership() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy_1".to_string());
-    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy1 = Addr::unchecked("proxy1".to_string());
+    let proxy2 = Addr::unchecked("proxy2".to_string());
 
-    let delegator = Addr::unchecked("delegator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
 
     // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy_pubkey1");
-    let proxy2_pubkey: String = String::from("proxy_pubkey2");
+    let proxy1_pubkey: String = String::from("proxy1_pubkey");
+    let proxy2_pubkey: String = String::from("proxy2_pubkey");
 
-    let data_id = String::from("DATA");
+    let data_id1 = String::from("DATA1");
+    let data_labels = Some(vec![String::from("label1")]);
 
-    let data_entry = DataEntry {
-        capsule: CAPSULE.to_string(),
-        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-    };
+    let capsule = String::from("capsule");
+    let new_capsule = String::from("new_capsule");
 
-    // Staking
-    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let proxy_stake = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
-    }];
     let request_reward = vec![Coin {
         denom: DEFAULT_STAKE_DENOM.to_string(),
         amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
     }];
 
     /*************** Initialise *************/
-    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone()];
     assert!(init_contract(
         deps.as_mut(),
         &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &Some(2),
         &None,
-        &Some(proxies),
-        &stake_denom,
+        &None,
+        &None,
+        &DEFAULT_STAKE_DENOM.to_string(),
         &None,
         &None,
         &None,
@@ -1850,7 +2843,10 @@ fn test_contract_lifecycle() {
     .is_ok());
 
     /*************** Register proxies *************/
-    // Proxies register -> submits pubkeys
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
     assert!(register_proxy(
         deps.as_mut(),
         &proxy1,
@@ -1868,46 +2864,32 @@ fn test_contract_lifecycle() {
     )
     .is_ok());
 
-    /*************** Add data and delegations by delegator *************/
-    // Add data by delegator
+    /*************** Add data, delegation and request *************/
     assert!(add_data(
         deps.as_mut(),
-        &delegator,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id,
-        &data_entry.delegator_pubkey,
-        &CAPSULE.to_string(),
-        &None,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &data_labels,
     )
     .is_ok());
 
-    // Add 2 delegations for 2 proxies
-    let proxy1_delegation_string = String::from("DS_P1");
-    let proxy2_delegation_string = String::from("DS_P2");
-
     let proxy_delegations: Vec<ProxyDelegationString> = vec![
         ProxyDelegationString {
             proxy_addr: proxy1.clone(),
-            delegation_string: proxy1_delegation_string.clone(),
+            delegation_string: String::from("DS_P1"),
         },
         ProxyDelegationString {
             proxy_addr: proxy2.clone(),
-            delegation_string: proxy2_delegation_string,
+            delegation_string: String::from("DS_P2"),
         },
     ];
 
-    assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::NonExisting
-    );
-
     assert!(add_delegation(
         deps.as_mut(),
-        &delegator,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &DELEGATOR1_PUBKEY.to_string(),
         &DELEGATEE1_PUBKEY.to_string(),
@@ -1916,418 +2898,270 @@ fn test_contract_lifecycle() {
     )
     .is_ok());
 
-    assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::Active
-    );
-
-    assert!(add_delegation(
+    assert!(request_reencryption(
         deps.as_mut(),
-        &delegator,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE2_PUBKEY.to_string(),
-        &proxy_delegations,
-        &None,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
     )
     .is_ok());
 
-    // No tasks yet
-    assert!(
-        get_proxy_tasks(deps.as_mut().storage, &proxy1, &DEFAULT_BLOCK_HEIGHT)
-            .unwrap()
-            .is_empty()
-    );
-    assert!(
-        get_proxy_tasks(deps.as_mut().storage, &proxy2, &DEFAULT_BLOCK_HEIGHT)
-            .unwrap()
-            .is_empty()
-    );
-
-    let state = store_get_state(deps.as_mut().storage).unwrap();
-    assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &0u64,
-        ),
-        ReencryptionRequestState::Inaccessible
-    );
-
-    /*************** Request reencryption by delegator *************/
-
-    assert!(request_reencryption(
+    // Only proxy1 finishes its task
+    assert!(provide_reencrypted_fragment(
         deps.as_mut(),
-        &delegator,
+        &proxy1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id,
+        &data_id1,
         &DELEGATEE1_PUBKEY.to_string(),
-        &request_reward,
+        &String::from(FRAGMENT_P1_DR1_DE1),
     )
     .is_ok());
 
-    let state = store_get_state(deps.as_mut().storage).unwrap();
-    assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &0u64,
+    /*************** Transfer ownership *************/
+    // Only owner can transfer data
+    assert!(is_err(
+        transfer_data_ownership(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATOR2_PUBKEY.to_string(),
+            &new_capsule,
         ),
-        ReencryptionRequestState::Ready
-    );
+        "not a data owner",
+    ));
 
-    // Check number of tasks
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
-        1
-    );
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
-        1
-    );
+    // Data can't be transferred to current owner pubkey
+    assert!(is_err(
+        transfer_data_ownership(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &new_capsule,
+        ),
+        "already owned",
+    ));
 
-    assert!(request_reencryption(
+    // Nothing to accept yet
+    assert!(is_err(
+        accept_data_ownership(deps.as_mut(), &delegator2, DEFAULT_BLOCK_HEIGHT, &data_id1),
+        "No pending ownership transfer",
+    ));
+
+    assert!(transfer_data_ownership(
         deps.as_mut(),
-        &delegator,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &request_reward,
+        &data_id1,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &new_capsule,
     )
     .is_ok());
 
-    // Check number of requests
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
-        2
-    );
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
-        2
-    );
-
-    /*************** Process reencryption by proxies *************/
-    let all_tasks = store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1);
-    assert_eq!(all_tasks.len(), 2);
-
-    // Check if proxy got task 1
-    let proxy1_task1 =
-        &get_proxy_tasks(deps.as_mut().storage, &proxy1, &DEFAULT_BLOCK_HEIGHT).unwrap()[0];
-    assert_eq!(
-        proxy1_task1,
-        &ProxyTaskResponse {
-            data_id: data_id.clone(),
-            capsule: data_entry.capsule.clone(),
-            delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
-            delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-            delegation_string: proxy1_delegation_string.clone(),
-        }
-    );
-
-    // Check stake before finishing re-encryption
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1).unwrap();
+    let res: GetDataOwnershipTransferResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDataOwnershipTransfer {
+                data_id: data_id1.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
     assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 2 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
+        res.transfer,
+        Some(DataOwnershipTransfer {
+            new_delegator_pubkey: DELEGATOR2_PUBKEY.to_string(),
+            new_capsule: new_capsule.clone(),
+        })
     );
 
-    // Proxy1 provides fragment for task1
-    let proxy1_fragment1: String = String::from("Agn8MTBWSKzz277FLeNKvhOwa3juw7HBciLmyA/3kZ2hAtQv0l/B+Ej2vQLxZDx+MHDr5uevth9PzntoIz6gbPI1xJk3dVwZohs3YgdaXJsBXpAambF1FpOGrola7KcwjtQDOL6tYr3e6dlMgsW9GnONyZUWk15ixjxdrAIZfp8qWAMCbOd9fCO820cnEqBeQHpit75l8gxb6Al3s28p4uMFeq4Dzsh5SbQgRk7KjI9LEq2a9YzQ2ts3O5KEx3SuZoCOE0UDns625ayBRPD5BHdYwGaCGo/w6oJ5PvRp7rEpMSvxpOACu5HXcj2KNZnzAc2QGNrHmrAIxxS4pUbp7ffoPjSK/eGOs3Yh2IaeLQMzj2FNpUCYii6D3KJMT5sWqdKQV+5Aw6ebgujLY0o4Gs2aJ3toE3GuNuSfwFKzySmpq5CfSGaJJftZDYt72g7t8cRKVFXT6D8ugCXfMVL6GRE7adJEkYU=");
-    assert!(provide_reencrypted_fragment(
+    // Cancel and offer again
+    assert!(is_err(
+        cancel_data_ownership_transfer(deps.as_mut(), &delegator2, DEFAULT_BLOCK_HEIGHT, &data_id1),
+        "not a data owner",
+    ));
+    assert!(cancel_data_ownership_transfer(
         deps.as_mut(),
-        &proxy1,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy1_fragment1,
+        &data_id1
     )
     .is_ok());
-
-    // Check if proxy got reward
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1).unwrap();
-    assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 1 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
-            + DEFAULT_TASK_REWARD_AMOUNT
-    );
-
-    // Proxy2 tries to provides fragment already provided by proxy1 for task1
     assert!(is_err(
-        provide_reencrypted_fragment(
-            deps.as_mut(),
-            &proxy2,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy1_fragment1,
-        ),
-        "Fragment already provided by other proxy.",
+        accept_data_ownership(deps.as_mut(), &delegator2, DEFAULT_BLOCK_HEIGHT, &data_id1),
+        "No pending ownership transfer",
     ));
 
-    // Check numbers of tasks
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
-        1
-    );
+    assert!(transfer_data_ownership(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &new_capsule,
+    )
+    .is_ok());
+
+    // Access decisions of current owner
+    assert!(deny_data_access(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE2_PUBKEY.to_string(),
+    )
+    .is_ok());
+    assert!(request_access(
+        deps.as_mut(),
+        &delegator2,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATOR1_PUBKEY,
+        DELEGATEE2_PUBKEY,
+        &AccessRequestTarget::DataIds(vec![data_id1.clone()]),
+    )
+    .is_ok());
+    assert!(request_access(
+        deps.as_mut(),
+        &delegator2,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATOR1_PUBKEY,
+        DELEGATEE2_PUBKEY,
+        &AccessRequestTarget::Labels(vec![String::from("label")]),
+    )
+    .is_ok());
     assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
+        get_pending_access_requests(&deps, DEFAULT_BLOCK_HEIGHT, DELEGATOR1_PUBKEY).len(),
         2
     );
 
-    // Check available fragments
-    assert_eq!(
-        get_all_fragments(
-            deps.as_mut().storage,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        vec![proxy1_fragment1.clone()]
-    );
-    assert_eq!(
-        get_all_fragments(
-            deps.as_mut().storage,
-            &data_id,
-            &DELEGATEE2_PUBKEY.to_string(),
-        )
-        .len(),
-        0
-    );
+    /*************** Accept ownership *************/
+    let res =
+        accept_data_ownership(deps.as_mut(), &delegator2, DEFAULT_BLOCK_HEIGHT, &data_id1).unwrap();
 
-    // Check if proxy got task 2
-    let proxy1_task2 =
-        &get_proxy_tasks(deps.as_mut().storage, &proxy1, &DEFAULT_BLOCK_HEIGHT).unwrap()[0];
+    // Rules and data requests of previous owner are dropped, label requests stay pending
+    assert!(store_get_all_data_access_rules(deps.as_mut().storage, &data_id1).is_empty());
     assert_eq!(
-        proxy1_task2,
-        &ProxyTaskResponse {
-            data_id: data_id.clone(),
-            capsule: data_entry.capsule,
-            delegatee_pubkey: DELEGATEE2_PUBKEY.to_string(),
-            delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-            delegation_string: proxy1_delegation_string,
-        }
+        get_pending_access_requests(&deps, DEFAULT_BLOCK_HEIGHT, DELEGATOR1_PUBKEY),
+        vec![1]
     );
 
-    // Proxy1 provides fragment for task1
-    let proxy1_fragment2: String = String::from("A5fWxYyjkfJu/k2oq5A6w+pLgRtWRIKu2uEHe/i0AGSiAsK7jq0a7KjTeiBCBRTC64ATDb/QfYQ9CBoiF5FDdbJwh3Yb5RoZgkclP0cqNtftZnRCdVUuycy2UpQ7f4x5tFkCkymfOr+pOYAe56kPnK9cTGjuwGdgcrV3i5A1ocF5xYsD0mFd9APeYHeRjAIgPzM3na8xJuYgSdY9FA6upZOYqxkDGWHWjB6Uvjby3zTbN+A8vuQmHRx0NST4ICR5HfKCCPQCE6/D/Dep4lyf4v9E03VgMisZKFWW7+YP5qAIWgNDSPoCb8OqfDTrYzJSKGZg+ti4l/Cjo5PaqmlZlj1MCR/Rb906nywBtIQCU9iVAHGUnE8h9QV+kYWik4s2Vcq2W6r/Y6MXnYLK9JsYpIwny6zDwHzOwfTk4Wn9mLGENf4q2s/5ZeM/1TJIu6wECI+L5zmGMWl40iloHhHcxeCVKwKnM0s=");
-    assert!(provide_reencrypted_fragment(
-        deps.as_mut(),
-        &proxy1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &proxy1_fragment2,
-    )
-    .is_ok());
-
-    // Check if proxy got reward
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1).unwrap();
+    // Unfinished task of proxy2 is refunded to requester
+    assert_eq!(res.messages.len(), 1);
     assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + 2 * DEFAULT_TASK_REWARD_AMOUNT
+        res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(DEFAULT_TASK_REWARD_AMOUNT, DEFAULT_STAKE_DENOM)],
+        })
     );
 
-    // All tasks completed for proxy1
-    assert!(
-        get_proxy_tasks(deps.as_mut().storage, &proxy1, &DEFAULT_BLOCK_HEIGHT)
+    // Proxy2 got its stake back, proxy1 kept reward
+    assert_eq!(
+        store_get_proxy_entry(deps.as_mut().storage, &proxy1)
             .unwrap()
-            .is_empty()
+            .unwrap()
+            .stake_amount
+            .u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + DEFAULT_TASK_REWARD_AMOUNT
     );
-    // But not for proxy2
-    assert!(
-        !get_proxy_tasks(deps.as_mut().storage, &proxy2, &DEFAULT_BLOCK_HEIGHT)
+    assert_eq!(
+        store_get_proxy_entry(deps.as_mut().storage, &proxy2)
             .unwrap()
-            .is_empty()
+            .unwrap()
+            .stake_amount
+            .u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
     );
+    assert!(store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).is_empty());
+
+    // All tasks are removed
+    for proxy in [&proxy1, &proxy2] {
+        assert_eq!(
+            store_get_delegatee_proxy_task(
+                deps.as_mut().storage,
+                &data_id1,
+                &DELEGATEE1_PUBKEY.to_string(),
+                proxy,
+            )
+            .unwrap(),
+            None
+        );
+    }
 
-    // Check available fragments
+    // Data entry belongs to new owner, labels are preserved
     assert_eq!(
-        get_all_fragments(
-            deps.as_mut().storage,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        vec![proxy1_fragment1]
+        store_get_data_entry(deps.as_mut().storage, &data_id1)
+            .unwrap()
+            .unwrap(),
+        DataEntry {
+            delegator_pubkey: DELEGATOR2_PUBKEY.to_string(),
+            capsule: new_capsule,
+            expires_at: None,
+        }
     );
     assert_eq!(
-        get_all_fragments(
-            deps.as_mut().storage,
-            &data_id,
-            &DELEGATEE2_PUBKEY.to_string(),
-        ),
-        vec![proxy1_fragment2]
+        store_get_delegator_address(deps.as_mut().storage, &DELEGATOR2_PUBKEY.to_string())
+            .unwrap()
+            .unwrap(),
+        delegator2
     );
-
-    let state = store_get_state(deps.as_mut().storage).unwrap();
     assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &0u64,
-        ),
-        ReencryptionRequestState::Ready
+        store_get_all_data_labels(deps.as_mut().storage, &data_id1),
+        data_labels.unwrap()
     );
     assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::Active
+        store_get_data_ownership_transfer(deps.as_mut().storage, &data_id1).unwrap(),
+        None
     );
 
-    // Re-encryption was requested in past
-    assert!(is_err(
-        request_reencryption(
-            deps.as_mut(),
-            &delegator,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &request_reward,
-        ),
-        "Reencryption already requested",
-    ));
+    // Previous owner lost access
+    assert!(remove_data(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &data_id1).is_err());
+    assert!(remove_data(deps.as_mut(), &delegator2, DEFAULT_BLOCK_HEIGHT, &data_id1).is_ok());
+}
 
-    // Proxy 2 leaves - all its delegations gets deleted
-    assert!(unregister_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).is_ok());
-
-    // Check proxy stake amount
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2).unwrap();
-    assert_eq!(proxy.stake_amount.u128(), 0);
-
-    // Proxy 2 gets back
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy2,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy2_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-
-    // Check proxy stake amount
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2).unwrap();
-    assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
-    );
-
-    let state = store_get_state(deps.as_mut().storage).unwrap();
-    assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
-            &data_id,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &0u64,
-        ),
-        ReencryptionRequestState::Abandoned
-    );
-    assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::NonExisting
-    );
-
-    // ProxyDelegation can be re-created again
-    assert!(add_delegation(
-        deps.as_mut(),
-        &delegator,
-        DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy_delegations,
-        &None,
-    )
-    .is_ok());
-
-    // Check if all stake gets returned to proxy after un-registered
-    let unregister_response =
-        unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(
-        unregister_response.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy1.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + DEFAULT_TASK_REWARD_AMOUNT * 2,
-                stake_denom.as_str(),
-            )],
-        })
-    );
-}
-
-#[test]
-fn test_proxy_unregister_with_requests() {
-    let mut deps = mock_dependencies();
+#[test]
+fn test_rotate_delegator_key() {
+    let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy_1".to_string());
-    let proxy2 = Addr::unchecked("proxy_2".to_string());
-    let proxy3 = Addr::unchecked("proxy_3".to_string());
-    let proxy4 = Addr::unchecked("proxy_4".to_string());
-    let proxy5 = Addr::unchecked("proxy_5".to_string());
+    let proxy1 = Addr::unchecked("proxy1".to_string());
+    let proxy2 = Addr::unchecked("proxy2".to_string());
 
     let delegator1 = Addr::unchecked("delegator1".to_string());
     let delegator2 = Addr::unchecked("delegator2".to_string());
 
     // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy_pubkey1");
-    let proxy2_pubkey: String = String::from("proxy_pubkey2");
-    let proxy3_pubkey: String = String::from("proxy_pubkey3");
-    let proxy4_pubkey: String = String::from("proxy_pubkey4");
-    let proxy5_pubkey: String = String::from("proxy_pubkey5");
+    let proxy1_pubkey: String = String::from("proxy1_pubkey");
+    let proxy2_pubkey: String = String::from("proxy2_pubkey");
+    let new_pubkey: String = String::from("new_delegator_pubkey");
 
     let data_id1 = String::from("DATA1");
     let data_id2 = String::from("DATA2");
     let data_id3 = String::from("DATA3");
 
-    let delegation_string = String::from("DELESTRING");
+    let capsule = String::from("capsule");
 
-    // Staking
-    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let proxy_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
-    }];
-    let request_reward_2_proxies = vec![Coin {
-        denom: stake_denom.clone(),
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
         amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
     }];
-    let request_reward_3_proxies = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 3),
-    }];
 
     /*************** Initialise *************/
-    let proxies: Vec<Addr> = vec![
-        proxy1.clone(),
-        proxy2.clone(),
-        proxy3.clone(),
-        proxy4.clone(),
-        proxy5.clone(),
-    ];
     assert!(init_contract(
         deps.as_mut(),
         &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &Some(2),
         &None,
-        &Some(proxies),
-        &stake_denom,
+        &None,
+        &None,
+        &DEFAULT_STAKE_DENOM.to_string(),
         &None,
         &None,
         &None,
@@ -2338,7 +3172,10 @@ fn test_proxy_unregister_with_requests() {
     .is_ok());
 
     /*************** Register proxies *************/
-    // Proxies register -> submits pubkeys
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
     assert!(register_proxy(
         deps.as_mut(),
         &proxy1,
@@ -2355,635 +3192,817 @@ fn test_proxy_unregister_with_requests() {
         &proxy_stake,
     )
     .is_ok());
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy3,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy3_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy4,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy4_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-    assert!(register_proxy(
+
+    /*************** Add data, delegation and request *************/
+    for data_id in [&data_id1, &data_id2] {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &None,
+        )
+        .is_ok());
+    }
+    // Data of other delegator
+    assert!(add_data(
         deps.as_mut(),
-        &proxy5,
+        &delegator2,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy5_pubkey,
-        &proxy_stake,
+        &data_id3,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &capsule,
+        &None,
     )
     .is_ok());
 
-    /*************** Add data and delegations by delegator *************/
-    // Add data by delegator
-    assert!(add_data(
+    assert_eq!(
+        store_get_all_delegator_data_ids(deps.as_mut().storage, DELEGATOR1_PUBKEY).unwrap(),
+        vec![data_id1.clone(), data_id2.clone()]
+    );
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: String::from("DS_P1"),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: String::from("DS_P2"),
+        },
+    ];
+
+    assert!(add_delegation(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
         &DELEGATOR1_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
         &None,
     )
     .is_ok());
-    assert!(add_data(
+
+    assert!(request_reencryption(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id2,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
-        &None,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
     )
     .is_ok());
-    assert!(add_data(
+
+    // Subscription to old pubkey with locked budget
+    let subscriber = Addr::unchecked("subscriber".to_string());
+    assert!(subscribe(
         deps.as_mut(),
-        &delegator2,
+        &subscriber,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id3,
-        &DELEGATOR2_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
-        &None,
+        DELEGATOR1_PUBKEY,
+        DELEGATEE2_PUBKEY,
+        &["x"],
+        DEFAULT_TASK_REWARD_AMOUNT,
+        &vec![Coin::new(DEFAULT_TASK_REWARD_AMOUNT, DEFAULT_STAKE_DENOM)],
     )
     .is_ok());
 
-    // Add delegations manually
+    /*************** Rotate key *************/
+    let updated_capsules = vec![
+        DataCapsule {
+            data_id: data_id1.clone(),
+            capsule: String::from("new_capsule1"),
+        },
+        DataCapsule {
+            data_id: data_id2.clone(),
+            capsule: String::from("new_capsule2"),
+        },
+    ];
 
-    let delegation1 = ProxyDelegation {
-        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-        delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
-        delegation_string: delegation_string.clone(),
-    };
+    // Only owner of pubkey can rotate it
+    assert!(is_err(
+        rotate_delegator_key(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &new_pubkey,
+            &updated_capsules,
+        ),
+        "not owner of delegator pubkey",
+    ));
 
-    // delegator1 with delegator1_pubkey and proxy1 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &0, &delegation1);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation1.delegator_pubkey,
-        &delegation1.delegatee_pubkey,
-        &proxy1,
-        &0,
-    );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy1, &0);
+    // Can't rotate to pubkey of other delegator
+    assert!(is_err(
+        rotate_delegator_key(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATOR2_PUBKEY.to_string(),
+            &updated_capsules,
+        ),
+        "already registered",
+    ));
 
-    // delegator1 with delegator1_pubkey and proxy2 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &1, &delegation1);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation1.delegator_pubkey,
-        &delegation1.delegatee_pubkey,
-        &proxy2,
-        &1,
+    // All data entries must be covered
+    assert!(is_err(
+        rotate_delegator_key(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &new_pubkey,
+            &updated_capsules[..1],
+        ),
+        "Missing capsule for data ID DATA2",
+    ));
+
+    // Data of other delegator can't be updated
+    let mut foreign_capsules = updated_capsules.clone();
+    foreign_capsules.push(DataCapsule {
+        data_id: data_id3.clone(),
+        capsule: String::from("new_capsule3"),
+    });
+    assert!(is_err(
+        rotate_delegator_key(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &new_pubkey,
+            &foreign_capsules,
+        ),
+        "Data ID DATA3 is not owned by old pubkey",
+    ));
+
+    let res = rotate_delegator_key(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &new_pubkey,
+        &updated_capsules,
+    )
+    .unwrap();
+
+    // Both unfinished tasks and subscription budget are refunded
+    assert_eq!(res.messages.len(), 2);
+    assert!(res.messages.contains(&SubMsg::new(BankMsg::Send {
+        to_address: delegator1.to_string(),
+        amount: vec![Coin::new(
+            DEFAULT_TASK_REWARD_AMOUNT * 2,
+            DEFAULT_STAKE_DENOM
+        )],
+    })));
+    assert!(res.messages.contains(&SubMsg::new(BankMsg::Send {
+        to_address: subscriber.to_string(),
+        amount: vec![Coin::new(DEFAULT_TASK_REWARD_AMOUNT, DEFAULT_STAKE_DENOM)],
+    })));
+    assert_eq!(
+        get_subscription_budget(&deps, DELEGATOR1_PUBKEY, DELEGATEE2_PUBKEY),
+        None
     );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy2, &1);
 
-    // delegator1 with delegator1_pubkey and proxy3 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &2, &delegation1);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation1.delegator_pubkey,
-        &delegation1.delegatee_pubkey,
-        &proxy3,
-        &2,
+    // Old pubkey is released, new one is bound to sender
+    assert_eq!(
+        store_get_delegator_address(deps.as_mut().storage, DELEGATOR1_PUBKEY).unwrap(),
+        None
     );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy3, &2);
+    assert_eq!(
+        store_get_delegator_address(deps.as_mut().storage, &new_pubkey).unwrap(),
+        Some(delegator1.clone())
+    );
+    for proxy in [&proxy1, &proxy2] {
+        assert_eq!(
+            store_get_proxy_entry(deps.as_mut().storage, proxy)
+                .unwrap()
+                .unwrap()
+                .stake_amount
+                .u128(),
+            DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+        );
+        assert!(store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, proxy).is_empty());
+    }
 
-    let delegation2 = ProxyDelegation {
-        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-        delegatee_pubkey: DELEGATEE2_PUBKEY.to_string(),
-        delegation_string: delegation_string.clone(),
-    };
-
-    // delegator1 with delegator1_pubkey and proxy1 for delegatee2
-    store_set_delegation(deps.as_mut().storage, &3, &delegation2);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation2.delegator_pubkey,
-        &delegation2.delegatee_pubkey,
-        &proxy1,
-        &3,
+    // Data entries moved to new pubkey
+    assert_eq!(
+        store_get_data_entry(deps.as_mut().storage, &data_id2)
+            .unwrap()
+            .unwrap(),
+        DataEntry {
+            delegator_pubkey: new_pubkey.clone(),
+            capsule: String::from("new_capsule2"),
+            expires_at: None,
+        }
     );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy1, &3);
-
-    // delegator1 with delegator1_pubkey and proxy2 for delegatee2
-    store_set_delegation(deps.as_mut().storage, &4, &delegation2);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation2.delegator_pubkey,
-        &delegation2.delegatee_pubkey,
-        &proxy2,
-        &4,
+    assert!(
+        store_get_all_delegator_data_ids(deps.as_mut().storage, DELEGATOR1_PUBKEY)
+            .unwrap()
+            .is_empty()
     );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy2, &4);
-
-    let delegation3 = ProxyDelegation {
-        delegator_pubkey: DELEGATOR2_PUBKEY.to_string(),
-        delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
-        delegation_string: delegation_string,
-    };
-
-    // delegator2 with delegator2_pubkey and proxy4 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &5, &delegation3);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation3.delegator_pubkey,
-        &delegation3.delegatee_pubkey,
-        &proxy4,
-        &5,
+    assert_eq!(
+        store_get_all_delegator_data_ids(deps.as_mut().storage, &new_pubkey).unwrap(),
+        vec![data_id1.clone(), data_id2.clone()]
     );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy4, &5);
-
-    // delegator2 with delegator2_pubkey and proxy5 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &6, &delegation3);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation3.delegator_pubkey,
-        &delegation3.delegatee_pubkey,
-        &proxy5,
-        &6,
+    assert_eq!(
+        store_get_all_delegator_data_ids(deps.as_mut().storage, DELEGATOR2_PUBKEY).unwrap(),
+        vec![data_id3]
     );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy5, &6);
 
-    // Check proxies stake amount
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1).unwrap();
+    // Delegations of old key are removed
     assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+        get_delegation_state(deps.as_mut().storage, DELEGATOR1_PUBKEY, DELEGATEE1_PUBKEY).unwrap(),
+        DelegationState::NonExisting
     );
+    for proxy in [&proxy1, &proxy2] {
+        assert!(
+            store_get_all_proxy_delegations(deps.as_mut().storage, proxy)
+                .unwrap()
+                .is_empty()
+        );
+    }
 
-    // Request re-encryptions
+    // Request needs new delegation
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_err());
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &new_pubkey,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
 
-    // Re-encryption requests with DATA1 to delegatee1
     assert!(request_reencryption(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &data_id1,
         &DELEGATEE1_PUBKEY.to_string(),
-        &request_reward_3_proxies,
+        &request_reward,
     )
     .is_ok());
+}
 
-    // Check proxy2 stake amount after creating reencryption request
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2).unwrap();
-    assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
-    );
+#[test]
+fn test_data_expiration() {
+    let mut deps = mock_dependencies();
 
-    // Re-encryption requests with DATA1 to delegatee2
-    assert!(request_reencryption(
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy1".to_string());
+    let proxy2 = Addr::unchecked("proxy2".to_string());
+
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let keeper = Addr::unchecked("keeper".to_string());
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy1_pubkey");
+    let proxy2_pubkey: String = String::from("proxy2_pubkey");
+
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+
+    let capsule = String::from("capsule");
+    let expiration_height = DEFAULT_BLOCK_HEIGHT + 20;
+    let expiration_time = mock_env().block.time.plus_seconds(100);
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
+    }];
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &None,
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    /*************** Register proxies *************/
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    /*************** Add data *************/
+    // Expiration must be in future
+    assert!(is_err(
+        add_data_with_expiration(
+            deps.as_mut(),
+            &delegator1,
+            expiration_height,
+            &data_id1,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &Expiration::AtHeight(expiration_height),
+        ),
+        "already expired",
+    ));
+
+    assert!(add_data_with_expiration(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &data_id1,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &request_reward_2_proxies,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Expiration::AtHeight(expiration_height),
     )
     .is_ok());
-    // Re-encryption requests with DATA2 to delegatee2
-    assert!(request_reencryption(
+    assert!(add_data(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &data_id2,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &request_reward_2_proxies,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(vec![String::from("label1")]),
     )
     .is_ok());
-    // Re-encryption requests with DATA3 to delegatee1
-    assert!(request_reencryption(
+    assert!(add_data_with_expiration(
         deps.as_mut(),
-        &delegator2,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &data_id3,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &request_reward_2_proxies,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Expiration::AtTime(expiration_time),
     )
     .is_ok());
 
-    // Check proxy2 stake amount after creating reencryption requests
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2).unwrap();
     assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 3 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
+        store_get_data_entry(deps.as_mut().storage, &data_id1)
+            .unwrap()
+            .unwrap()
+            .expires_at,
+        Some(Expiration::AtHeight(expiration_height))
     );
 
-    /*
-    // Provide wrong fragment
-    assert!(is_err(
-        provide_reencrypted_fragment(
-            deps.as_mut(),
-            &proxy2,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
-            &DELEGATEE2_PUBKEY.to_string(),
-            &FRAGMENT_P2_DR1_DE1.to_string(),
-        ),
-        "Invalid KeyFrag signature",
-    ));
-     */
+    /*************** Add delegation and request *************/
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: String::from("DS_P1"),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: String::from("DS_P2"),
+        },
+    ];
 
-    // Complete tasks
-    assert!(provide_reencrypted_fragment(
+    assert!(add_delegation(
         deps.as_mut(),
-        &proxy2,
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &data_id1,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &FRAGMENT_P2_DR1_DE2.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
     )
     .is_ok());
 
-    // Check proxy2 stake amount after finishing one reencryption task
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2).unwrap();
+    let state = store_get_state(deps.as_mut().storage).unwrap();
     assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 2 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
-            + DEFAULT_TASK_REWARD_AMOUNT
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id1,
+            DELEGATEE1_PUBKEY,
+            &mock_block_height(expiration_height - 1),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Ready
     );
 
-    // Unregister proxy2
-    let unregister_response =
-        unregister_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).unwrap();
-
-    // Check if stake from unfinished request get returned to delegator
+    /*************** Data expired *************/
     assert_eq!(
-        unregister_response.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: delegator1.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_TASK_REWARD_AMOUNT * 2,
-                stake_denom.as_str(),
-            )],
-        })
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id1,
+            DELEGATEE1_PUBKEY,
+            &mock_block_height(expiration_height),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Inaccessible
     );
 
-    // Check if stake gets returned to proxy and if proxy got slashed
+    let res: GetFragmentsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env_height(&delegator1, expiration_height, &vec![]).0,
+            QueryMsg::GetFragments {
+                data_id: data_id1.clone(),
+                delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
     assert_eq!(
-        unregister_response.messages[1],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy2.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + DEFAULT_TASK_REWARD_AMOUNT
-                    - 2 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT,
-                stake_denom.as_str(),
-            )],
-        })
+        res.reencryption_request_state,
+        ReencryptionRequestState::Inaccessible
     );
+    assert!(res.fragments.is_empty());
 
-    // Already unregistered
     assert!(is_err(
-        unregister_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT),
-        "Proxy already unregistered",
+        provide_reencrypted_fragment(
+            deps.as_mut(),
+            &proxy1,
+            expiration_height,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &String::from(FRAGMENT_P1_DR1_DE1),
+        ),
+        "Data entry has expired",
     ));
 
-    // Check state of delegations
-
-    // ProxyDelegation 1 - Stays
-    // delgator1, delegatee1, proxy1 - Unaffected
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy1,
-    )
-    .is_some());
-    assert!(store_get_delegation(deps.as_mut().storage, &0).is_some());
-    assert!(store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy1,
-        &0,
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            expiration_height,
+            &data_id1,
+            &DELEGATEE2_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "Data entry has expired",
     ));
 
-    // delgator1, delegatee1, proxy2 - Removed
-    assert!(store_get_proxy_delegation_id(
+    // Time based expiration
+    let mut block = mock_block_height(DEFAULT_BLOCK_HEIGHT);
+    assert!(!is_data_expired(deps.as_mut().storage, &data_id3, &block).unwrap());
+    block.time = expiration_time;
+    assert!(is_data_expired(deps.as_mut().storage, &data_id3, &block).unwrap());
+    assert_eq!(
+        store_get_expired_data_ids(deps.as_mut().storage, &block, 10).unwrap(),
+        vec![data_id3.clone()]
+    );
+
+    /*************** Prune *************/
+    // Nothing expired yet
+    let res = prune_expired_data(deps.as_mut(), &keeper, DEFAULT_BLOCK_HEIGHT, None).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(store_get_data_entry(deps.as_mut().storage, &data_id1)
+        .unwrap()
+        .is_some());
+
+    // Anyone can prune expired data
+    let res = prune_expired_data(deps.as_mut(), &keeper, expiration_height, None).unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(
+                DEFAULT_TASK_REWARD_AMOUNT * 2,
+                DEFAULT_STAKE_DENOM
+            )],
+        })
+    );
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("data_ids", data_id1.as_str())));
+
+    for proxy in [&proxy1, &proxy2] {
+        assert_eq!(
+            store_get_proxy_entry(deps.as_mut().storage, proxy)
+                .unwrap()
+                .unwrap()
+                .stake_amount
+                .u128(),
+            DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+        );
+        assert!(store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, proxy).is_empty());
+    }
+
+    assert_eq!(
+        store_get_data_entry(deps.as_mut().storage, &data_id1).unwrap(),
+        None
+    );
+    assert!(store_get_data_entry(deps.as_mut().storage, &data_id2)
+        .unwrap()
+        .is_some());
+    assert!(store_get_data_entry(deps.as_mut().storage, &data_id3)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_all_delegator_data_ids(deps.as_mut().storage, DELEGATOR1_PUBKEY).unwrap(),
+        vec![data_id2, data_id3]
+    );
+    assert!(store_get_expired_data_ids(
         deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy2,
+        &mock_block_height(expiration_height),
+        10
     )
-    .is_none());
-    assert!(store_get_delegation(deps.as_mut().storage, &1).is_none());
-    assert!(!store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy2,
-        &1,
-    ));
+    .unwrap()
+    .is_empty());
+}
 
-    // delgator1, delegatee1, proxy3 - stays
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy3,
+#[test]
+fn test_add_delegation_and_then_data_with_diffent_proxy_same_pubkey() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy1".to_string());
+    let proxy2 = Addr::unchecked("proxy2".to_string());
+
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy1_pubkey");
+
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+
+    let capsule = String::from("capsule");
+
+    // Staking
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &Some(vec![proxy1.clone(), proxy2]),
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
     )
-    .is_some());
-    assert!(store_get_delegation(deps.as_mut().storage, &2).is_some());
-    assert!(store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy3,
-        &2,
-    ));
+    .is_ok());
 
-    // ProxyDelegation 2 - Number of proxies below threshold, removed entire delegation
-    // delgator1, delegatee2, proxy1 - Removed
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE2_PUBKEY.to_string(),
+    /*************** Register proxies *************/
+    // Proxies register -> submits pubkeys
+    assert!(register_proxy(
+        deps.as_mut(),
         &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
     )
-    .is_none());
-    assert!(store_get_delegation(deps.as_mut().storage, &3).is_none());
-    assert!(!store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy1,
-        &3,
-    ));
+    .is_ok());
 
-    // delgator1, delegatee2, proxy2 - Removed
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE2_PUBKEY.to_string(),
-        &proxy2,
-    )
-    .is_none());
-    assert!(store_get_delegation(deps.as_mut().storage, &4).is_none());
-    assert!(!store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy1,
-        &4,
-    ));
+    // Add delegation for proxy
+    let proxy1_delegation_string = String::from("DS_P1");
 
-    // ProxyDelegation 3 - Unaffected
-    // delgator2, delegatee1, proxy4 - Unaffected
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR2_PUBKEY.to_string(),
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
+        proxy_addr: proxy1,
+        delegation_string: proxy1_delegation_string,
+    }];
+
+    // Add delegation
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
         &DELEGATEE1_PUBKEY.to_string(),
-        &proxy4,
+        &proxy_delegations,
+        &None,
     )
-    .is_some());
-    assert!(store_get_delegation(deps.as_mut().storage, &5).is_some());
-    assert!(store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy4,
-        &5,
+    .is_ok());
+
+    // Add data by delegator2 with already used delegator1_pubkey is prevented
+    assert!(is_err(
+        add_data(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &None,
+        ),
+        "Delegator delegator1 already registered with this pubkey.",
     ));
 
-    // delgator2, delegatee1, proxy5 - Unaffected
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator2,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
         &DELEGATOR2_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy5,
+        &capsule,
+        &None,
     )
-    .is_some());
-    assert!(store_get_delegation(deps.as_mut().storage, &6).is_some());
-    assert!(store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy5,
-        &6,
-    ));
+    .is_ok());
+}
 
-    // Check state of re-encryption requests
+#[test]
+fn test_add_delegation_edge_cases() {
+    let mut deps = mock_dependencies();
 
-    // Re-encryption requests with DATA1 to delegatee1
-    // DATA1, delegatee1, proxy1 - unaffected
-    assert!(store_get_proxy_task(deps.as_mut().storage, &0).is_some());
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy1,
-        )
-        .unwrap(),
-        0
-    );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy1,
-        &0,
-    ));
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy1".to_string());
+    let proxy2 = Addr::unchecked("proxy2".to_string());
 
-    // DATA1, delegatee1, proxy2 - incomplete and removed
-    assert!(
-        store_get_proxy_task(deps.as_mut().storage, &1)
-            .unwrap()
-            .abandoned
-    );
-    assert!(!store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy1,
-        &1,
-    ));
+    let delegator1 = Addr::unchecked("delegator1".to_string());
 
-    // DATA1, delegatee1, proxy3 - unaffected
-    assert!(store_get_proxy_task(deps.as_mut().storage, &2).is_some());
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy3,
-        )
-        .unwrap(),
-        2
-    );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy3,
-        &2,
-    ));
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy1_pubkey");
 
-    // Re-encryption requests with DATA1 to delegatee1
-    // DATA1, delegatee2, proxy1 - Not removed because it can still be completed
-    assert!(store_get_proxy_task(deps.as_mut().storage, &3).is_some());
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE2_PUBKEY.to_string(),
-            &proxy1,
-        )
-        .unwrap(),
-        3
-    );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy1,
-        &3,
-    ));
+    let proxy1_delegation_string = String::from("DS_P1");
+    let proxy2_delegation_string = String::from("DS_P2");
 
-    // DATA1, delegatee2, proxy2 - complete - unaffected
-    // Request can still be obtained by delegatee
-    assert!(store_get_proxy_task(deps.as_mut().storage, &4).is_some());
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE2_PUBKEY.to_string(),
-            &proxy2,
-        )
-        .unwrap(),
-        4
-    );
-    // Request is completed - won't appear in proxy tasks
-    assert!(!store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy2,
-        &4,
-    ));
+    // Staking
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
 
-    // Re-encryption requests with DATA2 to delegatee2
-    // DATA2, delegatee2, proxy1 - incomplete, not removed
-    assert!(store_get_proxy_task(deps.as_mut().storage, &5).is_some());
-    assert!(store_get_delegatee_proxy_task(
-        deps.as_mut().storage,
-        &data_id2,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &proxy1,
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &Some(vec![proxy1.clone(), proxy2.clone()]),
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &Some(false),
+        &None,
     )
-    .is_some());
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
+    .is_ok());
+
+    /*************** Register proxies *************/
+    // Proxies register -> submits pubkeys
+    assert!(register_proxy(
+        deps.as_mut(),
         &proxy1,
-        &5,
-    ));
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
 
-    // DATA1, delegatee2, proxy2 - complete - removed because more than threshold fragments cannot be provided
-    // Request can still be obtained by delegatee
-    assert!(
-        store_get_proxy_task(deps.as_mut().storage, &6)
-            .unwrap()
-            .abandoned
-    );
-    // Request is completed - won't appear in proxy tasks
-    assert!(!store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy2,
-        &6,
-    ));
+    // Try adding delegation for proxy 2 which is not registered
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
+        proxy_addr: proxy2,
+        delegation_string: proxy2_delegation_string,
+    }];
 
-    // Re-encryption requests with DATA3 to delegatee1
-    // DATA3, delegatee1, proxy4 - unaffected
-    assert!(store_get_proxy_task(deps.as_mut().storage, &7).is_some());
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id3,
+    assert!(is_err(
+        add_delegation(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &DELEGATOR1_PUBKEY.to_string(),
             &DELEGATEE1_PUBKEY.to_string(),
-            &proxy4,
-        )
-        .unwrap(),
-        7
-    );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy4,
-        &7,
+            &proxy_delegations,
+            &None,
+        ),
+        "Unregistered proxy with address proxy2",
     ));
 
-    // DATA3, delegatee1, proxy5 - unaffected
-    assert!(store_get_proxy_task(deps.as_mut().storage, &8).is_some());
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id3,
+    // Cannot add same pubkey twice
+    let proxy_1_delegation = ProxyDelegationString {
+        proxy_addr: proxy1,
+        delegation_string: proxy1_delegation_string,
+    };
+    let proxy_delegations: Vec<ProxyDelegationString> =
+        vec![proxy_1_delegation.clone(), proxy_1_delegation];
+
+    assert!(is_err(
+        add_delegation(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &DELEGATOR1_PUBKEY.to_string(),
             &DELEGATEE1_PUBKEY.to_string(),
-            &proxy5,
-        )
-        .unwrap(),
-        8
-    );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy5,
-        &8,
+            &proxy_delegations,
+            &None,
+        ),
+        "Delegation string was already provided for proxy proxy1",
     ));
 
-    // ProxyDelegation 1 was removed with all re-encryption requests
+    // Cannot add delegation for less than minimum number of proxies
     assert!(is_err(
-        request_reencryption(
+        add_delegation(
             deps.as_mut(),
             &delegator1,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
+            &DELEGATOR2_PUBKEY.to_string(),
             &DELEGATEE1_PUBKEY.to_string(),
-            &request_reward_3_proxies,
+            &[],
+            &None,
         ),
-        "Reencryption already requested",
+        "Required at least 1 proxies.",
     ));
 }
 
 #[test]
-fn test_proxy_deactivate_and_remove_with_requests() {
+fn test_provide_reencrypted_fragment() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy_1".to_string());
-    let proxy2 = Addr::unchecked("proxy_2".to_string());
-    let proxy3 = Addr::unchecked("proxy_3".to_string());
-    let proxy4 = Addr::unchecked("proxy_4".to_string());
-    let proxy5 = Addr::unchecked("proxy_5".to_string());
+    let proxy = Addr::unchecked("proxy".to_string());
 
-    let delegator1 = Addr::unchecked("delegator1".to_string());
-    let delegator2 = Addr::unchecked("delegator2".to_string());
+    let delegator = Addr::unchecked("delegator".to_string());
 
     // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy_pubkey1");
-    let proxy2_pubkey: String = String::from("proxy_pubkey2");
-    let proxy3_pubkey: String = String::from("proxy_pubkey3");
-    let proxy4_pubkey: String = String::from("proxy_pubkey4");
-    let proxy5_pubkey: String = String::from("proxy_pubkey5");
+    let delegator_pubkey = String::from(DELEGATOR1_PUBKEY);
+    let delegatee_pubkey = String::from(DELEGATEE1_PUBKEY);
+    let other_delegatee_pubkey: String = String::from("DEK2");
+    let proxy_pubkey: String = String::from("proxy_pubkey");
 
-    let data_id1 = String::from("DATA1");
-    let data_id2 = String::from("DATA2");
-    let data_id3 = String::from("DATA3");
+    let data_id = String::from("DATA");
+    let capsule = String::from(CAPSULE);
 
-    let delegation_string = String::from("DELESTRING");
+    let data_entry = DataEntry {
+        capsule: capsule.clone(),
+        delegator_pubkey: delegator_pubkey.clone(),
+        expires_at: None,
+    };
 
     // Staking
-    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
     let proxy_stake = vec![Coin {
-        denom: stake_denom.clone(),
+        denom: DEFAULT_STAKE_DENOM.to_string(),
         amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
     }];
-    let request_reward_2_proxies = vec![Coin {
-        denom: stake_denom.clone(),
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
         amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
     }];
-    let request_reward_3_proxies = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 3),
-    }];
 
     /*************** Initialise *************/
-    let proxies: Vec<Addr> = vec![
-        proxy1.clone(),
-        proxy2.clone(),
-        proxy3.clone(),
-        proxy4.clone(),
-        proxy5.clone(),
-    ];
     assert!(init_contract(
         deps.as_mut(),
         &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &Some(2),
         &None,
-        &Some(proxies),
-        &stake_denom,
+        &None,
+        &Some(vec![proxy.clone()]),
+        &DEFAULT_STAKE_DENOM.to_string(),
         &None,
         &None,
         &None,
@@ -2997,577 +4016,404 @@ fn test_proxy_deactivate_and_remove_with_requests() {
     // Proxies register -> submits pubkeys
     assert!(register_proxy(
         deps.as_mut(),
-        &proxy1,
+        &proxy,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy1_pubkey,
+        &proxy_pubkey,
         &proxy_stake,
     )
     .is_ok());
-    assert!(register_proxy(
+
+    /*************** Add data and delegations by delegator *************/
+    // Add data by delegator
+    assert!(add_data(
         deps.as_mut(),
-        &proxy2,
+        &delegator,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy2_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy3,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy3_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy4,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy4_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy5,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy5_pubkey,
-        &proxy_stake,
+        &data_id,
+        &data_entry.delegator_pubkey,
+        &capsule,
+        &None,
     )
     .is_ok());
 
-    /*************** Add data and delegations by delegator *************/
-    // Add data by delegator
-    assert!(add_data(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
-        &None,
-    )
-    .is_ok());
-    assert!(add_data(
+    // Add delegation for proxy
+    let proxy_delegation_string = String::from("DS_P1");
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
+        proxy_addr: proxy.clone(),
+        delegation_string: proxy_delegation_string,
+    }];
+
+    assert!(add_delegation(
         deps.as_mut(),
-        &delegator1,
+        &delegator,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id2,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
+        &delegator_pubkey,
+        &delegatee_pubkey,
+        &proxy_delegations,
         &None,
     )
     .is_ok());
-    assert!(add_data(
+
+    /*************** Request re-encryption *************/
+    assert!(request_reencryption(
         deps.as_mut(),
-        &delegator2,
+        &delegator,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id3,
-        &DELEGATOR2_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
-        &None,
+        &data_id,
+        &delegatee_pubkey,
+        &request_reward,
     )
     .is_ok());
 
-    // Add delegations manually
+    /*************** Provide reencrypted fragment *************/
+    assert_eq!(
+        store_get_delegatee_proxy_task(deps.as_mut().storage, &data_id, &delegatee_pubkey, &proxy)
+            .unwrap()
+            .unwrap(),
+        0u64
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy,
+        &0u64,
+    ));
 
-    let delegation1 = ProxyDelegation {
-        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-        delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
-        delegation_string: delegation_string.clone(),
-    };
+    let proxy_fragment = String::from(FRAGMENT_P1_DR1_DE1);
+    // Provide unwanted fragment
+    assert!(is_err(
+        provide_reencrypted_fragment(
+            deps.as_mut(),
+            &proxy,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id,
+            &other_delegatee_pubkey,
+            &proxy_fragment,
+        ),
+        "This fragment was not requested.",
+    ));
 
-    // delegator1 with delegator1_pubkey and proxy1 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &0, &delegation1);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation1.delegator_pubkey,
-        &delegation1.delegatee_pubkey,
-        &proxy1,
-        &0,
-    );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy1, &0);
+    // Not a proxy
+    assert!(is_err(
+        provide_reencrypted_fragment(
+            deps.as_mut(),
+            &creator,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id,
+            &delegatee_pubkey,
+            &proxy_fragment,
+        ),
+        "Proxy not registered",
+    ));
 
-    // delegator1 with delegator1_pubkey and proxy2 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &1, &delegation1);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation1.delegator_pubkey,
-        &delegation1.delegatee_pubkey,
-        &proxy2,
-        &1,
-    );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy2, &1);
+    // Provide fragment correctly
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id,
+        &delegatee_pubkey,
+        &proxy_fragment,
+    )
+    .is_ok());
+    // Fragment already provided
+    assert!(is_err(
+        provide_reencrypted_fragment(
+            deps.as_mut(),
+            &proxy,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id,
+            &delegatee_pubkey,
+            &proxy_fragment,
+        ),
+        "Fragment already provided.",
+    ));
 
-    // delegator1 with delegator1_pubkey and proxy3 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &2, &delegation1);
-    store_set_delegation_id(
+    // This entry is removed when proxy task is done
+    assert!(!store_is_proxy_task_in_queue(
         deps.as_mut().storage,
-        &delegation1.delegator_pubkey,
-        &delegation1.delegatee_pubkey,
-        &proxy3,
-        &2,
-    );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy3, &2);
+        &proxy,
+        &0u64,
+    ));
 
-    let delegation2 = ProxyDelegation {
-        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
-        delegatee_pubkey: DELEGATEE2_PUBKEY.to_string(),
-        delegation_string: delegation_string.clone(),
-    };
+    let task = store_get_proxy_task(deps.as_mut().storage, &0u64)
+        .unwrap()
+        .unwrap();
+    assert_eq!(task.fragment, Some(proxy_fragment));
+}
 
-    // delegator1 with delegator1_pubkey and proxy1 for delegatee2
-    store_set_delegation(deps.as_mut().storage, &3, &delegation2);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation2.delegator_pubkey,
-        &delegation2.delegatee_pubkey,
-        &proxy1,
-        &3,
-    );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy1, &3);
+#[test]
+fn test_provide_reencrypted_fragments() {
+    let mut deps = mock_dependencies();
 
-    // delegator1 with delegator1_pubkey and proxy2 for delegatee2
-    store_set_delegation(deps.as_mut().storage, &4, &delegation2);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation2.delegator_pubkey,
-        &delegation2.delegatee_pubkey,
-        &proxy2,
-        &4,
-    );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy2, &4);
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy = Addr::unchecked("proxy".to_string());
 
-    let delegation3 = ProxyDelegation {
-        delegator_pubkey: DELEGATOR2_PUBKEY.to_string(),
-        delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
-        delegation_string: delegation_string,
-    };
+    let delegator = Addr::unchecked("delegator".to_string());
 
-    // delegator2 with delegator2_pubkey and proxy4 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &5, &delegation3);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation3.delegator_pubkey,
-        &delegation3.delegatee_pubkey,
-        &proxy4,
-        &5,
-    );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy4, &5);
+    // Pubkeys
+    let delegator_pubkey = String::from(DELEGATOR1_PUBKEY);
+    let delegatee1_pubkey = String::from(DELEGATEE1_PUBKEY);
+    let delegatee2_pubkey = String::from(DELEGATEE2_PUBKEY);
+    let proxy_pubkey: String = String::from("proxy_pubkey");
 
-    // delegator2 with delegator2_pubkey and proxy5 for delegatee1
-    store_set_delegation(deps.as_mut().storage, &6, &delegation3);
-    store_set_delegation_id(
-        deps.as_mut().storage,
-        &delegation3.delegator_pubkey,
-        &delegation3.delegatee_pubkey,
-        &proxy5,
-        &6,
-    );
-    store_add_per_proxy_delegation(deps.as_mut().storage, &proxy5, &6);
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let capsule = String::from(CAPSULE);
 
-    // Request re-encryptions
+    // Staking
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT),
+    }];
 
-    // Re-encryption requests with DATA1 to delegatee1
-    assert!(request_reencryption(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &request_reward_3_proxies,
-    )
-    .is_ok());
-    // Re-encryption requests with DATA1 to delegatee2
-    assert!(request_reencryption(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &request_reward_2_proxies,
-    )
-    .is_ok());
-    // Re-encryption requests with DATA2 to delegatee2
-    assert!(request_reencryption(
+    /*************** Initialise *************/
+    assert!(init_contract(
         deps.as_mut(),
-        &delegator1,
+        &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id2,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &request_reward_2_proxies,
+        &None,
+        &None,
+        &Some(vec![proxy.clone()]),
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
     )
     .is_ok());
-    // Re-encryption requests with DATA3 to delegatee1
-    assert!(request_reencryption(
+
+    /*************** Register proxies *************/
+    assert!(register_proxy(
         deps.as_mut(),
-        &delegator2,
+        &proxy,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id3,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &request_reward_2_proxies,
+        &proxy_pubkey,
+        &proxy_stake,
     )
     .is_ok());
 
-    // Complete requests
-    assert!(provide_reencrypted_fragment(
-        deps.as_mut(),
-        &proxy2, DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &String::from("AlCR5oC5v9i8G6IohBPRXg5qfYDP3awqEk2RIusDZo3wAwJEdUq0N3TG1iJ0lqfwRnNos1w3ysr3Vd0WPfFoBc991Pmeib1ZyAX52bfvtjcB3VdzunAnXrH0x259LtNX94oCkymfOr+pOYAe56kPnK9cTGjuwGdgcrV3i5A1ocF5xYsCK1m3Gcr1OeLeHMh26lX7rSDQKP7PoKJC4N/Mgeaqn0wDmP4BflDmAFm7AHvNbq6j5wlYLbZ0SDrpQ/L0axS4huYCYaHbevxWAYoQl2o1m+b5KtVg3c//Iaw1L4RRut+m1GMDhhuHPM1wslLQnN799sLX6itYkBWwTYnCDEc/9NBeCv4/P0LPZSNH6OW8Ta4x08yMA3WPSuBBj3rPcZt5Nydl+Mf4oj47fbmN6AhI9js2P7JBUCi/54jiNUkB4tDgqD2nNzC5ngPVPsnJEcSPx74aW1ppKowvRT7DwM9CNsEAbG0="),
-    )
+    /*************** Add data and delegations by delegator *************/
+    for data_id in [&data_id1, &data_id2] {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &delegator_pubkey,
+            &capsule,
+            &None,
+        )
         .is_ok());
+    }
 
-    // Check proxy state
-    assert!(store_get_is_proxy_active(deps.as_mut().storage, &proxy2));
-    assert!(
-        !get_proxy_tasks(deps.as_mut().storage, &proxy2, &DEFAULT_BLOCK_HEIGHT)
-            .unwrap()
-            .is_empty()
-    );
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2).unwrap();
-    assert_eq!(proxy.state, ProxyState::Registered);
-    assert_eq!(proxy.proxy_pubkey.unwrap(), proxy2_pubkey);
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
+        proxy_addr: proxy.clone(),
+        delegation_string: String::from("DS_P1"),
+    }];
 
-    // Sender is not a proxy
-    assert!(is_err(
-        deactivate_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT),
-        "Sender is not a proxy",
-    ));
+    for delegatee_pubkey in [&delegatee1_pubkey, &delegatee2_pubkey] {
+        assert!(add_delegation(
+            deps.as_mut(),
+            &delegator,
+            DEFAULT_BLOCK_HEIGHT,
+            &delegator_pubkey,
+            delegatee_pubkey,
+            &proxy_delegations,
+            &None,
+        )
+        .is_ok());
+    }
 
-    // Deactivate proxy2
-    assert!(deactivate_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).is_ok());
-    // Already deactivated
-    assert!(is_err(
-        deactivate_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT),
-        "Proxy already deactivated",
-    ));
+    /*************** Request re-encryption *************/
+    for (data_id, delegatee_pubkey) in [
+        (&data_id1, &delegatee1_pubkey),
+        (&data_id1, &delegatee2_pubkey),
+        (&data_id2, &delegatee1_pubkey),
+    ] {
+        assert!(request_reencryption(
+            deps.as_mut(),
+            &delegator,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            delegatee_pubkey,
+            &request_reward,
+        )
+        .is_ok());
+    }
 
-    // Check proxy state
-    assert!(!store_get_is_proxy_active(deps.as_mut().storage, &proxy2));
-    assert!(
-        !get_proxy_tasks(deps.as_mut().storage, &proxy2, &DEFAULT_BLOCK_HEIGHT)
+    let stake_after_requests =
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 3 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT;
+    assert_eq!(
+        store_get_proxy_entry(deps.as_ref().storage, &proxy)
             .unwrap()
-            .is_empty()
+            .unwrap()
+            .stake_amount
+            .u128(),
+        stake_after_requests
     );
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2).unwrap();
-    assert_eq!(proxy.state, ProxyState::Leaving);
-    assert_eq!(proxy.proxy_pubkey.unwrap(), proxy2_pubkey);
-
-    // Check state of delegations
-
-    // ProxyDelegation 1 - Proxy 2 left, but delegation still exists
-    // delgator1, delegatee1, proxy1 - Unaffected
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy1,
-    )
-    .is_some());
-    assert!(store_get_delegation(deps.as_mut().storage, &0).is_some());
-    assert!(store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy1,
-        &0,
-    ));
-
-    // delgator1, delegatee1, proxy2 - Removed
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy2,
-    )
-    .is_none());
-    assert!(store_get_delegation(deps.as_mut().storage, &1).is_none());
-    assert!(!store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy2,
-        &1,
-    ));
-
-    // delgator1, delegatee1, proxy3 - still exist
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy3,
-    )
-    .is_some());
-    assert!(store_get_delegation(deps.as_mut().storage, &2).is_some());
-    assert!(store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy3,
-        &2,
-    ));
 
-    // ProxyDelegation 2 - Number of proxies below threshold, removed entire delegation
-    // delgator1, delegatee2, proxy1 - Removed
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE2_PUBKEY.to_string(),
-        &proxy1,
-    )
-    .is_none());
-    assert!(store_get_delegation(deps.as_mut().storage, &3).is_none());
-    assert!(!store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy1,
-        &3,
+    /*************** Provide reencrypted fragments *************/
+    // Empty batch
+    assert!(is_err(
+        provide_reencrypted_fragments(deps.as_mut(), &proxy, DEFAULT_BLOCK_HEIGHT, &[], &None),
+        "No fragments provided.",
     ));
 
-    // delgator1, delegatee2, proxy2 - Removed
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE2_PUBKEY.to_string(),
-        &proxy2,
-    )
-    .is_none());
-    assert!(store_get_delegation(deps.as_mut().storage, &4).is_none());
-    assert!(!store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy1,
-        &4,
+    // Not a proxy
+    assert!(is_err(
+        provide_reencrypted_fragments(
+            deps.as_mut(),
+            &creator,
+            DEFAULT_BLOCK_HEIGHT,
+            &[ProxyFragment {
+                data_id: data_id1.clone(),
+                delegatee_pubkey: delegatee1_pubkey.clone(),
+                fragment: FRAGMENT_P1_DR1_DE1.to_string(),
+            }],
+            &None,
+        ),
+        "Proxy not registered",
     ));
 
-    // ProxyDelegation 3 - Unaffected
-    // delgator2, delegatee1, proxy4 - Unaffected
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR2_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy4,
-    )
-    .is_some());
-    assert!(store_get_delegation(deps.as_mut().storage, &5).is_some());
-    assert!(store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy4,
-        &5,
+    // Fail entire batch when one fragment was not requested
+    assert!(is_err(
+        provide_reencrypted_fragments(
+            deps.as_mut(),
+            &proxy,
+            DEFAULT_BLOCK_HEIGHT,
+            &[
+                ProxyFragment {
+                    data_id: data_id2.clone(),
+                    delegatee_pubkey: delegatee2_pubkey.clone(),
+                    fragment: FRAGMENT_P1_DR1_DE2.to_string(),
+                },
+                ProxyFragment {
+                    data_id: data_id1.clone(),
+                    delegatee_pubkey: delegatee1_pubkey.clone(),
+                    fragment: FRAGMENT_P1_DR1_DE1.to_string(),
+                },
+            ],
+            &Some(true),
+        ),
+        "This fragment was not requested.",
     ));
 
-    // delgator2, delegatee1, proxy5 - Unaffected
-    assert!(store_get_proxy_delegation_id(
-        deps.as_mut().storage,
-        &DELEGATOR2_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy5,
+    // Process valid fragments and report failed ones
+    let res = provide_reencrypted_fragments(
+        deps.as_mut(),
+        &proxy,
+        DEFAULT_BLOCK_HEIGHT,
+        &[
+            ProxyFragment {
+                data_id: data_id1.clone(),
+                delegatee_pubkey: delegatee1_pubkey.clone(),
+                fragment: FRAGMENT_P1_DR1_DE1.to_string(),
+            },
+            ProxyFragment {
+                data_id: data_id2.clone(),
+                delegatee_pubkey: delegatee2_pubkey.clone(),
+                fragment: FRAGMENT_P1_DR1_DE2.to_string(),
+            },
+            ProxyFragment {
+                data_id: data_id1.clone(),
+                delegatee_pubkey: delegatee2_pubkey.clone(),
+                fragment: FRAGMENT_P1_DR1_DE2.to_string(),
+            },
+            // Same fragment twice
+            ProxyFragment {
+                data_id: data_id1.clone(),
+                delegatee_pubkey: delegatee1_pubkey.clone(),
+                fragment: FRAGMENT_P1_DR1_DE1.to_string(),
+            },
+        ],
+        &None,
     )
-    .is_some());
-    assert!(store_get_delegation(deps.as_mut().storage, &6).is_some());
-    assert!(store_is_proxy_delegation(
-        deps.as_mut().storage,
-        &proxy5,
-        &6,
-    ));
+    .unwrap();
 
-    // Remove proxy by admin
-    assert!(remove_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &proxy2).is_ok());
-    // Already removed
-    assert!(is_err(
-        remove_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &proxy2),
-        "Sender is not a proxy",
-    ));
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("provided_fragments", "2")));
 
-    // Check proxy state
-    assert!(!store_get_is_proxy_active(deps.as_mut().storage, &proxy2));
-    assert!(
-        get_proxy_tasks(deps.as_mut().storage, &proxy2, &DEFAULT_BLOCK_HEIGHT)
+    // Reward is credited for both provided fragments
+    assert_eq!(
+        store_get_proxy_entry(deps.as_ref().storage, &proxy)
             .unwrap()
-            .is_empty()
+            .unwrap()
+            .stake_amount
+            .u128(),
+        stake_after_requests
+            + 2 * (DEFAULT_TASK_REWARD_AMOUNT + DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT)
     );
-    assert!(store_get_proxy_entry(deps.as_mut().storage, &proxy2).is_none());
-
-    // Check state of re-encryption requests
 
-    // Re-encryption requests with DATA1 to delegatee1
-    // DATA1, delegatee1, proxy1 - unaffected
-    assert!(store_get_proxy_task(deps.as_mut().storage, &0).is_some());
     assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy1,
-        )
-        .unwrap(),
-        0
+        get_all_fragments(deps.as_ref().storage, &data_id1, &delegatee1_pubkey).unwrap(),
+        vec![FRAGMENT_P1_DR1_DE1.to_string()]
     );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy1,
-        &0,
-    ));
-
-    // DATA1, delegatee1, proxy2 - incomplete and removed
-    assert!(
-        store_get_proxy_task(deps.as_mut().storage, &1)
-            .unwrap()
-            .abandoned
+    assert_eq!(
+        get_all_fragments(deps.as_ref().storage, &data_id1, &delegatee2_pubkey).unwrap(),
+        vec![FRAGMENT_P1_DR1_DE2.to_string()]
     );
-    assert!(!store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy1,
-        &1,
-    ));
-
-    // DATA1, delegatee1, proxy3 - unaffected
     assert!(
-        !store_get_proxy_task(deps.as_mut().storage, &2)
+        get_all_fragments(deps.as_ref().storage, &data_id2, &delegatee1_pubkey)
             .unwrap()
-            .abandoned
-    );
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy3,
-        )
-        .unwrap(),
-        2
+            .is_empty()
     );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy3,
-        &2,
-    ));
 
-    // Re-encryption requests with DATA1 to delegatee1
-    // DATA1, delegatee2, proxy1 - Not removed because it can still be completed
-    assert!(store_get_proxy_task(deps.as_mut().storage, &3).is_some());
+    // Only unfinished task remains in queue
     assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE2_PUBKEY.to_string(),
-            &proxy1,
-        )
-        .unwrap(),
-        3
+        store_get_all_proxy_tasks_in_queue(deps.as_ref().storage, &proxy),
+        vec![2u64]
     );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy1,
-        &3,
-    ));
-
-    // DATA1, delegatee2, proxy2 - complete - unaffected
-    // Request can still be obtained by delegatee
-    assert!(store_get_proxy_task(deps.as_mut().storage, &4).is_some());
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id1,
-            &DELEGATEE2_PUBKEY.to_string(),
-            &proxy2,
-        )
-        .unwrap(),
-        4
-    );
-    // Request is completed - won't appear in proxy tasks
-    assert!(!store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy2,
-        &4,
-    ));
-
-    // Re-encryption requests with DATA2 to delegatee2
-    // DATA2, delegatee2, proxy1 - incomplete and not removed
-    assert!(store_get_proxy_task(deps.as_mut().storage, &5).is_some());
-    assert!(store_get_delegatee_proxy_task(
-        deps.as_mut().storage,
-        &data_id2,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &proxy1,
-    )
-    .is_some());
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy1,
-        &5,
-    ));
-
-    // DATA1, delegatee2, proxy2 - complete - removed because more than threshold fragments cannot be provided
-    // Request can still be obtained by delegatee
-    assert!(
-        store_get_proxy_task(deps.as_mut().storage, &6)
-            .unwrap()
-            .abandoned
-    );
-    // Request is completed - won't appear in proxy tasks
-    assert!(!store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy2,
-        &6,
-    ));
-
-    // Re-encryption requests with DATA3 to delegatee1
-    // DATA3, delegatee1, proxy4 - unaffected
-    assert!(store_get_proxy_task(deps.as_mut().storage, &7).is_some());
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id3,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy4,
-        )
-        .unwrap(),
-        7
-    );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy4,
-        &7,
-    ));
-
-    // DATA3, delegatee1, proxy5 - unaffected
-    assert!(store_get_proxy_task(deps.as_mut().storage, &8).is_some());
-    assert_eq!(
-        store_get_delegatee_proxy_task(
-            deps.as_mut().storage,
-            &data_id3,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &proxy5,
-        )
-        .unwrap(),
-        8
-    );
-    assert!(store_is_proxy_task_in_queue(
-        deps.as_mut().storage,
-        &proxy5,
-        &8,
-    ));
-}
+}
 
 #[test]
-fn test_proxy_stake_withdrawal() {
+fn test_contract_lifecycle() {
     let mut deps = mock_dependencies();
+
+    // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy1".to_string());
-    let proxy2 = Addr::unchecked("proxy2".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
 
-    let proxy1_pubkey: String = String::from("proxy1_pubkey");
-    let proxy2_pubkey: String = String::from("proxy2_pubkey");
+    let delegator = Addr::unchecked("delegator".to_string());
 
-    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone()];
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+    let proxy2_pubkey: String = String::from("proxy_pubkey2");
+
+    let data_id = String::from("DATA");
+
+    let data_entry = DataEntry {
+        capsule: CAPSULE.to_string(),
+        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+        expires_at: None,
+    };
 
     // Staking
     let stake_denom = DEFAULT_STAKE_DENOM.to_string();
     let proxy_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + 50),
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
     }];
 
+    /*************** Initialise *************/
+    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone()];
     assert!(init_contract(
         deps.as_mut(),
         &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &None,
+        &Some(2),
         &None,
         &Some(proxies),
         &stake_denom,
@@ -3580,6 +4426,8 @@ fn test_proxy_stake_withdrawal() {
     )
     .is_ok());
 
+    /*************** Register proxies *************/
+    // Proxies register -> submits pubkeys
     assert!(register_proxy(
         deps.as_mut(),
         &proxy1,
@@ -3597,249 +4445,514 @@ fn test_proxy_stake_withdrawal() {
     )
     .is_ok());
 
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1).unwrap();
+    /*************** Add data and delegations by delegator *************/
+    // Add data by delegator
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id,
+        &data_entry.delegator_pubkey,
+        &CAPSULE.to_string(),
+        &None,
+    )
+    .is_ok());
+
+    // Add 2 delegations for 2 proxies
+    let proxy1_delegation_string = String::from("DS_P1");
+    let proxy2_delegation_string = String::from("DS_P2");
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: proxy1_delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: proxy2_delegation_string,
+        },
+    ];
+
     assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + 50
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::NonExisting
     );
 
-    // Proxy1 is trying to withdraw more than is available gives you maximum available stake
-    let withdraw_res = withdraw_stake(
+    assert!(add_delegation(
         deps.as_mut(),
-        &proxy1,
+        &delegator,
         DEFAULT_BLOCK_HEIGHT,
-        &Some(Uint128::new(51)),
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
     )
-    .unwrap();
+    .is_ok());
+
     assert_eq!(
-        withdraw_res.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy1.to_string(),
-            amount: vec![Coin::new(50, stake_denom.as_str())],
-        })
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::Active
     );
 
-    // All stake of proxy1 withdrawn
-    assert!(is_err(
-        withdraw_stake(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT, &None),
-        "Not enough stake to withdraw",
-    ));
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
 
-    // Proxy2 is trying to withdraw maximum available stake
-    let withdraw_res = withdraw_stake(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT, &None).unwrap();
+    // No tasks yet
+    assert!(get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy1,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None
+    )
+    .unwrap()
+    .proxy_tasks
+    .is_empty());
+    assert!(get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy2,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None
+    )
+    .unwrap()
+    .proxy_tasks
+    .is_empty());
+
+    let state = store_get_state(deps.as_mut().storage).unwrap();
     assert_eq!(
-        withdraw_res.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy2.to_string(),
-            amount: vec![Coin::new(50, stake_denom.as_str())],
-        })
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &mock_block_height(0),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Inaccessible
     );
 
-    // All stake of proxy2 withdrawn
-    assert!(is_err(
-        withdraw_stake(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT, &None),
-        "Not enough stake to withdraw",
-    ));
+    /*************** Request reencryption by delegator *************/
+
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
 
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1).unwrap();
+    let state = store_get_state(deps.as_mut().storage).unwrap();
     assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &mock_block_height(0),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Ready
     );
 
-    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2).unwrap();
+    // Check number of tasks
     assert_eq!(
-        proxy.stake_amount.u128(),
-        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
+        1
+    );
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
+        1
     );
 
-    // Insufficient stake
-    assert!(is_err(
-        withdraw_stake(
-            deps.as_mut(),
-            &proxy1,
-            DEFAULT_BLOCK_HEIGHT,
-            &Some(Uint128::new(1)),
-        ),
-        "Not enough stake to withdraw",
-    ));
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
 
-    // Remaining stake can be withdrawn only by unregistering
-    let unregister_res = unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
+    // Check number of requests
     assert_eq!(
-        unregister_res.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy1.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT,
-                stake_denom.as_str(),
-            )],
-        })
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
+        2
+    );
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
+        2
     );
 
-    // Proxy unregistered
-    assert!(is_err(
-        withdraw_stake(
-            deps.as_mut(),
-            &proxy1,
-            DEFAULT_BLOCK_HEIGHT,
-            &Some(Uint128::new(1)),
-        ),
-        "Not enough stake to withdraw",
-    ));
-}
-
-#[test]
-fn test_proxy_add_stake() {
-    let mut deps = mock_dependencies();
-    let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy1".to_string());
-
-    let proxy1_pubkey: String = String::from("proxy1_pubkey");
-
-    let proxies: Vec<Addr> = vec![proxy1.clone()];
-
-    // Staking
-    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let proxy_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
-    }];
-
-    let proxy_additional_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(50),
-    }];
-
-    let proxy_wrong_coins_additional_stake = vec![
-        Coin {
-            denom: stake_denom.clone(),
-            amount: Uint128::new(50),
-        },
-        Coin {
-            denom: String::from("othercoin"),
-            amount: Uint128::new(20),
-        },
-    ];
+    /*************** Process reencryption by proxies *************/
+    let all_tasks = store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1);
+    assert_eq!(all_tasks.len(), 2);
 
-    assert!(init_contract(
-        deps.as_mut(),
-        &creator,
-        DEFAULT_BLOCK_HEIGHT,
-        &None,
-        &None,
-        &Some(proxies),
-        &stake_denom,
-        &None,
-        &None,
-        &None,
-        &None,
-        &None,
+    // Check if proxy got task 1
+    let proxy1_task1 = &get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy1,
+        &DEFAULT_BLOCK_HEIGHT,
         &None,
+        None,
     )
-    .is_ok());
+    .unwrap()
+    .proxy_tasks[0];
+    assert_eq!(
+        proxy1_task1,
+        &ProxyTaskResponse {
+            data_id: data_id.clone(),
+            capsule: data_entry.capsule.clone(),
+            delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+            delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+            delegation_string: proxy1_delegation_string.clone(),
+        }
+    );
 
-    assert!(register_proxy(
+    // Check stake before finishing re-encryption
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 2 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
+    );
+
+    // Proxy1 provides fragment for task1
+    let proxy1_fragment1: String = String::from("Agn8MTBWSKzz277FLeNKvhOwa3juw7HBciLmyA/3kZ2hAtQv0l/B+Ej2vQLxZDx+MHDr5uevth9PzntoIz6gbPI1xJk3dVwZohs3YgdaXJsBXpAambF1FpOGrola7KcwjtQDOL6tYr3e6dlMgsW9GnONyZUWk15ixjxdrAIZfp8qWAMCbOd9fCO820cnEqBeQHpit75l8gxb6Al3s28p4uMFeq4Dzsh5SbQgRk7KjI9LEq2a9YzQ2ts3O5KEx3SuZoCOE0UDns625ayBRPD5BHdYwGaCGo/w6oJ5PvRp7rEpMSvxpOACu5HXcj2KNZnzAc2QGNrHmrAIxxS4pUbp7ffoPjSK/eGOs3Yh2IaeLQMzj2FNpUCYii6D3KJMT5sWqdKQV+5Aw6ebgujLY0o4Gs2aJ3toE3GuNuSfwFKzySmpq5CfSGaJJftZDYt72g7t8cRKVFXT6D8ugCXfMVL6GRE7adJEkYU=");
+    assert!(provide_reencrypted_fragment(
         deps.as_mut(),
         &proxy1,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy1_pubkey,
-        &proxy_stake,
+        &data_id,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy1_fragment1,
     )
     .is_ok());
 
+    // Check if proxy got reward
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 1 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
+            + DEFAULT_TASK_REWARD_AMOUNT
+    );
+
+    // Proxy2 tries to provides fragment already provided by proxy1 for task1
     assert!(is_err(
-        add_stake(
+        provide_reencrypted_fragment(
             deps.as_mut(),
-            &proxy1,
+            &proxy2,
             DEFAULT_BLOCK_HEIGHT,
-            &proxy_wrong_coins_additional_stake,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy1_fragment1,
         ),
-        "Expected 1 Coin with denom atestfet",
+        "Fragment already provided by other proxy.",
     ));
-    assert!(add_stake(
+
+    // Check numbers of tasks
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
+        1
+    );
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
+        2
+    );
+
+    // Check available fragments
+    assert_eq!(
+        get_all_fragments(
+            deps.as_mut().storage,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        vec![proxy1_fragment1.clone()]
+    );
+    assert_eq!(
+        get_all_fragments(
+            deps.as_mut().storage,
+            &data_id,
+            &DELEGATEE2_PUBKEY.to_string(),
+        )
+        .unwrap()
+        .len(),
+        0
+    );
+
+    // Check if proxy got task 2
+    let proxy1_task2 = &get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy1,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks[0];
+    assert_eq!(
+        proxy1_task2,
+        &ProxyTaskResponse {
+            data_id: data_id.clone(),
+            capsule: data_entry.capsule,
+            delegatee_pubkey: DELEGATEE2_PUBKEY.to_string(),
+            delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+            delegation_string: proxy1_delegation_string,
+        }
+    );
+
+    // Proxy1 provides fragment for task1
+    let proxy1_fragment2: String = String::from("A5fWxYyjkfJu/k2oq5A6w+pLgRtWRIKu2uEHe/i0AGSiAsK7jq0a7KjTeiBCBRTC64ATDb/QfYQ9CBoiF5FDdbJwh3Yb5RoZgkclP0cqNtftZnRCdVUuycy2UpQ7f4x5tFkCkymfOr+pOYAe56kPnK9cTGjuwGdgcrV3i5A1ocF5xYsD0mFd9APeYHeRjAIgPzM3na8xJuYgSdY9FA6upZOYqxkDGWHWjB6Uvjby3zTbN+A8vuQmHRx0NST4ICR5HfKCCPQCE6/D/Dep4lyf4v9E03VgMisZKFWW7+YP5qAIWgNDSPoCb8OqfDTrYzJSKGZg+ti4l/Cjo5PaqmlZlj1MCR/Rb906nywBtIQCU9iVAHGUnE8h9QV+kYWik4s2Vcq2W6r/Y6MXnYLK9JsYpIwny6zDwHzOwfTk4Wn9mLGENf4q2s/5ZeM/1TJIu6wECI+L5zmGMWl40iloHhHcxeCVKwKnM0s=");
+    assert!(provide_reencrypted_fragment(
         deps.as_mut(),
         &proxy1,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy_additional_stake,
+        &data_id,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy1_fragment2,
     )
     .is_ok());
 
-    // Check if withdrawn stake amount
-    let unregister_res = unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
+    // Check if proxy got reward
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+        .unwrap()
+        .unwrap();
     assert_eq!(
-        unregister_res.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy1.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + 50,
-                stake_denom.as_str(),
-            )],
-        })
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + 2 * DEFAULT_TASK_REWARD_AMOUNT
     );
-}
 
-#[test]
-fn test_proxy_insufficient_funds_task_skip() {
-    let mut deps = mock_dependencies();
+    // All tasks completed for proxy1
+    assert!(get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy1,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None
+    )
+    .unwrap()
+    .proxy_tasks
+    .is_empty());
+    // But not for proxy2
+    assert!(!get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy2,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None
+    )
+    .unwrap()
+    .proxy_tasks
+    .is_empty());
 
-    // Addresses
-    let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy_1".to_string());
-    let proxy2 = Addr::unchecked("proxy_2".to_string());
-    let proxy3 = Addr::unchecked("proxy_3".to_string());
+    // Check available fragments
+    assert_eq!(
+        get_all_fragments(
+            deps.as_mut().storage,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        vec![proxy1_fragment1]
+    );
+    assert_eq!(
+        get_all_fragments(
+            deps.as_mut().storage,
+            &data_id,
+            &DELEGATEE2_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        vec![proxy1_fragment2]
+    );
 
-    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let state = store_get_state(deps.as_mut().storage).unwrap();
+    assert_eq!(
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &mock_block_height(0),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Ready
+    );
+    assert_eq!(
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::Active
+    );
+
+    // Re-encryption was requested in past
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegator,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "Reencryption already requested",
+    ));
+
+    // Proxy 2 leaves - all its delegations gets deleted
+    assert!(unregister_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).is_ok());
+
+    // Check proxy stake amount
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+        .unwrap()
+        .unwrap();
+    assert_eq!(proxy.stake_amount.u128(), 0);
+
+    // Proxy 2 gets back
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    // Check proxy stake amount
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+    );
+
+    let state = store_get_state(deps.as_mut().storage).unwrap();
+    assert_eq!(
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &mock_block_height(0),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Abandoned
+    );
+    assert_eq!(
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::NonExisting
+    );
+
+    // ProxyDelegation can be re-created again
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+
+    // Check if all stake gets returned to proxy after un-registered
+    let unregister_response =
+        unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
+    assert_eq!(
+        unregister_response.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy1.to_string(),
+            amount: vec![Coin::new(
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + DEFAULT_TASK_REWARD_AMOUNT * 2,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+}
+
+#[test]
+fn test_proxy_unregister_with_requests() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+    let proxy4 = Addr::unchecked("proxy_4".to_string());
+    let proxy5 = Addr::unchecked("proxy_5".to_string());
+
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
 
     // Pubkeys
     let proxy1_pubkey: String = String::from("proxy_pubkey1");
     let proxy2_pubkey: String = String::from("proxy_pubkey2");
     let proxy3_pubkey: String = String::from("proxy_pubkey3");
+    let proxy4_pubkey: String = String::from("proxy_pubkey4");
+    let proxy5_pubkey: String = String::from("proxy_pubkey5");
 
     let data_id1 = String::from("DATA1");
     let data_id2 = String::from("DATA2");
     let data_id3 = String::from("DATA3");
 
-    let capsule = String::from("capsule");
-
     let delegation_string = String::from("DELESTRING");
 
     // Staking
     let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let minimum_proxy_stake_amount: u128 = 100;
-    let per_proxy_task_reward_amount: u128 = 40;
-    let per_task_slash_stake_amount: u128 = 98;
-
-    let proxy1_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(minimum_proxy_stake_amount),
-    }];
-
-    let proxy2_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(2 * minimum_proxy_stake_amount),
-    }];
-
-    let proxy3_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(3 * minimum_proxy_stake_amount),
-    }];
-
-    let request_reward_1_proxy = vec![Coin {
+    let proxy_stake = vec![Coin {
         denom: stake_denom.clone(),
-        amount: Uint128::new(per_proxy_task_reward_amount * 1),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
     }];
     let request_reward_2_proxies = vec![Coin {
         denom: stake_denom.clone(),
-        amount: Uint128::new(per_proxy_task_reward_amount * 2),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
     }];
     let request_reward_3_proxies = vec![Coin {
         denom: stake_denom.clone(),
-        amount: Uint128::new(per_proxy_task_reward_amount * 3),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 3),
     }];
 
     /*************** Initialise *************/
-    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone(), proxy3.clone()];
+    let proxies: Vec<Addr> = vec![
+        proxy1.clone(),
+        proxy2.clone(),
+        proxy3.clone(),
+        proxy4.clone(),
+        proxy5.clone(),
+    ];
     assert!(init_contract(
         deps.as_mut(),
         &creator,
@@ -3848,9 +4961,9 @@ fn test_proxy_insufficient_funds_task_skip() {
         &None,
         &Some(proxies),
         &stake_denom,
-        &Some(Uint128::new(minimum_proxy_stake_amount)),
-        &Some(Uint128::new(per_proxy_task_reward_amount)),
-        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
         &None,
         &None,
         &None,
@@ -3864,7 +4977,7 @@ fn test_proxy_insufficient_funds_task_skip() {
         &proxy1,
         DEFAULT_BLOCK_HEIGHT,
         &proxy1_pubkey,
-        &proxy1_stake,
+        &proxy_stake,
     )
     .is_ok());
     assert!(register_proxy(
@@ -3872,7 +4985,7 @@ fn test_proxy_insufficient_funds_task_skip() {
         &proxy2,
         DEFAULT_BLOCK_HEIGHT,
         &proxy2_pubkey,
-        &proxy2_stake,
+        &proxy_stake,
     )
     .is_ok());
     assert!(register_proxy(
@@ -3880,7 +4993,23 @@ fn test_proxy_insufficient_funds_task_skip() {
         &proxy3,
         DEFAULT_BLOCK_HEIGHT,
         &proxy3_pubkey,
-        &proxy3_stake,
+        &proxy_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy4,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy4_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy5,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy5_pubkey,
+        &proxy_stake,
     )
     .is_ok());
 
@@ -3892,7 +5021,7 @@ fn test_proxy_insufficient_funds_task_skip() {
         DEFAULT_BLOCK_HEIGHT,
         &data_id1,
         &DELEGATOR1_PUBKEY.to_string(),
-        &capsule,
+        &CAPSULE.to_string(),
         &None,
     )
     .is_ok());
@@ -3902,57 +5031,109 @@ fn test_proxy_insufficient_funds_task_skip() {
         DEFAULT_BLOCK_HEIGHT,
         &data_id2,
         &DELEGATOR1_PUBKEY.to_string(),
-        &capsule,
+        &CAPSULE.to_string(),
         &None,
     )
     .is_ok());
     assert!(add_data(
         deps.as_mut(),
-        &delegator1,
+        &delegator2,
         DEFAULT_BLOCK_HEIGHT,
         &data_id3,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &capsule,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &CAPSULE.to_string(),
         &None,
     )
     .is_ok());
 
-    let proxy_delegations: Vec<ProxyDelegationString> = vec![
-        ProxyDelegationString {
-            proxy_addr: proxy1.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
+    // Add delegations manually
+
+    let delegation1 = ProxyDelegation {
+        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+        delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+        delegation_string: delegation_string.clone(),
+        proxy_addr: proxy1.clone(),
+    };
+
+    // delegator1 with delegator1_pubkey and proxy1 for delegatee1
+    store_set_delegation(deps.as_mut().storage, &0, &delegation1).unwrap();
+
+    // delegator1 with delegator1_pubkey and proxy2 for delegatee1
+    store_set_delegation(
+        deps.as_mut().storage,
+        &1,
+        &ProxyDelegation {
             proxy_addr: proxy2.clone(),
-            delegation_string: delegation_string.clone(),
+            ..delegation1.clone()
         },
-        ProxyDelegationString {
+    )
+    .unwrap();
+
+    // delegator1 with delegator1_pubkey and proxy3 for delegatee1
+    store_set_delegation(
+        deps.as_mut().storage,
+        &2,
+        &ProxyDelegation {
             proxy_addr: proxy3.clone(),
-            delegation_string: delegation_string,
+            ..delegation1.clone()
         },
-    ];
+    )
+    .unwrap();
 
-    assert!(add_delegation(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy_delegations,
-        &None,
+    let delegation2 = ProxyDelegation {
+        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+        delegatee_pubkey: DELEGATEE2_PUBKEY.to_string(),
+        delegation_string: delegation_string.clone(),
+        proxy_addr: proxy1.clone(),
+    };
+
+    // delegator1 with delegator1_pubkey and proxy1 for delegatee2
+    store_set_delegation(deps.as_mut().storage, &3, &delegation2).unwrap();
+
+    // delegator1 with delegator1_pubkey and proxy2 for delegatee2
+    store_set_delegation(
+        deps.as_mut().storage,
+        &4,
+        &ProxyDelegation {
+            proxy_addr: proxy2.clone(),
+            ..delegation2.clone()
+        },
     )
-    .is_ok());
+    .unwrap();
 
-    // Create first reencryption request -- All proxies has enough stake
-    assert_eq!(
-        get_n_available_proxies_from_delegation(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-            &per_task_slash_stake_amount,
-        ),
-        3
+    let delegation3 = ProxyDelegation {
+        delegator_pubkey: DELEGATOR2_PUBKEY.to_string(),
+        delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+        delegation_string: delegation_string,
+        proxy_addr: proxy4.clone(),
+    };
+
+    // delegator2 with delegator2_pubkey and proxy4 for delegatee1
+    store_set_delegation(deps.as_mut().storage, &5, &delegation3).unwrap();
+
+    // delegator2 with delegator2_pubkey and proxy5 for delegatee1
+    store_set_delegation(
+        deps.as_mut().storage,
+        &6,
+        &ProxyDelegation {
+            proxy_addr: proxy5.clone(),
+            ..delegation3.clone()
+        },
+    )
+    .unwrap();
+
+    // Check proxies stake amount
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
     );
+
+    // Request re-encryptions
+
+    // Re-encryption requests with DATA1 to delegatee1
     assert!(request_reencryption(
         deps.as_mut(),
         &delegator1,
@@ -3963,183 +5144,1665 @@ fn test_proxy_insufficient_funds_task_skip() {
     )
     .is_ok());
 
+    // Check proxy2 stake amount after creating reencryption request
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+        .unwrap()
+        .unwrap();
     assert_eq!(
-        store_get_proxy_entry(deps.as_mut().storage, &proxy2)
-            .unwrap()
-            .stake_amount
-            .u128(),
-        102
-    );
-
-    // Create second reencryption request -- 1 proxies skipped because of insufficient funds
-    assert_eq!(
-        get_n_available_proxies_from_delegation(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-            &per_task_slash_stake_amount,
-        ),
-        2
-    );
-    assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::Active
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
     );
 
+    // Re-encryption requests with DATA1 to delegatee2
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &request_reward_2_proxies,
+    )
+    .is_ok());
+    // Re-encryption requests with DATA2 to delegatee2
     assert!(request_reencryption(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &data_id2,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &request_reward_2_proxies,
+    )
+    .is_ok());
+    // Re-encryption requests with DATA3 to delegatee1
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator2,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id3,
         &DELEGATEE1_PUBKEY.to_string(),
         &request_reward_2_proxies,
     )
     .is_ok());
 
-    // Creating third reencryption request fails -- 2 proxies skipped because of insufficient funds
+    // Check proxy2 stake amount after creating reencryption requests
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+        .unwrap()
+        .unwrap();
     assert_eq!(
-        get_n_available_proxies_from_delegation(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-            &per_task_slash_stake_amount,
-        ),
-        1
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 3 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
     );
 
-    assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::ProxiesAreBusy
-    );
+    /*
+    // Provide wrong fragment
     assert!(is_err(
-        request_reencryption(
+        provide_reencrypted_fragment(
             deps.as_mut(),
-            &delegator1,
+            &proxy2,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id2,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &request_reward_1_proxy,
+            &data_id1,
+            &DELEGATEE2_PUBKEY.to_string(),
+            &FRAGMENT_P2_DR1_DE1.to_string(),
         ),
-        "Proxies are too busy, try again later. Available 1 proxies out of 3, minimum is 2",
+        "Invalid KeyFrag signature",
     ));
+     */
 
-    // Requests:
-    // req1 - (0)proxy1, (1)proxy2, (2)proxy3
-    // req2 - (3)proxy2, (4)proxy3
+    // Complete tasks
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &FRAGMENT_P2_DR1_DE2.to_string(),
+    )
+    .is_ok());
 
-    // Check proxy2 unregister
-    // Proxy3 gets slashed for 2 unfinished tasks - 1 portion is returned to delegator
+    // Check proxy2 stake amount after finishing one reencryption task
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 2 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
+            + DEFAULT_TASK_REWARD_AMOUNT
+    );
+
+    // Unregister proxy2
     let unregister_response =
         unregister_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).unwrap();
+
+    // Check if stake from unfinished request get returned to delegator
     assert_eq!(
         unregister_response.messages[0],
         SubMsg::new(BankMsg::Send {
             to_address: delegator1.to_string(),
             amount: vec![Coin::new(
-                per_proxy_task_reward_amount * 2,
+                DEFAULT_TASK_REWARD_AMOUNT * 2,
                 stake_denom.as_str(),
             )],
         })
     );
+
+    // Check if stake gets returned to proxy and if proxy got slashed
     assert_eq!(
         unregister_response.messages[1],
         SubMsg::new(BankMsg::Send {
             to_address: proxy2.to_string(),
             amount: vec![Coin::new(
-                2 * minimum_proxy_stake_amount - 2 * per_task_slash_stake_amount,
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + DEFAULT_TASK_REWARD_AMOUNT
+                    - 2 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT,
                 stake_denom.as_str(),
             )],
         })
     );
 
-    // Requests:
-    // req1 - (0)proxy1, (2)proxy3
-    // req2 - (4)proxy3 - Unfinishable
-
-    // Check if only tasks for request2 are deleted
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
-        1
-    );
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
-        0
-    );
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy3).len(),
-        2
-    );
+    // Already unregistered
+    assert!(is_err(
+        unregister_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT),
+        "Proxy already unregistered",
+    ));
 
-    // Check proxy3 unregister
-    // Proxy3 gets slashed for 1 unfinished task, delegator gets full refund for request that cannot be completed
-    let unregister_response =
-        unregister_proxy(deps.as_mut(), &proxy3, DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(
-        unregister_response.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: delegator1.to_string(),
-            amount: vec![Coin::new(
-                per_proxy_task_reward_amount * 3,
-                stake_denom.as_str(),
-            )],
-        })
-    );
-    assert_eq!(
-        unregister_response.messages[1],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy3.to_string(),
-            amount: vec![Coin::new(
-                3 * minimum_proxy_stake_amount - 2 * per_task_slash_stake_amount,
-                stake_denom.as_str(),
-            )],
-        })
-    );
+    // Check state of delegations
 
-    // Requests:
-    // req1 - deleted
-    // req2 - deleted
+    // ProxyDelegation 1 - Stays
+    // delgator1, delegatee1, proxy1 - Unaffected
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy1,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_get_delegation(deps.as_mut().storage, &0)
+        .unwrap()
+        .is_some());
+    assert!(store_is_proxy_delegation(deps.as_mut().storage, &proxy1, &0,).unwrap());
 
-    // Check proxy tasks
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
-        1
-    );
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
-        0
-    );
-    assert_eq!(
-        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy3).len(),
-        0
-    );
+    // delgator1, delegatee1, proxy2 - Removed
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy2,
+    )
+    .unwrap()
+    .is_none());
+    assert!(store_get_delegation(deps.as_mut().storage, &1)
+        .unwrap()
+        .is_none());
+    assert!(!store_is_proxy_delegation(deps.as_mut().storage, &proxy2, &1,).unwrap());
 
-    // Check proxy1 unregister
-    // Proxy1 gets slashed for not finishing taskss
-    let unregister_response =
-        unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(
-        unregister_response.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy1.to_string(),
-            amount: vec![Coin::new(
-                minimum_proxy_stake_amount - per_task_slash_stake_amount,
-                stake_denom.as_str(),
-            )],
-        })
-    );
-}
+    // delgator1, delegatee1, proxy3 - stays
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy3,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_get_delegation(deps.as_mut().storage, &2)
+        .unwrap()
+        .is_some());
+    assert!(store_is_proxy_delegation(deps.as_mut().storage, &proxy3, &2,).unwrap());
 
-#[test]
-fn test_get_n_minimum_proxies_for_refund() {
-    let mut state = State {
+    // ProxyDelegation 2 - Number of proxies below threshold, removed entire delegation
+    // delgator1, delegatee2, proxy1 - Removed
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy1,
+    )
+    .unwrap()
+    .is_none());
+    assert!(store_get_delegation(deps.as_mut().storage, &3)
+        .unwrap()
+        .is_none());
+    assert!(!store_is_proxy_delegation(deps.as_mut().storage, &proxy1, &3,).unwrap());
+
+    // delgator1, delegatee2, proxy2 - Removed
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy2,
+    )
+    .unwrap()
+    .is_none());
+    assert!(store_get_delegation(deps.as_mut().storage, &4)
+        .unwrap()
+        .is_none());
+    assert!(!store_is_proxy_delegation(deps.as_mut().storage, &proxy1, &4,).unwrap());
+
+    // ProxyDelegation 3 - Unaffected
+    // delgator2, delegatee1, proxy4 - Unaffected
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy4,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_get_delegation(deps.as_mut().storage, &5)
+        .unwrap()
+        .is_some());
+    assert!(store_is_proxy_delegation(deps.as_mut().storage, &proxy4, &5,).unwrap());
+
+    // delgator2, delegatee1, proxy5 - Unaffected
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy5,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_get_delegation(deps.as_mut().storage, &6)
+        .unwrap()
+        .is_some());
+    assert!(store_is_proxy_delegation(deps.as_mut().storage, &proxy5, &6,).unwrap());
+
+    // Check state of re-encryption requests
+
+    // Re-encryption requests with DATA1 to delegatee1
+    // DATA1, delegatee1, proxy1 - unaffected
+    assert!(store_get_proxy_task(deps.as_mut().storage, &0)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy1,
+        )
+        .unwrap()
+        .unwrap(),
+        0
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy1,
+        &0,
+    ));
+
+    // DATA1, delegatee1, proxy2 - incomplete and removed
+    assert!(
+        store_get_proxy_task(deps.as_mut().storage, &1)
+            .unwrap()
+            .unwrap()
+            .abandoned
+    );
+    assert!(!store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy1,
+        &1,
+    ));
+
+    // DATA1, delegatee1, proxy3 - unaffected
+    assert!(store_get_proxy_task(deps.as_mut().storage, &2)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy3,
+        )
+        .unwrap()
+        .unwrap(),
+        2
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy3,
+        &2,
+    ));
+
+    // Re-encryption requests with DATA1 to delegatee1
+    // DATA1, delegatee2, proxy1 - Not removed because it can still be completed
+    assert!(store_get_proxy_task(deps.as_mut().storage, &3)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
+            &DELEGATEE2_PUBKEY.to_string(),
+            &proxy1,
+        )
+        .unwrap()
+        .unwrap(),
+        3
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy1,
+        &3,
+    ));
+
+    // DATA1, delegatee2, proxy2 - complete - unaffected
+    // Request can still be obtained by delegatee
+    assert!(store_get_proxy_task(deps.as_mut().storage, &4)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
+            &DELEGATEE2_PUBKEY.to_string(),
+            &proxy2,
+        )
+        .unwrap()
+        .unwrap(),
+        4
+    );
+    // Request is completed - won't appear in proxy tasks
+    assert!(!store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy2,
+        &4,
+    ));
+
+    // Re-encryption requests with DATA2 to delegatee2
+    // DATA2, delegatee2, proxy1 - incomplete, not removed
+    assert!(store_get_proxy_task(deps.as_mut().storage, &5)
+        .unwrap()
+        .is_some());
+    assert!(store_get_delegatee_proxy_task(
+        deps.as_mut().storage,
+        &data_id2,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy1,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy1,
+        &5,
+    ));
+
+    // DATA1, delegatee2, proxy2 - complete - removed because more than threshold fragments cannot be provided
+    // Request can still be obtained by delegatee
+    assert!(
+        store_get_proxy_task(deps.as_mut().storage, &6)
+            .unwrap()
+            .unwrap()
+            .abandoned
+    );
+    // Request is completed - won't appear in proxy tasks
+    assert!(!store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy2,
+        &6,
+    ));
+
+    // Re-encryption requests with DATA3 to delegatee1
+    // DATA3, delegatee1, proxy4 - unaffected
+    assert!(store_get_proxy_task(deps.as_mut().storage, &7)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id3,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy4,
+        )
+        .unwrap()
+        .unwrap(),
+        7
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy4,
+        &7,
+    ));
+
+    // DATA3, delegatee1, proxy5 - unaffected
+    assert!(store_get_proxy_task(deps.as_mut().storage, &8)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id3,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy5,
+        )
+        .unwrap()
+        .unwrap(),
+        8
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy5,
+        &8,
+    ));
+
+    // ProxyDelegation 1 was removed with all re-encryption requests
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward_3_proxies,
+        ),
+        "Reencryption already requested",
+    ));
+}
+
+#[test]
+fn test_proxy_deactivate_and_remove_with_requests() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+    let proxy4 = Addr::unchecked("proxy_4".to_string());
+    let proxy5 = Addr::unchecked("proxy_5".to_string());
+
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+    let proxy2_pubkey: String = String::from("proxy_pubkey2");
+    let proxy3_pubkey: String = String::from("proxy_pubkey3");
+    let proxy4_pubkey: String = String::from("proxy_pubkey4");
+    let proxy5_pubkey: String = String::from("proxy_pubkey5");
+
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+
+    let delegation_string = String::from("DELESTRING");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let proxy_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
+    let request_reward_2_proxies = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 2),
+    }];
+    let request_reward_3_proxies = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 3),
+    }];
+
+    /*************** Initialise *************/
+    let proxies: Vec<Addr> = vec![
+        proxy1.clone(),
+        proxy2.clone(),
+        proxy3.clone(),
+        proxy4.clone(),
+        proxy5.clone(),
+    ];
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(2),
+        &None,
+        &Some(proxies),
+        &stake_denom,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    /*************** Register proxies *************/
+    // Proxies register -> submits pubkeys
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy3,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy3_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy4,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy4_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy5,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy5_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    /*************** Add data and delegations by delegator *************/
+    // Add data by delegator
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &CAPSULE.to_string(),
+        &None,
+    )
+    .is_ok());
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &CAPSULE.to_string(),
+        &None,
+    )
+    .is_ok());
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator2,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id3,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &CAPSULE.to_string(),
+        &None,
+    )
+    .is_ok());
+
+    // Add delegations manually
+
+    let delegation1 = ProxyDelegation {
+        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+        delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+        delegation_string: delegation_string.clone(),
+        proxy_addr: proxy1.clone(),
+    };
+
+    // delegator1 with delegator1_pubkey and proxy1 for delegatee1
+    store_set_delegation(deps.as_mut().storage, &0, &delegation1).unwrap();
+
+    // delegator1 with delegator1_pubkey and proxy2 for delegatee1
+    store_set_delegation(
+        deps.as_mut().storage,
+        &1,
+        &ProxyDelegation {
+            proxy_addr: proxy2.clone(),
+            ..delegation1.clone()
+        },
+    )
+    .unwrap();
+
+    // delegator1 with delegator1_pubkey and proxy3 for delegatee1
+    store_set_delegation(
+        deps.as_mut().storage,
+        &2,
+        &ProxyDelegation {
+            proxy_addr: proxy3.clone(),
+            ..delegation1.clone()
+        },
+    )
+    .unwrap();
+
+    let delegation2 = ProxyDelegation {
+        delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+        delegatee_pubkey: DELEGATEE2_PUBKEY.to_string(),
+        delegation_string: delegation_string.clone(),
+        proxy_addr: proxy1.clone(),
+    };
+
+    // delegator1 with delegator1_pubkey and proxy1 for delegatee2
+    store_set_delegation(deps.as_mut().storage, &3, &delegation2).unwrap();
+
+    // delegator1 with delegator1_pubkey and proxy2 for delegatee2
+    store_set_delegation(
+        deps.as_mut().storage,
+        &4,
+        &ProxyDelegation {
+            proxy_addr: proxy2.clone(),
+            ..delegation2.clone()
+        },
+    )
+    .unwrap();
+
+    let delegation3 = ProxyDelegation {
+        delegator_pubkey: DELEGATOR2_PUBKEY.to_string(),
+        delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+        delegation_string: delegation_string,
+        proxy_addr: proxy4.clone(),
+    };
+
+    // delegator2 with delegator2_pubkey and proxy4 for delegatee1
+    store_set_delegation(deps.as_mut().storage, &5, &delegation3).unwrap();
+
+    // delegator2 with delegator2_pubkey and proxy5 for delegatee1
+    store_set_delegation(
+        deps.as_mut().storage,
+        &6,
+        &ProxyDelegation {
+            proxy_addr: proxy5.clone(),
+            ..delegation3.clone()
+        },
+    )
+    .unwrap();
+
+    // Request re-encryptions
+
+    // Re-encryption requests with DATA1 to delegatee1
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward_3_proxies,
+    )
+    .is_ok());
+    // Re-encryption requests with DATA1 to delegatee2
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &request_reward_2_proxies,
+    )
+    .is_ok());
+    // Re-encryption requests with DATA2 to delegatee2
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &request_reward_2_proxies,
+    )
+    .is_ok());
+    // Re-encryption requests with DATA3 to delegatee1
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator2,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id3,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward_2_proxies,
+    )
+    .is_ok());
+
+    // Complete requests
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy2, DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &String::from("AlCR5oC5v9i8G6IohBPRXg5qfYDP3awqEk2RIusDZo3wAwJEdUq0N3TG1iJ0lqfwRnNos1w3ysr3Vd0WPfFoBc991Pmeib1ZyAX52bfvtjcB3VdzunAnXrH0x259LtNX94oCkymfOr+pOYAe56kPnK9cTGjuwGdgcrV3i5A1ocF5xYsCK1m3Gcr1OeLeHMh26lX7rSDQKP7PoKJC4N/Mgeaqn0wDmP4BflDmAFm7AHvNbq6j5wlYLbZ0SDrpQ/L0axS4huYCYaHbevxWAYoQl2o1m+b5KtVg3c//Iaw1L4RRut+m1GMDhhuHPM1wslLQnN799sLX6itYkBWwTYnCDEc/9NBeCv4/P0LPZSNH6OW8Ta4x08yMA3WPSuBBj3rPcZt5Nydl+Mf4oj47fbmN6AhI9js2P7JBUCi/54jiNUkB4tDgqD2nNzC5ngPVPsnJEcSPx74aW1ppKowvRT7DwM9CNsEAbG0="),
+    )
+        .is_ok());
+
+    // Check proxy state
+    assert!(store_get_is_proxy_active(deps.as_mut().storage, &proxy2));
+    assert!(!get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy2,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None
+    )
+    .unwrap()
+    .proxy_tasks
+    .is_empty());
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+        .unwrap()
+        .unwrap();
+    assert_eq!(proxy.state, ProxyState::Registered);
+    assert_eq!(proxy.proxy_pubkey.unwrap(), proxy2_pubkey);
+
+    // Sender is not a proxy
+    assert!(is_err(
+        deactivate_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT),
+        "Sender is not a proxy",
+    ));
+
+    // Deactivate proxy2
+    assert!(deactivate_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).is_ok());
+    // Already deactivated
+    assert!(is_err(
+        deactivate_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT),
+        "Proxy already deactivated",
+    ));
+
+    // Check proxy state
+    assert!(!store_get_is_proxy_active(deps.as_mut().storage, &proxy2));
+    assert!(!get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy2,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None
+    )
+    .unwrap()
+    .proxy_tasks
+    .is_empty());
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+        .unwrap()
+        .unwrap();
+    assert_eq!(proxy.state, ProxyState::Leaving);
+    assert_eq!(proxy.proxy_pubkey.unwrap(), proxy2_pubkey);
+
+    // Check state of delegations
+
+    // ProxyDelegation 1 - Proxy 2 left, but delegation still exists
+    // delgator1, delegatee1, proxy1 - Unaffected
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy1,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_get_delegation(deps.as_mut().storage, &0)
+        .unwrap()
+        .is_some());
+    assert!(store_is_proxy_delegation(deps.as_mut().storage, &proxy1, &0,).unwrap());
+
+    // delgator1, delegatee1, proxy2 - Removed
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy2,
+    )
+    .unwrap()
+    .is_none());
+    assert!(store_get_delegation(deps.as_mut().storage, &1)
+        .unwrap()
+        .is_none());
+    assert!(!store_is_proxy_delegation(deps.as_mut().storage, &proxy2, &1,).unwrap());
+
+    // delgator1, delegatee1, proxy3 - still exist
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy3,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_get_delegation(deps.as_mut().storage, &2)
+        .unwrap()
+        .is_some());
+    assert!(store_is_proxy_delegation(deps.as_mut().storage, &proxy3, &2,).unwrap());
+
+    // ProxyDelegation 2 - Number of proxies below threshold, removed entire delegation
+    // delgator1, delegatee2, proxy1 - Removed
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy1,
+    )
+    .unwrap()
+    .is_none());
+    assert!(store_get_delegation(deps.as_mut().storage, &3)
+        .unwrap()
+        .is_none());
+    assert!(!store_is_proxy_delegation(deps.as_mut().storage, &proxy1, &3,).unwrap());
+
+    // delgator1, delegatee2, proxy2 - Removed
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy2,
+    )
+    .unwrap()
+    .is_none());
+    assert!(store_get_delegation(deps.as_mut().storage, &4)
+        .unwrap()
+        .is_none());
+    assert!(!store_is_proxy_delegation(deps.as_mut().storage, &proxy1, &4,).unwrap());
+
+    // ProxyDelegation 3 - Unaffected
+    // delgator2, delegatee1, proxy4 - Unaffected
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy4,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_get_delegation(deps.as_mut().storage, &5)
+        .unwrap()
+        .is_some());
+    assert!(store_is_proxy_delegation(deps.as_mut().storage, &proxy4, &5,).unwrap());
+
+    // delgator2, delegatee1, proxy5 - Unaffected
+    assert!(store_get_proxy_delegation_id(
+        deps.as_mut().storage,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy5,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_get_delegation(deps.as_mut().storage, &6)
+        .unwrap()
+        .is_some());
+    assert!(store_is_proxy_delegation(deps.as_mut().storage, &proxy5, &6,).unwrap());
+
+    // Remove proxy by admin
+    assert!(remove_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &proxy2).is_ok());
+    // Already removed
+    assert!(is_err(
+        remove_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &proxy2),
+        "Sender is not a proxy",
+    ));
+
+    // Check proxy state
+    assert!(!store_get_is_proxy_active(deps.as_mut().storage, &proxy2));
+    assert!(get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy2,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None
+    )
+    .unwrap()
+    .proxy_tasks
+    .is_empty());
+    assert!(store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+        .unwrap()
+        .is_none());
+
+    // Check state of re-encryption requests
+
+    // Re-encryption requests with DATA1 to delegatee1
+    // DATA1, delegatee1, proxy1 - unaffected
+    assert!(store_get_proxy_task(deps.as_mut().storage, &0)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy1,
+        )
+        .unwrap()
+        .unwrap(),
+        0
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy1,
+        &0,
+    ));
+
+    // DATA1, delegatee1, proxy2 - incomplete and removed
+    assert!(
+        store_get_proxy_task(deps.as_mut().storage, &1)
+            .unwrap()
+            .unwrap()
+            .abandoned
+    );
+    assert!(!store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy1,
+        &1,
+    ));
+
+    // DATA1, delegatee1, proxy3 - unaffected
+    assert!(
+        !store_get_proxy_task(deps.as_mut().storage, &2)
+            .unwrap()
+            .unwrap()
+            .abandoned
+    );
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy3,
+        )
+        .unwrap()
+        .unwrap(),
+        2
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy3,
+        &2,
+    ));
+
+    // Re-encryption requests with DATA1 to delegatee1
+    // DATA1, delegatee2, proxy1 - Not removed because it can still be completed
+    assert!(store_get_proxy_task(deps.as_mut().storage, &3)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
+            &DELEGATEE2_PUBKEY.to_string(),
+            &proxy1,
+        )
+        .unwrap()
+        .unwrap(),
+        3
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy1,
+        &3,
+    ));
+
+    // DATA1, delegatee2, proxy2 - complete - unaffected
+    // Request can still be obtained by delegatee
+    assert!(store_get_proxy_task(deps.as_mut().storage, &4)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id1,
+            &DELEGATEE2_PUBKEY.to_string(),
+            &proxy2,
+        )
+        .unwrap()
+        .unwrap(),
+        4
+    );
+    // Request is completed - won't appear in proxy tasks
+    assert!(!store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy2,
+        &4,
+    ));
+
+    // Re-encryption requests with DATA2 to delegatee2
+    // DATA2, delegatee2, proxy1 - incomplete and not removed
+    assert!(store_get_proxy_task(deps.as_mut().storage, &5)
+        .unwrap()
+        .is_some());
+    assert!(store_get_delegatee_proxy_task(
+        deps.as_mut().storage,
+        &data_id2,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy1,
+    )
+    .unwrap()
+    .is_some());
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy1,
+        &5,
+    ));
+
+    // DATA1, delegatee2, proxy2 - complete - removed because more than threshold fragments cannot be provided
+    // Request can still be obtained by delegatee
+    assert!(
+        store_get_proxy_task(deps.as_mut().storage, &6)
+            .unwrap()
+            .unwrap()
+            .abandoned
+    );
+    // Request is completed - won't appear in proxy tasks
+    assert!(!store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy2,
+        &6,
+    ));
+
+    // Re-encryption requests with DATA3 to delegatee1
+    // DATA3, delegatee1, proxy4 - unaffected
+    assert!(store_get_proxy_task(deps.as_mut().storage, &7)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id3,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy4,
+        )
+        .unwrap()
+        .unwrap(),
+        7
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy4,
+        &7,
+    ));
+
+    // DATA3, delegatee1, proxy5 - unaffected
+    assert!(store_get_proxy_task(deps.as_mut().storage, &8)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        store_get_delegatee_proxy_task(
+            deps.as_mut().storage,
+            &data_id3,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy5,
+        )
+        .unwrap()
+        .unwrap(),
+        8
+    );
+    assert!(store_is_proxy_task_in_queue(
+        deps.as_mut().storage,
+        &proxy5,
+        &8,
+    ));
+}
+
+#[test]
+fn test_proxy_stake_withdrawal() {
+    let mut deps = mock_dependencies();
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy1".to_string());
+    let proxy2 = Addr::unchecked("proxy2".to_string());
+
+    let proxy1_pubkey: String = String::from("proxy1_pubkey");
+    let proxy2_pubkey: String = String::from("proxy2_pubkey");
+
+    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone()];
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let proxy_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + 50),
+    }];
+
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &Some(proxies),
+        &stake_denom,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + 50
+    );
+
+    // Proxy1 is trying to withdraw more than is available gives you maximum available stake
+    let withdraw_res = withdraw_stake(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(Uint128::new(51)),
+    )
+    .unwrap();
+    assert_eq!(
+        withdraw_res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy1.to_string(),
+            amount: vec![Coin::new(50, stake_denom.as_str())],
+        })
+    );
+
+    // All stake of proxy1 withdrawn
+    assert!(is_err(
+        withdraw_stake(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT, &None),
+        "Not enough stake to withdraw",
+    ));
+
+    // Proxy2 is trying to withdraw maximum available stake
+    let withdraw_res = withdraw_stake(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT, &None).unwrap();
+    assert_eq!(
+        withdraw_res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy2.to_string(),
+            amount: vec![Coin::new(50, stake_denom.as_str())],
+        })
+    );
+
+    // All stake of proxy2 withdrawn
+    assert!(is_err(
+        withdraw_stake(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT, &None),
+        "Not enough stake to withdraw",
+    ));
+
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+    );
+
+    let proxy = store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        proxy.stake_amount.u128(),
+        DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT
+    );
+
+    // Insufficient stake
+    assert!(is_err(
+        withdraw_stake(
+            deps.as_mut(),
+            &proxy1,
+            DEFAULT_BLOCK_HEIGHT,
+            &Some(Uint128::new(1)),
+        ),
+        "Not enough stake to withdraw",
+    ));
+
+    // Remaining stake can be withdrawn only by unregistering
+    let unregister_res = unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
+    assert_eq!(
+        unregister_res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy1.to_string(),
+            amount: vec![Coin::new(
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+
+    // Proxy unregistered
+    assert!(is_err(
+        withdraw_stake(
+            deps.as_mut(),
+            &proxy1,
+            DEFAULT_BLOCK_HEIGHT,
+            &Some(Uint128::new(1)),
+        ),
+        "Not enough stake to withdraw",
+    ));
+}
+
+#[test]
+fn test_proxy_add_stake() {
+    let mut deps = mock_dependencies();
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy1".to_string());
+
+    let proxy1_pubkey: String = String::from("proxy1_pubkey");
+
+    let proxies: Vec<Addr> = vec![proxy1.clone()];
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let proxy_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
+
+    let proxy_additional_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(50),
+    }];
+
+    let proxy_wrong_coins_additional_stake = vec![
+        Coin {
+            denom: stake_denom.clone(),
+            amount: Uint128::new(50),
+        },
+        Coin {
+            denom: String::from("othercoin"),
+            amount: Uint128::new(20),
+        },
+    ];
+
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &Some(proxies),
+        &stake_denom,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    assert!(is_err(
+        add_stake(
+            deps.as_mut(),
+            &proxy1,
+            DEFAULT_BLOCK_HEIGHT,
+            &proxy_wrong_coins_additional_stake,
+        ),
+        "Expected 1 Coin with denom atestfet",
+    ));
+    assert!(add_stake(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy_additional_stake,
+    )
+    .is_ok());
+
+    // Check if withdrawn stake amount
+    let unregister_res = unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
+    assert_eq!(
+        unregister_res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy1.to_string(),
+            amount: vec![Coin::new(
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT + 50,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+}
+
+#[test]
+fn test_proxy_insufficient_funds_task_skip() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+    let proxy2_pubkey: String = String::from("proxy_pubkey2");
+    let proxy3_pubkey: String = String::from("proxy_pubkey3");
+
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+
+    let capsule = String::from("capsule");
+
+    let delegation_string = String::from("DELESTRING");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 100;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy1_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let proxy2_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(2 * minimum_proxy_stake_amount),
+    }];
+
+    let proxy3_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(3 * minimum_proxy_stake_amount),
+    }];
+
+    let request_reward_1_proxy = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(per_proxy_task_reward_amount * 1),
+    }];
+    let request_reward_2_proxies = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(per_proxy_task_reward_amount * 2),
+    }];
+    let request_reward_3_proxies = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(per_proxy_task_reward_amount * 3),
+    }];
+
+    /*************** Initialise *************/
+    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone(), proxy3.clone()];
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(2),
+        &None,
+        &Some(proxies),
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    /*************** Register proxies *************/
+    // Proxies register -> submits pubkeys
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy1_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy2_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy3,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy3_pubkey,
+        &proxy3_stake,
+    )
+    .is_ok());
+
+    /*************** Add data and delegations by delegator *************/
+    // Add data by delegator
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &None,
+    )
+    .is_ok());
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &None,
+    )
+    .is_ok());
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id3,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &None,
+    )
+    .is_ok());
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy3.clone(),
+            delegation_string: delegation_string,
+        },
+    ];
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+
+    // Create first reencryption request -- All proxies has enough stake
+    assert_eq!(
+        get_n_available_proxies_from_delegation(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+            &per_task_slash_stake_amount,
+        )
+        .unwrap(),
+        3
+    );
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward_3_proxies,
+    )
+    .is_ok());
+
+    assert_eq!(
+        store_get_proxy_entry(deps.as_mut().storage, &proxy2)
+            .unwrap()
+            .unwrap()
+            .stake_amount
+            .u128(),
+        102
+    );
+
+    // Create second reencryption request -- 1 proxies skipped because of insufficient funds
+    assert_eq!(
+        get_n_available_proxies_from_delegation(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+            &per_task_slash_stake_amount,
+        )
+        .unwrap(),
+        2
+    );
+    assert_eq!(
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::Active
+    );
+
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward_2_proxies,
+    )
+    .is_ok());
+
+    // Creating third reencryption request fails -- 2 proxies skipped because of insufficient funds
+    assert_eq!(
+        get_n_available_proxies_from_delegation(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+            &per_task_slash_stake_amount,
+        )
+        .unwrap(),
+        1
+    );
+
+    assert_eq!(
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::ProxiesAreBusy
+    );
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id2,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward_1_proxy,
+        ),
+        "Proxies are too busy, try again later. Available 1 proxies out of 3, minimum is 2",
+    ));
+
+    // Requests:
+    // req1 - (0)proxy1, (1)proxy2, (2)proxy3
+    // req2 - (3)proxy2, (4)proxy3
+
+    // Check proxy2 unregister
+    // Proxy3 gets slashed for 2 unfinished tasks - 1 portion is returned to delegator
+    let unregister_response =
+        unregister_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).unwrap();
+    assert_eq!(
+        unregister_response.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(
+                per_proxy_task_reward_amount * 2,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+    assert_eq!(
+        unregister_response.messages[1],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy2.to_string(),
+            amount: vec![Coin::new(
+                2 * minimum_proxy_stake_amount - 2 * per_task_slash_stake_amount,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+
+    // Requests:
+    // req1 - (0)proxy1, (2)proxy3
+    // req2 - (4)proxy3 - Unfinishable
+
+    // Check if only tasks for request2 are deleted
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
+        1
+    );
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
+        0
+    );
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy3).len(),
+        2
+    );
+
+    // Check proxy3 unregister
+    // Proxy3 gets slashed for 1 unfinished task, delegator gets full refund for request that cannot be completed
+    let unregister_response =
+        unregister_proxy(deps.as_mut(), &proxy3, DEFAULT_BLOCK_HEIGHT).unwrap();
+    assert_eq!(
+        unregister_response.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(
+                per_proxy_task_reward_amount * 3,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+    assert_eq!(
+        unregister_response.messages[1],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy3.to_string(),
+            amount: vec![Coin::new(
+                3 * minimum_proxy_stake_amount - 2 * per_task_slash_stake_amount,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+
+    // Requests:
+    // req1 - deleted
+    // req2 - deleted
+
+    // Check proxy tasks
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy1).len(),
+        1
+    );
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy2).len(),
+        0
+    );
+    assert_eq!(
+        store_get_all_proxy_tasks_in_queue(deps.as_mut().storage, &proxy3).len(),
+        0
+    );
+
+    // Check proxy1 unregister
+    // Proxy1 gets slashed for not finishing taskss
+    let unregister_response =
+        unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).unwrap();
+    assert_eq!(
+        unregister_response.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy1.to_string(),
+            amount: vec![Coin::new(
+                minimum_proxy_stake_amount - per_task_slash_stake_amount,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+}
+
+#[test]
+fn test_get_n_minimum_proxies_for_refund() {
+    let mut state = State {
         admin: Addr::unchecked("admin"),
         threshold: 123,
         next_proxy_task_id: 0,
@@ -4149,638 +6812,4353 @@ fn test_get_n_minimum_proxies_for_refund() {
         withdrawn: false,
         terminate_height: 0,
         withdrawal_period: 1,
+        paused: false,
+    };
+    let mut staking_config = StakingConfig {
+        stake_denom: "denom".to_string(),
+        minimum_proxy_stake_amount: Uint128::new(0),
+        per_proxy_task_reward_amount: Uint128::new(0),
+        per_task_slash_stake_amount: Uint128::new(0),
+    };
+
+    // zero division case
+    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
+    staking_config.per_task_slash_stake_amount = Uint128::new(0);
+    state.threshold = 123;
+    assert_eq!(
+        get_n_minimum_proxies_for_refund(&state, &staking_config),
+        123
+    );
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
+    staking_config.per_task_slash_stake_amount = Uint128::new(100);
+    state.threshold = 3;
+    assert_eq!(get_n_minimum_proxies_for_refund(&state, &staking_config), 4);
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
+    staking_config.per_task_slash_stake_amount = Uint128::new(100);
+    state.threshold = 123;
+    assert_eq!(
+        get_n_minimum_proxies_for_refund(&state, &staking_config),
+        244
+    );
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
+    staking_config.per_task_slash_stake_amount = Uint128::new(50);
+    state.threshold = 123;
+    assert_eq!(
+        get_n_minimum_proxies_for_refund(&state, &staking_config),
+        366
+    );
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(50);
+    staking_config.per_task_slash_stake_amount = Uint128::new(100);
+    state.threshold = 123;
+    assert_eq!(
+        get_n_minimum_proxies_for_refund(&state, &staking_config),
+        183
+    );
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(1);
+    staking_config.per_task_slash_stake_amount = Uint128::new(121);
+    state.threshold = 123;
+    assert_eq!(
+        get_n_minimum_proxies_for_refund(&state, &staking_config),
+        124
+    );
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(1);
+    staking_config.per_task_slash_stake_amount = Uint128::new(122);
+    state.threshold = 123;
+    assert_eq!(
+        get_n_minimum_proxies_for_refund(&state, &staking_config),
+        123
+    );
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(1);
+    staking_config.per_task_slash_stake_amount = Uint128::new(1000);
+    state.threshold = 10;
+    assert_eq!(
+        get_n_minimum_proxies_for_refund(&state, &staking_config),
+        10
+    );
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(1000);
+    staking_config.per_task_slash_stake_amount = Uint128::new(1);
+    state.threshold = 10;
+    assert_eq!(
+        get_n_minimum_proxies_for_refund(&state, &staking_config),
+        9009
+    );
+
+    // Large numbers check
+    staking_config.per_proxy_task_reward_amount = Uint128::new(100000000000000000000);
+    staking_config.per_task_slash_stake_amount = Uint128::new(1000000000000);
+    state.threshold = 10;
+    assert_eq!(
+        get_n_minimum_proxies_for_refund(&state, &staking_config),
+        900000009
+    );
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
+    staking_config.per_task_slash_stake_amount = Uint128::new(100);
+    state.threshold = 1;
+    assert_eq!(get_n_minimum_proxies_for_refund(&state, &staking_config), 1);
+
+    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
+    staking_config.per_task_slash_stake_amount = Uint128::new(100);
+    state.threshold = 2;
+    assert_eq!(get_n_minimum_proxies_for_refund(&state, &staking_config), 2);
+}
+
+/*
+#[test]
+fn test_verify_fragments() {
+    assert!(verify_fragment(
+        &FRAGMENT_P1_DR1_DE1.to_string(),
+        &CAPSULE.to_string(),
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .is_ok());
+
+    assert!(verify_fragment(
+        &FRAGMENT_P1_DR1_DE2.to_string(),
+        &CAPSULE.to_string(),
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .is_err());
+}
+ */
+
+#[test]
+fn test_timeouts() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+    let proxy2_pubkey: String = String::from("proxy_pubkey2");
+    let proxy3_pubkey: String = String::from("proxy_pubkey3");
+
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let delegation_string = String::from("DELESTRING");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 100;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    // Timetous
+    let timeout_height: u64 = 100;
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 3),
+    }];
+
+    // Scenario
+
+    /*************** Initialise *************/
+    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone(), proxy3.clone()];
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        0,
+        &Some(2),
+        &None,
+        &Some(proxies),
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &Some(timeout_height),
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    let proxy_register_stake = vec![Coin {
+        denom: stake_denom.clone(),
+        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
+    }];
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        0,
+        &proxy1_pubkey,
+        &proxy_register_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        0,
+        &proxy2_pubkey,
+        &proxy_register_stake,
+    )
+    .is_ok());
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy3,
+        0,
+        &proxy3_pubkey,
+        &proxy_register_stake,
+    )
+    .is_ok());
+
+    /*************** Add data and delegations by delegator *************/
+    // Add data by delegator
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        0,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &CAPSULE.to_string(),
+        &None,
+    )
+    .is_ok());
+
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator2,
+        0,
+        &data_id2,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &CAPSULE.to_string(),
+        &None,
+    )
+    .is_ok());
+
+    // Add delegations
+    let delegation1: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy3.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+    ];
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &delegation1,
+        &None,
+    )
+    .is_ok());
+
+    let delegation2: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy3.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+    ];
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE2_PUBKEY.to_string(),
+        &delegation2,
+        &None,
+    )
+    .is_ok());
+
+    let delegation3: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy3.clone(),
+            delegation_string: delegation_string,
+        },
+    ];
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator2,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &delegation3,
+        &None,
+    )
+    .is_ok());
+
+    /*************** Add and check re-encryption requests *************/
+
+    // Requests:
+    // req1 - (0)proxy1, (1)proxy2, (2)proxy3  - timeout at 300
+    // req2 - (3)proxy1,            (4)proxy3  - timeout at 350
+    // req3 - (4)proxy1, (5)proxy2, (6)proxy3  - timeout at 400
+
+    // Request 1 will timeout at 200+100
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        200,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    // Complete request1 before timeout
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy1,
+        220,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &FRAGMENT_P1_DR1_DE1.to_string(),
+    )
+    .is_ok());
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy2,
+        220,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &FRAGMENT_P2_DR1_DE1.to_string(),
+    )
+    .is_ok());
+
+    let state = store_get_state(deps.as_mut().storage).unwrap();
+    // Check if request is completed
+    assert_eq!(
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id1,
+            DELEGATEE1_PUBKEY,
+            &mock_block_height(220),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Granted
+    );
+    // Check if request1 state stays granted after timeout
+    let state = store_get_state(deps.as_mut().storage).unwrap();
+    assert_eq!(
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id1,
+            DELEGATEE1_PUBKEY,
+            &mock_block_height(400),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Granted
+    );
+
+    // Request 2 will timeout at 250+100
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        250,
+        &data_id1,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    // Check request 2 timeout height
+    let state = store_get_state(deps.as_mut().storage).unwrap();
+    assert_eq!(
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id1,
+            DELEGATEE2_PUBKEY,
+            &mock_block_height(350),
+        )
+        .unwrap(),
+        ReencryptionRequestState::TimedOut
+    );
+
+    // Height = 250
+    // Current states of requests/tasks:
+    // req1 - (0)proxy1, (1)proxy2, (2)proxy3  - timeout at 300 - Completed
+    // req2 - (3)proxy1,            (4)proxy3  - timeout at 350 - Ready
+    // req3 - (4)proxy1, (5)proxy2, (6)proxy3  - timeout at 400 - Will be created at height 300
+
+    // Request 3 will timeout at 300+100
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator2,
+        300,
+        &data_id2,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    // Height = 300
+    // Current states of requests/tasks:
+    // req1 - (0)proxy1, (1)proxy2, (2)proxy3  - timeout at 300 - Completed
+    // req2 - (3)proxy1,            (4)proxy3  - timeout at 350 - Ready
+    // req3 - (4)proxy1, (5)proxy2, (6)proxy3  - timeout at 400 - Ready
+
+    // Unregister proxy after timeout
+    // Request gets first timed out and then abandoned (will be displayed as TimedOut)
+    let res = unregister_proxy(deps.as_mut(), &proxy3, 350).unwrap();
+    assert_eq!(res.messages.len(), 2);
+    // Timeout BankMsgs
+    // Funds from timed out request 2 are sent back to delegator1
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(
+                2 * per_proxy_task_reward_amount,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+    // Unregister BankMsgs
+    // Proxy 3 got slashed for 3 unfinished tasks
+    assert_eq!(
+        res.messages[1],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy3.to_string(),
+            amount: vec![Coin::new(
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 3 * per_task_slash_stake_amount,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+
+    let state = store_get_state(deps.as_mut().storage).unwrap();
+    assert_eq!(
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id1,
+            &DELEGATEE2_PUBKEY.to_string(),
+            &mock_block_height(350),
+        )
+        .unwrap(),
+        ReencryptionRequestState::TimedOut
+    );
+
+    // Height = 350
+    // Current states of requests/tasks:
+    // Proxy 3 unregistered
+    // req1 - (0)  proxy1,  (1)proxy2,   (2)  proxy3  - timeout at 300 - Completed
+    // req2 - (x3x)proxy1,               (x4x)proxy3  - timeout at 350 - TimedOut (Abandoned)
+    // req3 - (4)  proxy1,  (5)proxy2,   (x6x)proxy3  - timeout at 400 - Ready
+
+    // Cannot provide fragment for timed-out request2
+    assert!(is_err(
+        provide_reencrypted_fragment(
+            deps.as_mut(),
+            &proxy1,
+            350,
+            &data_id1,
+            &DELEGATEE2_PUBKEY.to_string(),
+            &FRAGMENT_P1_DR1_DE2.to_string(),
+        ),
+        "Request timed out.",
+    ));
+
+    // Tasks can be obtained before timeout
+    assert!(
+        !get_proxy_tasks(deps.as_mut().storage, &proxy1, &350, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .is_empty()
+    );
+    assert!(
+        !get_proxy_tasks(deps.as_mut().storage, &proxy2, &350, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .is_empty()
+    );
+    assert!(
+        get_proxy_tasks(deps.as_mut().storage, &proxy3, &350, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .is_empty()
+    );
+
+    // No tasks to complete after timeout
+    // These tasks still exist but are skipped in get_proxy_tasks
+    assert!(
+        get_proxy_tasks(deps.as_mut().storage, &proxy1, &500, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .is_empty()
+    );
+    assert!(
+        get_proxy_tasks(deps.as_mut().storage, &proxy2, &500, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .is_empty()
+    );
+    assert!(
+        get_proxy_tasks(deps.as_mut().storage, &proxy3, &500, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .is_empty()
+    );
+
+    let res = resolve_timed_out_request(
+        deps.as_mut(),
+        &delegator2,
+        500,
+        &data_id2,
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+
+    // Delegator 2 gets a refund.
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: delegator2.to_string(),
+            amount: vec![Coin::new(
+                3 * per_proxy_task_reward_amount,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+
+    // Height = 500
+    // Current states of requests:
+    // Proxy 3 unregistered
+    // req1 - (0)  proxy1,  (1)proxy2,       (2)  proxy3  - timeout at 300 - Completed
+    // req2 - (x3x)proxy1,                   (x4x)proxy3  - timeout at 350 - TimedOut (Abandoned)
+    // req3 - (x4x)  proxy1,  (x5x)proxy2,   (x6x)proxy3  - timeout at 400 - TimedOut
+
+    // Check if tasks from queue were deleted due to refund
+    assert_eq!(
+        get_proxy_tasks(deps.as_mut().storage, &proxy1, &0, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .len(),
+        1
+    );
+    assert!(
+        get_proxy_tasks(deps.as_mut().storage, &proxy2, &0, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .is_empty()
+    );
+    assert!(
+        get_proxy_tasks(deps.as_mut().storage, &proxy3, &0, &None, None)
+            .unwrap()
+            .proxy_tasks
+            .is_empty()
+    );
+
+    // Unregister proxy 1
+    let res = unregister_proxy(deps.as_mut(), &proxy1, 500).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    // Proxy 1 got slashed for 2 tasks, rewarded for 1 task
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy1.to_string(),
+            amount: vec![Coin::new(
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 2 * per_task_slash_stake_amount
+                    + per_proxy_task_reward_amount,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+
+    // Unregister proxy 2
+    let res = unregister_proxy(deps.as_mut(), &proxy2, 500).unwrap();
+    assert_eq!(res.messages.len(), 1);
+    // Proxy 1 got slashed for 1 task and rewarded for 1 task
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: proxy2.to_string(),
+            amount: vec![Coin::new(
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - per_task_slash_stake_amount
+                    + per_proxy_task_reward_amount,
+                stake_denom.as_str(),
+            )],
+        })
+    );
+}
+
+#[test]
+fn test_terminate_contract() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+    let recipient = Addr::unchecked("recipient".to_string());
+
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+    let proxy2_pubkey: String = String::from("proxy_pubkey2");
+    let proxy3_pubkey: String = String::from("proxy_pubkey3");
+
+    let data_id1 = String::from("DATA1");
+    let delegation_string = String::from("DELESTRING");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 200;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let withdrawal_period: u64 = 399;
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(per_proxy_task_reward_amount * 3),
+    }];
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    // Timetous
+    let timeout_height: u64 = 100;
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        0,
+        &Some(1),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &Some(timeout_height),
+        &Some(false),
+        &Some(withdrawal_period),
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    // Prepare scenario
+    // Proxy 1,2 gets 2 re-encryption tasks each
+
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &CAPSULE.to_string(),
+        &None,
+    )
+    .is_ok());
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: delegation_string,
+        },
+    ];
+
+    // Add delegations
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    // Both proxies are available
+    assert_eq!(
+        get_proxies_availability(deps.as_mut().storage)
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // Try to withdraw contract
+    assert!(is_err(
+        withdraw_contract(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &recipient),
+        "Only admin can execute this method.",
+    ));
+
+    assert!(is_err(
+        withdraw_contract(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &recipient),
+        "Contract not terminated",
+    ));
+
+    // Terminate contract
+    assert!(is_err(
+        terminate_contract(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT),
+        "Only admin can execute this method.",
+    ));
+    assert!(terminate_contract(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT).is_ok());
+    assert!(is_err(
+        terminate_contract(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT),
+        "Contract was terminated.",
+    ));
+
+    // Check if contract state is terminated
+    let state: State = store_get_state(deps.as_mut().storage).unwrap();
+    assert!(state.terminated);
+
+    assert!(is_err(
+        add_delegation(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &DELEGATOR2_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+            &proxy_delegations,
+            &None,
+        ),
+        "Contract was terminated.",
+    ));
+
+    assert!(is_err(
+        register_proxy(
+            deps.as_mut(),
+            &proxy3,
+            DEFAULT_BLOCK_HEIGHT,
+            &proxy3_pubkey,
+            &proxy_stake,
+        ),
+        "Contract was terminated.",
+    ));
+
+    // Proxies still can finish their jobs
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &FRAGMENT_P1_DR1_DE1.to_string(),
+    )
+    .is_ok());
+
+    // Have to wait for withdrawal period
+    assert!(is_err(
+        withdraw_contract(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &recipient),
+        "Withdrawal will be possible at height",
+    ));
+
+    // Nothing to withdraw
+    assert!(is_err(
+        withdraw_contract(
+            deps.as_mut(),
+            &creator,
+            DEFAULT_BLOCK_HEIGHT + withdrawal_period,
+            &recipient,
+        ),
+        "Nothing to withdraw",
+    ));
+
+    // Add balance to contract
+    let contract_balance: Vec<Coin> = vec![
+        Coin {
+            denom: "something".to_string(),
+            amount: Uint128::new(123),
+        },
+        Coin {
+            denom: stake_denom,
+            // amount is equal to remaining proxy stake amount
+            amount: Uint128::new(2 * minimum_proxy_stake_amount - 254),
+        },
+    ];
+    deps.querier
+        .update_balance(mock_env().contract.address, contract_balance);
+
+    // Withdrawing is now possible
+    let res = withdraw_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT + withdrawal_period,
+        &recipient,
+    )
+    .unwrap();
+
+    // Return remaining stake to recipient
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: [
+                Coin {
+                    denom: "something".to_string(),
+                    amount: Uint128::new(123),
+                },
+                Coin {
+                    denom: "atestfet".to_string(),
+                    amount: Uint128::new(146),
+                },
+                // proxy stake got subtracted
+            ]
+            .to_vec(),
+        })
+    );
+}
+
+#[test]
+fn test_skip_task() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+    let proxy4 = Addr::unchecked("proxy_4".to_string());
+
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+    let proxy2_pubkey: String = String::from("proxy_pubkey2");
+    let proxy3_pubkey: String = String::from("proxy_pubkey3");
+    let proxy4_pubkey: String = String::from("proxy_pubkey4");
+
+    let data_id1 = String::from("DATA1");
+    let delegation_string = String::from("DELESTRING");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 200;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(per_proxy_task_reward_amount * 3),
+    }];
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    // Timetous
+    let timeout_height: u64 = 100;
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        0,
+        &Some(2),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &Some(timeout_height),
+        &Some(false),
+        &None,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy3,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy3_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy4,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy4_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    // Prepare scenario
+    // Proxy 1,2,3 gets2 re-encryption tasks each
+
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &CAPSULE.to_string(),
+        &None,
+    )
+    .is_ok());
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy3.clone(),
+            delegation_string: delegation_string,
+        },
+    ];
+
+    // Add delegations
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    // Not a proxy
+    assert!(is_err(
+        skip_reencryption_task(
+            deps.as_mut(),
+            &creator,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+        ),
+        "Sender is not a proxy",
+    ));
+
+    // Incorrect task
+    assert!(is_err(
+        skip_reencryption_task(
+            deps.as_mut(),
+            &proxy4,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+        ),
+        "Task doesn't exist",
+    ));
+
+    let p1_tasks = get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy1,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(p1_tasks.len(), 2);
+    let p2_tasks = get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy2,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(p2_tasks.len(), 2);
+    let p3_tasks = get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy3,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(p3_tasks.len(), 2);
+
+    // Remove task for data1 by proxy1 for delegatee1
+    assert!(skip_reencryption_task(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .is_ok());
+
+    // Check request state
+    let state = store_get_state(deps.as_mut().storage).unwrap();
+    assert_eq!(
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &mock_block_height(DEFAULT_BLOCK_HEIGHT),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Ready
+    );
+
+    let p1_tasks = get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy1,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(p1_tasks.len(), 1);
+    let p2_tasks = get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy2,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(p2_tasks.len(), 2);
+    let p3_tasks = get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy3,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(p3_tasks.len(), 2);
+
+    // Remove task for data1 by proxy3 for delegatee1
+    assert!(skip_reencryption_task(
+        deps.as_mut(),
+        &proxy3,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .is_ok());
+
+    // Task is Abandoned
+    let state = store_get_state(deps.as_mut().storage).unwrap();
+    assert_eq!(
+        get_reencryption_request_state(
+            deps.as_mut().storage,
+            &state,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &mock_block_height(DEFAULT_BLOCK_HEIGHT),
+        )
+        .unwrap(),
+        ReencryptionRequestState::Abandoned
+    );
+
+    let p1_tasks = get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy1,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(p1_tasks.len(), 1);
+    let p2_tasks = get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy2,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(p2_tasks.len(), 2);
+    let p3_tasks = get_proxy_tasks(
+        deps.as_mut().storage,
+        &proxy3,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(p3_tasks.len(), 1);
+
+    // Proxy 3 finish the task
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &FRAGMENT_P2_DR1_DE1.to_string(),
+    )
+    .is_ok());
+
+    // Can't skip finished task
+    assert!(is_err(
+        skip_reencryption_task(
+            deps.as_mut(),
+            &proxy2,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+        ),
+        "Task was already completed.",
+    ));
+}
+
+#[test]
+fn test_remove_proxies_from_delegation() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+    let proxy2_pubkey: String = String::from("proxy_pubkey2");
+    let proxy3_pubkey: String = String::from("proxy_pubkey3");
+
+    let delegation_string = String::from("DELESTRING");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 200;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    // Timetous
+    let timeout_height: u64 = 100;
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        0,
+        &Some(2),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &Some(timeout_height),
+        &Some(false),
+        &None,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1.clone(),
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy2.clone(),
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy2_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy3,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy3_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: delegation_string.clone(),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy3.clone(),
+            delegation_string: delegation_string,
+        },
+    ];
+
+    // Add delegation
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+
+    assert_eq!(
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::Active
+    );
+
+    // Delegation still exists when proxy 1 leaves
+    assert!(unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).is_ok());
+    assert_eq!(
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::Active
+    );
+
+    // Delegation gets deleted when number of proxies is less than minimum
+    assert!(unregister_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).is_ok());
+
+    assert_eq!(
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::NonExisting
+    );
+
+    assert!(unregister_proxy(deps.as_mut(), &proxy3, DEFAULT_BLOCK_HEIGHT).is_ok());
+
+    assert_eq!(
+        get_delegation_state(
+            deps.as_mut().storage,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &DELEGATEE1_PUBKEY.to_string(),
+        )
+        .unwrap(),
+        DelegationState::NonExisting
+    );
+}
+
+#[test]
+fn test_add_remove_labels() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+
+    let capsule = String::from("capsule");
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &None,
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    /*************** Add data and delegations by delegator *************/
+
+    // Add data by delegator1
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(["x".to_string()].to_vec()),
+    )
+    .is_ok());
+
+    // Add data by delegator2
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator2,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATOR2_PUBKEY.to_string(),
+        &capsule,
+        &None,
+    )
+    .is_ok());
+
+    assert!(add_data_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &["a".to_string(), "b".to_string()]
+    )
+    .is_ok());
+    assert_eq!(
+        store_get_all_data_labels(deps.as_ref().storage, &data_id1),
+        &["a".to_string(), "b".to_string(), "x".to_string()]
+    );
+
+    assert!(add_data_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &["b".to_string(), "c".to_string()]
+    )
+    .is_ok());
+    assert_eq!(
+        store_get_all_data_labels(deps.as_ref().storage, &data_id1),
+        &[
+            "a".to_string(),
+            "b".to_string(),
+            "c".to_string(),
+            "x".to_string()
+        ]
+    );
+
+    // Try to add label for that that are not owned by delegator2
+    assert!(is_err(
+        add_data_labels(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &["d".to_string()]
+        ),
+        "Sender is not a data owner."
+    ));
+
+    assert!(add_delegatee_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &["d".to_string(), "e".to_string()]
+    )
+    .is_ok());
+
+    // Reencryption not permitted
+    assert!(!get_permission(
+        deps.as_mut().storage,
+        &delegator1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &data_id1,
+        &mock_block_height(DEFAULT_BLOCK_HEIGHT)
+    ));
+
+    // Add label "b" that is also under data1
+    assert!(add_delegatee_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &["b".to_string(), "e".to_string()]
+    )
+    .is_ok());
+
+    // Reencryption is now permitted
+    assert!(get_permission(
+        deps.as_mut().storage,
+        &delegator1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &data_id1,
+        &mock_block_height(DEFAULT_BLOCK_HEIGHT)
+    ));
+
+    // Add label "b" that is also under data1
+    assert!(remove_delegatee_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &["b".to_string(), "e".to_string()]
+    )
+    .is_ok());
+
+    // Reencryption not permitted
+    assert!(!get_permission(
+        deps.as_mut().storage,
+        &delegator1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &data_id1,
+        &mock_block_height(DEFAULT_BLOCK_HEIGHT)
+    ));
+
+    // Only owner can add/remove data labels
+    assert!(is_err(
+        add_data_labels(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &["b".to_string()]
+        ),
+        "Sender is not a data owner."
+    ));
+    assert!(is_err(
+        remove_data_labels(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &["b".to_string()]
+        ),
+        "Sender is not a data owner."
+    ));
+    // Entry doesn't exist
+    assert!(is_err(
+        add_data_labels(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id3,
+            &["b".to_string()]
+        ),
+        "Data entry doesn't exist."
+    ));
+    assert!(is_err(
+        remove_data_labels(
+            deps.as_mut(),
+            &delegator2,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id3,
+            &["b".to_string()]
+        ),
+        "Data entry doesn't exist."
+    ));
+
+    // Remove label "b" from data1
+    assert!(remove_data_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &["b".to_string()]
+    )
+    .is_ok());
+    assert_eq!(
+        store_get_all_data_labels(deps.as_ref().storage, &data_id1),
+        &["a".to_string(), "c".to_string(), "x".to_string()]
+    );
+}
+
+#[test]
+fn test_reencryption_with_labels() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let capsule = String::from("capsule");
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+
+    let delegation_string = String::from("DELESTRING");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 200;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(per_proxy_task_reward_amount * 1),
+    }];
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(1),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1.clone(),
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    /*************** Add data and delegations by delegator *************/
+
+    // Add data by delegator1
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(["x".to_string()].to_vec()),
+    )
+    .is_ok());
+
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(["y".to_string()].to_vec()),
+    )
+    .is_ok());
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
+        proxy_addr: proxy1.clone(),
+        delegation_string: delegation_string.clone(),
+    }];
+
+    // Add delegation
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &Some(["x".to_string()].to_vec()),
+    )
+    .is_ok());
+
+    // Reencryption not permitted
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &creator,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id2,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "Reencryption is not permitted.",
+    ));
+
+    // Only delegator can request data2 for delegatee1
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    // But Data1 can be re-encrypted for delegatee1
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_reencryption_with_policy() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+    let data_id4 = String::from("DATA4");
+    let capsule = String::from("capsule");
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+
+    let label = |label: &str| LabelExpression::Label(label.to_string());
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 200;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(per_proxy_task_reward_amount),
+    }];
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(1),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    /*************** Add data and delegation *************/
+    for (data_id, data_labels) in [
+        (&data_id1, vec!["project-x"]),
+        (&data_id2, vec!["project-x", "restricted"]),
+        (&data_id3, vec!["eu", "tier-2"]),
+        (&data_id4, vec!["us", "tier-1"]),
+    ] {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &Some(data_labels.iter().map(|label| label.to_string()).collect()),
+        )
+        .is_ok());
+    }
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
+        proxy_addr: proxy1.clone(),
+        delegation_string: String::from("DELESTRING"),
+    }];
+
+    // Delegatee label would grant access to DATA2 as well
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &Some(vec!["project-x".to_string()]),
+    )
+    .is_ok());
+    assert!(check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id2
+    ));
+
+    /*************** Invalid policies *************/
+    assert!(is_err(
+        set_delegatee_policy(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &Some(LabelExpression::And(vec![])),
+        ),
+        "at least one operand",
+    ));
+
+    let mut deep_policy = label("project-x");
+    for _ in 0..MAX_POLICY_DEPTH {
+        deep_policy = LabelExpression::Not(Box::new(deep_policy));
+    }
+    assert!(is_err(
+        set_delegatee_policy(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &Some(deep_policy),
+        ),
+        "Policy is too deep",
+    ));
+
+    /*************** project-x AND NOT restricted *************/
+    assert!(set_delegatee_policy(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &Some(LabelExpression::And(vec![
+            label("project-x"),
+            LabelExpression::Not(Box::new(label("restricted"))),
+        ])),
+    )
+    .is_ok());
+
+    assert!(check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id1
+    ));
+    assert!(!check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id2
+    ));
+    assert!(!check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id3
+    ));
+
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &creator,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id2,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "Reencryption is not permitted.",
+    ));
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    /*************** (eu OR us) AND tier-2 *************/
+    assert!(set_delegatee_policy(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &Some(LabelExpression::And(vec![
+            LabelExpression::Or(vec![label("eu"), label("us")]),
+            label("tier-2"),
+        ])),
+    )
+    .is_ok());
+
+    assert!(!check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id1
+    ));
+    assert!(check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id3
+    ));
+    assert!(!check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id4
+    ));
+
+    // Policy of other delegatee is not affected
+    assert!(!check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE2_PUBKEY,
+        &data_id3
+    ));
+
+    /*************** Remove policy *************/
+    assert!(set_delegatee_policy(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &None,
+    )
+    .is_ok());
+
+    // Delegatee labels are used again
+    assert!(check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id2
+    ));
+    assert!(!check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id3
+    ));
+}
+
+#[test]
+fn test_data_access_rules() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let capsule = String::from("capsule");
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 200;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 50;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(per_proxy_task_reward_amount),
+    }];
+
+    // Grant is valid for mock_env() queries
+    let grant_expiration = Expiration::AtHeight(mock_env().block.height + 1);
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(1),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    /*************** Add data and delegation *************/
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(vec!["x".to_string()]),
+    )
+    .is_ok());
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &None,
+    )
+    .is_ok());
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
+        proxy_addr: proxy1.clone(),
+        delegation_string: String::from("DELESTRING"),
+    }];
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &Some(vec!["x".to_string()]),
+    )
+    .is_ok());
+
+    assert!(check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id1
+    ));
+    assert!(!check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id2
+    ));
+
+    /*************** Grant *************/
+    // Only data owner can grant access
+    assert!(is_err(
+        grant_data_access(
+            deps.as_mut(),
+            &creator,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id2,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &None,
+        ),
+        "not a data owner",
+    ));
+
+    assert!(is_err(
+        grant_data_access(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id2,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &Some(Expiration::AtHeight(DEFAULT_BLOCK_HEIGHT)),
+        ),
+        "Grant is already expired",
+    ));
+
+    assert!(grant_data_access(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &Some(grant_expiration.clone()),
+    )
+    .is_ok());
+    assert!(check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id2
+    ));
+
+    // Grant lapses
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &creator,
+            mock_env().block.height + 1,
+            &data_id2,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "Reencryption is not permitted.",
+    ));
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    /*************** Deny *************/
+    assert!(deny_data_access(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .is_ok());
+
+    // Deny overrides label match
+    assert!(!check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id1
+    ));
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &creator,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "Reencryption is not permitted.",
+    ));
+
+    let res: GetDataAccessGrantsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDataAccessGrants {
+                data_id: data_id2.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.grants,
+        vec![DataAccessGrant {
+            delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+            expires_at: Some(grant_expiration),
+        }]
+    );
+    assert!(res.denied_delegatees.is_empty());
+
+    let res: GetDataAccessGrantsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDataAccessGrants {
+                data_id: data_id1.clone(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(res.grants.is_empty());
+    assert_eq!(res.denied_delegatees, vec![DELEGATEE1_PUBKEY.to_string()]);
+
+    /*************** Revoke *************/
+    assert!(is_err(
+        revoke_data_access(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATEE2_PUBKEY.to_string(),
+        ),
+        "No access rule",
+    ));
+    assert!(revoke_data_access(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .is_ok());
+
+    // Labels are used again
+    assert!(check_permission(
+        &deps,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id1
+    ));
+
+    // Rules are removed with data
+    assert!(remove_data(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &data_id2).is_ok());
+    assert!(store_get_all_data_access_rules(deps.as_mut().storage, &data_id2).is_empty());
+}
+
+#[test]
+fn test_delegatee_labels_expiration() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let capsule = String::from("capsule");
+
+    let expiration = Expiration::AtHeight(DEFAULT_BLOCK_HEIGHT + 10);
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &None,
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(vec!["a".to_string(), "b".to_string()]),
+    )
+    .is_ok());
+
+    /*************** Add labels with expiration *************/
+    assert!(is_err(
+        add_delegatee_labels_with_expiration(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT + 10,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &["a".to_string()],
+            &expiration,
+        ),
+        "already expired",
+    ));
+
+    assert!(add_delegatee_labels_with_expiration(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &["a".to_string()],
+        &expiration,
+    )
+    .is_ok());
+    assert!(add_delegatee_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATEE2_PUBKEY.to_string(),
+        &["b".to_string()],
+    )
+    .is_ok());
+
+    let res: GetDelegateeLabelsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDelegateeLabels {
+                delegator_addr: delegator1.clone(),
+                delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delegatee_labels, vec!["a".to_string()]);
+    assert_eq!(
+        res.delegatee_label_expirations,
+        vec![DelegateeLabel {
+            label: "a".to_string(),
+            expires_at: Some(expiration.clone()),
+        }]
+    );
+
+    /*************** Labels lapse *************/
+    for (delegatee_pubkey, height, permitted) in [
+        (DELEGATEE1_PUBKEY, DEFAULT_BLOCK_HEIGHT + 9, true),
+        (DELEGATEE1_PUBKEY, DEFAULT_BLOCK_HEIGHT + 10, false),
+        (DELEGATEE2_PUBKEY, DEFAULT_BLOCK_HEIGHT + 10, true),
+    ] {
+        assert_eq!(
+            get_permission(
+                deps.as_mut().storage,
+                &delegator1,
+                delegatee_pubkey,
+                &data_id1,
+                &mock_block_height(height)
+            ),
+            permitted
+        );
+    }
+
+    // Label added again without expiration is permanent
+    assert!(add_delegatee_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &["a".to_string()],
+    )
+    .is_ok());
+    assert!(get_permission(
+        deps.as_mut().storage,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id1,
+        &mock_block_height(DEFAULT_BLOCK_HEIGHT + 10)
+    ));
+}
+
+#[test]
+fn test_access_requests() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegatee1 = Addr::unchecked("delegatee1".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let capsule = String::from("capsule");
+
+    let timeout_height = DEFAULT_BLOCK_HEIGHT + ACCESS_REQUEST_TIMEOUT_HEIGHT;
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
+        &None,
+        &None,
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    // Delegator has to be known
+    assert!(is_err(
+        request_access(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATOR1_PUBKEY,
+            DELEGATEE1_PUBKEY,
+            &AccessRequestTarget::Labels(vec!["x".to_string()]),
+        ),
+        "Unknown delegator pubkey",
+    ));
+
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(vec!["x".to_string()]),
+    )
+    .is_ok());
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &None,
+    )
+    .is_ok());
+
+    /*************** Request access *************/
+    assert!(is_err(
+        request_access(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATOR1_PUBKEY,
+            DELEGATEE1_PUBKEY,
+            &AccessRequestTarget::Labels(vec![]),
+        ),
+        "Nothing to request",
+    ));
+
+    for (delegatee_pubkey, target) in [
+        (
+            DELEGATEE1_PUBKEY,
+            AccessRequestTarget::Labels(vec!["x".to_string()]),
+        ),
+        (
+            DELEGATEE2_PUBKEY,
+            AccessRequestTarget::DataIds(vec![data_id2.clone()]),
+        ),
+        (
+            DELEGATEE2_PUBKEY,
+            AccessRequestTarget::DataIds(vec![data_id1.clone()]),
+        ),
+    ] {
+        assert!(request_access(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATOR1_PUBKEY,
+            delegatee_pubkey,
+            &target,
+        )
+        .is_ok());
+    }
+
+    assert_eq!(
+        get_pending_access_requests(&deps, DEFAULT_BLOCK_HEIGHT, DELEGATOR1_PUBKEY),
+        vec![0, 1, 2]
+    );
+    assert_eq!(
+        store_get_access_request(deps.as_mut().storage, 0).unwrap(),
+        AccessRequest {
+            delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+            delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+            requester_addr: delegatee1.clone(),
+            target: AccessRequestTarget::Labels(vec!["x".to_string()]),
+            message: Some(String::from("Please")),
+            expiry_height: timeout_height,
+        }
+    );
+
+    /*************** Approve and reject *************/
+    // Only delegator can resolve requests
+    assert!(is_err(
+        approve_access_request(deps.as_mut(), &delegatee1, DEFAULT_BLOCK_HEIGHT, 0),
+        "not owner of delegator pubkey",
+    ));
+    assert!(is_err(
+        approve_access_request(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, 5),
+        "doesn't exist",
+    ));
+
+    let block = mock_block_height(DEFAULT_BLOCK_HEIGHT);
+    assert!(!get_permission(
+        deps.as_mut().storage,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id1,
+        &block
+    ));
+
+    // Labels are added to delegatee
+    assert!(approve_access_request(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, 0).is_ok());
+    assert!(get_permission(
+        deps.as_mut().storage,
+        &delegator1,
+        DELEGATEE1_PUBKEY,
+        &data_id1,
+        &block
+    ));
+
+    // Data access is granted
+    assert!(approve_access_request(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, 1).is_ok());
+    assert_eq!(
+        store_get_data_access_rule(deps.as_mut().storage, &data_id2, DELEGATEE2_PUBKEY),
+        Some(DataAccessRule::Grant { expires_at: None })
+    );
+
+    assert!(reject_access_request(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, 2).is_ok());
+    assert_eq!(
+        store_get_data_access_rule(deps.as_mut().storage, &data_id1, DELEGATEE2_PUBKEY),
+        None
+    );
+
+    // Resolved requests are removed
+    assert!(get_pending_access_requests(&deps, DEFAULT_BLOCK_HEIGHT, DELEGATOR1_PUBKEY).is_empty());
+    assert!(is_err(
+        reject_access_request(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, 2),
+        "doesn't exist",
+    ));
+
+    /*************** Expiration *************/
+    assert!(request_access(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATOR1_PUBKEY,
+        DELEGATEE2_PUBKEY,
+        &AccessRequestTarget::Labels(vec!["x".to_string()]),
+    )
+    .is_ok());
+
+    assert_eq!(
+        get_pending_access_requests(&deps, timeout_height - 1, DELEGATOR1_PUBKEY),
+        vec![3]
+    );
+    assert!(get_pending_access_requests(&deps, timeout_height, DELEGATOR1_PUBKEY).is_empty());
+    assert!(is_err(
+        approve_access_request(deps.as_mut(), &delegator1, timeout_height, 3),
+        "expired",
+    ));
+
+    // Expired requests are pruned with new request
+    assert!(request_access(
+        deps.as_mut(),
+        &delegatee1,
+        timeout_height,
+        DELEGATOR1_PUBKEY,
+        DELEGATEE2_PUBKEY,
+        &AccessRequestTarget::Labels(vec!["x".to_string()]),
+    )
+    .is_ok());
+    assert_eq!(store_get_access_request(deps.as_mut().storage, 3), None);
+    assert_eq!(
+        store_get_all_delegator_access_requests(deps.as_mut().storage, DELEGATOR1_PUBKEY),
+        vec![4]
+    );
+}
+
+#[test]
+fn test_delegatee_authorised_requesters() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegatee1 = Addr::unchecked("delegatee1".to_string());
+    let requester1 = Addr::unchecked("requester1".to_string());
+    let third_party = Addr::unchecked("third_party".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+
+    // Data
+    let data_ids: Vec<String> = (1..=5).map(|i| format!("DATA{}", i)).collect();
+    let capsule = String::from("capsule");
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 1000;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(per_proxy_task_reward_amount),
+    }];
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(1),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    for data_id in &data_ids {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &Some(vec!["x".to_string()]),
+        )
+        .is_ok());
+    }
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
+        proxy_addr: proxy1.clone(),
+        delegation_string: String::from("DELESTRING"),
+    }];
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &Some(vec!["x".to_string()]),
+    )
+    .is_ok());
+
+    /*************** Unregistered delegatee *************/
+    // Anyone can request for unregistered delegatee
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &third_party,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_ids[0],
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    assert!(is_err(
+        add_authorised_requesters(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATEE1_PUBKEY,
+            vec![requester1.clone()],
+        ),
+        "is not registered",
+    ));
+
+    /*************** Register delegatee *************/
+    assert!(register_delegatee(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        vec![requester1.clone()],
+    )
+    .is_ok());
+
+    // Pubkey can be registered only once
+    assert!(is_err(
+        register_delegatee(
+            deps.as_mut(),
+            &third_party,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATEE1_PUBKEY,
+            vec![third_party.clone()],
+        ),
+        "already registered",
+    ));
+
+    let res: GetDelegateeRegistrationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDelegateeRegistration {
+                delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delegatee_addr, Some(delegatee1.clone()));
+    assert_eq!(res.authorised_requesters, vec![requester1.clone()]);
+
+    /*************** Requests *************/
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &third_party,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_ids[1],
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "not authorised to request",
+    ));
+
+    // Authorised requester and delegatee itself can request
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &requester1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_ids[1],
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_ids[2],
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    /*************** Update requesters *************/
+    assert!(is_err(
+        add_authorised_requesters(
+            deps.as_mut(),
+            &third_party,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATEE1_PUBKEY,
+            vec![third_party.clone()],
+        ),
+        "not owner of delegatee pubkey",
+    ));
+
+    assert!(remove_authorised_requesters(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        vec![requester1.clone()],
+    )
+    .is_ok());
+    assert!(add_authorised_requesters(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        vec![third_party.clone()],
+    )
+    .is_ok());
+
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &requester1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_ids[3],
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "not authorised to request",
+    ));
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &third_party,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_ids[3],
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    // Data owner is always allowed to request
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_ids[4],
+        &DELEGATEE1_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+
+    /*************** Override registration *************/
+    // Squatted pubkey can be reclaimed only by delegator of delegatee
+    assert!(register_delegatee(
+        deps.as_mut(),
+        &third_party,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE2_PUBKEY,
+        vec![],
+    )
+    .is_ok());
+
+    assert!(is_err(
+        override_delegatee_registration(
+            deps.as_mut(),
+            &third_party,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATOR1_PUBKEY,
+            DELEGATEE2_PUBKEY,
+            &third_party,
+            vec![],
+        ),
+        "not owner of delegator pubkey",
+    ));
+    assert!(is_err(
+        override_delegatee_registration(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATOR1_PUBKEY,
+            DELEGATEE2_PUBKEY,
+            &delegatee1,
+            vec![],
+        ),
+        "Delegation doesn't exist",
+    ));
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE2_PUBKEY.to_string(),
+        &proxy_delegations,
+        &Some(vec!["x".to_string()]),
+    )
+    .is_ok());
+    assert!(add_authorised_requesters(
+        deps.as_mut(),
+        &third_party,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE2_PUBKEY,
+        vec![third_party.clone()],
+    )
+    .is_ok());
+
+    assert!(override_delegatee_registration(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATOR1_PUBKEY,
+        DELEGATEE2_PUBKEY,
+        &delegatee1,
+        vec![requester1.clone()],
+    )
+    .is_ok());
+
+    let res: GetDelegateeRegistrationResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::GetDelegateeRegistration {
+                delegatee_pubkey: DELEGATEE2_PUBKEY.to_string(),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delegatee_addr, Some(delegatee1.clone()));
+    assert_eq!(res.authorised_requesters, vec![requester1.clone()]);
+
+    // Squatter lost control of pubkey
+    assert!(is_err(
+        add_authorised_requesters(
+            deps.as_mut(),
+            &third_party,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATEE2_PUBKEY,
+            vec![third_party.clone()],
+        ),
+        "not owner of delegatee pubkey",
+    ));
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &third_party,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_ids[0],
+            &DELEGATEE2_PUBKEY.to_string(),
+            &request_reward,
+        ),
+        "not authorised to request",
+    ));
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &requester1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_ids[0],
+        &DELEGATEE2_PUBKEY.to_string(),
+        &request_reward,
+    )
+    .is_ok());
+}
+
+#[test]
+fn test_delegatee_allowance() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegatee1 = Addr::unchecked("delegatee1".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+    let capsule = String::from("capsule");
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+    let proxy2_pubkey: String = String::from("proxy_pubkey2");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 1000;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let deposit = |amount: u128| {
+        vec![Coin {
+            denom: DEFAULT_STAKE_DENOM.to_string(),
+            amount: Uint128::new(amount),
+        }]
+    };
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(1),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    for (proxy_addr, proxy_pubkey) in [(&proxy1, &proxy1_pubkey), (&proxy2, &proxy2_pubkey)] {
+        assert!(register_proxy(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            proxy_pubkey,
+            &proxy_stake,
+        )
+        .is_ok());
+    }
+
+    for data_id in [&data_id1, &data_id2, &data_id3] {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &Some(vec!["x".to_string()]),
+        )
+        .is_ok());
+    }
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![
+        ProxyDelegationString {
+            proxy_addr: proxy1.clone(),
+            delegation_string: String::from("DELESTRING1"),
+        },
+        ProxyDelegationString {
+            proxy_addr: proxy2.clone(),
+            delegation_string: String::from("DELESTRING2"),
+        },
+    ];
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &Some(vec!["x".to_string()]),
+    )
+    .is_ok());
+
+    // Both proxies are rewarded
+    let request_reward_amount = 2 * per_proxy_task_reward_amount;
+
+    /*************** Fund allowance *************/
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &vec![],
+        ),
+        "no allowance for delegatee",
+    ));
+
+    assert!(is_err(
+        fund_delegatee_allowance(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATEE1_PUBKEY,
+            &None,
+            &None,
+            &vec![],
+        ),
+        "has to be funded",
+    ));
+
+    assert!(fund_delegatee_allowance(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        &None,
+        &None,
+        &deposit(100),
+    )
+    .is_ok());
+    assert_eq!(
+        get_allowance_balance(&deps, &delegator1, DELEGATEE1_PUBKEY),
+        Some(100)
+    );
+
+    /*************** Request from allowance *************/
+    let res = request_reencryption(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &vec![],
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        get_allowance_balance(&deps, &delegator1, DELEGATEE1_PUBKEY),
+        Some(100 - request_reward_amount)
+    );
+
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id2,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &vec![],
+        ),
+        "Insufficient delegatee allowance",
+    ));
+
+    // Top up and limit amount drawn by one request
+    assert!(fund_delegatee_allowance(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        &Some(Uint128::new(request_reward_amount - 1)),
+        &None,
+        &deposit(200),
+    )
+    .is_ok());
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id2,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &vec![],
+        ),
+        "exceeds allowance spend limit",
+    ));
+
+    // Terms can be updated without funds
+    assert!(fund_delegatee_allowance(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        &Some(Uint128::new(request_reward_amount)),
+        &None,
+        &vec![],
+    )
+    .is_ok());
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &vec![],
+    )
+    .is_ok());
+    assert_eq!(
+        get_allowance_balance(&deps, &delegator1, DELEGATEE1_PUBKEY),
+        Some(300 - 2 * request_reward_amount)
+    );
+
+    /*************** Refunds go back to allowance *************/
+    assert!(skip_reencryption_task(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .is_ok());
+    let res = skip_reencryption_task(
+        deps.as_mut(),
+        &proxy2,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        get_allowance_balance(&deps, &delegator1, DELEGATEE1_PUBKEY),
+        Some(300 - request_reward_amount)
+    );
+
+    let res = resolve_timed_out_request(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT + DEFAULT_TIMEOUT_HEIGHT,
+        &data_id2,
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        get_allowance_balance(&deps, &delegator1, DELEGATEE1_PUBKEY),
+        Some(300)
+    );
+
+    // Removing data refunds allowance instead of data owner
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id3,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &vec![],
+    )
+    .is_ok());
+    assert_eq!(
+        get_allowance_balance(&deps, &delegator1, DELEGATEE1_PUBKEY),
+        Some(300 - request_reward_amount)
+    );
+
+    let res = remove_data(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &data_id3).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        get_allowance_balance(&deps, &delegator1, DELEGATEE1_PUBKEY),
+        Some(300)
+    );
+
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id3,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(vec!["x".to_string()]),
+    )
+    .is_ok());
+
+    /*************** Expiration *************/
+    assert!(fund_delegatee_allowance(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        &None,
+        &Some(Expiration::AtHeight(DEFAULT_BLOCK_HEIGHT + 10)),
+        &vec![],
+    )
+    .is_ok());
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT + 10,
+            &data_id3,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &vec![],
+        ),
+        "allowance has expired",
+    ));
+
+    /*************** Withdraw allowance *************/
+    let res = withdraw_delegatee_allowance(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT + 10,
+        DELEGATEE1_PUBKEY,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(300, DEFAULT_STAKE_DENOM)],
+        })]
+    );
+    assert_eq!(
+        get_allowance_balance(&deps, &delegator1, DELEGATEE1_PUBKEY),
+        None
+    );
+
+    assert!(is_err(
+        withdraw_delegatee_allowance(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT + 10,
+            DELEGATEE1_PUBKEY,
+        ),
+        "No allowance for delegatee",
+    ));
+}
+
+#[test]
+fn test_subscriptions() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegatee1 = Addr::unchecked("delegatee1".to_string());
+    let other = Addr::unchecked("other".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+
+    // Data
+    let data_id0 = String::from("DATA0");
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+    let capsule = String::from("capsule");
+
+    // Pubkeys
+    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 1000;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let deposit = |amount: u128| {
+        vec![Coin {
+            denom: DEFAULT_STAKE_DENOM.to_string(),
+            amount: Uint128::new(amount),
+        }]
     };
-    let mut staking_config = StakingConfig {
-        stake_denom: "denom".to_string(),
-        minimum_proxy_stake_amount: Uint128::new(0),
-        per_proxy_task_reward_amount: Uint128::new(0),
-        per_task_slash_stake_amount: Uint128::new(0),
+
+    let requested_event = |data_id: &str| {
+        Event::new("subscription_reencryption_requested")
+            .add_attribute("data_id", data_id)
+            .add_attribute("delegatee_pubkey", DELEGATEE1_PUBKEY)
     };
 
-    // zero division case
-    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
-    staking_config.per_task_slash_stake_amount = Uint128::new(0);
-    state.threshold = 123;
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(1),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    assert!(register_proxy(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &proxy1_pubkey,
+        &proxy_stake,
+    )
+    .is_ok());
+
+    assert!(is_err(
+        subscribe(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATOR1_PUBKEY,
+            DELEGATEE1_PUBKEY,
+            &["x"],
+            60,
+            &deposit(60),
+        ),
+        "Unknown delegator pubkey",
+    ));
+
+    assert!(add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id0,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(vec!["x".to_string()]),
+    )
+    .is_ok());
+
+    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
+        proxy_addr: proxy1.clone(),
+        delegation_string: String::from("DELESTRING"),
+    }];
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &Some(vec!["x".to_string()]),
+    )
+    .is_ok());
+
+    /*************** Subscribe *************/
+    assert!(is_err(
+        subscribe(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATOR1_PUBKEY,
+            DELEGATEE1_PUBKEY,
+            &[],
+            60,
+            &deposit(60),
+        ),
+        "No subscription labels",
+    ));
+
+    // Excess funds are returned
+    let res = subscribe(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATOR1_PUBKEY,
+        DELEGATEE1_PUBKEY,
+        &["x"],
+        60,
+        &deposit(70),
+    )
+    .unwrap();
     assert_eq!(
-        get_n_minimum_proxies_for_refund(&state, &staking_config),
-        123
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: delegatee1.to_string(),
+            amount: vec![Coin::new(10, DEFAULT_STAKE_DENOM)],
+        })]
+    );
+    assert_eq!(
+        get_subscription_budget(&deps, DELEGATOR1_PUBKEY, DELEGATEE1_PUBKEY),
+        Some(60)
     );
 
-    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
-    staking_config.per_task_slash_stake_amount = Uint128::new(100);
-    state.threshold = 3;
-    assert_eq!(get_n_minimum_proxies_for_refund(&state, &staking_config), 4);
+    assert!(is_err(
+        subscribe(
+            deps.as_mut(),
+            &other,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATOR1_PUBKEY,
+            DELEGATEE1_PUBKEY,
+            &["y"],
+            60,
+            &deposit(60),
+        ),
+        "already subscribed",
+    ));
 
-    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
-    staking_config.per_task_slash_stake_amount = Uint128::new(100);
-    state.threshold = 123;
+    /*************** Automatic requests *************/
+    let res = add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(vec!["x".to_string()]),
+    )
+    .unwrap();
+    assert_eq!(res.events, vec![requested_event(&data_id1)]);
     assert_eq!(
-        get_n_minimum_proxies_for_refund(&state, &staking_config),
-        244
+        get_subscription_budget(&deps, DELEGATOR1_PUBKEY, DELEGATEE1_PUBKEY),
+        Some(60 - per_proxy_task_reward_amount)
     );
-
-    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
-    staking_config.per_task_slash_stake_amount = Uint128::new(50);
-    state.threshold = 123;
     assert_eq!(
-        get_n_minimum_proxies_for_refund(&state, &staking_config),
-        366
+        get_proxy_tasks(
+            deps.as_mut().storage,
+            &proxy1,
+            &DEFAULT_BLOCK_HEIGHT,
+            &None,
+            None
+        )
+        .unwrap()
+        .proxy_tasks
+        .len(),
+        1
     );
 
-    staking_config.per_proxy_task_reward_amount = Uint128::new(50);
-    staking_config.per_task_slash_stake_amount = Uint128::new(100);
-    state.threshold = 123;
-    assert_eq!(
-        get_n_minimum_proxies_for_refund(&state, &staking_config),
-        183
-    );
+    // Labels don't match
+    let res = add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(vec!["y".to_string()]),
+    )
+    .unwrap();
+    assert!(res.events.is_empty());
 
-    staking_config.per_proxy_task_reward_amount = Uint128::new(1);
-    staking_config.per_task_slash_stake_amount = Uint128::new(121);
-    state.threshold = 123;
-    assert_eq!(
-        get_n_minimum_proxies_for_refund(&state, &staking_config),
-        124
-    );
+    // Remaining budget is not sufficient, data is still added
+    let res = add_data(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id3,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &capsule,
+        &Some(vec!["x".to_string()]),
+    )
+    .unwrap();
+    assert!(res.events.is_empty());
 
-    staking_config.per_proxy_task_reward_amount = Uint128::new(1);
-    staking_config.per_task_slash_stake_amount = Uint128::new(122);
-    state.threshold = 123;
+    // Top up budget and label existing data
+    assert!(subscribe(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATOR1_PUBKEY,
+        DELEGATEE1_PUBKEY,
+        &["x"],
+        40,
+        &deposit(40),
+    )
+    .is_ok());
+    let res = add_data_labels(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id2,
+        &["x".to_string()],
+    )
+    .unwrap();
+    assert_eq!(res.events, vec![requested_event(&data_id2)]);
     assert_eq!(
-        get_n_minimum_proxies_for_refund(&state, &staking_config),
-        123
+        get_subscription_budget(&deps, DELEGATOR1_PUBKEY, DELEGATEE1_PUBKEY),
+        Some(20)
     );
 
-    staking_config.per_proxy_task_reward_amount = Uint128::new(1);
-    staking_config.per_task_slash_stake_amount = Uint128::new(1000);
-    state.threshold = 10;
+    // Refund of automatic request goes to subscriber
+    let res = skip_reencryption_task(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &DELEGATEE1_PUBKEY.to_string(),
+    )
+    .unwrap();
     assert_eq!(
-        get_n_minimum_proxies_for_refund(&state, &staking_config),
-        10
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: delegatee1.to_string(),
+            amount: vec![Coin::new(per_proxy_task_reward_amount, DEFAULT_STAKE_DENOM)],
+        })]
     );
 
-    staking_config.per_proxy_task_reward_amount = Uint128::new(1000);
-    staking_config.per_task_slash_stake_amount = Uint128::new(1);
-    state.threshold = 10;
+    /*************** Unsubscribe *************/
+    assert!(is_err(
+        unsubscribe(
+            deps.as_mut(),
+            &other,
+            DEFAULT_BLOCK_HEIGHT,
+            DELEGATOR1_PUBKEY,
+            DELEGATEE1_PUBKEY,
+        ),
+        "not the subscriber",
+    ));
+
+    let res = unsubscribe(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATOR1_PUBKEY,
+        DELEGATEE1_PUBKEY,
+    )
+    .unwrap();
     assert_eq!(
-        get_n_minimum_proxies_for_refund(&state, &staking_config),
-        9009
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: delegatee1.to_string(),
+            amount: vec![Coin::new(20, DEFAULT_STAKE_DENOM)],
+        })]
     );
-
-    // Large numbers check
-    staking_config.per_proxy_task_reward_amount = Uint128::new(100000000000000000000);
-    staking_config.per_task_slash_stake_amount = Uint128::new(1000000000000);
-    state.threshold = 10;
     assert_eq!(
-        get_n_minimum_proxies_for_refund(&state, &staking_config),
-        900000009
+        get_subscription_budget(&deps, DELEGATOR1_PUBKEY, DELEGATEE1_PUBKEY),
+        None
     );
-
-    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
-    staking_config.per_task_slash_stake_amount = Uint128::new(100);
-    state.threshold = 1;
-    assert_eq!(get_n_minimum_proxies_for_refund(&state, &staking_config), 1);
-
-    staking_config.per_proxy_task_reward_amount = Uint128::new(100);
-    staking_config.per_task_slash_stake_amount = Uint128::new(100);
-    state.threshold = 2;
-    assert_eq!(get_n_minimum_proxies_for_refund(&state, &staking_config), 2);
 }
 
-/*
 #[test]
-fn test_verify_fragments() {
-    assert!(verify_fragment(
-        &FRAGMENT_P1_DR1_DE1.to_string(),
-        &CAPSULE.to_string(),
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
+fn test_cancel_reencryption_request() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegatee1 = Addr::unchecked("delegatee1".to_string());
+    let other = Addr::unchecked("other".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let capsule = String::from("capsule");
+    let delegatee1_pubkey = DELEGATEE1_PUBKEY.to_string();
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 1000;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(3 * per_proxy_task_reward_amount),
+    }];
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(2),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
     )
     .is_ok());
 
-    assert!(verify_fragment(
-        &FRAGMENT_P1_DR1_DE2.to_string(),
-        &CAPSULE.to_string(),
+    let mut proxy_delegations: Vec<ProxyDelegationString> = Vec::new();
+    for proxy_addr in [&proxy1, &proxy2, &proxy3] {
+        assert!(register_proxy(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &format!("{}_pubkey", proxy_addr),
+            &proxy_stake,
+        )
+        .is_ok());
+
+        proxy_delegations.push(ProxyDelegationString {
+            proxy_addr: proxy_addr.clone(),
+            delegation_string: format!("DELESTRING_{}", proxy_addr),
+        });
+    }
+
+    for data_id in [&data_id1, &data_id2] {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &Some(vec!["x".to_string()]),
+        )
+        .is_ok());
+    }
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
         &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
+        &delegatee1_pubkey,
+        &proxy_delegations,
+        &Some(vec!["x".to_string()]),
     )
-    .is_err());
+    .is_ok());
+
+    /*************** Request and cancel *************/
+    assert!(is_err(
+        cancel_reencryption_request(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &delegatee1_pubkey,
+        ),
+        "doesn't exist",
+    ));
+
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &delegatee1_pubkey,
+        &request_reward,
+    )
+    .is_ok());
+
+    assert!(provide_reencrypted_fragment(
+        deps.as_mut(),
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &delegatee1_pubkey,
+        &String::from("fragment1"),
+    )
+    .is_ok());
+
+    assert!(is_err(
+        cancel_reencryption_request(
+            deps.as_mut(),
+            &other,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &delegatee1_pubkey,
+        ),
+        "Only requester",
+    ));
+
+    // Rewards of proxies without fragment are refunded
+    let res = cancel_reencryption_request(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &delegatee1_pubkey,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: delegatee1.to_string(),
+            amount: vec![Coin::new(
+                2 * per_proxy_task_reward_amount,
+                DEFAULT_STAKE_DENOM
+            )],
+        })]
+    );
+
+    // Delivering proxy keeps reward, others get stake back without slashing
+    assert_eq!(
+        store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+            .unwrap()
+            .unwrap()
+            .stake_amount
+            .u128(),
+        minimum_proxy_stake_amount + per_proxy_task_reward_amount
+    );
+    for proxy_addr in [&proxy2, &proxy3] {
+        assert_eq!(
+            store_get_proxy_entry(deps.as_mut().storage, proxy_addr)
+                .unwrap()
+                .unwrap()
+                .stake_amount
+                .u128(),
+            minimum_proxy_stake_amount
+        );
+        assert!(get_proxy_tasks(
+            deps.as_mut().storage,
+            proxy_addr,
+            &DEFAULT_BLOCK_HEIGHT,
+            &None,
+            None
+        )
+        .unwrap()
+        .proxy_tasks
+        .is_empty());
+    }
+
+    // Cancelled request can be requested again
+    assert!(request_reencryption(
+        deps.as_mut(),
+        &delegatee1,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &delegatee1_pubkey,
+        &request_reward,
+    )
+    .is_ok());
+
+    /*************** Granted request *************/
+    for proxy_addr in [&proxy1, &proxy2] {
+        assert!(provide_reencrypted_fragment(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &delegatee1_pubkey,
+            &format!("fragment_{}", proxy_addr),
+        )
+        .is_ok());
+    }
+
+    assert!(is_err(
+        cancel_reencryption_request(
+            deps.as_mut(),
+            &delegatee1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &delegatee1_pubkey,
+        ),
+        "Only pending reencryption request",
+    ));
 }
- */
 
 #[test]
-fn test_timeouts() {
+fn test_repeated_reencryption_requests() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegatee1 = Addr::unchecked("delegatee1".to_string());
     let proxy1 = Addr::unchecked("proxy_1".to_string());
     let proxy2 = Addr::unchecked("proxy_2".to_string());
     let proxy3 = Addr::unchecked("proxy_3".to_string());
 
-    let delegator1 = Addr::unchecked("delegator1".to_string());
-    let delegator2 = Addr::unchecked("delegator2".to_string());
-
-    // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy_pubkey1");
-    let proxy2_pubkey: String = String::from("proxy_pubkey2");
-    let proxy3_pubkey: String = String::from("proxy_pubkey3");
-
+    // Data
     let data_id1 = String::from("DATA1");
-    let data_id2 = String::from("DATA2");
-    let delegation_string = String::from("DELESTRING");
+    let capsule = String::from("capsule");
+    let delegatee1_pubkey = DELEGATEE1_PUBKEY.to_string();
 
     // Staking
     let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let minimum_proxy_stake_amount: u128 = 100;
+    let minimum_proxy_stake_amount: u128 = 1000;
     let per_proxy_task_reward_amount: u128 = 40;
     let per_task_slash_stake_amount: u128 = 98;
 
-    // Timetous
-    let timeout_height: u64 = 100;
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
 
+    let request_reward_amount = 3 * per_proxy_task_reward_amount;
     let request_reward = vec![Coin {
         denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(DEFAULT_TASK_REWARD_AMOUNT * 3),
+        amount: Uint128::new(request_reward_amount),
     }];
 
-    // Scenario
-
     /*************** Initialise *************/
-    let proxies: Vec<Addr> = vec![proxy1.clone(), proxy2.clone(), proxy3.clone()];
     assert!(init_contract(
         deps.as_mut(),
         &creator,
-        0,
+        DEFAULT_BLOCK_HEIGHT,
         &Some(2),
         &None,
-        &Some(proxies),
+        &None,
         &stake_denom,
         &Some(Uint128::new(minimum_proxy_stake_amount)),
         &Some(Uint128::new(per_proxy_task_reward_amount)),
         &Some(Uint128::new(per_task_slash_stake_amount)),
-        &Some(timeout_height),
         &None,
         &None,
-    )
-    .is_ok());
-
-    let proxy_register_stake = vec![Coin {
-        denom: stake_denom.clone(),
-        amount: Uint128::new(DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT),
-    }];
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy1,
-        0,
-        &proxy1_pubkey,
-        &proxy_register_stake,
-    )
-    .is_ok());
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy2,
-        0,
-        &proxy2_pubkey,
-        &proxy_register_stake,
-    )
-    .is_ok());
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy3,
-        0,
-        &proxy3_pubkey,
-        &proxy_register_stake,
-    )
-    .is_ok());
-
-    /*************** Add data and delegations by delegator *************/
-    // Add data by delegator
-    assert!(add_data(
-        deps.as_mut(),
-        &delegator1,
-        0,
-        &data_id1,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
         &None,
     )
     .is_ok());
 
-    assert!(add_data(
-        deps.as_mut(),
-        &delegator2,
-        0,
-        &data_id2,
-        &DELEGATOR2_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
-        &None,
-    )
-    .is_ok());
+    let mut proxy_delegations: Vec<ProxyDelegationString> = Vec::new();
+    for proxy_addr in [&proxy1, &proxy2, &proxy3] {
+        assert!(register_proxy(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &format!("{}_pubkey", proxy_addr),
+            &proxy_stake,
+        )
+        .is_ok());
 
-    // Add delegations
-    let delegation1: Vec<ProxyDelegationString> = vec![
-        ProxyDelegationString {
-            proxy_addr: proxy1.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy2.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy3.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-    ];
+        proxy_delegations.push(ProxyDelegationString {
+            proxy_addr: proxy_addr.clone(),
+            delegation_string: format!("DELESTRING_{}", proxy_addr),
+        });
+    }
 
-    assert!(add_delegation(
+    assert!(add_data(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
         &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &delegation1,
+        &capsule,
         &None,
     )
     .is_ok());
 
-    let delegation2: Vec<ProxyDelegationString> = vec![
-        ProxyDelegationString {
-            proxy_addr: proxy1.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy3.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-    ];
-
     assert!(add_delegation(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE2_PUBKEY.to_string(),
-        &delegation2,
-        &None,
-    )
-    .is_ok());
-
-    let delegation3: Vec<ProxyDelegationString> = vec![
-        ProxyDelegationString {
-            proxy_addr: proxy1.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy2.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy3.clone(),
-            delegation_string: delegation_string,
-        },
-    ];
-
-    assert!(add_delegation(
-        deps.as_mut(),
-        &delegator2,
-        DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR2_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &delegation3,
+        &delegatee1_pubkey,
+        &proxy_delegations,
         &None,
     )
     .is_ok());
 
-    /*************** Add and check re-encryption requests *************/
-
-    // Requests:
-    // req1 - (0)proxy1, (1)proxy2, (2)proxy3  - timeout at 300
-    // req2 - (3)proxy1,            (4)proxy3  - timeout at 350
-    // req3 - (4)proxy1, (5)proxy2, (6)proxy3  - timeout at 400
-
-    // Request 1 will timeout at 200+100
+    /*************** Abandoned request *************/
     assert!(request_reencryption(
         deps.as_mut(),
         &delegator1,
-        200,
+        DEFAULT_BLOCK_HEIGHT,
         &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
+        &delegatee1_pubkey,
         &request_reward,
     )
     .is_ok());
 
-    // Complete request1 before timeout
-    assert!(provide_reencrypted_fragment(
-        deps.as_mut(),
-        &proxy1,
-        220,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &FRAGMENT_P1_DR1_DE1.to_string(),
-    )
-    .is_ok());
-    assert!(provide_reencrypted_fragment(
-        deps.as_mut(),
-        &proxy2,
-        220,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &FRAGMENT_P2_DR1_DE1.to_string(),
-    )
-    .is_ok());
-
-    let state = store_get_state(deps.as_mut().storage).unwrap();
-    // Check if request is completed
-    assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
+    // Pending request can't be repeated
+    assert!(is_err(
+        request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
             &data_id1,
-            DELEGATEE1_PUBKEY,
-            &220u64,
+            &delegatee1_pubkey,
+            &request_reward,
         ),
-        ReencryptionRequestState::Granted
-    );
-    // Check if request1 state stays granted after timeout
-    let state = store_get_state(deps.as_mut().storage).unwrap();
-    assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
+        "Reencryption already requested",
+    ));
+
+    for proxy_addr in [&proxy1, &proxy2] {
+        assert!(skip_reencryption_task(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
             &data_id1,
-            DELEGATEE1_PUBKEY,
-            &400u64,
-        ),
-        ReencryptionRequestState::Granted
-    );
+            &delegatee1_pubkey,
+        )
+        .is_ok());
+    }
 
-    // Request 2 will timeout at 250+100
-    assert!(request_reencryption(
+    /*************** Timed-out request *************/
+    // Abandoned request was already refunded
+    let res = request_reencryption(
         deps.as_mut(),
         &delegator1,
-        250,
+        DEFAULT_BLOCK_HEIGHT,
         &data_id1,
-        &DELEGATEE2_PUBKEY.to_string(),
+        &delegatee1_pubkey,
         &request_reward,
     )
-    .is_ok());
+    .unwrap();
+    assert!(res.messages.is_empty());
 
-    // Check request 2 timeout height
-    let state = store_get_state(deps.as_mut().storage).unwrap();
     assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
-            &data_id1,
-            DELEGATEE2_PUBKEY,
-            &350u64,
-        ),
-        ReencryptionRequestState::TimedOut
+        get_reencryption_requests(&deps, DEFAULT_BLOCK_HEIGHT, &data_id1, DELEGATEE1_PUBKEY),
+        vec![
+            (0, ReencryptionRequestState::Abandoned, 0),
+            (1, ReencryptionRequestState::Ready, 0)
+        ]
     );
 
-    // Height = 250
-    // Current states of requests/tasks:
-    // req1 - (0)proxy1, (1)proxy2, (2)proxy3  - timeout at 300 - Completed
-    // req2 - (3)proxy1,            (4)proxy3  - timeout at 350 - Ready
-    // req3 - (4)proxy1, (5)proxy2, (6)proxy3  - timeout at 400 - Will be created at height 300
-
-    // Request 3 will timeout at 300+100
-    assert!(request_reencryption(
+    /*************** Granted request *************/
+    // Retry resolves timed-out request
+    let timeout_height = DEFAULT_BLOCK_HEIGHT + DEFAULT_TIMEOUT_HEIGHT;
+    let res = request_reencryption(
         deps.as_mut(),
-        &delegator2,
-        300,
-        &data_id2,
-        &DELEGATEE1_PUBKEY.to_string(),
+        &delegator1,
+        timeout_height,
+        &data_id1,
+        &delegatee1_pubkey,
         &request_reward,
     )
-    .is_ok());
-
-    // Height = 300
-    // Current states of requests/tasks:
-    // req1 - (0)proxy1, (1)proxy2, (2)proxy3  - timeout at 300 - Completed
-    // req2 - (3)proxy1,            (4)proxy3  - timeout at 350 - Ready
-    // req3 - (4)proxy1, (5)proxy2, (6)proxy3  - timeout at 400 - Ready
-
-    // Unregister proxy after timeout
-    // Request gets first timed out and then abandoned (will be displayed as TimedOut)
-    let res = unregister_proxy(deps.as_mut(), &proxy3, 350).unwrap();
-    assert_eq!(res.messages.len(), 2);
-    // Timeout BankMsgs
-    // Funds from timed out request 2 are sent back to delegator1
+    .unwrap();
     assert_eq!(
-        res.messages[0],
-        SubMsg::new(BankMsg::Send {
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
             to_address: delegator1.to_string(),
-            amount: vec![Coin::new(
-                2 * per_proxy_task_reward_amount,
-                stake_denom.as_str(),
-            )],
-        })
+            amount: vec![Coin::new(request_reward_amount, DEFAULT_STAKE_DENOM)],
+        })]
     );
-    // Unregister BankMsgs
-    // Proxy 3 got slashed for 3 unfinished tasks
+    assert!(res.attributes.contains(&Attribute::new("request_seq", "2")));
+
+    // Skipped tasks and unswept timed-out tasks are each slashed once
     assert_eq!(
-        res.messages[1],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy3.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 3 * per_task_slash_stake_amount,
-                stake_denom.as_str(),
-            )],
-        })
+        get_contract_stats(&deps).total_slashed,
+        Uint128::new(5 * per_task_slash_stake_amount)
     );
-
-    let state = store_get_state(deps.as_mut().storage).unwrap();
+    let res = resolve_timed_out_requests(deps.as_mut(), &creator, timeout_height, None).unwrap();
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("resolved_tasks", "0")));
     assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
-            &data_id1,
-            &DELEGATEE2_PUBKEY.to_string(),
-            &350u64,
-        ),
-        ReencryptionRequestState::TimedOut
+        get_contract_stats(&deps).total_slashed,
+        Uint128::new(5 * per_task_slash_stake_amount)
     );
 
-    // Height = 350
-    // Current states of requests/tasks:
-    // Proxy 3 unregistered
-    // req1 - (0)  proxy1,  (1)proxy2,   (2)  proxy3  - timeout at 300 - Completed
-    // req2 - (x3x)proxy1,               (x4x)proxy3  - timeout at 350 - TimedOut (Abandoned)
-    // req3 - (4)  proxy1,  (5)proxy2,   (x6x)proxy3  - timeout at 400 - Ready
+    for proxy_addr in [&proxy1, &proxy2] {
+        assert!(provide_reencrypted_fragment(
+            deps.as_mut(),
+            proxy_addr,
+            timeout_height,
+            &data_id1,
+            &delegatee1_pubkey,
+            &format!("fragment_{}", proxy_addr),
+        )
+        .is_ok());
+    }
 
-    // Cannot provide fragment for timed-out request2
     assert!(is_err(
-        provide_reencrypted_fragment(
+        request_reencryption(
             deps.as_mut(),
-            &proxy1,
-            350,
+            &delegator1,
+            timeout_height,
             &data_id1,
-            &DELEGATEE2_PUBKEY.to_string(),
-            &FRAGMENT_P1_DR1_DE2.to_string(),
+            &delegatee1_pubkey,
+            &request_reward,
         ),
-        "Request timed out.",
+        "Reencryption already requested",
     ));
 
-    // Tasks can be obtained before timeout
-    assert!(!get_proxy_tasks(deps.as_mut().storage, &proxy1, &350)
-        .unwrap()
-        .is_empty());
-    assert!(!get_proxy_tasks(deps.as_mut().storage, &proxy2, &350)
-        .unwrap()
-        .is_empty());
-    assert!(get_proxy_tasks(deps.as_mut().storage, &proxy3, &350)
-        .unwrap()
-        .is_empty());
-
-    // No tasks to complete after timeout
-    // These tasks still exist but are skipped in get_proxy_tasks
-    assert!(get_proxy_tasks(deps.as_mut().storage, &proxy1, &500)
-        .unwrap()
-        .is_empty());
-    assert!(get_proxy_tasks(deps.as_mut().storage, &proxy2, &500)
-        .unwrap()
-        .is_empty());
-    assert!(get_proxy_tasks(deps.as_mut().storage, &proxy3, &500)
-        .unwrap()
-        .is_empty());
-
-    let res = resolve_timed_out_request(
-        deps.as_mut(),
-        &delegator2,
-        500,
-        &data_id2,
-        &DELEGATEE1_PUBKEY.to_string(),
+    // Fragments query returns latest request
+    let res: GetFragmentsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env_height(&delegatee1, timeout_height, &vec![]).0,
+            QueryMsg::GetFragments {
+                data_id: data_id1.clone(),
+                delegatee_pubkey: delegatee1_pubkey.clone(),
+            },
+        )
+        .unwrap(),
     )
     .unwrap();
-    assert_eq!(res.messages.len(), 1);
+    assert_eq!(
+        res.reencryption_request_state,
+        ReencryptionRequestState::Granted
+    );
+    assert_eq!(res.fragments.len(), 2);
 
-    // Delegator 2 gets a refund.
+    // States of past requests are aggregated the same way, incomplete ones time out eventually
     assert_eq!(
-        res.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: delegator2.to_string(),
-            amount: vec![Coin::new(
-                3 * per_proxy_task_reward_amount,
-                stake_denom.as_str(),
-            )],
-        })
+        get_reencryption_requests(&deps, timeout_height, &data_id1, DELEGATEE1_PUBKEY),
+        vec![
+            (0, ReencryptionRequestState::TimedOut, 0),
+            (1, ReencryptionRequestState::TimedOut, 0),
+            (2, ReencryptionRequestState::Granted, 2)
+        ]
     );
 
-    // Height = 500
-    // Current states of requests:
-    // Proxy 3 unregistered
-    // req1 - (0)  proxy1,  (1)proxy2,       (2)  proxy3  - timeout at 300 - Completed
-    // req2 - (x3x)proxy1,                   (x4x)proxy3  - timeout at 350 - TimedOut (Abandoned)
-    // req3 - (x4x)  proxy1,  (x5x)proxy2,   (x6x)proxy3  - timeout at 400 - TimedOut
+    /*************** Remove data *************/
+    // Only unfinished task of latest request is refunded
+    let res = remove_data(deps.as_mut(), &delegator1, timeout_height, &data_id1).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(per_proxy_task_reward_amount, DEFAULT_STAKE_DENOM)],
+        })]
+    );
+    assert!(
+        get_reencryption_requests(&deps, timeout_height, &data_id1, DELEGATEE1_PUBKEY).is_empty()
+    );
+    assert_eq!(
+        store_get_next_request_seq(deps.as_mut().storage, &data_id1, DELEGATEE1_PUBKEY),
+        0
+    );
+}
 
-    // Check if tasks from queue were deleted due to refund
+#[test]
+fn test_resolve_timed_out_requests() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let keeper = Addr::unchecked("keeper".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let capsule = String::from("capsule");
+    let delegatee1_pubkey = DELEGATEE1_PUBKEY.to_string();
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 1000;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(3 * per_proxy_task_reward_amount),
+    }];
+
+    /*************** Initialise *************/
+    assert!(init_contract(
+        deps.as_mut(),
+        &creator,
+        DEFAULT_BLOCK_HEIGHT,
+        &Some(2),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
+    )
+    .is_ok());
+
+    let mut proxy_delegations: Vec<ProxyDelegationString> = Vec::new();
+    for proxy_addr in [&proxy1, &proxy2, &proxy3] {
+        assert!(register_proxy(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &format!("{}_pubkey", proxy_addr),
+            &proxy_stake,
+        )
+        .is_ok());
+
+        proxy_delegations.push(ProxyDelegationString {
+            proxy_addr: proxy_addr.clone(),
+            delegation_string: format!("DELESTRING_{}", proxy_addr),
+        });
+    }
+
+    assert!(add_delegation(
+        deps.as_mut(),
+        &delegator1,
+        DEFAULT_BLOCK_HEIGHT,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &delegatee1_pubkey,
+        &proxy_delegations,
+        &None,
+    )
+    .is_ok());
+
+    for data_id in [&data_id1, &data_id2] {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &None,
+        )
+        .is_ok());
+
+        assert!(request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &delegatee1_pubkey,
+            &request_reward,
+        )
+        .is_ok());
+    }
+
+    /*************** Sweep before timeout *************/
+    let res =
+        resolve_timed_out_requests(deps.as_mut(), &keeper, DEFAULT_BLOCK_HEIGHT, None).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("resolved_tasks", "0")));
+
+    // Skipped task is slashed right away, it doesn't count to keeper bounty
+    assert!(skip_reencryption_task(
+        deps.as_mut(),
+        &proxy3,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &delegatee1_pubkey,
+    )
+    .is_ok());
+
+    /*************** Sweep after timeout *************/
+    let timeout_height = DEFAULT_BLOCK_HEIGHT + DEFAULT_TIMEOUT_HEIGHT;
+
+    // Request of DATA2 is resolved directly, its timeout entries are skipped by sweep
+    assert!(resolve_timed_out_request(
+        deps.as_mut(),
+        &delegator1,
+        timeout_height,
+        &data_id2,
+        &delegatee1_pubkey,
+    )
+    .is_ok());
+
+    // Limit is applied to timeout index entries
+    let res = resolve_timed_out_requests(deps.as_mut(), &keeper, timeout_height, Some(2)).unwrap();
     assert_eq!(
-        get_proxy_tasks(deps.as_mut().storage, &proxy1, &0)
-            .unwrap()
-            .len(),
-        1
+        res.messages,
+        vec![
+            SubMsg::new(BankMsg::Send {
+                to_address: delegator1.to_string(),
+                amount: vec![Coin::new(
+                    2 * per_proxy_task_reward_amount,
+                    DEFAULT_STAKE_DENOM
+                )],
+            }),
+            SubMsg::new(BankMsg::Send {
+                to_address: keeper.to_string(),
+                amount: vec![Coin::new(
+                    2 * per_task_slash_stake_amount * KEEPER_BOUNTY_PERCENTAGE / 100,
+                    DEFAULT_STAKE_DENOM
+                )],
+            })
+        ]
     );
-    assert!(get_proxy_tasks(deps.as_mut().storage, &proxy2, &0)
-        .unwrap()
-        .is_empty());
-    assert!(get_proxy_tasks(deps.as_mut().storage, &proxy3, &0)
-        .unwrap()
-        .is_empty());
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("resolved_tasks", "2")));
 
-    // Unregister proxy 1
-    let res = unregister_proxy(deps.as_mut(), &proxy1, 500).unwrap();
-    assert_eq!(res.messages.len(), 1);
-    // Proxy 1 got slashed for 2 tasks, rewarded for 1 task
+    // Remaining skipped task of DATA1 is refunded without bounty, tasks of DATA2 were already refunded
+    let res = resolve_timed_out_requests(deps.as_mut(), &keeper, timeout_height, None).unwrap();
     assert_eq!(
-        res.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy1.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 2 * per_task_slash_stake_amount
-                    + per_proxy_task_reward_amount,
-                stake_denom.as_str(),
-            )],
-        })
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: delegator1.to_string(),
+            amount: vec![Coin::new(per_proxy_task_reward_amount, DEFAULT_STAKE_DENOM)],
+        })]
     );
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("resolved_tasks", "1")));
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("keeper_bounty", "0")));
 
-    // Unregister proxy 2
-    let res = unregister_proxy(deps.as_mut(), &proxy2, 500).unwrap();
-    assert_eq!(res.messages.len(), 1);
-    // Proxy 1 got slashed for 1 task and rewarded for 1 task
     assert_eq!(
-        res.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: proxy2.to_string(),
-            amount: vec![Coin::new(
-                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - per_task_slash_stake_amount
-                    + per_proxy_task_reward_amount,
-                stake_denom.as_str(),
-            )],
-        })
+        get_reencryption_requests(&deps, timeout_height, &data_id1, DELEGATEE1_PUBKEY),
+        vec![(0, ReencryptionRequestState::TimedOut, 0)]
     );
+
+    // Timeout index is empty
+    let res = resolve_timed_out_requests(deps.as_mut(), &keeper, timeout_height, None).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("resolved_tasks", "0")));
+
+    // Nothing is refunded twice
+    let res = resolve_timed_out_request(
+        deps.as_mut(),
+        &delegator1,
+        timeout_height,
+        &data_id1,
+        &delegatee1_pubkey,
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    /*************** Removing swept data *************/
+    // Resolved tasks are neither refunded again nor get slashed stake back
+    let res = remove_data(deps.as_mut(), &delegator1, timeout_height, &data_id1).unwrap();
+    assert!(res.messages.is_empty());
+    assert!(res.attributes.contains(&Attribute::new(
+        "json",
+        "{\"remove_data\":{\"proxies\":[]}}"
+    )));
+
+    for proxy_addr in [&proxy1, &proxy2, &proxy3] {
+        assert_eq!(
+            store_get_proxy_entry(deps.as_mut().storage, proxy_addr)
+                .unwrap()
+                .unwrap()
+                .stake_amount,
+            Uint128::new(minimum_proxy_stake_amount - 2 * per_task_slash_stake_amount)
+        );
+    }
 }
 
 #[test]
-fn test_terminate_contract() {
+fn test_sudo_and_admin_controls() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let proxy1 = Addr::unchecked("proxy_1".to_string());
-    let proxy2 = Addr::unchecked("proxy_2".to_string());
-    let proxy3 = Addr::unchecked("proxy_3".to_string());
-    let recipient = Addr::unchecked("recipient".to_string());
-
+    let new_admin = Addr::unchecked("new_admin".to_string());
     let delegator1 = Addr::unchecked("delegator1".to_string());
-    let delegator2 = Addr::unchecked("delegator2".to_string());
-
-    // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy_pubkey1");
-    let proxy2_pubkey: String = String::from("proxy_pubkey2");
-    let proxy3_pubkey: String = String::from("proxy_pubkey3");
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
 
+    // Data
     let data_id1 = String::from("DATA1");
-    let delegation_string = String::from("DELESTRING");
+    let capsule = String::from("capsule");
 
     // Staking
     let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let minimum_proxy_stake_amount: u128 = 200;
-    let per_proxy_task_reward_amount: u128 = 40;
-    let per_task_slash_stake_amount: u128 = 98;
-
-    let withdrawal_period: u64 = 399;
-
-    let request_reward = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(per_proxy_task_reward_amount * 3),
-    }];
+    let minimum_proxy_stake_amount: u128 = 1000;
 
     let proxy_stake = vec![Coin {
         denom: DEFAULT_STAKE_DENOM.to_string(),
         amount: Uint128::new(minimum_proxy_stake_amount),
     }];
 
-    // Timetous
-    let timeout_height: u64 = 100;
-
     /*************** Initialise *************/
     assert!(init_contract(
         deps.as_mut(),
         &creator,
-        0,
-        &Some(1),
+        DEFAULT_BLOCK_HEIGHT,
+        &None,
         &None,
         &None,
         &stake_denom,
         &Some(Uint128::new(minimum_proxy_stake_amount)),
-        &Some(Uint128::new(per_proxy_task_reward_amount)),
-        &Some(Uint128::new(per_task_slash_stake_amount)),
-        &Some(timeout_height),
-        &Some(false),
-        &Some(withdrawal_period),
+        &None,
+        &None,
+        &None,
+        &None,
+        &None,
     )
     .is_ok());
 
@@ -4788,259 +11166,423 @@ fn test_terminate_contract() {
         deps.as_mut(),
         &proxy1,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy1_pubkey,
+        &String::from("proxy_1_pubkey"),
         &proxy_stake,
     )
     .is_ok());
 
-    assert!(register_proxy(
+    /*************** Pause *************/
+    assert!(is_err(
+        pause_contract(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT),
+        "Only admin",
+    ));
+    assert!(pause_contract(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT).is_ok());
+    assert!(is_err(
+        sudo_height(
+            deps.as_mut(),
+            DEFAULT_BLOCK_HEIGHT,
+            SudoMsg::PauseContract {}
+        ),
+        "Contract is already paused.",
+    ));
+
+    let res: GetContractStateResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetContractState {}).unwrap())
+            .unwrap();
+    assert!(res.paused);
+
+    assert!(is_err(
+        add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &None,
+        ),
+        "Contract is paused.",
+    ));
+
+    // Governance lifts pause set by admin
+    assert!(sudo_height(
         deps.as_mut(),
-        &proxy2,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy2_pubkey,
-        &proxy_stake,
+        SudoMsg::UnpauseContract {}
     )
     .is_ok());
-
-    // Prepare scenario
-    // Proxy 1,2 gets 2 re-encryption tasks each
-
     assert!(add_data(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &data_id1,
         &DELEGATOR1_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
+        &capsule,
         &None,
     )
     .is_ok());
 
-    let proxy_delegations: Vec<ProxyDelegationString> = vec![
-        ProxyDelegationString {
-            proxy_addr: proxy1.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy2.clone(),
-            delegation_string: delegation_string,
-        },
-    ];
-
-    // Add delegations
-    assert!(add_delegation(
+    /*************** Config *************/
+    assert!(is_err(
+        sudo_height(
+            deps.as_mut(),
+            DEFAULT_BLOCK_HEIGHT,
+            SudoMsg::UpdateConfig {
+                minimum_proxy_stake_amount: None,
+                timeout_height: Some(0),
+                proxy_whitelisting: None,
+            },
+        ),
+        "Timeout height cannot be 0",
+    ));
+    assert!(sudo_height(
         deps.as_mut(),
-        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy_delegations,
-        &None,
+        SudoMsg::UpdateConfig {
+            minimum_proxy_stake_amount: Some(Uint128::new(2000)),
+            timeout_height: None,
+            proxy_whitelisting: Some(true),
+        },
     )
     .is_ok());
-    assert!(add_delegation(
+
+    let state = store_get_state(deps.as_mut().storage).unwrap();
+    assert!(state.proxy_whitelisting);
+    assert_eq!(
+        store_get_staking_config(deps.as_mut().storage)
+            .unwrap()
+            .minimum_proxy_stake_amount,
+        Uint128::new(2000)
+    );
+
+    /*************** Admin replacement *************/
+    // Compromised admin key is replaced by governance
+    let res = sudo_height(
         deps.as_mut(),
-        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE2_PUBKEY.to_string(),
-        &proxy_delegations,
-        &None,
+        SudoMsg::UpdateAdmin {
+            admin: new_admin.clone(),
+        },
     )
-    .is_ok());
+    .unwrap();
+    assert!(res.attributes.contains(&Attribute::new("sudo", "true")));
 
-    assert!(request_reencryption(
+    assert!(is_err(
+        update_admin(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &creator),
+        "Only admin",
+    ));
+    assert!(pause_contract(deps.as_mut(), &new_admin, DEFAULT_BLOCK_HEIGHT).is_ok());
+
+    /*************** Forced proxy removal *************/
+    let res = sudo_height(
         deps.as_mut(),
-        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &request_reward,
+        SudoMsg::RemoveProxy {
+            proxy_addr: proxy1.clone(),
+        },
     )
-    .is_ok());
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: proxy1.to_string(),
+            amount: vec![Coin::new(minimum_proxy_stake_amount, DEFAULT_STAKE_DENOM)],
+        })]
+    );
+    assert!(store_get_proxy_entry(deps.as_mut().storage, &proxy1)
+        .unwrap()
+        .is_none());
 
-    assert!(request_reencryption(
+    assert!(is_err(
+        sudo_height(
+            deps.as_mut(),
+            DEFAULT_BLOCK_HEIGHT,
+            SudoMsg::RemoveProxy {
+                proxy_addr: proxy1.clone(),
+            },
+        ),
+        "Sender is not a proxy",
+    ));
+}
+
+#[test]
+fn test_list_queries() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let delegator2 = Addr::unchecked("delegator2".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+    let capsule = String::from("capsule");
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 1000;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(3 * per_proxy_task_reward_amount),
+    }];
+
+    /*************** Initialise *************/
+    assert!(init_contract(
         deps.as_mut(),
-        &delegator1,
+        &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &request_reward,
+        &Some(2),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
+        &None,
+        &None,
     )
     .is_ok());
 
-    // Both proxies are available
-    assert_eq!(get_proxies_availability(deps.as_mut().storage).len(), 2);
+    let mut proxy_delegations: Vec<ProxyDelegationString> = Vec::new();
+    for proxy_addr in [&proxy1, &proxy2, &proxy3] {
+        assert!(register_proxy(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &format!("{}_pubkey", proxy_addr),
+            &proxy_stake,
+        )
+        .is_ok());
 
-    // Try to withdraw contract
-    assert!(is_err(
-        withdraw_contract(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &recipient),
-        "Only admin can execute this method.",
-    ));
+        proxy_delegations.push(ProxyDelegationString {
+            proxy_addr: proxy_addr.clone(),
+            delegation_string: format!("DELESTRING_{}", proxy_addr),
+        });
+    }
 
-    assert!(is_err(
-        withdraw_contract(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &recipient),
-        "Contract not terminated",
-    ));
+    for data_id in [&data_id1, &data_id2, &data_id3] {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &None,
+        )
+        .is_ok());
+    }
 
-    // Terminate contract
-    assert!(is_err(
-        terminate_contract(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT),
-        "Only admin can execute this method.",
-    ));
-    assert!(terminate_contract(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT).is_ok());
-    assert!(is_err(
-        terminate_contract(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT),
-        "Contract was terminated.",
-    ));
+    for (delegator_addr, delegator_pubkey, delegatee_pubkey) in [
+        (&delegator1, DELEGATOR1_PUBKEY, DELEGATEE1_PUBKEY),
+        (&delegator1, DELEGATOR1_PUBKEY, DELEGATEE2_PUBKEY),
+        (&delegator2, DELEGATOR2_PUBKEY, DELEGATEE1_PUBKEY),
+    ] {
+        assert!(add_delegation(
+            deps.as_mut(),
+            delegator_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &delegator_pubkey.to_string(),
+            &delegatee_pubkey.to_string(),
+            &proxy_delegations,
+            &None,
+        )
+        .is_ok());
+    }
 
-    // Check if contract state is terminated
-    let state: State = store_get_state(deps.as_mut().storage).unwrap();
-    assert!(state.terminated);
+    /*************** Data *************/
+    let res: ListDataByDelegatorResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListDataByDelegator {
+                delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+                start_after: None,
+                limit: Some(2),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.data_ids, vec![data_id1.clone(), data_id2.clone()]);
+
+    let res: ListDataByDelegatorResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListDataByDelegator {
+                delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+                start_after: Some(data_id2.clone()),
+                limit: Some(2),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.data_ids, vec![data_id3.clone()]);
+
+    /*************** Delegations *************/
+    let res: ListDelegationsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListDelegationsByDelegator {
+                delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delegations.len(), 2);
+    assert_eq!(res.delegations[0].delegatee_pubkey, DELEGATEE1_PUBKEY);
+    assert_eq!(res.delegations[1].delegatee_pubkey, DELEGATEE2_PUBKEY);
+    assert_eq!(res.delegations[0].delegation_state, DelegationState::Active);
+
+    // Proxies of one delegation are counted as single delegation
+    let res: ListDelegationsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListDelegationsByDelegator {
+                delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+                start_after: None,
+                limit: Some(1),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delegations.len(), 1);
+    assert_eq!(res.delegations[0].delegatee_pubkey, DELEGATEE1_PUBKEY);
+
+    let res: ListDelegationsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListDelegationsByDelegatee {
+                delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+                start_after: Some(DELEGATOR1_PUBKEY.to_string()),
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delegations.len(), 1);
+    assert_eq!(res.delegations[0].delegator_pubkey, DELEGATOR2_PUBKEY);
+    assert_eq!(res.delegations[0].delegatee_pubkey, DELEGATEE1_PUBKEY);
 
-    assert!(is_err(
-        add_delegation(
+    /*************** Tasks *************/
+    for data_id in [&data_id1, &data_id2] {
+        assert!(request_reencryption(
             deps.as_mut(),
-            &delegator2,
+            &delegator1,
             DEFAULT_BLOCK_HEIGHT,
-            &DELEGATOR2_PUBKEY.to_string(),
+            data_id,
             &DELEGATEE1_PUBKEY.to_string(),
-            &proxy_delegations,
-            &None,
-        ),
-        "Contract was terminated.",
-    ));
-
-    assert!(is_err(
-        register_proxy(
-            deps.as_mut(),
-            &proxy3,
-            DEFAULT_BLOCK_HEIGHT,
-            &proxy3_pubkey,
-            &proxy_stake,
-        ),
-        "Contract was terminated.",
-    ));
+            &request_reward,
+        )
+        .is_ok());
+    }
 
-    // Proxies still can finish their jobs
-    assert!(provide_reencrypted_fragment(
-        deps.as_mut(),
-        &proxy1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &FRAGMENT_P1_DR1_DE1.to_string(),
-    )
-    .is_ok());
+    let proxy1_tasks = list_tasks_by_proxy(&deps, &proxy1, None, None);
+    assert_eq!(proxy1_tasks.len(), 2);
+    assert_eq!(proxy1_tasks[0].1, data_id1);
+    assert_eq!(proxy1_tasks[1].1, data_id2);
 
-    // Have to wait for withdrawal period
-    assert!(is_err(
-        withdraw_contract(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &recipient),
-        "Withdrawal will be possible at height",
-    ));
+    assert_eq!(
+        list_tasks_by_proxy(&deps, &proxy1, Some(proxy1_tasks[0].0), Some(1)),
+        vec![proxy1_tasks[1].clone()]
+    );
 
-    // Nothing to withdraw
-    assert!(is_err(
-        withdraw_contract(
-            deps.as_mut(),
-            &creator,
-            DEFAULT_BLOCK_HEIGHT + withdrawal_period,
-            &recipient,
-        ),
-        "Nothing to withdraw",
-    ));
+    /*************** Proxies *************/
+    assert!(deactivate_proxy(deps.as_mut(), &proxy3, DEFAULT_BLOCK_HEIGHT).is_ok());
 
-    // Add balance to contract
-    let contract_balance: Vec<Coin> = vec![
-        Coin {
-            denom: "something".to_string(),
-            amount: Uint128::new(123),
-        },
-        Coin {
-            denom: stake_denom,
-            // amount is equal to remaining proxy stake amount
-            amount: Uint128::new(2 * minimum_proxy_stake_amount - 254),
-        },
-    ];
-    deps.querier
-        .update_balance(mock_env().contract.address, contract_balance);
+    assert_eq!(
+        list_proxies(&deps, None, None, Some(2)),
+        vec![proxy1.clone(), proxy2.clone()]
+    );
+    assert_eq!(
+        list_proxies(&deps, None, Some(proxy2.clone()), Some(2)),
+        vec![proxy3.clone()]
+    );
+    assert_eq!(
+        list_proxies(&deps, Some(ProxyState::Registered), None, None),
+        vec![proxy1.clone(), proxy2.clone()]
+    );
+    assert_eq!(
+        list_proxies(&deps, Some(ProxyState::Leaving), None, None),
+        vec![proxy3.clone()]
+    );
 
-    // Withdrawing is now possible
-    let res = withdraw_contract(
-        deps.as_mut(),
-        &creator,
-        DEFAULT_BLOCK_HEIGHT + withdrawal_period,
-        &recipient,
-    )
-    .unwrap();
+    // Abandoned tasks stay in history of proxy
+    assert_eq!(list_tasks_by_proxy(&deps, &proxy3, None, None).len(), 2);
 
-    // Return remaining stake to recipient
+    /*************** Removal *************/
+    assert!(remove_data(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &data_id1).is_ok());
     assert_eq!(
-        res.messages[0],
-        SubMsg::new(BankMsg::Send {
-            to_address: recipient.to_string(),
-            amount: [
-                Coin {
-                    denom: "something".to_string(),
-                    amount: Uint128::new(123),
-                },
-                Coin {
-                    denom: "atestfet".to_string(),
-                    amount: Uint128::new(146),
-                },
-                // proxy stake got subtracted
-            ]
-            .to_vec(),
-        })
+        list_tasks_by_proxy(&deps, &proxy1, None, None),
+        vec![proxy1_tasks[1].clone()]
     );
+
+    assert!(remove_proxy(deps.as_mut(), &creator, DEFAULT_BLOCK_HEIGHT, &proxy3).is_ok());
+    assert!(list_proxies(&deps, Some(ProxyState::Leaving), None, None).is_empty());
 }
 
 #[test]
-fn test_skip_task() {
+fn test_proxy_tasks_pagination() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
     let proxy1 = Addr::unchecked("proxy_1".to_string());
     let proxy2 = Addr::unchecked("proxy_2".to_string());
     let proxy3 = Addr::unchecked("proxy_3".to_string());
-    let proxy4 = Addr::unchecked("proxy_4".to_string());
-
-    let delegator1 = Addr::unchecked("delegator1".to_string());
-
-    // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy_pubkey1");
-    let proxy2_pubkey: String = String::from("proxy_pubkey2");
-    let proxy3_pubkey: String = String::from("proxy_pubkey3");
-    let proxy4_pubkey: String = String::from("proxy_pubkey4");
 
+    // Data
     let data_id1 = String::from("DATA1");
-    let delegation_string = String::from("DELESTRING");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+    let capsule = String::from("capsule");
 
     // Staking
     let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let minimum_proxy_stake_amount: u128 = 200;
+    let minimum_proxy_stake_amount: u128 = 1000;
     let per_proxy_task_reward_amount: u128 = 40;
     let per_task_slash_stake_amount: u128 = 98;
 
-    let request_reward = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(per_proxy_task_reward_amount * 3),
-    }];
-
     let proxy_stake = vec![Coin {
         denom: DEFAULT_STAKE_DENOM.to_string(),
         amount: Uint128::new(minimum_proxy_stake_amount),
     }];
 
-    // Timetous
-    let timeout_height: u64 = 100;
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(3 * per_proxy_task_reward_amount),
+    }];
 
     /*************** Initialise *************/
     assert!(init_contract(
         deps.as_mut(),
         &creator,
-        0,
+        DEFAULT_BLOCK_HEIGHT,
         &Some(2),
         &None,
         &None,
@@ -5048,256 +11590,383 @@ fn test_skip_task() {
         &Some(Uint128::new(minimum_proxy_stake_amount)),
         &Some(Uint128::new(per_proxy_task_reward_amount)),
         &Some(Uint128::new(per_task_slash_stake_amount)),
-        &Some(timeout_height),
-        &Some(false),
+        &None,
+        &None,
         &None,
     )
     .is_ok());
 
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy1,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy1_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
+    let mut proxy_delegations: Vec<ProxyDelegationString> = Vec::new();
+    for proxy_addr in [&proxy1, &proxy2, &proxy3] {
+        assert!(register_proxy(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &format!("{}_pubkey", proxy_addr),
+            &proxy_stake,
+        )
+        .is_ok());
 
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy2,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy2_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
+        proxy_delegations.push(ProxyDelegationString {
+            proxy_addr: proxy_addr.clone(),
+            delegation_string: format!("DELESTRING_{}", proxy_addr),
+        });
+    }
 
-    assert!(register_proxy(
+    assert!(add_delegation(
         deps.as_mut(),
-        &proxy3,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &proxy3_pubkey,
-        &proxy_stake,
+        &DELEGATOR1_PUBKEY.to_string(),
+        &DELEGATEE1_PUBKEY.to_string(),
+        &proxy_delegations,
+        &None,
     )
     .is_ok());
 
-    assert!(register_proxy(
+    // Requests are created in different blocks, DATA3 first to get tasks ordered by timeout not by ID
+    for (data_id, block_height) in [
+        (&data_id3, DEFAULT_BLOCK_HEIGHT + 20),
+        (&data_id1, DEFAULT_BLOCK_HEIGHT),
+        (&data_id2, DEFAULT_BLOCK_HEIGHT + 10),
+    ] {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &None,
+        )
+        .is_ok());
+
+        assert!(request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            block_height,
+            data_id,
+            &DELEGATEE1_PUBKEY.to_string(),
+            &request_reward,
+        )
+        .is_ok());
+    }
+
+    /*************** Paging *************/
+    let query_height = DEFAULT_BLOCK_HEIGHT + 20;
+
+    let (tasks, cursor) = get_proxy_tasks_page(&deps, query_height, &proxy1, None, Some(2));
+    assert_eq!(tasks, vec![data_id1.clone(), data_id2.clone()]);
+    assert_eq!(
+        cursor.as_ref().unwrap().timeout_height,
+        DEFAULT_BLOCK_HEIGHT + 10 + DEFAULT_TIMEOUT_HEIGHT
+    );
+
+    let (tasks, cursor) = get_proxy_tasks_page(&deps, query_height, &proxy1, cursor, Some(2));
+    assert_eq!(tasks, vec![data_id3.clone()]);
+    assert!(cursor.is_none());
+
+    /*************** Timed-out tasks are skipped *************/
+    let query_height = DEFAULT_BLOCK_HEIGHT + DEFAULT_TIMEOUT_HEIGHT;
+
+    // Full page returns cursor even when there are no more tasks
+    let (tasks, cursor) = get_proxy_tasks_page(&deps, query_height, &proxy1, None, Some(2));
+    assert_eq!(tasks, vec![data_id2.clone(), data_id3.clone()]);
+    assert!(cursor.is_some());
+
+    let (tasks, cursor) = get_proxy_tasks_page(&deps, query_height, &proxy1, cursor, Some(2));
+    assert!(tasks.is_empty());
+    assert!(cursor.is_none());
+
+    /*************** Completed tasks leave queue *************/
+    assert!(provide_reencrypted_fragment(
         deps.as_mut(),
-        &proxy4,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy4_pubkey,
-        &proxy_stake,
+        &proxy1,
+        DEFAULT_BLOCK_HEIGHT + 20,
+        &data_id2,
+        &DELEGATEE1_PUBKEY.to_string(),
+        &String::from("fragment_proxy_1"),
     )
     .is_ok());
 
-    // Prepare scenario
-    // Proxy 1,2,3 gets2 re-encryption tasks each
+    let (tasks, _) = get_proxy_tasks_page(&deps, DEFAULT_BLOCK_HEIGHT + 20, &proxy1, None, None);
+    assert_eq!(tasks, vec![data_id1.clone(), data_id3.clone()]);
 
-    assert!(add_data(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
+    /*************** Stale queue entries are skipped *************/
+    let task_id = store_get_delegatee_proxy_task(
+        deps.as_mut().storage,
         &data_id1,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &CAPSULE.to_string(),
-        &None,
+        DELEGATEE1_PUBKEY,
+        &proxy1,
     )
-    .is_ok());
+    .unwrap()
+    .unwrap();
+    store_remove_proxy_task(deps.as_mut().storage, &task_id).unwrap();
 
-    let proxy_delegations: Vec<ProxyDelegationString> = vec![
-        ProxyDelegationString {
-            proxy_addr: proxy1.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy2.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy3.clone(),
-            delegation_string: delegation_string,
-        },
-    ];
+    let (tasks, _) = get_proxy_tasks_page(&deps, DEFAULT_BLOCK_HEIGHT + 20, &proxy1, None, None);
+    assert_eq!(tasks, vec![data_id3]);
+}
+
+#[test]
+fn test_delegatee_requests() {
+    let mut deps = mock_dependencies();
+
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let data_id3 = String::from("DATA3");
+    let capsule = String::from("capsule");
+    let delegatee1_pubkey = DELEGATEE1_PUBKEY.to_string();
+    let delegatee2_pubkey = DELEGATEE2_PUBKEY.to_string();
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 1000;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
 
-    // Add delegations
-    assert!(add_delegation(
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(3 * per_proxy_task_reward_amount),
+    }];
+
+    /*************** Initialise *************/
+    assert!(init_contract(
         deps.as_mut(),
-        &delegator1,
+        &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy_delegations,
+        &Some(2),
+        &None,
+        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
+        &None,
         &None,
-    )
-    .is_ok());
-    assert!(add_delegation(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE2_PUBKEY.to_string(),
-        &proxy_delegations,
         &None,
     )
     .is_ok());
 
-    assert!(request_reencryption(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &request_reward,
-    )
-    .is_ok());
+    let mut proxy_delegations: Vec<ProxyDelegationString> = Vec::new();
+    for proxy_addr in [&proxy1, &proxy2, &proxy3] {
+        assert!(register_proxy(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &format!("{}_pubkey", proxy_addr),
+            &proxy_stake,
+        )
+        .is_ok());
 
-    assert!(request_reencryption(
-        deps.as_mut(),
-        &delegator1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE2_PUBKEY.to_string(),
-        &request_reward,
-    )
-    .is_ok());
+        proxy_delegations.push(ProxyDelegationString {
+            proxy_addr: proxy_addr.clone(),
+            delegation_string: format!("DELESTRING_{}", proxy_addr),
+        });
+    }
 
-    // Not a proxy
-    assert!(is_err(
-        skip_reencryption_task(
+    for delegatee_pubkey in [&delegatee1_pubkey, &delegatee2_pubkey] {
+        assert!(add_delegation(
             deps.as_mut(),
-            &creator,
+            &delegator1,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        "Sender is not a proxy",
-    ));
+            &DELEGATOR1_PUBKEY.to_string(),
+            delegatee_pubkey,
+            &proxy_delegations,
+            &None,
+        )
+        .is_ok());
+    }
 
-    // Incorrect task
-    assert!(is_err(
-        skip_reencryption_task(
+    for (data_id, delegatee_pubkey) in [
+        (&data_id1, &delegatee1_pubkey),
+        (&data_id2, &delegatee1_pubkey),
+        (&data_id3, &delegatee2_pubkey),
+    ] {
+        assert!(add_data(
             deps.as_mut(),
-            &proxy4,
+            &delegator1,
             DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        "Task doesn't exist",
-    ));
+            data_id,
+            &DELEGATOR1_PUBKEY.to_string(),
+            &capsule,
+            &None,
+        )
+        .is_ok());
 
-    let p1_tasks = get_proxy_tasks(deps.as_mut().storage, &proxy1, &DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(p1_tasks.len(), 2);
-    let p2_tasks = get_proxy_tasks(deps.as_mut().storage, &proxy2, &DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(p2_tasks.len(), 2);
-    let p3_tasks = get_proxy_tasks(deps.as_mut().storage, &proxy3, &DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(p3_tasks.len(), 2);
+        assert!(request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            delegatee_pubkey,
+            &request_reward,
+        )
+        .is_ok());
+    }
 
-    // Remove task for data1 by proxy1 for delegatee1
-    assert!(skip_reencryption_task(
-        deps.as_mut(),
-        &proxy1,
-        DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-    )
-    .is_ok());
+    for proxy_addr in [&proxy1, &proxy2] {
+        assert!(provide_reencrypted_fragment(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id2,
+            &delegatee1_pubkey,
+            &format!("fragment_{}", proxy_addr),
+        )
+        .is_ok());
+    }
 
-    // Check request state
-    let state = store_get_state(deps.as_mut().storage).unwrap();
+    /*************** Inbox *************/
+    let timeout_height = DEFAULT_BLOCK_HEIGHT + DEFAULT_TIMEOUT_HEIGHT;
+
+    let res = get_delegatee_requests(
+        &deps,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        None,
+        None,
+        None,
+    );
     assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &DEFAULT_BLOCK_HEIGHT,
-        ),
-        ReencryptionRequestState::Ready
+        res.requests,
+        vec![
+            DelegateeRequestResponse {
+                data_id: data_id1.clone(),
+                reencryption_request_state: ReencryptionRequestState::Ready,
+                n_fragments: 0,
+                threshold: 2,
+                timeout_height,
+            },
+            DelegateeRequestResponse {
+                data_id: data_id2.clone(),
+                reencryption_request_state: ReencryptionRequestState::Granted,
+                n_fragments: 2,
+                threshold: 2,
+                timeout_height,
+            }
+        ]
     );
+    assert!(res.cursor.is_none());
 
-    let p1_tasks = get_proxy_tasks(deps.as_mut().storage, &proxy1, &DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(p1_tasks.len(), 1);
-    let p2_tasks = get_proxy_tasks(deps.as_mut().storage, &proxy2, &DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(p2_tasks.len(), 2);
-    let p3_tasks = get_proxy_tasks(deps.as_mut().storage, &proxy3, &DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(p3_tasks.len(), 2);
+    let res = get_delegatee_requests(
+        &deps,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        Some(ReencryptionRequestState::Granted),
+        None,
+        None,
+    );
+    assert_eq!(res.requests.len(), 1);
+    assert_eq!(res.requests[0].data_id, data_id2);
 
-    // Remove task for data1 by proxy3 for delegatee1
-    assert!(skip_reencryption_task(
-        deps.as_mut(),
-        &proxy3,
+    /*************** Paging *************/
+    // Filtered out requests are skipped until page is full
+    let res = get_delegatee_requests(
+        &deps,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-    )
-    .is_ok());
+        DELEGATEE1_PUBKEY,
+        Some(ReencryptionRequestState::Granted),
+        None,
+        Some(1),
+    );
+    assert_eq!(res.requests.len(), 1);
+    assert_eq!(res.requests[0].data_id, data_id2);
+    assert_eq!(res.cursor, Some(data_id2.clone()));
 
-    // Task is Abandoned
-    let state = store_get_state(deps.as_mut().storage).unwrap();
-    assert_eq!(
-        get_reencryption_request_state(
-            deps.as_mut().storage,
-            &state,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &DEFAULT_BLOCK_HEIGHT,
-        ),
-        ReencryptionRequestState::Abandoned
+    let res = get_delegatee_requests(
+        &deps,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        Some(ReencryptionRequestState::Granted),
+        res.cursor,
+        Some(1),
     );
+    assert!(res.requests.is_empty());
+    assert!(res.cursor.is_none());
 
-    let p1_tasks = get_proxy_tasks(deps.as_mut().storage, &proxy1, &DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(p1_tasks.len(), 1);
-    let p2_tasks = get_proxy_tasks(deps.as_mut().storage, &proxy2, &DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(p2_tasks.len(), 2);
-    let p3_tasks = get_proxy_tasks(deps.as_mut().storage, &proxy3, &DEFAULT_BLOCK_HEIGHT).unwrap();
-    assert_eq!(p3_tasks.len(), 1);
+    let res = get_delegatee_requests(
+        &deps,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE1_PUBKEY,
+        None,
+        None,
+        Some(1),
+    );
+    assert_eq!(res.requests.len(), 1);
+    assert_eq!(res.requests[0].data_id, data_id1);
+    assert_eq!(res.cursor, Some(data_id1.clone()));
 
-    // Proxy 3 finish the task
-    assert!(provide_reencrypted_fragment(
-        deps.as_mut(),
-        &proxy2,
+    /*************** Timeout and removal *************/
+    let res = get_delegatee_requests(
+        &deps,
+        timeout_height,
+        DELEGATEE1_PUBKEY,
+        Some(ReencryptionRequestState::TimedOut),
+        None,
+        None,
+    );
+    assert_eq!(res.requests.len(), 1);
+    assert_eq!(res.requests[0].data_id, data_id1);
+
+    assert!(remove_data(deps.as_mut(), &delegator1, DEFAULT_BLOCK_HEIGHT, &data_id1).is_ok());
+
+    let res = get_delegatee_requests(
+        &deps,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &FRAGMENT_P2_DR1_DE1.to_string(),
-    )
-    .is_ok());
+        DELEGATEE1_PUBKEY,
+        None,
+        None,
+        None,
+    );
+    assert_eq!(res.requests.len(), 1);
+    assert_eq!(res.requests[0].data_id, data_id2);
 
-    // Can't skip finished task
-    assert!(is_err(
-        skip_reencryption_task(
-            deps.as_mut(),
-            &proxy2,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        "Task was already completed.",
-    ));
+    let res = get_delegatee_requests(
+        &deps,
+        DEFAULT_BLOCK_HEIGHT,
+        DELEGATEE2_PUBKEY,
+        None,
+        None,
+        None,
+    );
+    assert_eq!(res.requests.len(), 1);
+    assert_eq!(res.requests[0].data_id, data_id3);
 }
 
 #[test]
-fn test_remove_proxies_from_delegation() {
+fn test_contract_stats() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
+    let delegator1 = Addr::unchecked("delegator1".to_string());
+    let keeper = Addr::unchecked("keeper".to_string());
     let proxy1 = Addr::unchecked("proxy_1".to_string());
     let proxy2 = Addr::unchecked("proxy_2".to_string());
     let proxy3 = Addr::unchecked("proxy_3".to_string());
 
-    let delegator1 = Addr::unchecked("delegator1".to_string());
-
-    // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy_pubkey1");
-    let proxy2_pubkey: String = String::from("proxy_pubkey2");
-    let proxy3_pubkey: String = String::from("proxy_pubkey3");
-
-    let delegation_string = String::from("DELESTRING");
+    // Data
+    let data_id1 = String::from("DATA1");
+    let data_id2 = String::from("DATA2");
+    let capsule = String::from("capsule");
+    let delegatee1_pubkey = DELEGATEE1_PUBKEY.to_string();
 
     // Staking
     let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let minimum_proxy_stake_amount: u128 = 200;
+    let minimum_proxy_stake_amount: u128 = 1000;
     let per_proxy_task_reward_amount: u128 = 40;
     let per_task_slash_stake_amount: u128 = 98;
 
@@ -5306,14 +11975,16 @@ fn test_remove_proxies_from_delegation() {
         amount: Uint128::new(minimum_proxy_stake_amount),
     }];
 
-    // Timetous
-    let timeout_height: u64 = 100;
+    let request_reward = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(3 * per_proxy_task_reward_amount),
+    }];
 
     /*************** Initialise *************/
     assert!(init_contract(
         deps.as_mut(),
         &creator,
-        0,
+        DEFAULT_BLOCK_HEIGHT,
         &Some(2),
         &None,
         &None,
@@ -5321,460 +11992,897 @@ fn test_remove_proxies_from_delegation() {
         &Some(Uint128::new(minimum_proxy_stake_amount)),
         &Some(Uint128::new(per_proxy_task_reward_amount)),
         &Some(Uint128::new(per_task_slash_stake_amount)),
-        &Some(timeout_height),
-        &Some(false),
+        &None,
+        &None,
         &None,
     )
     .is_ok());
 
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy1.clone(),
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy1_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy2.clone(),
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy2_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
-
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy3,
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy3_pubkey,
-        &proxy_stake,
-    )
-    .is_ok());
+    assert_eq!(
+        get_contract_stats(&deps),
+        GetContractStatsResponse {
+            total_staked: Uint128::zero(),
+            n_active_proxies: 0,
+            n_open_tasks: 0,
+            n_completed_requests: 0,
+            total_rewards_paid: Uint128::zero(),
+            total_slashed: Uint128::zero(),
+        }
+    );
 
-    let proxy_delegations: Vec<ProxyDelegationString> = vec![
-        ProxyDelegationString {
-            proxy_addr: proxy1.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy2.clone(),
-            delegation_string: delegation_string.clone(),
-        },
-        ProxyDelegationString {
-            proxy_addr: proxy3.clone(),
-            delegation_string: delegation_string,
-        },
-    ];
+    let mut proxy_delegations: Vec<ProxyDelegationString> = Vec::new();
+    for proxy_addr in [&proxy1, &proxy2, &proxy3] {
+        assert!(register_proxy(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &format!("{}_pubkey", proxy_addr),
+            &proxy_stake,
+        )
+        .is_ok());
+
+        proxy_delegations.push(ProxyDelegationString {
+            proxy_addr: proxy_addr.clone(),
+            delegation_string: format!("DELESTRING_{}", proxy_addr),
+        });
+    }
+
+    let stats = get_contract_stats(&deps);
+    assert_eq!(
+        stats.total_staked,
+        Uint128::new(3 * minimum_proxy_stake_amount)
+    );
+    assert_eq!(stats.n_active_proxies, 3);
 
-    // Add delegation
     assert!(add_delegation(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
+        &delegatee1_pubkey,
         &proxy_delegations,
         &None,
     )
     .is_ok());
 
-    assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
+    /*************** Request re-encryptions *************/
+    for data_id in [&data_id1, &data_id2] {
+        assert!(add_data(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
             &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::Active
+            &capsule,
+            &None,
+        )
+        .is_ok());
+
+        assert!(request_reencryption(
+            deps.as_mut(),
+            &delegator1,
+            DEFAULT_BLOCK_HEIGHT,
+            data_id,
+            &delegatee1_pubkey,
+            &request_reward,
+        )
+        .is_ok());
+    }
+
+    // Stake for 2 tasks is withdrawn from each proxy
+    let stats = get_contract_stats(&deps);
+    assert_eq!(
+        stats.total_staked,
+        Uint128::new(3 * (minimum_proxy_stake_amount - 2 * per_task_slash_stake_amount))
     );
+    assert_eq!(stats.n_open_tasks, 6);
+
+    /*************** Complete request of DATA1 *************/
+    for proxy_addr in [&proxy1, &proxy2] {
+        assert!(provide_reencrypted_fragment(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id1,
+            &delegatee1_pubkey,
+            &format!("FRAGMENT_{}", proxy_addr),
+        )
+        .is_ok());
+    }
+
+    // Third proxy abandons task which is no longer needed and gets slashed
+    assert!(skip_reencryption_task(
+        deps.as_mut(),
+        &proxy3,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id1,
+        &delegatee1_pubkey,
+    )
+    .is_ok());
 
-    // Delegation still exists when proxy 1 leaves
-    assert!(unregister_proxy(deps.as_mut(), &proxy1, DEFAULT_BLOCK_HEIGHT).is_ok());
     assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::Active
+        get_contract_stats(&deps),
+        GetContractStatsResponse {
+            total_staked: Uint128::new(
+                3 * (minimum_proxy_stake_amount - 2 * per_task_slash_stake_amount)
+                    + 2 * (per_proxy_task_reward_amount + per_task_slash_stake_amount)
+            ),
+            n_active_proxies: 3,
+            n_open_tasks: 3,
+            n_completed_requests: 1,
+            total_rewards_paid: Uint128::new(2 * per_proxy_task_reward_amount),
+            total_slashed: Uint128::new(per_task_slash_stake_amount),
+        }
     );
 
-    // Delegation gets deleted when number of proxies is less than minimum
-    assert!(unregister_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).is_ok());
+    /*************** Time out request of DATA2 *************/
+    let timeout_height = DEFAULT_BLOCK_HEIGHT + DEFAULT_TIMEOUT_HEIGHT;
+    assert!(resolve_timed_out_requests(deps.as_mut(), &keeper, timeout_height, None).is_ok());
 
+    let stats = get_contract_stats(&deps);
+    assert_eq!(stats.n_open_tasks, 0);
+    assert_eq!(stats.n_completed_requests, 1);
     assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::NonExisting
+        stats.total_slashed,
+        Uint128::new(4 * per_task_slash_stake_amount)
     );
 
-    assert!(unregister_proxy(deps.as_mut(), &proxy3, DEFAULT_BLOCK_HEIGHT).is_ok());
+    // Repeated resolution doesn't slash twice
+    assert!(resolve_timed_out_request(
+        deps.as_mut(),
+        &delegator1,
+        timeout_height,
+        &data_id2,
+        &delegatee1_pubkey,
+    )
+    .is_ok());
+    assert_eq!(
+        get_contract_stats(&deps).total_slashed,
+        Uint128::new(4 * per_task_slash_stake_amount)
+    );
+
+    /*************** Proxies leave *************/
+    assert!(deactivate_proxy(deps.as_mut(), &proxy3, timeout_height).is_ok());
+    assert!(unregister_proxy(deps.as_mut(), &proxy1, timeout_height).is_ok());
 
     assert_eq!(
-        get_delegation_state(
-            deps.as_mut().storage,
-            &DELEGATOR1_PUBKEY.to_string(),
-            &DELEGATEE1_PUBKEY.to_string(),
-        ),
-        DelegationState::NonExisting
+        get_contract_stats(&deps),
+        GetContractStatsResponse {
+            total_staked: Uint128::new(
+                2 * minimum_proxy_stake_amount + per_proxy_task_reward_amount
+                    - 3 * per_task_slash_stake_amount
+            ),
+            n_active_proxies: 1,
+            n_open_tasks: 0,
+            n_completed_requests: 1,
+            total_rewards_paid: Uint128::new(2 * per_proxy_task_reward_amount),
+            total_slashed: Uint128::new(4 * per_task_slash_stake_amount),
+        }
     );
 }
 
 #[test]
-fn test_add_remove_labels() {
+fn test_simulate_reencryption_request() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
     let delegator1 = Addr::unchecked("delegator1".to_string());
-    let delegator2 = Addr::unchecked("delegator2".to_string());
+    let requester = Addr::unchecked("requester".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
 
     // Data
-    let data_id1 = String::from("DATA1");
-    let data_id2 = String::from("DATA2");
-    let data_id3 = String::from("DATA3");
-
+    let data_id = String::from("DATA");
     let capsule = String::from("capsule");
+    let delegatee1_pubkey = DELEGATEE1_PUBKEY.to_string();
+
+    // Staking
+    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
+    let minimum_proxy_stake_amount: u128 = 1000;
+    let per_proxy_task_reward_amount: u128 = 40;
+    let per_task_slash_stake_amount: u128 = 98;
+
+    let proxy_stake = vec![Coin {
+        denom: DEFAULT_STAKE_DENOM.to_string(),
+        amount: Uint128::new(minimum_proxy_stake_amount),
+    }];
+
+    let required_amount = 3 * per_proxy_task_reward_amount;
+    let timeout_height = DEFAULT_BLOCK_HEIGHT + DEFAULT_TIMEOUT_HEIGHT;
 
     /*************** Initialise *************/
     assert!(init_contract(
         deps.as_mut(),
         &creator,
         DEFAULT_BLOCK_HEIGHT,
+        &Some(2),
         &None,
         &None,
-        &None,
-        &DEFAULT_STAKE_DENOM.to_string(),
-        &None,
-        &None,
-        &None,
+        &stake_denom,
+        &Some(Uint128::new(minimum_proxy_stake_amount)),
+        &Some(Uint128::new(per_proxy_task_reward_amount)),
+        &Some(Uint128::new(per_task_slash_stake_amount)),
         &None,
         &None,
         &None,
     )
     .is_ok());
 
-    /*************** Add data and delegations by delegator *************/
+    // Data entry doesn't exist
+    assert_eq!(
+        simulate_reencryption_request(
+            &deps,
+            DEFAULT_BLOCK_HEIGHT,
+            &data_id,
+            &delegatee1_pubkey,
+            &delegator1,
+            &None,
+        ),
+        SimulateReencryptionRequestResponse {
+            is_permitted: false,
+            available_proxies: vec![],
+            required_funds: Coin::new(0, DEFAULT_STAKE_DENOM),
+            excess_refund: Coin::new(0, DEFAULT_STAKE_DENOM),
+            timeout_height,
+            failure_reason: Some(String::from("Data entry doesn't exist.")),
+        }
+    );
 
-    // Add data by delegator1
-    assert!(add_data(
+    let mut proxy_delegations: Vec<ProxyDelegationString> = Vec::new();
+    for proxy_addr in [&proxy1, &proxy2, &proxy3] {
+        assert!(register_proxy(
+            deps.as_mut(),
+            proxy_addr,
+            DEFAULT_BLOCK_HEIGHT,
+            &format!("{}_pubkey", proxy_addr),
+            &proxy_stake,
+        )
+        .is_ok());
+
+        proxy_delegations.push(ProxyDelegationString {
+            proxy_addr: proxy_addr.clone(),
+            delegation_string: format!("DELESTRING_{}", proxy_addr),
+        });
+    }
+
+    assert!(add_delegation(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
         &DELEGATOR1_PUBKEY.to_string(),
-        &capsule,
-        &Some(["x".to_string()].to_vec()),
+        &delegatee1_pubkey,
+        &proxy_delegations,
+        &None,
     )
     .is_ok());
 
-    // Add data by delegator2
     assert!(add_data(
         deps.as_mut(),
-        &delegator2,
+        &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id2,
-        &DELEGATOR2_PUBKEY.to_string(),
+        &data_id,
+        &DELEGATOR1_PUBKEY.to_string(),
         &capsule,
         &None,
     )
     .is_ok());
 
-    assert!(add_data_labels(
-        deps.as_mut(),
-        &delegator1,
+    /*************** Permissions *************/
+    let funds = Some(Coin::new(200, DEFAULT_STAKE_DENOM));
+
+    // Requester other than delegator needs permission
+    let res = simulate_reencryption_request(
+        &deps,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &["a".to_string(), "b".to_string()]
-    )
-    .is_ok());
+        &data_id,
+        &delegatee1_pubkey,
+        &requester,
+        &funds,
+    );
+    assert!(!res.is_permitted);
+    assert_eq!(res.available_proxies, vec![proxy1.clone(), proxy2, proxy3]);
     assert_eq!(
-        store_get_all_data_labels(deps.as_ref().storage, &data_id1),
-        &["a".to_string(), "b".to_string(), "x".to_string()]
+        res.required_funds,
+        Coin::new(required_amount, DEFAULT_STAKE_DENOM)
+    );
+    assert_eq!(
+        res.failure_reason,
+        Some(String::from("Reencryption is not permitted."))
     );
 
-    assert!(add_data_labels(
+    assert!(grant_data_access(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &["b".to_string(), "c".to_string()]
+        &data_id,
+        &delegatee1_pubkey,
+        &None,
     )
     .is_ok());
+
+    /*************** Funds *************/
+    // Delegator without funds and allowance
+    let res = simulate_reencryption_request(
+        &deps,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id,
+        &delegatee1_pubkey,
+        &delegator1,
+        &None,
+    );
+    assert!(res.is_permitted);
     assert_eq!(
-        store_get_all_data_labels(deps.as_ref().storage, &data_id1),
-        &[
-            "a".to_string(),
-            "b".to_string(),
-            "c".to_string(),
-            "x".to_string()
-        ]
+        res.required_funds,
+        Coin::new(required_amount, DEFAULT_STAKE_DENOM)
+    );
+    assert_eq!(res.excess_refund, Coin::new(0, DEFAULT_STAKE_DENOM));
+    assert_eq!(
+        res.failure_reason,
+        Some(String::from(
+            "No funds provided and no allowance for delegatee."
+        ))
     );
 
-    // Try to add label for that that are not owned by delegator2
-    assert!(is_err(
-        add_data_labels(
-            deps.as_mut(),
-            &delegator2,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
-            &["d".to_string()]
-        ),
-        "Sender is not a data owner."
-    ));
-
-    assert!(add_delegatee_labels(
-        deps.as_mut(),
-        &delegator1,
+    // Insufficient funds
+    let res = simulate_reencryption_request(
+        &deps,
         DEFAULT_BLOCK_HEIGHT,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &["d".to_string(), "e".to_string()]
-    )
-    .is_ok());
+        &data_id,
+        &delegatee1_pubkey,
+        &requester,
+        &Some(Coin::new(100, DEFAULT_STAKE_DENOM)),
+    );
+    assert_eq!(
+        res.failure_reason,
+        Some(format!(
+            "Requires at least {} {}.",
+            required_amount, DEFAULT_STAKE_DENOM
+        ))
+    );
 
-    // Reencryption not permitted
-    assert!(!get_permission(
-        deps.as_mut().storage,
-        &delegator1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &data_id1
-    ));
+    // Successful request returns excess funds
+    let simulation = simulate_reencryption_request(
+        &deps,
+        DEFAULT_BLOCK_HEIGHT,
+        &data_id,
+        &delegatee1_pubkey,
+        &requester,
+        &funds,
+    );
+    assert!(simulation.is_permitted);
+    assert_eq!(
+        simulation.excess_refund,
+        Coin::new(200 - required_amount, DEFAULT_STAKE_DENOM)
+    );
+    assert_eq!(simulation.failure_reason, None);
 
-    // Add label "b" that is also under data1
-    assert!(add_delegatee_labels(
+    /*************** Request matches simulation *************/
+    let res = request_reencryption(
         deps.as_mut(),
-        &delegator1,
+        &requester,
         DEFAULT_BLOCK_HEIGHT,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &["b".to_string(), "e".to_string()]
+        &data_id,
+        &delegatee1_pubkey,
+        &vec![funds.clone().unwrap()],
     )
-    .is_ok());
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(BankMsg::Send {
+            to_address: requester.to_string(),
+            amount: vec![simulation.excess_refund],
+        })]
+    );
 
-    // Reencryption is now permitted
-    assert!(get_permission(
-        deps.as_mut().storage,
-        &delegator1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &data_id1
-    ));
+    let proxy_tasks = get_proxy_tasks(
+        deps.as_ref().storage,
+        &proxy1,
+        &DEFAULT_BLOCK_HEIGHT,
+        &None,
+        None,
+    )
+    .unwrap()
+    .proxy_tasks;
+    assert_eq!(proxy_tasks.len(), 1);
+    assert_eq!(
+        get_reencryption_requests(&deps, DEFAULT_BLOCK_HEIGHT, &data_id, DELEGATEE1_PUBKEY),
+        vec![(0, ReencryptionRequestState::Ready, 0)]
+    );
 
-    // Add label "b" that is also under data1
-    assert!(remove_delegatee_labels(
-        deps.as_mut(),
-        &delegator1,
+    let res = simulate_reencryption_request(
+        &deps,
         DEFAULT_BLOCK_HEIGHT,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &["b".to_string(), "e".to_string()]
-    )
-    .is_ok());
+        &data_id,
+        &delegatee1_pubkey,
+        &requester,
+        &funds,
+    );
+    assert_eq!(
+        res.failure_reason,
+        Some(String::from("Reencryption already requested"))
+    );
+}
 
-    // Reencryption not permitted
-    assert!(!get_permission(
-        deps.as_mut().storage,
-        &delegator1,
-        &DELEGATEE1_PUBKEY.to_string(),
-        &data_id1
-    ));
+#[test]
+fn test_get_config() {
+    let mut deps = mock_dependencies();
 
-    // Only owner can add/remove data labels
-    assert!(is_err(
-        add_data_labels(
-            deps.as_mut(),
-            &delegator2,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
-            &["b".to_string()]
-        ),
-        "Sender is not a data owner."
-    ));
-    assert!(is_err(
-        remove_data_labels(
-            deps.as_mut(),
-            &delegator2,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id1,
-            &["b".to_string()]
-        ),
-        "Sender is not a data owner."
-    ));
-    // Entry doesn't exist
-    assert!(is_err(
-        add_data_labels(
-            deps.as_mut(),
-            &delegator2,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id3,
-            &["b".to_string()]
-        ),
-        "Data entry doesn't exist."
-    ));
-    assert!(is_err(
-        remove_data_labels(
-            deps.as_mut(),
-            &delegator2,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id3,
-            &["b".to_string()]
-        ),
-        "Data entry doesn't exist."
-    ));
+    // Addresses
+    let creator = Addr::unchecked("creator".to_string());
+    let admin = Addr::unchecked("admin".to_string());
+    let proxy1 = Addr::unchecked("proxy_1".to_string());
 
-    // Remove label "b" from data1
-    assert!(remove_data_labels(
+    /*************** Initialise *************/
+    assert!(init_contract(
         deps.as_mut(),
-        &delegator1,
+        &creator,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &["b".to_string()]
+        &Some(2),
+        &Some(admin.clone()),
+        &Some(vec![proxy1]),
+        &DEFAULT_STAKE_DENOM.to_string(),
+        &Some(Uint128::new(1000)),
+        &Some(Uint128::new(40)),
+        &Some(Uint128::new(98)),
+        &Some(77),
+        &Some(true),
+        &Some(300),
     )
     .is_ok());
+
+    /*************** Individual configs *************/
+    let staking_config: GetStakingConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetStakingConfig {}).unwrap())
+            .unwrap();
     assert_eq!(
-        store_get_all_data_labels(deps.as_ref().storage, &data_id1),
-        &["a".to_string(), "c".to_string(), "x".to_string()]
+        staking_config,
+        GetStakingConfigResponse {
+            stake_denom: DEFAULT_STAKE_DENOM.to_string(),
+            minimum_proxy_stake_amount: Uint128::new(1000),
+            per_proxy_task_reward_amount: Uint128::new(40),
+            per_task_slash_stake_amount: Uint128::new(98),
+        }
+    );
+
+    let timeouts_config: GetTimeoutsConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetTimeoutsConfig {}).unwrap())
+            .unwrap();
+    assert_eq!(timeouts_config.timeout_height, 77);
+
+    /*************** Complete config *************/
+    let config: GetConfigResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+    assert_eq!(
+        config,
+        GetConfigResponse {
+            state: State {
+                admin,
+                threshold: 2,
+                proxy_whitelisting: true,
+                next_proxy_task_id: 0,
+                next_delegation_id: 0,
+                terminated: false,
+                withdrawn: false,
+                terminate_height: 0,
+                withdrawal_period: 300,
+                paused: false,
+            },
+            staking_config: StakingConfig {
+                stake_denom: DEFAULT_STAKE_DENOM.to_string(),
+                minimum_proxy_stake_amount: Uint128::new(1000),
+                per_proxy_task_reward_amount: Uint128::new(40),
+                per_task_slash_stake_amount: Uint128::new(98),
+            },
+            timeouts_config: TimeoutsConfig { timeout_height: 77 },
+        }
     );
 }
 
 #[test]
-fn test_reencryption_with_labels() {
+fn test_migrate_legacy_storage() {
     let mut deps = mock_dependencies();
 
     // Addresses
     let creator = Addr::unchecked("creator".to_string());
-    let delegator1 = Addr::unchecked("delegator1".to_string());
     let proxy1 = Addr::unchecked("proxy_1".to_string());
+    let proxy2 = Addr::unchecked("proxy_2".to_string());
+    let proxy3 = Addr::unchecked("proxy_3".to_string());
+    let delegator1 = Addr::unchecked("delegator_1".to_string());
+    let keeper = Addr::unchecked("keeper".to_string());
+
+    let delegation_string = String::from("DELESTRING");
 
     // Data
     let data_id1 = String::from("DATA1");
     let data_id2 = String::from("DATA2");
-    let capsule = String::from("capsule");
 
-    // Pubkeys
-    let proxy1_pubkey: String = String::from("proxy_pubkey1");
+    // Request of data_id2 is timed-out, request of data_id1 is pending
+    let timeout_height1 = DEFAULT_BLOCK_HEIGHT + DEFAULT_TIMEOUT_HEIGHT;
+    let timeout_height2 = DEFAULT_BLOCK_HEIGHT - 1;
 
-    let delegation_string = String::from("DELESTRING");
+    let stake_coins = |amount: u128| {
+        vec![Coin {
+            denom: DEFAULT_STAKE_DENOM.to_string(),
+            amount: Uint128::new(amount),
+        }]
+    };
 
-    // Staking
-    let stake_denom = DEFAULT_STAKE_DENOM.to_string();
-    let minimum_proxy_stake_amount: u128 = 200;
-    let per_proxy_task_reward_amount: u128 = 40;
-    let per_task_slash_stake_amount: u128 = 98;
+    /*************** Write legacy layout *************/
+    let storage = deps.as_mut().storage;
 
-    let proxy_stake = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(minimum_proxy_stake_amount),
-    }];
+    storage.set(
+        &to_length_prefixed(b"State"),
+        format!(
+            "{{\"admin\":\"{}\",\"threshold\":1,\"proxy_whitelisting\":false,\"next_proxy_task_id\":257,\"next_delegation_id\":2,\"terminated\":false,\"withdrawn\":false,\"terminate_height\":0,\"withdrawal_period\":500}}",
+            creator
+        )
+        .as_bytes(),
+    );
+    storage.set(
+        &to_length_prefixed(b"StakingConfig"),
+        format!(
+            "{{\"stake_denom\":\"{}\",\"minimum_proxy_stake_amount\":\"{}\",\"per_proxy_task_reward_amount\":\"{}\",\"per_task_slash_stake_amount\":\"{}\"}}",
+            DEFAULT_STAKE_DENOM,
+            DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT,
+            DEFAULT_TASK_REWARD_AMOUNT,
+            DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
+        )
+        .as_bytes(),
+    );
+    storage.set(
+        &to_length_prefixed(b"TimeoutsConfig"),
+        format!("{{\"timeout_height\":{}}}", DEFAULT_TIMEOUT_HEIGHT).as_bytes(),
+    );
 
-    let request_reward = vec![Coin {
-        denom: DEFAULT_STAKE_DENOM.to_string(),
-        amount: Uint128::new(per_proxy_task_reward_amount * 1),
-    }];
+    // Stake of each open task is locked
+    let mut store = PrefixedStorage::new(storage, b"Proxies");
+    for (proxy_addr, proxy) in [
+        (
+            &proxy1,
+            format!(
+                "{{\"state\":\"registered\",\"proxy_pubkey\":\"proxy_1_pubkey\",\"stake_amount\":\"{}\"}}",
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
+            ),
+        ),
+        (
+            &proxy2,
+            format!(
+                "{{\"state\":\"registered\",\"proxy_pubkey\":\"proxy_2_pubkey\",\"stake_amount\":\"{}\"}}",
+                DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 2 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT
+            ),
+        ),
+        (
+            &proxy3,
+            String::from(
+                "{\"state\":\"authorised\",\"proxy_pubkey\":null,\"stake_amount\":\"0\"}",
+            ),
+        ),
+    ] {
+        store.set(proxy_addr.as_bytes(), proxy.as_bytes());
+    }
+    let mut store = PrefixedStorage::new(storage, b"IsProxyActive");
+    store.set(proxy1.as_bytes(), &[1]);
+    store.set(proxy2.as_bytes(), &[1]);
+
+    let mut store = PrefixedStorage::new(storage, b"DelegatorAddr");
+    store.set(DELEGATOR1_PUBKEY.as_bytes(), delegator1.as_bytes());
+
+    // Legacy delegation doesn't contain proxy_addr
+    for (delegation_id, proxy_addr) in [(0u64, &proxy1), (1u64, &proxy2)] {
+        let mut store = PrefixedStorage::multilevel(
+            storage,
+            &[
+                b"ProxyDelegationIDStore",
+                DELEGATOR1_PUBKEY.as_bytes(),
+                DELEGATEE1_PUBKEY.as_bytes(),
+            ],
+        );
+        store.set(proxy_addr.as_bytes(), &delegation_id.to_le_bytes());
+        let mut store = PrefixedStorage::new(storage, b"ProxyDelegationsStore");
+        store.set(
+            &delegation_id.to_le_bytes(),
+            format!(
+                "{{\"delegator_pubkey\":\"{}\",\"delegatee_pubkey\":\"{}\",\"delegation_string\":\"{}\"}}",
+                DELEGATOR1_PUBKEY, DELEGATEE1_PUBKEY, delegation_string
+            )
+            .as_bytes(),
+        );
+        let mut store = PrefixedStorage::multilevel(
+            storage,
+            &[b"PerProxyDelegationsStore", proxy_addr.as_bytes()],
+        );
+        store.set(&delegation_id.to_le_bytes(), &[1]);
+    }
 
-    /*************** Initialise *************/
-    assert!(init_contract(
-        deps.as_mut(),
-        &creator,
-        DEFAULT_BLOCK_HEIGHT,
-        &Some(1),
-        &None,
-        &None,
-        &stake_denom,
-        &Some(Uint128::new(minimum_proxy_stake_amount)),
-        &Some(Uint128::new(per_proxy_task_reward_amount)),
-        &Some(Uint128::new(per_task_slash_stake_amount)),
-        &None,
-        &None,
-        &None,
-    )
-    .is_ok());
+    for data_id in [&data_id1, &data_id2] {
+        let mut store = PrefixedStorage::new(storage, b"DataEntries");
+        store.set(
+            data_id.as_bytes(),
+            format!(
+                "{{\"delegator_pubkey\":\"{}\",\"capsule\":\"{}\"}}",
+                DELEGATOR1_PUBKEY, CAPSULE
+            )
+            .as_bytes(),
+        );
+    }
 
-    assert!(register_proxy(
-        deps.as_mut(),
-        &proxy1.clone(),
-        DEFAULT_BLOCK_HEIGHT,
-        &proxy1_pubkey,
-        &proxy_stake,
+    // Ids above 255 would be out of order with little endian keys
+    for (task_id, data_id, proxy_addr, timeout_height) in [
+        (255u64, &data_id1, &proxy1, timeout_height1),
+        (256u64, &data_id1, &proxy2, timeout_height1),
+        (2u64, &data_id2, &proxy2, timeout_height2),
+    ] {
+        let mut store = PrefixedStorage::new(storage, b"ProxyTasks");
+        store.set(
+            &task_id.to_le_bytes(),
+            format!(
+                "{{\"data_id\":\"{}\",\"delegatee_pubkey\":\"{}\",\"proxy_addr\":\"{}\",\"fragment\":null,\"delegation_string\":\"{}\",\"timeout_height\":{},\"refund_addr\":\"{}\",\"resolved\":false,\"abandoned\":false}}",
+                data_id, DELEGATEE1_PUBKEY, proxy_addr, delegation_string, timeout_height, delegator1
+            )
+            .as_bytes(),
+        );
+        let mut store = PrefixedStorage::multilevel(
+            storage,
+            &[
+                b"DelegateeProxyTasks",
+                data_id.as_bytes(),
+                DELEGATEE1_PUBKEY.as_bytes(),
+            ],
+        );
+        store.set(proxy_addr.as_bytes(), &task_id.to_le_bytes());
+        let mut store =
+            PrefixedStorage::multilevel(storage, &[b"ProxyTasksQueue", proxy_addr.as_bytes()]);
+        store.set(&task_id.to_le_bytes(), &[1]);
+        let mut store =
+            PrefixedStorage::multilevel(storage, &[b"DataIDProxyTasks", data_id.as_bytes()]);
+        store.set(&task_id.to_le_bytes(), &[1]);
+    }
+
+    // Task of earlier request was replaced in DelegateeProxyTasks by latest request
+    let mut store = PrefixedStorage::new(storage, b"ProxyTasks");
+    store.set(
+        &1u64.to_le_bytes(),
+        format!(
+            "{{\"data_id\":\"{}\",\"delegatee_pubkey\":\"{}\",\"proxy_addr\":\"{}\",\"fragment\":null,\"delegation_string\":\"{}\",\"timeout_height\":{},\"refund_addr\":\"{}\",\"resolved\":true,\"abandoned\":true}}",
+            data_id1, DELEGATEE1_PUBKEY, proxy1, delegation_string, timeout_height2, delegator1
+        )
+        .as_bytes(),
+    );
+    let mut store =
+        PrefixedStorage::multilevel(storage, &[b"DataIDProxyTasks", data_id1.as_bytes()]);
+    store.set(&1u64.to_le_bytes(), &[1]);
+
+    /*************** Migrate *************/
+    assert!(migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(0) }).is_err());
+
+    // Limit is exhausted by proxies and first per proxy delegations entry
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: Some(4) }).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "migrate"),
+            Attribute::new("migrated_proxies", "3"),
+            Attribute::new("migrated_delegations", "0"),
+            Attribute::new("migrated_proxy_tasks", "0"),
+            Attribute::new("migrated_data_entries", "0"),
+            Attribute::new("migration_finished", "false"),
+        ]
+    );
+
+    // Contract is locked until migration is finished
+    assert!(deactivate_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).is_err());
+
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "migrate"),
+            Attribute::new("migrated_proxies", "0"),
+            Attribute::new("migrated_delegations", "2"),
+            Attribute::new("migrated_proxy_tasks", "4"),
+            Attribute::new("migrated_data_entries", "2"),
+            Attribute::new("migration_finished", "true"),
+        ]
+    );
+
+    // Stats are counted from legacy entries
+    let total_staked =
+        2 * DEFAULT_MINIMUM_PROXY_STAKE_AMOUNT - 3 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT;
+    assert_eq!(
+        get_contract_stats(&deps),
+        GetContractStatsResponse {
+            total_staked: Uint128::new(total_staked),
+            n_active_proxies: 2,
+            n_open_tasks: 3,
+            n_completed_requests: 0,
+            total_rewards_paid: Uint128::zero(),
+            total_slashed: Uint128::zero(),
+        }
+    );
+
+    // Indexes of migrated entries
+    assert_eq!(
+        list_proxies(&deps, Some(ProxyState::Registered), None, None),
+        vec![proxy1.clone(), proxy2.clone()]
+    );
+    assert_eq!(
+        list_proxies(&deps, Some(ProxyState::Authorised), None, None),
+        vec![proxy3]
+    );
+    assert_eq!(
+        list_tasks_by_proxy(&deps, &proxy2, None, None),
+        vec![(2, data_id2.clone()), (256, data_id1.clone())]
+    );
+    assert_eq!(
+        store_get_delegation(deps.as_ref().storage, &1).unwrap(),
+        Some(ProxyDelegation {
+            delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+            delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+            delegation_string,
+            proxy_addr: proxy2.clone(),
+        })
+    );
+    assert_eq!(
+        store_get_all_delegator_data_ids(deps.as_ref().storage, DELEGATOR1_PUBKEY).unwrap(),
+        vec![data_id1.clone(), data_id2.clone()]
+    );
+
+    // Delegator and delegatee lists
+    let res: ListDelegationsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListDelegationsByDelegator {
+                delegator_pubkey: DELEGATOR1_PUBKEY.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
     )
-    .is_ok());
+    .unwrap();
+    assert_eq!(res.delegations.len(), 1);
+    assert_eq!(res.delegations[0].delegatee_pubkey, DELEGATEE1_PUBKEY);
+
+    let res: ListDelegationsResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListDelegationsByDelegatee {
+                delegatee_pubkey: DELEGATEE1_PUBKEY.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.delegations.len(), 1);
 
-    /*************** Add data and delegations by delegator *************/
+    // Proxy timeout queue skips timed-out task
+    assert_eq!(
+        get_proxy_tasks_page(&deps, DEFAULT_BLOCK_HEIGHT, &proxy2, None, None),
+        (vec![data_id1.clone()], None)
+    );
 
-    // Add data by delegator1
-    assert!(add_data(
-        deps.as_mut(),
-        &delegator1,
+    // Delegatee request index
+    let res = get_delegatee_requests(
+        &deps,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id1,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &capsule,
-        &Some(["x".to_string()].to_vec()),
-    )
-    .is_ok());
+        DELEGATEE1_PUBKEY,
+        None,
+        None,
+        None,
+    );
+    assert_eq!(
+        res.requests
+            .into_iter()
+            .map(|request| (request.data_id, request.reencryption_request_state))
+            .collect::<Vec<(String, ReencryptionRequestState)>>(),
+        vec![
+            (data_id1.clone(), ReencryptionRequestState::Ready),
+            (data_id2.clone(), ReencryptionRequestState::TimedOut),
+        ]
+    );
 
-    assert!(add_data(
+    // Request history, earlier legacy requests are merged into first request
+    assert_eq!(
+        get_reencryption_requests(&deps, DEFAULT_BLOCK_HEIGHT, &data_id1, DELEGATEE1_PUBKEY),
+        vec![
+            (0, ReencryptionRequestState::TimedOut, 0),
+            (1, ReencryptionRequestState::Ready, 0)
+        ]
+    );
+
+    /*************** Stake *************/
+    assert!(add_stake(
         deps.as_mut(),
-        &delegator1,
+        &proxy1,
         DEFAULT_BLOCK_HEIGHT,
-        &data_id2,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &capsule,
-        &Some(["y".to_string()].to_vec()),
+        &stake_coins(3 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT),
     )
     .is_ok());
-
-    let proxy_delegations: Vec<ProxyDelegationString> = vec![ProxyDelegationString {
-        proxy_addr: proxy1.clone(),
-        delegation_string: delegation_string.clone(),
-    }];
-
-    // Add delegation
-    assert!(add_delegation(
+    assert!(withdraw_stake(
         deps.as_mut(),
-        &delegator1,
+        &proxy1,
         DEFAULT_BLOCK_HEIGHT,
-        &DELEGATOR1_PUBKEY.to_string(),
-        &DELEGATEE1_PUBKEY.to_string(),
-        &proxy_delegations,
-        &Some(["x".to_string()].to_vec()),
+        &Some(Uint128::new(DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT)),
     )
     .is_ok());
+    let total_staked = total_staked + 2 * DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT;
+    assert_eq!(
+        get_contract_stats(&deps).total_staked,
+        Uint128::new(total_staked)
+    );
 
-    // Reencryption not permitted
-    assert!(is_err(
-        request_reencryption(
-            deps.as_mut(),
-            &creator,
-            DEFAULT_BLOCK_HEIGHT,
-            &data_id2,
-            &DELEGATEE1_PUBKEY.to_string(),
-            &request_reward,
-        ),
-        "Reencryption is not permitted.",
-    ));
+    /*************** Keeper sweep *************/
+    let res =
+        resolve_timed_out_requests(deps.as_mut(), &keeper, DEFAULT_BLOCK_HEIGHT, None).unwrap();
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("resolved_tasks", "1")));
 
-    // Only delegator can request data2 for delegatee1
-    assert!(request_reencryption(
+    let stats = get_contract_stats(&deps);
+    assert_eq!(stats.n_open_tasks, 2);
+    assert_eq!(
+        stats.total_slashed,
+        Uint128::new(DEFAULT_PER_TASK_SLASH_STAKE_AMOUNT)
+    );
+
+    // Retried request continues request history
+    let res = request_reencryption(
         deps.as_mut(),
         &delegator1,
         DEFAULT_BLOCK_HEIGHT,
         &data_id2,
         &DELEGATEE1_PUBKEY.to_string(),
-        &request_reward,
+        &stake_coins(2 * DEFAULT_TASK_REWARD_AMOUNT),
     )
-    .is_ok());
+    .unwrap();
+    assert!(res.attributes.contains(&Attribute::new("request_seq", "2")));
+    assert_eq!(get_contract_stats(&deps).n_open_tasks, 4);
 
-    // But Data1 can be re-encrypted for delegatee1
-    assert!(request_reencryption(
+    /*************** Fragment *************/
+    assert!(provide_reencrypted_fragment(
         deps.as_mut(),
-        &creator,
+        &proxy1,
         DEFAULT_BLOCK_HEIGHT,
         &data_id1,
         &DELEGATEE1_PUBKEY.to_string(),
-        &request_reward,
+        &String::from(FRAGMENT_P1_DR1_DE1),
     )
     .is_ok());
+    assert_eq!(
+        get_reencryption_requests(&deps, DEFAULT_BLOCK_HEIGHT, &data_id1, DELEGATEE1_PUBKEY),
+        vec![
+            (0, ReencryptionRequestState::TimedOut, 0),
+            (1, ReencryptionRequestState::Granted, 1)
+        ]
+    );
+
+    let stats = get_contract_stats(&deps);
+    assert_eq!(stats.n_completed_requests, 1);
+    // Task of other proxy stays open until it provides fragment
+    assert_eq!(stats.n_open_tasks, 3);
+
+    /*************** Deactivate proxy *************/
+    assert!(deactivate_proxy(deps.as_mut(), &proxy2, DEFAULT_BLOCK_HEIGHT).is_ok());
+
+    let stats = get_contract_stats(&deps);
+    assert_eq!(stats.n_active_proxies, 1);
+
+    // Migration can be repeated
+    let res = migrate(deps.as_mut(), mock_env(), MigrateMsg { limit: None }).unwrap();
+    assert_eq!(res.attributes[1], Attribute::new("migrated_proxies", "0"));
+    assert_eq!(get_contract_stats(&deps), stats);
+
+    // Malformed legacy keys
+    assert!(split_legacy_namespace(&[0]).is_err());
+    assert!(split_legacy_namespace(&[0, 3, 1]).is_err());
+    assert_eq!(
+        split_legacy_namespace(&[0, 1, 1, 2]).unwrap(),
+        (&[1u8][..], &[2u8][..])
+    );
 }
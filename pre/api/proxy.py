@@ -1,6 +1,13 @@
 from typing import List, Optional
 
-from pre.common import Coin, PrivateKey, ProxyState, ProxyStatus, ProxyTask
+from pre.common import (
+    Coin,
+    ContractStats,
+    PrivateKey,
+    ProxyState,
+    ProxyStatus,
+    ProxyTask,
+)
 from pre.contract.base_contract import AbstractProxyContract
 from pre.crypto.base_crypto import AbstractCrypto
 from pre.ledger.base_ledger import AbstractLedgerCrypto
@@ -134,3 +141,11 @@ class ProxyAPI:
         :return: None or ProxyStatus instance
         """
         return self._contract.get_proxy_status(self._ledger_crypto.get_address())
+
+    def get_contract_stats(self) -> ContractStats:
+        """
+        Get contract running counters.
+
+        :return: ContractStats instance
+        """
+        return self._contract.get_contract_stats()
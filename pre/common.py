@@ -173,6 +173,18 @@ class ContractState:
     withdrawn: bool
 
 
+@dataclass
+class ContractStats:
+    """Contract running counters data class."""
+
+    total_staked: int
+    n_active_proxies: int
+    n_open_tasks: int
+    n_completed_requests: int
+    total_rewards_paid: int
+    total_slashed: int
+
+
 @dataclass
 class StakingConfig:
     stake_denom: str
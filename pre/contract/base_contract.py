@@ -6,6 +6,7 @@ from pre.common import (
     Address,
     Coin,
     ContractState,
+    ContractStats,
     Delegation,
     DelegationStatus,
     GetFragmentsResponse,
@@ -84,6 +85,14 @@ class AbstractContractQueries(BaseAbstractContract):
     def get_staking_config(self) -> StakingConfig:
         """Get contract staking config."""
 
+    @abstractmethod
+    def get_contract_stats(self) -> ContractStats:
+        """
+        Get contract running counters.
+
+        :return: ContractStats instance
+        """
+
     @abstractmethod
     def get_data_entry(self, data_id: HashID) -> Optional[DataEntry]:
         """
@@ -404,6 +413,14 @@ class AbstractProxyContract(BaseAbstractContract, ABC):
         :return: StakingConfig instance
         """
 
+    @abstractmethod
+    def get_contract_stats(self) -> ContractStats:
+        """
+        Get contract running counters.
+
+        :return: ContractStats instance
+        """
+
     @abstractmethod
     def get_proxy_status(self, proxy_address: str) -> Optional[ProxyStatus]:
         """
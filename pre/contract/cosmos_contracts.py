@@ -7,6 +7,7 @@ from pre.common import (
     Address,
     Coin,
     ContractState,
+    ContractStats,
     Delegation,
     DelegationState,
     DelegationStatus,
@@ -244,6 +245,23 @@ class ContractQueries(AbstractContractQueries):
             ),
         )
 
+    def get_contract_stats(self) -> ContractStats:
+        """
+        Get contract running counters.
+
+        :return: ContractStats instance
+        """
+        state_msg: Dict = {"get_contract_stats": {}}
+        json_res = self._send_query(state_msg)
+        return ContractStats(
+            total_staked=int(cast(str, json_res["total_staked"])),
+            n_active_proxies=cast(int, json_res["n_active_proxies"]),
+            n_open_tasks=cast(int, json_res["n_open_tasks"]),
+            n_completed_requests=cast(int, json_res["n_completed_requests"]),
+            total_rewards_paid=int(cast(str, json_res["total_rewards_paid"])),
+            total_slashed=int(cast(str, json_res["total_slashed"])),
+        )
+
     def get_proxy_tasks(self, proxy_address: str) -> List[ProxyTask]:
         """
         Get proxy tasks for proxy specified by proxy public key.
@@ -252,29 +270,38 @@ class ContractQueries(AbstractContractQueries):
 
         :return: List of ProxyTask
         """
-        state_msg: Dict = {"get_proxy_tasks": {"proxy_addr": proxy_address}}
-        json_res = self._send_query(state_msg)
-
-        proxy_tasks: Dict = cast(Dict, json_res.get("proxy_tasks"))
-
         tasks = []
-        for proxy_task in proxy_tasks:
-            tasks.append(
-                ProxyTask(
-                    hash_id=cast(str, proxy_task["data_id"]),
-                    capsule=b64decode(cast(str, proxy_task["capsule"])),
-                    delegatee_pubkey=b64decode(
-                        cast(str, proxy_task["delegatee_pubkey"])
-                    ),
-                    delegator_pubkey=b64decode(
-                        cast(str, proxy_task["delegator_pubkey"])
-                    ),
-                    delegation_string=b64decode(
-                        cast(str, proxy_task["delegation_string"])
-                    ),
+        cursor: Optional[Dict] = None
+
+        # Tasks are paginated, follow cursor until last page
+        while True:
+            state_msg: Dict = {
+                "get_proxy_tasks": {"proxy_addr": proxy_address, "start_after": cursor}
+            }
+            json_res = self._send_query(state_msg)
+
+            proxy_tasks: Dict = cast(Dict, json_res.get("proxy_tasks"))
+
+            for proxy_task in proxy_tasks:
+                tasks.append(
+                    ProxyTask(
+                        hash_id=cast(str, proxy_task["data_id"]),
+                        capsule=b64decode(cast(str, proxy_task["capsule"])),
+                        delegatee_pubkey=b64decode(
+                            cast(str, proxy_task["delegatee_pubkey"])
+                        ),
+                        delegator_pubkey=b64decode(
+                            cast(str, proxy_task["delegator_pubkey"])
+                        ),
+                        delegation_string=b64decode(
+                            cast(str, proxy_task["delegation_string"])
+                        ),
+                    )
                 )
-            )
-        return tasks
+
+            cursor = json_res.get("cursor")
+            if cursor is None:
+                return tasks
 
     def get_fragments_response(
         self, hash_id: HashID, delegatee_pubkey_bytes: bytes
@@ -807,6 +834,11 @@ class ProxyContract(AbstractProxyContract, ContractExecuteExceptionMixIn):
             ledger=self.ledger, contract_address=self.contract_address
         ).get_staking_config()
 
+    def get_contract_stats(self) -> ContractStats:
+        return ContractQueries(
+            ledger=self.ledger, contract_address=self.contract_address
+        ).get_contract_stats()
+
     def get_proxy_status(self, proxy_address: str) -> Optional[ProxyStatus]:
         """
         Get proxy status.
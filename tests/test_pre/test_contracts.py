@@ -232,6 +232,11 @@ class TestAdminContract(BaseContractTestCase):
         assert contract_state.threshold == self.THRESHOLD
         assert not contract_state.terminated
 
+    def test_get_contract_stats(self):
+        contract_stats = self.contract_queries.get_contract_stats()
+        assert contract_stats.n_open_tasks == 0
+        assert contract_stats.total_slashed == 0
+
     def test_bad_set_contract(self):
         with pytest.raises(
             ContractInstantiateFailure,